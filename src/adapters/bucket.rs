@@ -0,0 +1,117 @@
+//! S3-compatible object storage (AWS S3, or GCS through its XML API with
+//! HMAC keys), signed with AWS Signature Version 4. Path-style URLs, so
+//! custom endpoints work without DNS per bucket.
+
+use crate::core::types::{ArchiveConfig, Config};
+use crate::ports::archive::ArchiveSink;
+use anyhow::Result;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+pub struct BucketSink {
+    client: reqwest::Client,
+    config: ArchiveConfig,
+}
+
+impl BucketSink {
+    /// The sink, when ARCHIVE_BUCKET is configured.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        Some(Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(120))
+                .build()
+                .unwrap_or_default(),
+            config: config.archive.clone()?,
+        })
+    }
+
+    /// `/{bucket}/{key}`, percent-encoded the way SigV4 canonicalizes it.
+    fn path(&self, key: &str) -> String {
+        format!("/{}/{}", uri_encode(&self.config.bucket), uri_encode(key))
+    }
+
+    /// Authorization header value for a PUT of `path` with the given
+    /// payload hash, at `now`.
+    fn authorization(&self, path: &str, host: &str, payload_hash: &str, now: chrono::DateTime<chrono::Utc>) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            path, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(format!("AWS4{}", self.config.secret_access_key).as_bytes(), date.as_bytes());
+        for part in [self.config.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+#[async_trait]
+impl ArchiveSink for BucketSink {
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        let host = self
+            .config
+            .endpoint
+            .split_once("://")
+            .map_or(self.config.endpoint.as_str(), |(_, rest)| rest)
+            .to_string();
+        let path = self.path(key);
+        let payload_hash = hex(&Sha256::digest(&body));
+        let now = chrono::Utc::now();
+        let resp = self
+            .client
+            .put(format!("{}{}", self.config.endpoint, path))
+            .header("host", &host)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("authorization", self.authorization(&path, &host, &payload_hash, now))
+            .header("content-type", content_type)
+            .body(body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("PUT {} returned {}: {}", key, status, text.chars().take(300).collect::<String>());
+        }
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// RFC 3986 encoding as SigV4 wants it: unreserved characters and `/`
+/// kept, everything else `%XX` in upper case.
+fn uri_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
@@ -1,7 +1,10 @@
 use super::auth::KalshiAuth;
+use super::limiter::RateLimiter;
 use super::types::*;
+use crate::capture;
 use crate::core::types::*;
-use crate::ports::exchange::Exchange;
+use crate::ports::exchange::{Exchange, ExchangeError};
+use crate::quota::{self, Provider};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
@@ -10,6 +13,9 @@ pub struct KalshiClient {
     client: reqwest::Client,
     auth: KalshiAuth,
     base_url: String,
+    retries: u32,
+    retry_base_ms: u64,
+    limiter: RateLimiter,
 }
 
 impl KalshiClient {
@@ -19,23 +25,31 @@ impl KalshiClient {
             &config.kalshi_private_key_pem,
         )?;
         Ok(Self {
-            client: reqwest::Client::new(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
             auth,
             base_url: config.kalshi_base_url.clone(),
+            retries: config.kalshi_retries,
+            retry_base_ms: config.kalshi_retry_base_ms,
+            limiter: RateLimiter::new(config.kalshi_rate_per_sec, config.kalshi_rate_burst),
         })
     }
 
-    async fn request<T: DeserializeOwned>(
-        &self,
-        method: reqwest::Method,
-        path: &str,
-        body: Option<&serde_json::Value>,
-    ) -> Result<T> {
-        let mut attempts = 0;
+    /// Send a request and return the success body, retrying transient
+    /// failures with exponential backoff and jitter. A 429 waits at least
+    /// its Retry-After. A POST (an order) is only retried when Kalshi can't
+    /// have acted on it — rate limited, or never connected — so a timeout
+    /// or 5xx after the order landed can't place it twice.
+    async fn send(&self, method: reqwest::Method, path: &str, body: Option<&serde_json::Value>) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
         loop {
+            // Sign after the wait: the signature carries a timestamp, and a
+            // request queued behind the bucket would go out with a stale one
+            self.limiter.acquire().await;
             let headers = self.auth.headers(method.as_str(), path);
-            let url = format!("{}{}", self.base_url, path);
-
             let mut req = self.client.request(method.clone(), &url);
             for (k, v) in &headers {
                 req = req.header(*k, v);
@@ -44,27 +58,63 @@ impl KalshiClient {
                 req = req.json(b);
             }
 
-            let resp = req.send().await?;
-            let status = resp.status();
+            quota::record(Provider::Kalshi);
+            let (err, safe_to_repeat) = match req.send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    let status = resp.status();
+                    let text = resp.text().await.map_err(|e| ExchangeError::Transport(e.to_string()))?;
+                    capture::save("kalshi", method.as_str(), &url, status.as_u16(), &text);
+                    return Ok(text);
+                }
+                Ok(resp) => {
+                    let status = resp.status();
+                    let retry_after = retry_after_secs(&resp);
+                    let err_body = resp.text().await.unwrap_or_default();
+                    capture::save("kalshi", method.as_str(), &url, status.as_u16(), &err_body);
+                    let safe = status == 429 || method != reqwest::Method::POST;
+                    (classify_error(status.as_u16(), &err_body, retry_after), safe)
+                }
+                Err(e) => (ExchangeError::Transport(e.to_string()), e.is_connect() || method != reqwest::Method::POST),
+            };
 
-            if status == 429 && attempts < 1 {
-                attempts += 1;
-                tracing::warn!("Kalshi 429 — retrying in 2s");
-                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                continue;
+            if !err.is_retryable() || !safe_to_repeat || attempt >= self.retries {
+                return Err(anyhow::Error::new(err).context(format!("Kalshi {} {}", method, path)));
             }
+            let delay = self.backoff(attempt, &err);
+            attempt += 1;
+            tracing::warn!(
+                "Kalshi {} {}: {} — retry {}/{} in {:.1}s",
+                method, path, err, attempt, self.retries, delay.as_secs_f64()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
 
-            if !status.is_success() {
-                let err_body = resp.text().await.unwrap_or_default();
-                anyhow::bail!("Kalshi {} {} -> {} : {}", method, path, status, err_body);
-            }
+    /// Delay before retry `attempt` (0-based): the base doubled per attempt,
+    /// capped, with up to half taken off at random so clients that failed
+    /// together don't retry together. Never shorter than a 429's Retry-After.
+    fn backoff(&self, attempt: u32, err: &ExchangeError) -> std::time::Duration {
+        use rand::Rng;
+        let ceiling = self.retry_base_ms.saturating_mul(1 << attempt.min(16)).min(MAX_RETRY_DELAY_MS);
+        let ms = rand::thread_rng().gen_range(ceiling / 2..=ceiling);
+        let floor = match err {
+            ExchangeError::RateLimited { retry_after_secs: Some(s) } => s.saturating_mul(1000),
+            _ => 0,
+        };
+        std::time::Duration::from_millis(ms.max(floor))
+    }
 
-            let text = resp.text().await?;
-            return serde_json::from_str::<T>(&text).map_err(|e| {
-                tracing::error!("Deserialize error on {}: {} (body: {}...)", path, e, &text[..text.len().min(300)]);
-                e.into()
-            });
-        }
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let text = self.send(method, path, body).await?;
+        serde_json::from_str::<T>(&text).map_err(|e| {
+            tracing::error!("Deserialize error on {}: {} (body: {}...)", path, e, &text[..text.len().min(300)]);
+            e.into()
+        })
     }
 
     async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
@@ -76,23 +126,69 @@ impl KalshiClient {
     }
 
     async fn delete_request(&self, path: &str) -> Result<()> {
-        let headers = self.auth.headers("DELETE", path);
-        let url = format!("{}{}", self.base_url, path);
+        self.send(reqwest::Method::DELETE, path, None).await.map(|_| ())
+    }
+}
+
+fn retry_after_secs(resp: &reqwest::Response) -> Option<u64> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
 
-        let mut req = self.client.delete(&url);
-        for (k, v) in &headers {
-            req = req.header(*k, v);
+/// Map a non-2xx Kalshi response onto the exchange error taxonomy.
+/// Kalshi bodies look like `{"error":{"code":"insufficient_balance","message":"..."}}`.
+fn classify_error(status: u16, body: &str, retry_after_secs: Option<u64>) -> ExchangeError {
+    let (code, message) = match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(r) => {
+            let msg = match r.error.details {
+                Some(d) if !d.is_empty() => format!("{} ({})", r.error.message, d),
+                _ => r.error.message,
+            };
+            (r.error.code.to_lowercase(), msg)
         }
+        Err(_) => (String::new(), body.to_string()),
+    };
+
+    match status {
+        401 | 403 => ExchangeError::Auth(message),
+        404 => ExchangeError::NotFound(message),
+        429 => ExchangeError::RateLimited { retry_after_secs },
+        500..=599 => ExchangeError::Server { status, body: message },
+        _ => classify_code(&code, message),
+    }
+}
 
-        let resp = req.send().await?;
-        if !resp.status().is_success() {
-            let err_body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Kalshi DELETE {} -> {}", path, err_body);
+/// Map a Kalshi error code with no HTTP status of its own, as on the
+/// per-order entries of a batch response.
+fn classify_code(code: &str, message: String) -> ExchangeError {
+    match code {
+        _ if code.contains("insufficient") => ExchangeError::InsufficientFunds(message),
+        _ if code.contains("closed") || code.contains("paused") || code.contains("not_active") => {
+            ExchangeError::MarketClosed(message)
         }
-        Ok(())
+        _ if code.contains("not_found") => ExchangeError::NotFound(message),
+        _ => ExchangeError::Validation(if code.is_empty() { message } else { format!("{}: {}", code, message) }),
     }
 }
 
+/// A request with no response by then fails as a transport error (and is
+/// retried if that's safe).
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+
+/// Longest wait between retries, before jitter.
+const MAX_RETRY_DELAY_MS: u64 = 10_000;
+
+/// Most orders Kalshi accepts in one batch request.
+const BATCH_MAX: usize = 20;
+
+/// Fills asked for per page.
+const FILLS_PAGE: usize = 200;
+
 #[async_trait]
 impl Exchange for KalshiClient {
     async fn active_markets_for_series(&self, series_ticker: &str) -> Result<Vec<MarketState>> {
@@ -147,9 +243,15 @@ impl Exchange for KalshiClient {
                 open_interest: m.open_interest.unwrap_or(0),
                 expiration_time: m.expected_expiration_time.or(m.expiration_time).unwrap_or_default(),
                 minutes_to_expiry: mins,
+                minutes_since_open: m
+                    .open_time
+                    .as_deref()
+                    .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                    .map(|t| (now - t.with_timezone(&chrono::Utc)).num_seconds() as f64 / 60.0),
                 floor_strike: m.floor_strike,
                 cap_strike: m.cap_strike,
                 strike_type: m.strike_type.clone().unwrap_or_default(),
+                position_limit_cents: m.risk_limit_cents.filter(|l| *l > 0).map(Cents::from_u64),
             })
             .collect();
 
@@ -193,9 +295,20 @@ impl Exchange for KalshiClient {
         Ok(resp
             .orders
             .into_iter()
-            .map(|o| RestingOrder {
-                order_id: o.order_id,
-                ticker: o.ticker,
+            .map(|o| {
+                let side = if o.side.as_deref() == Some("no") { Side::No } else { Side::Yes };
+                let price_cents = match side {
+                    Side::Yes => o.yes_price.unwrap_or(0),
+                    Side::No => o.no_price.unwrap_or(0),
+                };
+                RestingOrder {
+                    order_id: o.order_id,
+                    client_order_id: o.client_order_id.unwrap_or_default(),
+                    ticker: o.ticker,
+                    side,
+                    price_cents,
+                    remaining_count: o.remaining_count.unwrap_or(0),
+                }
             })
             .collect())
     }
@@ -205,6 +318,29 @@ impl Exchange for KalshiClient {
         self.delete_request(&path).await
     }
 
+    async fn cancel_orders(&self, order_ids: &[String]) -> Result<Vec<Result<()>>> {
+        let path = "/trade-api/v2/portfolio/orders/batched";
+        let mut results = Vec::with_capacity(order_ids.len());
+        for chunk in order_ids.chunks(BATCH_MAX) {
+            let body = serde_json::json!({ "ids": chunk });
+            let resp: BatchCancelResponse = self.request(reqwest::Method::DELETE, path, Some(&body)).await?;
+            for id in chunk {
+                results.push(match resp.orders.iter().find(|e| e.id() == Some(id.as_str())) {
+                    Some(BatchCancelEntry { error: Some(err), .. }) => {
+                        Err(anyhow::Error::new(classify_code(&err.code.to_lowercase(), err.message.clone()))
+                            .context(format!("Kalshi batch cancel {}", id)))
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(anyhow::Error::new(ExchangeError::Validation(format!(
+                        "batch cancel response has no entry for {}",
+                        id
+                    )))),
+                });
+            }
+        }
+        Ok(results)
+    }
+
     async fn place_order(&self, order: &OrderRequest) -> Result<OrderResult> {
         let path = "/trade-api/v2/portfolio/orders";
         let side_str = match order.side {
@@ -218,11 +354,56 @@ impl Exchange for KalshiClient {
             "count": order.shares,
             "type": "limit",
             "yes_price": if order.side == Side::Yes { order.price_cents } else { 100 - order.price_cents },
-            "client_order_id": uuid::Uuid::new_v4().to_string(),
+            "client_order_id": format!("{}{}", BOT_ORDER_PREFIX, uuid::Uuid::new_v4()),
         });
 
         let resp: CreateOrderResponse = self.post(path, &body).await?;
         Ok(OrderResult {
+            fee_cents: resp.order.final_fee_cents(),
+            order_id: resp.order.order_id,
+            status: resp.order.status,
+        })
+    }
+
+    async fn sell_position(&self, order: &OrderRequest) -> Result<OrderResult> {
+        let path = "/trade-api/v2/portfolio/orders";
+        let body = serde_json::json!({
+            "ticker": order.ticker,
+            "action": "sell",
+            "side": match order.side {
+                Side::Yes => "yes",
+                Side::No => "no",
+            },
+            "count": order.shares,
+            "type": "limit",
+            "yes_price": if order.side == Side::Yes { order.price_cents } else { 100 - order.price_cents },
+            "client_order_id": format!("{}{}", BOT_ORDER_PREFIX, uuid::Uuid::new_v4()),
+        });
+
+        let resp: CreateOrderResponse = self.post(path, &body).await?;
+        Ok(OrderResult {
+            fee_cents: resp.order.final_fee_cents(),
+            order_id: resp.order.order_id,
+            status: resp.order.status,
+        })
+    }
+
+    async fn amend_order(&self, order_id: &str, order: &OrderRequest) -> Result<OrderResult> {
+        let path = format!("/trade-api/v2/portfolio/orders/{}/amend", order_id);
+        let body = serde_json::json!({
+            "ticker": order.ticker,
+            "action": "buy",
+            "side": match order.side {
+                Side::Yes => "yes",
+                Side::No => "no",
+            },
+            "count": order.shares,
+            "yes_price": if order.side == Side::Yes { order.price_cents } else { 100 - order.price_cents },
+        });
+
+        let resp: CreateOrderResponse = self.post(&path, &body).await?;
+        Ok(OrderResult {
+            fee_cents: resp.order.final_fee_cents(),
             order_id: resp.order.order_id,
             status: resp.order.status,
         })
@@ -235,14 +416,25 @@ impl Exchange for KalshiClient {
         Ok(resp
             .market_positions
             .into_iter()
-            .filter(|p| p.market_exposure.unwrap_or(0) != 0)
-            .map(|p| {
-                let exposure = p.market_exposure.unwrap_or(0);
-                Position {
-                    ticker: p.ticker,
-                    side: if exposure > 0 { Side::Yes } else { Side::No },
-                    count: exposure.unsigned_abs() as u32,
+            .filter_map(|p| {
+                // Contracts held: positive YES, negative NO. Exposure is an
+                // unsigned cost in cents, so without `position` neither the
+                // side nor the count is known
+                let Some(held) = p.position else {
+                    if p.market_exposure.unwrap_or(0) != 0 {
+                        tracing::warn!("Position on {} has no contract count — skipped", p.ticker);
+                    }
+                    return None;
+                };
+                if held == 0 {
+                    return None;
                 }
+                Some(Position {
+                    ticker: p.ticker,
+                    side: if held > 0 { Side::Yes } else { Side::No },
+                    count: held.unsigned_abs() as u32,
+                    exposure_cents: Cents::from_u64(p.market_exposure.unwrap_or(0).unsigned_abs()),
+                })
             })
             .collect())
     }
@@ -256,27 +448,72 @@ impl Exchange for KalshiClient {
             .into_iter()
             .map(|s| {
                 let pnl = s.revenue.unwrap_or(0);
+                let market_result = MarketResult::parse(&s.market_result);
+                if market_result.is_none() && !s.market_result.trim().is_empty() {
+                    tracing::warn!("Settlement for {} has unrecognised market_result {:?}", s.ticker, s.market_result);
+                }
                 Settlement {
                     ticker: s.ticker,
-                    side: Side::Yes,
-                    count: 0,
+                    side: if s.no_count > s.yes_count { Side::No } else { Side::Yes },
+                    count: s.yes_count + s.no_count,
                     price_cents: 0,
-                    result: if pnl > 0 {
-                        "win".into()
-                    } else {
-                        "loss".into()
+                    result: match market_result {
+                        Some(MarketResult::Void) => "void".into(),
+                        _ if pnl > 0 => "win".into(),
+                        _ => "loss".into(),
                     },
-                    pnl_cents: pnl,
+                    pnl_cents: Cents(pnl),
                     settled_time: s.settled_time.unwrap_or_default(),
-                    market_result: s.market_result,
+                    market_result,
                 }
             })
             .collect())
     }
 
-    async fn balance(&self) -> Result<u64> {
+    async fn fills(&self, ticker: &str, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Fill>> {
+        let mut fills = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut path = format!(
+                "/trade-api/v2/portfolio/fills?ticker={}&min_ts={}&limit={}",
+                ticker, since.timestamp(), FILLS_PAGE
+            );
+            if let Some(c) = &cursor {
+                path.push_str(&format!("&cursor={}", c));
+            }
+            let resp: FillsResponse = self.get(&path).await?;
+            fills.extend(resp.fills.into_iter().map(|f| {
+                let side = if f.side.as_deref() == Some("no") { Side::No } else { Side::Yes };
+                let price_cents = match side {
+                    Side::Yes => f.yes_price.unwrap_or(0),
+                    Side::No => f.no_price.or(f.yes_price.map(|p| 100 - p)).unwrap_or(0),
+                };
+                Fill {
+                    order_id: f.order_id,
+                    ticker: f.ticker,
+                    side,
+                    buy: f.action.as_deref() != Some("sell"),
+                    count: f.count,
+                    price_cents,
+                    created_time: f.created_time.unwrap_or_default(),
+                }
+            }));
+            cursor = resp.cursor.filter(|c| !c.is_empty());
+            if cursor.is_none() {
+                return Ok(fills);
+            }
+        }
+    }
+
+    async fn balance(&self) -> Result<Cents> {
         let path = "/trade-api/v2/portfolio/balance";
         let resp: BalanceResponse = self.get(path).await?;
-        Ok(resp.balance)
+        Ok(Cents::from_u64(resp.balance))
+    }
+
+    async fn market_result(&self, ticker: &str) -> Result<Option<MarketResult>> {
+        let path = format!("/trade-api/v2/markets/{}", ticker);
+        let resp: MarketResponse = self.get(&path).await?;
+        Ok(resp.market.result.as_deref().and_then(MarketResult::parse))
     }
 }
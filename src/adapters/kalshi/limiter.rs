@@ -0,0 +1,49 @@
+//! Token bucket pacing every Kalshi request, so a burst of orderbook
+//! fetches on a many-bracket event waits its turn instead of drawing 429s.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct RateLimiter {
+    /// Tokens added per second; 0 = no limit
+    rate: f64,
+    /// Most tokens the bucket holds, i.e. the largest burst
+    burst: f64,
+    state: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// May go negative: callers reserve a token and wait for it to refill
+    tokens: f64,
+    at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(per_sec: f64, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+        Self { rate: per_sec.max(0.0), burst, state: Mutex::new(Bucket { tokens: burst, at: Instant::now() }) }
+    }
+
+    /// Take a token, sleeping until one is free.
+    pub async fn acquire(&self) {
+        if self.rate == 0.0 {
+            return;
+        }
+        let wait = {
+            let mut bucket = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            bucket.tokens = (bucket.tokens + now.duration_since(bucket.at).as_secs_f64() * self.rate).min(self.burst);
+            bucket.at = now;
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64(-bucket.tokens / self.rate)
+            }
+        };
+        if !wait.is_zero() {
+            tracing::debug!("Kalshi rate limiter: waiting {}ms", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
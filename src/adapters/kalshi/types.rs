@@ -1,10 +1,10 @@
+use crate::core::types::Cents;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct MarketsResponse {
     #[serde(default)]
     pub markets: Vec<KalshiMarket>,
-    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -12,15 +12,11 @@ pub struct KalshiMarket {
     pub ticker: String,
     #[serde(default)]
     pub event_ticker: String,
-    pub market_type: Option<String>,
     #[serde(default)]
     pub title: String,
-    pub subtitle: Option<String>,
     pub open_time: Option<String>,
-    pub close_time: Option<String>,
     pub expiration_time: Option<String>,
     pub expected_expiration_time: Option<String>,
-    pub status: Option<String>,
     pub yes_bid: Option<u32>,
     pub yes_ask: Option<u32>,
     pub no_bid: Option<u32>,
@@ -30,10 +26,44 @@ pub struct KalshiMarket {
     pub volume_24h: Option<u64>,
     pub open_interest: Option<u64>,
     pub result: Option<String>,
-    pub series_ticker: Option<String>,
+    #[serde(default, deserialize_with = "strike")]
     pub floor_strike: Option<f64>,
+    #[serde(default, deserialize_with = "strike")]
     pub cap_strike: Option<f64>,
     pub strike_type: Option<String>,
+    /// Max exposure one member may hold in this market
+    #[serde(default)]
+    pub risk_limit_cents: Option<u64>,
+}
+
+/// Strikes usually arrive as JSON numbers, but some series send strings
+/// like "75°" — accept both and normalize through `parse_strike`.
+fn strike<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(f64),
+        Text(String),
+    }
+    Ok(match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Number(v)) if v.is_finite() => Some(v),
+        Some(Raw::Number(_)) | None => None,
+        Some(Raw::Text(t)) => {
+            let parsed = crate::core::types::parse_strike(&t);
+            if parsed.is_none() && !t.trim().is_empty() {
+                tracing::warn!("Unrecognized strike format: {:?}", t);
+            }
+            parsed
+        }
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketResponse {
+    pub market: KalshiMarket,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,6 +86,43 @@ pub struct CreateOrderResponse {
 pub struct OrderInfo {
     pub order_id: String,
     pub status: String,
+    #[serde(default)]
+    pub taker_fees: Option<u32>,
+    #[serde(default)]
+    pub maker_fees: Option<u32>,
+}
+
+impl OrderInfo {
+    /// Fees are only final once the order has executed in full.
+    pub fn final_fee_cents(&self) -> Option<Cents> {
+        if self.status != "executed" {
+            return None;
+        }
+        Some(Cents(self.taker_fees.unwrap_or(0) as i64 + self.maker_fees.unwrap_or(0) as i64))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCancelResponse {
+    #[serde(default)]
+    pub orders: Vec<BatchCancelEntry>,
+}
+
+/// One order's outcome in a batch cancel: the cancelled order, or an error.
+#[derive(Debug, Deserialize)]
+pub struct BatchCancelEntry {
+    #[serde(default)]
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub order: Option<OrderInfo>,
+    #[serde(default)]
+    pub error: Option<ErrorDetail>,
+}
+
+impl BatchCancelEntry {
+    pub fn id(&self) -> Option<&str> {
+        self.order_id.as_deref().or(self.order.as_ref().map(|o| o.order_id.as_str()))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,7 +140,9 @@ pub struct PositionsResponse {
 pub struct KalshiPosition {
     pub ticker: String,
     pub market_exposure: Option<i64>,
-    pub resting_orders_count: Option<u32>,
+    /// Contracts held: positive YES, negative NO
+    #[serde(default)]
+    pub position: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -85,8 +154,12 @@ pub struct OrdersResponse {
 #[derive(Debug, Deserialize)]
 pub struct KalshiOrder {
     pub order_id: String,
+    pub client_order_id: Option<String>,
     pub ticker: String,
-    pub status: String,
+    pub side: Option<String>,
+    pub yes_price: Option<u32>,
+    pub no_price: Option<u32>,
+    pub remaining_count: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -101,4 +174,66 @@ pub struct KalshiSettlement {
     pub market_result: String,
     pub revenue: Option<i64>,
     pub settled_time: Option<String>,
+    #[serde(default)]
+    pub yes_count: u32,
+    #[serde(default)]
+    pub no_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FillsResponse {
+    #[serde(default)]
+    pub fills: Vec<KalshiFill>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KalshiFill {
+    pub order_id: String,
+    pub ticker: String,
+    pub side: Option<String>,
+    pub action: Option<String>,
+    #[serde(default)]
+    pub count: u32,
+    pub yes_price: Option<u32>,
+    pub no_price: Option<u32>,
+    pub created_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorResponse {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorDetail {
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+    pub details: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strikes(json: &str) -> (Option<f64>, Option<f64>) {
+        let m: KalshiMarket = serde_json::from_str(json).unwrap();
+        (m.floor_strike, m.cap_strike)
+    }
+
+    #[test]
+    fn strikes_accept_numbers_and_text() {
+        assert_eq!(strikes(r#"{"ticker":"T","floor_strike":74.5,"cap_strike":76.5}"#), (Some(74.5), Some(76.5)));
+        assert_eq!(strikes(r#"{"ticker":"T","floor_strike":"74.5°","cap_strike":"76.5 ºF"}"#), (Some(74.5), Some(76.5)));
+        assert_eq!(strikes(r#"{"ticker":"T","floor_strike":"2\""}"#), (Some(2.0), None));
+    }
+
+    #[test]
+    fn missing_null_or_garbled_strikes_are_none() {
+        assert_eq!(strikes(r#"{"ticker":"T"}"#), (None, None));
+        assert_eq!(strikes(r#"{"ticker":"T","floor_strike":null,"cap_strike":""}"#), (None, None));
+        assert_eq!(strikes(r#"{"ticker":"T","floor_strike":"warm"}"#), (None, None));
+    }
 }
@@ -0,0 +1,132 @@
+//! STRATEGY=ml: an ONNX model trained offline on the research dataset
+//! (`kalshi-bot dataset`) prices each bracket from `dataset::FEATURES`,
+//! and the rules brain turns its YES probability into a decision — the
+//! same fees, edge floor, price cap, spread and liquidity vetoes and
+//! sizing as STRATEGY=rules. Inference is local (tract); no network calls.
+
+use crate::core::dataset;
+use crate::core::rules_brain;
+use crate::core::types::*;
+use crate::ports::brain::Brain;
+use anyhow::Result;
+use async_trait::async_trait;
+use tract_onnx::prelude::*;
+
+type Plan = SimplePlan<TypedFact, Box<dyn TypedOp>, TypedModel>;
+
+pub struct MlBrain {
+    model: Plan,
+    rules: Box<dyn Brain>,
+}
+
+impl MlBrain {
+    /// Fails when the file is missing or isn't ONNX, uses an operator tract
+    /// doesn't support, or doesn't map a `FEATURES` row to a probability.
+    pub fn load(path: &str, rules: Box<dyn Brain>) -> Result<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(path)
+            .and_then(|m| m.with_input_fact(0, f32::fact([1, dataset::FEATURES.len()]).into()))
+            .and_then(|m| m.into_optimized())
+            .and_then(|m| m.into_runnable())
+            .map_err(|e| anyhow::anyhow!("{}: {:#}", path, e))?;
+        let out = run(&model, &[0.0; dataset::FEATURES.len()])
+            .map_err(|e| anyhow::anyhow!("{}: doesn't run on {} features: {:#}", path, dataset::FEATURES.len(), e))?;
+        if !(1..=2).contains(&out.len()) {
+            anyhow::bail!("{}: outputs {} values; expected P(YES) or [P(NO), P(YES)]", path, out.len());
+        }
+        Ok(Self { model, rules })
+    }
+
+    /// The model's YES probability for this bracket, None when it can't
+    /// give a usable one.
+    fn probability(&self, ctx: &DecisionContext) -> Option<f64> {
+        let inputs = DecisionInputs::new(&ctx.market, ctx.weather.as_ref());
+        let row = dataset::features(&inputs, Some(ctx.market.minutes_to_expiry), &chrono::Utc::now().to_rfc3339());
+        let out = match run(&self.model, &row) {
+            Ok(out) => out,
+            Err(e) => {
+                tracing::warn!("ML model failed on {}: {:#}", ctx.market.ticker, e);
+                return None;
+            }
+        };
+        yes_probability(&out)
+    }
+}
+
+/// One `FEATURES` row through the model: the values of its last float
+/// output, so a classifier's label output ahead of its probabilities is
+/// skipped.
+fn run(model: &Plan, row: &[f32]) -> Result<Vec<f32>> {
+    let input: Tensor = tract_ndarray::Array2::from_shape_vec((1, row.len()), row.to_vec())?.into();
+    let outputs = model.run(tvec!(input.into()))?;
+    let probabilities = outputs
+        .iter()
+        .rev()
+        .find(|t| t.datum_type() == f32::datum_type())
+        .ok_or_else(|| anyhow::anyhow!("no float output"))?;
+    Ok(probabilities.as_slice::<f32>()?.to_vec())
+}
+
+/// P(YES) from either output layout; None outside [0, 1] or NaN.
+fn yes_probability(out: &[f32]) -> Option<f64> {
+    let p = *out.last()? as f64;
+    (0.0..=1.0).contains(&p).then_some(p)
+}
+
+#[async_trait]
+impl Brain for MlBrain {
+    async fn decide(&self, ctx: &DecisionContext) -> Result<TradeDecision> {
+        // An operator's fair value still beats the model's
+        if ctx.fair_override.is_some() {
+            return self.rules.decide(ctx).await;
+        }
+        let Some(p) = self.probability(ctx) else {
+            return Ok(rules_brain::pass(PassReason::NoProbability, "ML model gave no usable probability"));
+        };
+        tracing::info!("ML model: {:.1}% YES on {}", p * 100.0, ctx.market.ticker);
+        let mut priced = ctx.clone();
+        priced.fair_override = Some(p);
+        self.rules.decide(&priced).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// sigmoid(0.1 × ensemble_mean − 7) over the 17 features
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/logistic.onnx");
+
+    fn fixture() -> MlBrain {
+        let rules = Box::new(rules_brain::RulesBrain::new(StrategyParams::default()));
+        MlBrain::load(FIXTURE, rules).expect("fixture loads")
+    }
+
+    #[test]
+    fn fixture_prices_from_ensemble_mean() {
+        let brain = fixture();
+        let mean = dataset::FEATURES.iter().position(|f| *f == "ensemble_mean").unwrap();
+        for (value, expected) in [(70.0, 0.5), (80.0, 1.0 / (1.0 + (-1.0f64).exp()))] {
+            let mut row = vec![0.0; dataset::FEATURES.len()];
+            row[mean] = value;
+            let p = yes_probability(&run(&brain.model, &row).unwrap()).unwrap();
+            assert!((p - expected).abs() < 1e-6, "mean {}: {} != {}", value, p, expected);
+        }
+    }
+
+    #[test]
+    fn rejects_unusable_output() {
+        assert_eq!(yes_probability(&[f32::NAN]), None);
+        assert_eq!(yes_probability(&[1.5]), None);
+        assert_eq!(yes_probability(&[0.3, 0.7]), Some(0.7f32 as f64));
+    }
+
+    #[test]
+    fn load_fails_on_garbage() {
+        let path = std::env::temp_dir().join(format!("kalshi-bot-garbage-{}.onnx", std::process::id()));
+        std::fs::write(&path, b"not onnx").unwrap();
+        let rules = Box::new(rules_brain::RulesBrain::new(StrategyParams::default()));
+        assert!(MlBrain::load(path.to_str().unwrap(), rules).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
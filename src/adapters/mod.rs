@@ -1,3 +1,8 @@
 pub mod weather;
+pub mod bucket;
 pub mod kalshi;
+pub mod ml_brain;
+pub mod notifier;
 pub mod openrouter;
+pub mod simulated;
+pub mod telegram;
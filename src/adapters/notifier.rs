@@ -0,0 +1,54 @@
+use crate::core::types::Config;
+use crate::adapters::telegram::TelegramBot;
+use crate::ports::notifier::Notifier;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Telegram when configured, else webhook if NOTIFY_WEBHOOK_URL is set,
+/// otherwise the log.
+pub fn notifier_for(config: &Config) -> Box<dyn Notifier> {
+    if let Some(bot) = TelegramBot::from_config(config) {
+        return Box::new(bot);
+    }
+    match &config.notify_webhook_url {
+        Some(url) => Box::new(WebhookNotifier {
+            client: reqwest::Client::new(),
+            url: url.clone(),
+        }),
+        None => Box::new(LogNotifier),
+    }
+}
+
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+        tracing::info!("{}\n{}", subject, body);
+        Ok(())
+    }
+}
+
+/// Posts `{"text": ...}` — the incoming-webhook shape Slack and most chat
+/// tools accept.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+        let text = format!("*{}*\n```\n{}\n```", subject, body);
+        let resp = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Notify webhook returned {}", resp.status());
+        }
+        Ok(())
+    }
+}
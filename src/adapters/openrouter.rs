@@ -30,7 +30,7 @@ impl Brain for OpenRouterClient {
         };
 
         let prompt = format!(
-            "{prompt}\n\n---\n## STATS\n{stats}\n\n---\n## LAST {n} TRADES\n{ledger}\n\n---\n## MARKET\n{market}\n\n---\n## ORDERBOOK\nYes bids: {yes_ob}\nNo bids: {no_ob}{weather}",
+            "{prompt}\n\n---\n## STATS\n{stats}\n\n---\n## LAST {n} TRADES\n{ledger}\n\n---\n## MARKET\n{market}\n\n---\n## ORDERBOOK\nYes bids: {yes_ob}\nNo bids: {no_ob}{weather}\n\n---\n## CONTEXT (JSON)\n```json\n{context}\n```\n\n{response_format}",
             prompt = ctx.prompt_md,
             stats = format_stats(&ctx.stats),
             n = ctx.last_n_trades.len(),
@@ -39,6 +39,8 @@ impl Brain for OpenRouterClient {
             yes_ob = format_ob_side(&ctx.orderbook.yes),
             no_ob = format_ob_side(&ctx.orderbook.no),
             weather = weather_section,
+            context = context_json(ctx),
+            response_format = RESPONSE_FORMAT,
         );
 
         let body = serde_json::json!({
@@ -64,8 +66,102 @@ impl Brain for OpenRouterClient {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("No content in OpenRouter response"))?;
 
-        parse_decision(content)
+        parse_decision(content, &ctx.market.ticker)
     }
+
+    fn uses_prompt(&self) -> bool {
+        true
+    }
+}
+
+/// Rough cap on the JSON context block (~4 chars per token → ~1k tokens).
+const CONTEXT_JSON_MAX_CHARS: usize = 4000;
+
+const RESPONSE_FORMAT: &str = "Respond with ONE ```json block and nothing else:\n\
+{\"ticker\": \"<echo context.market.ticker>\", \"action\": \"BUY\"|\"PASS\", \"side\": \"yes\"|\"no\"|null, \
+\"shares\": int|null, \"max_price_cents\": int|null, \"edge_magnitude\": float, \"reasoning\": string}";
+
+/// Machine-readable copy of the decision context. Bulky, low-value fields
+/// are dropped first when the block would exceed `CONTEXT_JSON_MAX_CHARS`.
+fn context_json(ctx: &DecisionContext) -> String {
+    let m = &ctx.market;
+    let mut context = serde_json::json!({
+        "market": {
+            "ticker": m.ticker,
+            "event_ticker": m.event_ticker,
+            "strike_type": m.strike_type,
+            "floor_strike": m.floor_strike,
+            "cap_strike": m.cap_strike,
+            "yes_bid": m.yes_bid,
+            "yes_ask": m.yes_ask,
+            "no_bid": m.no_bid,
+            "no_ask": m.no_ask,
+            "volume_24h": m.volume_24h,
+            "open_interest": m.open_interest,
+            "minutes_to_expiry": (m.minutes_to_expiry * 10.0).round() / 10.0,
+        },
+        "orderbook": {
+            "yes": ctx.orderbook.yes.iter().take(5).collect::<Vec<_>>(),
+            "no": ctx.orderbook.no.iter().take(5).collect::<Vec<_>>(),
+        },
+        "stats": {
+            "total_trades": ctx.stats.total_trades,
+            "win_rate": ctx.stats.win_rate,
+            "today_pnl_cents": ctx.stats.today_pnl_cents,
+            "current_streak": ctx.stats.current_streak,
+        },
+        "implied_yes": match (&ctx.implied, MarketType::from_market(m)) {
+            (Some(d), Some(mt)) => Some(d.probability(&mt)),
+            _ => None,
+        },
+        "operator_fair_yes": ctx.fair_override,
+        "weather": ctx.weather.as_ref().map(|w| serde_json::json!({
+            "city": w.city,
+            "current_temp_f": w.current_temp_f,
+            "observed_high_f": w.observed_high_f,
+            "observed_high_so_far": w.observed_high_so_far,
+            "nws_forecast_high": w.nws_forecast_high,
+            "open_meteo_forecast_high": w.open_meteo_forecast_high,
+            "ensemble": w.ensemble,
+            "ensemble_run": w.ensemble_run,
+            "nbm": w.nbm,
+            "hrrr_high_f": w.hrrr_high_f,
+            "quorum": w.quorum,
+            "confidence": w.confidence,
+            "buckets": w.bucket_probabilities.iter()
+                .map(|b| serde_json::json!({"label": b.label, "p": (b.probability * 1000.0).round() / 1000.0}))
+                .collect::<Vec<_>>(),
+        })),
+        "exposure": {
+            "event_positions": ctx.exposure.positions.iter().map(|p| serde_json::json!({
+                "ticker": p.ticker, "side": p.side, "count": p.count, "exposure_cents": p.exposure_cents,
+            })).collect::<Vec<_>>(),
+            "event_resting": ctx.exposure.resting.iter().map(|o| serde_json::json!({
+                "ticker": o.ticker, "side": o.side, "price_cents": o.price_cents, "remaining": o.remaining_count,
+            })).collect::<Vec<_>>(),
+            "portfolio_held_cents": ctx.exposure.portfolio_held_cents,
+            "portfolio_resting_cents": ctx.exposure.portfolio_resting_cents,
+            "portfolio_markets": ctx.exposure.portfolio_markets,
+        },
+        "last_trades": ctx.last_n_trades.iter().rev().take(5).map(|t| serde_json::json!({
+            "ticker": t.ticker, "side": t.side, "shares": t.shares, "price": t.price, "result": t.result,
+        })).collect::<Vec<_>>(),
+    });
+
+    let mut text = context.to_string();
+    for drop in ["last_trades", "buckets", "orderbook"] {
+        if text.len() <= CONTEXT_JSON_MAX_CHARS {
+            break;
+        }
+        if let Some(obj) = context.as_object_mut() {
+            obj.remove(drop);
+        }
+        if let Some(weather) = context["weather"].as_object_mut() {
+            weather.remove(drop);
+        }
+        text = context.to_string();
+    }
+    text
 }
 
 fn format_stats(s: &Stats) -> String {
@@ -136,11 +232,22 @@ fn format_weather(w: &WeatherSnapshot) -> String {
     }
 
     s.push_str(&format!("Open-Meteo forecast high: {:.1}°F\n", w.open_meteo_forecast_high));
+    for a in &w.alerts {
+        s.push_str(&format!("Active alert: {} — {}\n", a.event, a.headline));
+    }
 
     if let Some(ref ens) = w.ensemble {
         s.push_str(&format!("Ensemble: {}\n", indicators::ensemble_summary(ens)));
     }
 
+    if let Some(hrrr) = w.hrrr_high_f {
+        s.push_str(&format!("HRRR high (same-day run): {:.1}°F\n", hrrr));
+    }
+    if let Some(ref nbm) = w.nbm {
+        let quantiles: Vec<String> = nbm.quantiles.iter().map(|q| format!("P{:.0} {:.0}°F", q.pct, q.value)).collect();
+        s.push_str(&format!("NBM {} high percentiles: {}\n", nbm.station, quantiles.join(", ")));
+    }
+
     if !w.bucket_probabilities.is_empty() {
         s.push_str("\nTemperature bucket probabilities (ensemble-derived):\n");
         for b in &w.bucket_probabilities {
@@ -159,7 +266,7 @@ fn format_weather(w: &WeatherSnapshot) -> String {
     s
 }
 
-fn parse_decision(raw: &str) -> Result<TradeDecision> {
+fn parse_decision(raw: &str, ticker: &str) -> Result<TradeDecision> {
     let json_str = if let Some(s) = raw.find("```json") {
         let start = s + 7;
         let end = raw[start..]
@@ -172,15 +279,163 @@ fn parse_decision(raw: &str) -> Result<TradeDecision> {
     } else if let (Some(s), Some(e)) = (raw.find('{'), raw.rfind('}')) {
         &raw[s..=e]
     } else {
-        return Ok(TradeDecision {
-            action: Action::Pass,
-            side: None,
-            shares: None,
-            max_price_cents: None,
-            reasoning: "Failed to parse AI response".into(),
-            edge_magnitude: 0.0,
-        });
+        return Ok(unparseable("Failed to parse AI response"));
     };
 
-    serde_json::from_str(json_str.trim()).map_err(Into::into)
+    // The model is asked to echo the ticker; a mismatch means it answered
+    // about some other market and the decision can't be trusted.
+    let value: serde_json::Value = serde_json::from_str(json_str.trim())?;
+    if let Some(echoed) = value.get("ticker").and_then(|t| t.as_str()) {
+        if echoed != ticker {
+            tracing::warn!("LLM echoed ticker {} but was asked about {}", echoed, ticker);
+            return Ok(unparseable("AI response was for a different ticker"));
+        }
+    }
+    serde_json::from_value(value).map_err(Into::into)
+}
+
+fn unparseable(reason: &str) -> TradeDecision {
+    TradeDecision {
+        action: Action::Pass,
+        side: None,
+        shares: None,
+        max_price_cents: None,
+        reasoning: reason.into(),
+        edge_magnitude: 0.0,
+        pass_reason: Some(PassReason::Unparseable),
+        counterfactual: None,
+        model_probability: None,
+        edge: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::stats;
+
+    const TICKER: &str = "KXHIGHNY-26OCT16-B74.5";
+
+    fn context(buckets: usize, trades: usize) -> DecisionContext {
+        let weather: WeatherSnapshot = serde_json::from_value(serde_json::json!({
+            "city": "New York",
+            "current_temp_f": 68.0,
+            "open_meteo_forecast_high": 75.0,
+            "remaining_forecast_high_f": 75.0,
+            "hourly_forecasts": [],
+            "ensemble": {
+                "model_count": 51, "mean_high": 75.2, "min_high": 69.8, "max_high": 80.1, "std_dev": 2.4,
+                "p10": 72.1, "p25": 73.6, "p75": 76.8, "p90": 78.3,
+            },
+            "bucket_probabilities": (0..buckets).map(|i| serde_json::json!({
+                "label": format!("{}-{}°F", 60 + i, 61 + i),
+                "lower": 60.0 + i as f64,
+                "upper": 61.0 + i as f64,
+                "probability": 1.0 / buckets as f64,
+            })).collect::<Vec<_>>(),
+            "ensemble_member_highs": (0..51).map(|i| 70.0 + i as f64 / 10.0).collect::<Vec<_>>(),
+            "ensemble_run": "2026-10-16T00:00:00Z",
+            "confidence": "Medium",
+        }))
+        .unwrap();
+        let row = LedgerRow {
+            timestamp: "2026-10-15T14:00:00Z".into(),
+            ticker: "KXHIGHNY-26OCT15-B72.5".into(),
+            side: "yes".into(),
+            shares: 3,
+            price: 41,
+            result: "won".into(),
+            pnl_cents: Cents(177),
+            cumulative_cents: Cents(177),
+            order_id: "ord-1".into(),
+            cancel_reason: None,
+            event_ticker: "KXHIGHNY-26OCT15".into(),
+            city: "New York".into(),
+        };
+        DecisionContext {
+            prompt_md: String::new(),
+            stats: stats::compute(&[]),
+            last_n_trades: vec![row; trades],
+            market: MarketState {
+                ticker: TICKER.into(),
+                event_ticker: "KXHIGHNY-26OCT16".into(),
+                title: "NYC high 74.5-76.5°".into(),
+                yes_bid: Some(38),
+                yes_ask: Some(41),
+                no_bid: Some(58),
+                no_ask: Some(62),
+                last_price: Some(40),
+                volume: 5200,
+                volume_24h: 1800,
+                open_interest: 950,
+                expiration_time: "2026-10-17T04:59:00Z".into(),
+                minutes_to_expiry: 612.4,
+                minutes_since_open: Some(900.0),
+                floor_strike: Some(74.5),
+                cap_strike: Some(76.5),
+                strike_type: "between".into(),
+                position_limit_cents: None,
+            },
+            orderbook: Orderbook {
+                yes: (1..=40).map(|p| (p, 100)).collect(),
+                no: (1..=58).map(|p| (p, 100)).collect(),
+            },
+            weather: Some(weather),
+            implied: None,
+            exposure: Exposure::default(),
+            fair_override: None,
+            features: FeatureSwitches::default(),
+        }
+    }
+
+    #[test]
+    fn context_block_keeps_everything_when_small() {
+        let text = context_json(&context(6, 3));
+        assert!(text.len() <= CONTEXT_JSON_MAX_CHARS, "{} chars", text.len());
+        let v: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(v["market"]["ticker"], TICKER);
+        assert_eq!(v["orderbook"]["yes"].as_array().unwrap().len(), 5);
+        assert_eq!(v["weather"]["buckets"].as_array().unwrap().len(), 6);
+        assert_eq!(v["last_trades"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn context_block_fits_the_budget_when_large() {
+        let text = context_json(&context(120, 20));
+        assert!(text.len() <= CONTEXT_JSON_MAX_CHARS, "{} chars", text.len());
+        let v: serde_json::Value = serde_json::from_str(&text).unwrap();
+        // Market and forecast survive; bulky extras go first
+        assert_eq!(v["market"]["ticker"], TICKER);
+        assert!(v["weather"]["ensemble"].is_object());
+        assert!(v.get("last_trades").is_none());
+        assert!(v["weather"].get("buckets").is_none());
+    }
+
+    #[test]
+    fn decision_with_echoed_ticker_parses() {
+        let raw = format!(
+            "Here you go:\n```json\n{{\"ticker\": \"{}\", \"action\": \"BUY\", \"side\": \"yes\", \"shares\": 2, \
+             \"max_price_cents\": 42, \"edge_magnitude\": 0.12, \"reasoning\": \"ensemble favours it\"}}\n```",
+            TICKER
+        );
+        let d = parse_decision(&raw, TICKER).unwrap();
+        assert_eq!(d.action, Action::Buy);
+        assert_eq!(d.side, Some(Side::Yes));
+        assert_eq!(d.shares, Some(2));
+    }
+
+    #[test]
+    fn decision_for_another_ticker_is_unparseable() {
+        let raw = r#"{"ticker": "KXHIGHCHI-26OCT16-B60.5", "action": "BUY", "side": "no", "shares": 1,
+            "max_price_cents": 30, "edge_magnitude": 0.2, "reasoning": "x"}"#;
+        let d = parse_decision(raw, TICKER).unwrap();
+        assert_eq!(d.action, Action::Pass);
+        assert_eq!(d.pass_reason, Some(PassReason::Unparseable));
+    }
+
+    #[test]
+    fn decision_without_json_is_unparseable() {
+        let d = parse_decision("I would pass on this one.", TICKER).unwrap();
+        assert_eq!(d.pass_reason, Some(PassReason::Unparseable));
+    }
 }
@@ -0,0 +1,396 @@
+//! Paper-trading exchange (`PAPER_SIMULATE=true`). Market data comes from
+//! the real exchange; orders fill against its live orderbook, positions and
+//! balance live in brain/paper_exchange.json, and a position settles once
+//! the weather feed reports its day's final high.
+
+use crate::core::risk;
+use crate::core::types::*;
+use crate::ports::exchange::{Exchange, ExchangeError};
+use crate::ports::weather_feed::WeatherFeed;
+use crate::storage;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+pub struct SimulatedExchange {
+    market: Box<dyn Exchange>,
+    weather: Arc<dyn WeatherFeed>,
+    cities: Vec<CityConfig>,
+    book: Mutex<PaperBook>,
+}
+
+impl SimulatedExchange {
+    /// Resumes the saved paper account, or opens one with `PAPER_BALANCE_CENTS`.
+    pub fn new(market: Box<dyn Exchange>, weather: Arc<dyn WeatherFeed>, config: &Config) -> Result<Self> {
+        let book = match storage::paper_book()? {
+            Some(book) => book,
+            None => {
+                tracing::info!("Opening simulated account with {}", config.paper_balance_cents);
+                PaperBook::new(config.paper_balance_cents)
+            }
+        };
+        Ok(Self { market, weather, cities: config.cities.clone(), book: Mutex::new(book) })
+    }
+
+    /// Apply `f` to the book and save it.
+    fn update<T>(&self, f: impl FnOnce(&mut PaperBook) -> Result<T>) -> Result<T> {
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let out = f(&mut book)?;
+        storage::write_paper_book(&book)?;
+        Ok(out)
+    }
+
+    fn snapshot(&self) -> PaperBook {
+        self.book.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn city_for(&self, ticker: &str) -> Option<&CityConfig> {
+        self.cities.iter().find(|c| ticker.starts_with(&format!("{}-", c.series_ticker)))
+    }
+
+    /// The market an order is for, from the exchange's active listing.
+    async fn lookup(&self, ticker: &str) -> Result<(MarketType, String)> {
+        let city = self
+            .city_for(ticker)
+            .ok_or_else(|| ExchangeError::Validation(format!("{} is not a configured city's market", ticker)))?;
+        let market = self
+            .market
+            .active_markets_for_series(&city.series_ticker)
+            .await?
+            .into_iter()
+            .find(|m| m.ticker == ticker)
+            .ok_or_else(|| ExchangeError::MarketClosed(format!("{} is not open", ticker)))?;
+        let market_type = MarketType::from_market(&market)
+            .ok_or_else(|| ExchangeError::Validation(format!("{} has no readable strikes", ticker)))?;
+        Ok((market_type, city.name.clone()))
+    }
+
+    /// Settle every position whose day has a final high. Highs are fetched
+    /// once per city and day.
+    async fn settle_due(&self) -> Result<()> {
+        let due: Vec<(String, String)> = {
+            let mut keys: Vec<(String, String)> =
+                self.snapshot().positions.iter().map(|p| (p.city.clone(), p.settles_on.clone())).collect();
+            keys.sort();
+            keys.dedup();
+            keys
+        };
+        for (city_name, day) in due {
+            let Some(city) = self.cities.iter().find(|c| c.name == city_name) else { continue };
+            let Some(high) = self.weather.daily_high(city, &day).await? else { continue };
+            self.update(|book| {
+                let (settled, open): (Vec<PaperPosition>, Vec<PaperPosition>) = std::mem::take(&mut book.positions)
+                    .into_iter()
+                    .partition(|p| p.city == city_name && p.settles_on == day);
+                book.positions = open;
+                for p in settled {
+                    let result = if p.market.yes_at(high) { MarketResult::Yes } else { MarketResult::No };
+                    let won = result.wins(&p.side) == Some(true);
+                    let payout = if won { Cents::for_contracts(p.count, 100) } else { Cents::ZERO };
+                    book.balance_cents += payout;
+                    tracing::info!(
+                        "SIM settled {} {:?} {}x: high {:.1}°F → {} ({})",
+                        p.ticker, p.side, p.count, high, result, payout - p.cost_cents
+                    );
+                    book.settlements.push(Settlement {
+                        ticker: p.ticker,
+                        side: p.side,
+                        count: p.count,
+                        price_cents: (p.cost_cents.0 / p.count.max(1) as i64) as u32,
+                        result: if won { "win".into() } else { "loss".into() },
+                        pnl_cents: payout - p.cost_cents,
+                        settled_time: chrono::Utc::now().to_rfc3339(),
+                        market_result: Some(result),
+                    });
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Contracts taken from the book and what they cost.
+struct Take {
+    shares: u32,
+    /// Price paid plus fees
+    cost: Cents,
+    fee: Cents,
+}
+
+/// Take offers at or below `limit_cents`, cheapest first, up to `shares`,
+/// paying the taker fee on each level.
+fn fill(asks: &[(u32, u32)], limit_cents: u32, shares: u32) -> Take {
+    let mut f = Take { shares: 0, cost: Cents::ZERO, fee: Cents::ZERO };
+    for &(ask, available) in asks.iter().filter(|(ask, _)| *ask <= limit_cents) {
+        let take = available.min(shares - f.shares);
+        if take == 0 {
+            break;
+        }
+        let fee = risk::taker_fee_cents(take, ask);
+        f.cost += Cents::for_contracts(take, ask) + fee;
+        f.fee += fee;
+        f.shares += take;
+    }
+    f
+}
+
+/// Add filled contracts to the book's position on that market and side,
+/// and log the fill at its average price before fees.
+fn add_position(book: &mut PaperBook, order: &PaperOrder, fill: &Take) {
+    book.balance_cents -= fill.cost;
+    record_fill(book, Fill {
+        order_id: order.order_id.clone(),
+        ticker: order.ticker.clone(),
+        side: order.side.clone(),
+        buy: true,
+        count: fill.shares,
+        price_cents: ((fill.cost - fill.fee).0 / fill.shares as i64) as u32,
+        created_time: chrono::Utc::now().to_rfc3339(),
+    });
+    let settles_on = event_date(&order.ticker).unwrap_or_default();
+    match book.positions.iter_mut().find(|p| p.ticker == order.ticker && p.side == order.side) {
+        Some(p) => {
+            p.count += fill.shares;
+            p.cost_cents += fill.cost;
+        }
+        None => book.positions.push(PaperPosition {
+            ticker: order.ticker.clone(),
+            side: order.side.clone(),
+            count: fill.shares,
+            cost_cents: fill.cost,
+            market: order.market.clone(),
+            city: order.city.clone(),
+            settles_on,
+        }),
+    }
+}
+
+/// Log a fill, dropping those older than a month.
+fn record_fill(book: &mut PaperBook, fill: Fill) {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(31)).to_rfc3339();
+    book.fills.retain(|f| f.created_time >= cutoff);
+    book.fills.push(fill);
+}
+
+/// Cash tied up by resting orders, at their limit prices.
+fn reserved(book: &PaperBook) -> Cents {
+    book.resting.iter().map(|o| Cents::for_contracts(o.remaining, o.price_cents)).sum()
+}
+
+#[async_trait]
+impl Exchange for SimulatedExchange {
+    async fn active_markets_for_series(&self, series_ticker: &str) -> Result<Vec<MarketState>> {
+        self.market.active_markets_for_series(series_ticker).await
+    }
+
+    async fn orderbook(&self, ticker: &str) -> Result<Orderbook> {
+        self.market.orderbook(ticker).await
+    }
+
+    /// Resting paper orders, after filling any the book now reaches.
+    async fn resting_orders(&self) -> Result<Vec<RestingOrder>> {
+        let tickers: std::collections::BTreeSet<String> =
+            self.snapshot().resting.iter().map(|o| o.ticker.clone()).collect();
+        let mut books = std::collections::HashMap::new();
+        for ticker in tickers {
+            books.insert(ticker.clone(), self.market.orderbook(&ticker).await?);
+        }
+        self.update(|book| {
+            let resting = std::mem::take(&mut book.resting);
+            for mut order in resting {
+                if let Some(ob) = books.get(&order.ticker) {
+                    let f = fill(&ob.asks(&order.side), order.price_cents, order.remaining);
+                    if f.shares > 0 {
+                        tracing::info!("SIM fill: {} {}x of {} @ ≤{}¢", order.order_id, f.shares, order.ticker, order.price_cents);
+                        add_position(book, &order, &f);
+                        order.remaining -= f.shares;
+                    }
+                }
+                if order.remaining > 0 {
+                    book.resting.push(order);
+                }
+            }
+            Ok(book
+                .resting
+                .iter()
+                .map(|o| RestingOrder {
+                    order_id: o.order_id.clone(),
+                    client_order_id: format!("{}{}", BOT_ORDER_PREFIX, o.order_id),
+                    ticker: o.ticker.clone(),
+                    side: o.side.clone(),
+                    price_cents: o.price_cents,
+                    remaining_count: o.remaining,
+                })
+                .collect())
+        })
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<()> {
+        self.update(|book| {
+            let before = book.resting.len();
+            book.resting.retain(|o| o.order_id != order_id);
+            if book.resting.len() == before {
+                return Err(ExchangeError::NotFound(format!("order {} is not resting", order_id)).into());
+            }
+            Ok(())
+        })
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResult> {
+        if order.shares == 0 || !(1..=99).contains(&order.price_cents) {
+            return Err(ExchangeError::Validation(format!("{}x @ {}¢", order.shares, order.price_cents)).into());
+        }
+        let (market, city) = self.lookup(&order.ticker).await?;
+        let asks = self.market.orderbook(&order.ticker).await?.asks(&order.side);
+
+        self.update(|book| {
+            let worst_case =
+                Cents::for_contracts(order.shares, order.price_cents) + risk::taker_fee_cents(order.shares, order.price_cents);
+            let free = book.balance_cents.saturating_sub_to_zero(reserved(book));
+            if worst_case > free {
+                return Err(ExchangeError::InsufficientFunds(format!("{} needed, {} free", worst_case, free)).into());
+            }
+
+            let mut paper = PaperOrder {
+                order_id: format!("sim-{}", book.next_order),
+                ticker: order.ticker.clone(),
+                side: order.side.clone(),
+                price_cents: order.price_cents,
+                remaining: order.shares,
+                market,
+                city,
+            };
+            book.next_order += 1;
+
+            let f = fill(&asks, order.price_cents, order.shares);
+            if f.shares > 0 {
+                add_position(book, &paper, &f);
+                paper.remaining -= f.shares;
+            }
+            let result = OrderResult {
+                order_id: paper.order_id.clone(),
+                status: if paper.remaining == 0 { "executed" } else { "resting" }.into(),
+                fee_cents: (paper.remaining == 0).then_some(f.fee),
+            };
+            if paper.remaining > 0 {
+                book.resting.push(paper);
+            }
+            Ok(result)
+        })
+    }
+
+    async fn amend_order(&self, order_id: &str, order: &OrderRequest) -> Result<OrderResult> {
+        let asks = self.market.orderbook(&order.ticker).await?.asks(&order.side);
+        self.update(|book| {
+            let i = book
+                .resting
+                .iter()
+                .position(|o| o.order_id == order_id)
+                .ok_or_else(|| ExchangeError::NotFound(format!("order {} is not resting", order_id)))?;
+            let mut paper = book.resting.remove(i);
+            paper.price_cents = order.price_cents;
+            paper.remaining = order.shares;
+            let f = fill(&asks, paper.price_cents, paper.remaining);
+            if f.shares > 0 {
+                add_position(book, &paper, &f);
+                paper.remaining -= f.shares;
+            }
+            let result = OrderResult {
+                order_id: paper.order_id.clone(),
+                status: if paper.remaining == 0 { "executed" } else { "resting" }.into(),
+                fee_cents: None,
+            };
+            if paper.remaining > 0 {
+                book.resting.push(paper);
+            }
+            Ok(result)
+        })
+    }
+
+    /// Sells into the bids at or above the limit right away; whatever they
+    /// can't take is dropped rather than rested.
+    async fn sell_position(&self, order: &OrderRequest) -> Result<OrderResult> {
+        let bids = self.market.orderbook(&order.ticker).await?.bids(&order.side);
+        self.update(|book| {
+            let i = book
+                .positions
+                .iter()
+                .position(|p| p.ticker == order.ticker && p.side == order.side)
+                .ok_or_else(|| ExchangeError::Validation(format!("no {:?} position in {}", order.side, order.ticker)))?;
+            let mut left = order.shares.min(book.positions[i].count);
+            let mut proceeds = Cents::ZERO;
+            let mut fee = Cents::ZERO;
+            let mut sold = 0;
+            for &(bid, available) in bids.iter().filter(|(bid, _)| *bid >= order.price_cents) {
+                let take = available.min(left);
+                if take == 0 {
+                    break;
+                }
+                let level_fee = risk::taker_fee_cents(take, bid);
+                proceeds += Cents::for_contracts(take, bid) - level_fee;
+                fee += level_fee;
+                sold += take;
+                left -= take;
+            }
+
+            let order_id = format!("sim-{}", book.next_order);
+            book.next_order += 1;
+            if sold > 0 {
+                let p = &mut book.positions[i];
+                let cost_sold = Cents(p.cost_cents.0 * sold as i64 / p.count as i64);
+                tracing::info!("SIM sold {} {:?} {}x for {} ({})", p.ticker, p.side, sold, proceeds, proceeds - cost_sold);
+                p.cost_cents -= cost_sold;
+                p.count -= sold;
+                record_fill(book, Fill {
+                    order_id: order_id.clone(),
+                    ticker: order.ticker.clone(),
+                    side: order.side.clone(),
+                    buy: false,
+                    count: sold,
+                    price_cents: ((proceeds + fee).0 / sold as i64) as u32,
+                    created_time: chrono::Utc::now().to_rfc3339(),
+                });
+                book.balance_cents += proceeds;
+                book.positions.retain(|p| p.count > 0);
+            }
+            Ok(OrderResult {
+                order_id,
+                status: if sold == order.shares { "executed" } else { "canceled" }.into(),
+                fee_cents: Some(fee),
+            })
+        })
+    }
+
+    async fn positions(&self) -> Result<Vec<Position>> {
+        Ok(self
+            .snapshot()
+            .positions
+            .into_iter()
+            .map(|p| Position { ticker: p.ticker, side: p.side, count: p.count, exposure_cents: p.cost_cents })
+            .collect())
+    }
+
+    async fn settlements(&self, ticker: &str) -> Result<Vec<Settlement>> {
+        self.settle_due().await?;
+        Ok(self.snapshot().settlements.into_iter().filter(|s| s.ticker == ticker).collect())
+    }
+
+    async fn fills(&self, ticker: &str, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Fill>> {
+        let since = since.to_rfc3339();
+        Ok(self
+            .snapshot()
+            .fills
+            .into_iter()
+            .filter(|f| f.ticker == ticker && f.created_time >= since)
+            .collect())
+    }
+
+    async fn balance(&self) -> Result<Cents> {
+        Ok(self.snapshot().balance_cents)
+    }
+
+    async fn market_result(&self, ticker: &str) -> Result<Option<MarketResult>> {
+        self.market.market_result(ticker).await
+    }
+}
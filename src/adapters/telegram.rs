@@ -0,0 +1,200 @@
+//! Telegram bot: reports and alerts go out to the operator's chat, and
+//! `/status`, `/pause`, `/resume` and `/close` come back in. Messages from
+//! any chat but TELEGRAM_CHAT_ID are ignored.
+
+use crate::core::types::Config;
+use crate::ports::notifier::Notifier;
+use crate::safety;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Long-poll wait per getUpdates request in loop mode.
+const POLL_SECS: u64 = 30;
+
+pub struct TelegramBot {
+    client: reqwest::Client,
+    token: String,
+    chat_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdatesResponse {
+    ok: bool,
+    #[serde(default)]
+    result: Vec<Update>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Status,
+    Pause,
+    Resume,
+    Close,
+    Help,
+}
+
+impl Command {
+    /// `/pause` or `/pause@SomeBot`; anything else that starts with `/`
+    /// gets the help text.
+    fn parse(text: &str) -> Option<Self> {
+        let word = text.split_whitespace().next()?.strip_prefix('/')?;
+        let name = word.split('@').next().unwrap_or_default();
+        Some(match name.to_ascii_lowercase().as_str() {
+            "status" => Command::Status,
+            "pause" => Command::Pause,
+            "resume" => Command::Resume,
+            "close" => Command::Close,
+            _ => Command::Help,
+        })
+    }
+}
+
+impl TelegramBot {
+    /// The bot, when TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID are both set.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        Some(Self {
+            // Longer than a long poll, so waiting for updates isn't a timeout
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(POLL_SECS + 10))
+                .build()
+                .unwrap_or_default(),
+            token: config.telegram_bot_token.clone()?,
+            chat_id: config.telegram_chat_id?,
+        })
+    }
+
+    fn url(&self, method: &str) -> String {
+        format!("https://api.telegram.org/bot{}/{}", self.token, method)
+    }
+
+    async fn send(&self, text: &str) -> Result<()> {
+        let resp = self
+            .client
+            .post(self.url("sendMessage"))
+            .json(&serde_json::json!({ "chat_id": self.chat_id, "text": text }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Telegram sendMessage returned {}", resp.status());
+        }
+        Ok(())
+    }
+
+    /// Updates from `offset` on, waiting up to `wait_secs` for the first.
+    /// Asking with a higher offset confirms everything before it.
+    async fn updates(&self, offset: i64, wait_secs: u64) -> Result<Vec<Update>> {
+        let resp: UpdatesResponse = self
+            .client
+            .get(self.url("getUpdates"))
+            .query(&[("offset", offset.to_string()), ("timeout", wait_secs.to_string())])
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !resp.ok {
+            anyhow::bail!("Telegram getUpdates failed: {}", resp.description.unwrap_or_default());
+        }
+        Ok(resp.result)
+    }
+
+    /// Answer the commands in `updates` and return the offset past them.
+    async fn handle(&self, updates: Vec<Update>, mut offset: i64) -> i64 {
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            let Some(message) = update.message else { continue };
+            if message.chat.id != self.chat_id {
+                tracing::warn!("Telegram: ignoring a message from chat {}", message.chat.id);
+                continue;
+            }
+            let Some(command) = message.text.as_deref().and_then(Command::parse) else { continue };
+            tracing::info!("Telegram command: {:?}", command);
+            let reply = match run(command) {
+                Ok(reply) => reply,
+                Err(e) => format!("{:?} failed: {:#}", command, e),
+            };
+            if let Err(e) = self.send(&reply).await {
+                tracing::warn!("Telegram reply failed: {}", e);
+            }
+        }
+        offset
+    }
+
+    /// Once mode: answer whatever arrived since the last run, then confirm
+    /// it so the next run doesn't see it again.
+    pub async fn handle_waiting(&self) -> Result<()> {
+        let updates = self.updates(0, 0).await?;
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let offset = self.handle(updates, 0).await;
+        self.updates(offset, 0).await?;
+        Ok(())
+    }
+
+    /// Loop mode: answer commands as they arrive, for the life of the
+    /// process. A failed poll is logged and retried after a pause.
+    pub async fn listen(&self) {
+        let mut offset = 0;
+        loop {
+            match self.updates(offset, POLL_SECS).await {
+                Ok(updates) => offset = self.handle(updates, offset).await,
+                Err(e) => {
+                    tracing::warn!("Telegram poll failed: {:#}", e);
+                    tokio::time::sleep(std::time::Duration::from_secs(POLL_SECS)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Carry out a command; the reply to send back.
+fn run(command: Command) -> Result<String> {
+    Ok(match command {
+        Command::Status => safety::status()?,
+        Command::Pause => {
+            safety::set_paused(true)?;
+            "Paused: no new entries. Settlement and exits keep running. /resume to trade again.".into()
+        }
+        Command::Resume => {
+            let cancelled_close = safety::close_requested();
+            safety::set_close_request(false)?;
+            safety::set_paused(false)?;
+            if cancelled_close {
+                "Resumed; the pending close-out was called off.".into()
+            } else {
+                "Resumed: new entries allowed from the next cycle.".into()
+            }
+        }
+        Command::Close => {
+            safety::set_close_request(true)?;
+            "Closing: the next cycle cancels the bot's orders, sells every position at the bid and pauses.".into()
+        }
+        Command::Help => "Commands: /status, /pause, /resume, /close".into(),
+    })
+}
+
+#[async_trait]
+impl Notifier for TelegramBot {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()> {
+        self.send(&format!("{}\n\n{}", subject, body)).await
+    }
+}
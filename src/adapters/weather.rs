@@ -1,16 +1,35 @@
+use crate::capture;
 use crate::core::types::*;
 use crate::ports::weather_feed::WeatherFeed;
+use crate::quota::{self, Provider};
+use crate::storage;
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use types::{EnsembleResponse, ForecastResponse, MetarReport, ModelMeta, NwsAlerts, NwsForecast, NwsPoints, SnowfallResponse};
+
+mod nbm;
+mod types;
 
 pub struct WeatherClient {
     client: reqwest::Client,
+    ensemble_hours: HourWindow,
+    /// Fetch NBM percentiles (NBM_WEIGHT > 0)
+    nbm: bool,
+    /// Fetch the HRRR run (HRRR_WEIGHT > 0)
+    hrrr: bool,
+    /// Record responses to, or replay them from, brain/weather_cache/
+    cache: WeatherCache,
+    /// Last NBM bulletin downloaded, with its URL; shared by every city
+    nbm_bulletin: tokio::sync::Mutex<Option<(String, std::sync::Arc<String>)>>,
+    /// Second ensemble API for quorum mode (QUORUM_ENSEMBLE_URL)
+    quorum_url: Option<String>,
 }
 
-/// Compute "today" in the configured timezone using a UTC offset.
-/// Open-Meteo returns data in the requested timezone, so we need "today"
-/// relative to that timezone, not the server's local time.
-fn today_in_timezone(tz: &str) -> String {
+/// Current wall-clock time in the configured timezone using a UTC offset.
+/// Open-Meteo returns data in the requested timezone, so "today" and "this
+/// hour" must be relative to that timezone, not the server's local time.
+fn now_in_timezone(tz: &str) -> chrono::DateTime<chrono::FixedOffset> {
     let offset_hours: i32 = match tz {
         "America/New_York" | "US/Eastern" | "America/Indiana/Indianapolis" => -5,
         "America/Chicago" | "US/Central" => -6,
@@ -28,68 +47,167 @@ fn today_in_timezone(tz: &str) -> String {
     };
     let utc_now = chrono::Utc::now();
     let offset = chrono::FixedOffset::east_opt(offset_hours * 3600).unwrap();
-    utc_now.with_timezone(&offset).format("%Y-%m-%d").to_string()
+    utc_now.with_timezone(&offset)
+}
+
+fn today_in_timezone(tz: &str) -> String {
+    now_in_timezone(tz).format("%Y-%m-%d").to_string()
+}
+
+const ENSEMBLE_MODELS: &str = "icon_seamless,gfs_seamless,ecmwf_ifs025,ecmwf_aifs025,gem_global";
+
+const ENSEMBLE_API: &str = "https://ensemble-api.open-meteo.com";
+
+/// Ensemble request for `hours` of one local day rather than two whole
+/// days per member. Open-Meteo reads start_hour/end_hour in the requested
+/// timezone, both inclusive, in place of forecast_days.
+fn ensemble_url(base: &str, lat: f64, lon: f64, timezone: &str, day: &str, hours: HourWindow, measure: Measure) -> String {
+    // Snow can fall at any hour, so its day is never trimmed
+    let hours = match measure {
+        Measure::HighTemp => hours,
+        Measure::Snowfall => HourWindow { start_hour: 0, end_hour: 24 },
+    };
+    format!(
+        "{}/v1/ensemble?latitude={}&longitude={}&hourly={}&models={}&temperature_unit=fahrenheit&precipitation_unit=inch&timezone={}&start_hour={}T{:02}:00&end_hour={}T{:02}:00",
+        base, lat, lon, ensemble_variable(measure), ENSEMBLE_MODELS, timezone, day, hours.start_hour, day, hours.end_hour - 1
+    )
+}
+
+/// The hourly Open-Meteo variable a measure is built from.
+fn ensemble_variable(measure: Measure) -> &'static str {
+    match measure {
+        Measure::HighTemp => "temperature_2m",
+        Measure::Snowfall => "snowfall",
+    }
+}
+
+/// Each member's high (or snowfall total) over the hours of local day
+/// `day` in an ensemble response.
+fn daily_member_values(data: &EnsembleResponse, day: &str, measure: Measure) -> Vec<f64> {
+    let mut values: Vec<f64> = Vec::new();
+
+    let day_indices: Vec<usize> = data
+        .hourly
+        .time
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.starts_with(day))
+        .map(|(i, _)| i)
+        .collect();
+
+    // One series per member per model: its high, or for snowfall its
+    // total. A member with no values that day (a model that doesn't reach
+    // this far out, or nulls) is left out.
+    let mut per_model: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    for (key, series) in data.hourly.members(ensemble_variable(measure)) {
+        let day_values = day_indices.iter().filter_map(|&idx| series.get(idx).copied().flatten());
+        let value = match measure {
+            Measure::HighTemp => day_values.fold(None, |high: Option<f64>, v| Some(high.map_or(v, |h| h.max(v)))),
+            Measure::Snowfall => day_values.fold(None, |total: Option<f64>, v| Some(total.unwrap_or_default() + v)),
+        };
+        if let Some(value) = value {
+            values.push(value);
+            *per_model.entry(key.model.unwrap_or_default()).or_default() += 1;
+        }
+    }
+    let missing: Vec<&str> = ENSEMBLE_MODELS.split(',').filter(|m| !per_model.contains_key(*m)).collect();
+    if !missing.is_empty() {
+        tracing::debug!("Open-Meteo ensemble: no members today from {}", missing.join(", "));
+    }
+    values
+}
+
+/// NWS requires a User-Agent identifying the app and a contact address.
+/// Set once on the client so every request carries it.
+fn user_agent(contact: &str) -> String {
+    if contact.is_empty() {
+        "(kalshi-weather-bot)".to_string()
+    } else {
+        format!("(kalshi-weather-bot, {})", contact)
+    }
 }
 
 impl WeatherClient {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: &Config) -> Result<Self> {
         Ok(Self {
             client: reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(10))
+                .user_agent(user_agent(&config.nws_contact))
                 .build()?,
+            ensemble_hours: config.ensemble_hours,
+            nbm: config.strategy.nbm_weight > 0.0,
+            hrrr: config.strategy.hrrr_weight > 0.0,
+            cache: config.weather_cache,
+            nbm_bulletin: tokio::sync::Mutex::new(None),
+            quorum_url: config.quorum_ensemble_url.clone(),
         })
     }
 
+    /// GET `url` as text. WEATHER_CACHE=record also saves the body;
+    /// replay returns the last saved body instead and never touches the
+    /// network. A non-2xx status is an error.
+    async fn get_text(&self, url: &str, provider: Provider) -> Result<String> {
+        let key = cache_key(url);
+        if self.cache == WeatherCache::Replay {
+            let (body, recorded_at) = storage::cached_weather_response(&key)
+                .ok_or_else(|| anyhow::anyhow!("OFFLINE DATA: nothing cached for {}", key))?;
+            tracing::warn!("OFFLINE DATA: {} (recorded {})", key, recorded_at);
+            return Ok(body);
+        }
+        quota::record(provider);
+        let resp = self.client.get(url).send().await?;
+        let status = resp.status();
+        let text = resp.text().await?;
+        capture::save("weather", "GET", url, status.as_u16(), &text);
+        if !status.is_success() {
+            anyhow::bail!("{} -> {}", url, status);
+        }
+        if self.cache == WeatherCache::Record {
+            if let Err(e) = storage::cache_weather_response(&key, &text) {
+                tracing::warn!("Weather cache write failed: {}", e);
+            }
+        }
+        Ok(text)
+    }
+
+    /// `get_text`, parsed as JSON.
+    async fn get_json<T: DeserializeOwned>(&self, url: &str, provider: Provider) -> Result<T> {
+        Ok(serde_json::from_str(&self.get_text(url, provider).await?)?)
+    }
+
     async fn fetch_nws(&self, lat: f64, lon: f64) -> Option<(Option<f64>, Option<f64>, Option<String>)> {
         let points_url = format!(
             "https://api.weather.gov/points/{:.4},{:.4}",
             lat, lon
         );
 
-        let points_resp = self
-            .client
-            .get(&points_url)
-            .header("User-Agent", "(kalshi-weather-bot, contact@kyzlolabs.com)")
-            .send()
-            .await
-            .ok()?;
-
-        if !points_resp.status().is_success() {
-            tracing::warn!("NWS points -> {}", points_resp.status());
-            return None;
-        }
-
-        let points: serde_json::Value = points_resp.json().await.ok()?;
-        let forecast_url = points["properties"]["forecast"].as_str()?;
-
-        let forecast_resp = self
-            .client
-            .get(forecast_url)
-            .header("User-Agent", "(kalshi-weather-bot, contact@kyzlolabs.com)")
-            .send()
-            .await
-            .ok()?;
-
-        if !forecast_resp.status().is_success() {
-            tracing::warn!("NWS forecast -> {}", forecast_resp.status());
-            return None;
-        }
+        let points: NwsPoints = match self.get_json(&points_url, Provider::Nws).await {
+            Ok(points) => points,
+            Err(e) => {
+                tracing::warn!("NWS points: {}", e);
+                return None;
+            }
+        };
+        let forecast_url = points.properties.forecast?;
 
-        let forecast: serde_json::Value = forecast_resp.json().await.ok()?;
-        let periods = forecast["properties"]["periods"].as_array()?;
+        let forecast: NwsForecast = match self.get_json(&forecast_url, Provider::Nws).await {
+            Ok(forecast) => forecast,
+            Err(e) => {
+                tracing::warn!("NWS forecast: {}", e);
+                return None;
+            }
+        };
 
         let mut high = None;
         let mut low = None;
         let mut short_forecast = None;
 
-        for period in periods.iter().take(4) {
-            let is_daytime = period["isDaytime"].as_bool().unwrap_or(false);
-            let temp = period["temperature"].as_f64();
-            if is_daytime && high.is_none() {
-                high = temp;
-                short_forecast = period["shortForecast"].as_str().map(String::from);
-            } else if !is_daytime && low.is_none() {
-                low = temp;
+        for period in forecast.properties.periods.into_iter().take(4) {
+            if period.is_daytime && high.is_none() {
+                high = period.temperature;
+                short_forecast = period.short_forecast;
+            } else if !period.is_daytime && low.is_none() {
+                low = period.temperature;
             }
             if high.is_some() && low.is_some() {
                 break;
@@ -105,36 +223,42 @@ impl WeatherClient {
             lat, lon, timezone
         );
 
-        let resp = self.client.get(&url).send().await?.json::<serde_json::Value>().await?;
+        let resp: ForecastResponse = self.get_json(&url, Provider::OpenMeteo).await?;
 
-        let current_temp = resp["current"]["temperature_2m"]
-            .as_f64()
+        let current_temp = resp
+            .current
+            .as_ref()
+            .and_then(|c| c.temperature_2m)
             .ok_or_else(|| anyhow::anyhow!("Missing current temp from Open-Meteo"))?;
+        if resp.hourly.time.is_empty() || resp.hourly.temperature_2m.is_empty() {
+            return Err(anyhow::anyhow!("Missing hourly temps"));
+        }
 
-        let times = resp["hourly"]["time"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Missing hourly times"))?;
-        let temps = resp["hourly"]["temperature_2m"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Missing hourly temps"))?;
-
-        let today = today_in_timezone(timezone);
+        let local_now = now_in_timezone(timezone);
+        let today = local_now.format("%Y-%m-%d").to_string();
+        let this_hour = local_now.format("%Y-%m-%dT%H:00").to_string();
 
         let mut hourly = Vec::new();
         let mut daily_high: f64 = f64::NEG_INFINITY;
+        let mut observed_high = current_temp;
+        let mut remaining_high: Option<f64> = None;
 
-        for (time_val, temp_val) in times.iter().zip(temps.iter()) {
-            let time_str = time_val.as_str().unwrap_or_default();
-            if let Some(temp) = temp_val.as_f64() {
-                if time_str.starts_with(&today) {
-                    if temp > daily_high {
-                        daily_high = temp;
-                    }
-                    hourly.push(HourlyForecast {
-                        time: time_str.to_string(),
-                        temperature_f: temp,
-                    });
+        for (time_str, temp) in resp.hourly.readings() {
+            if time_str.starts_with(&today) {
+                if temp > daily_high {
+                    daily_high = temp;
+                }
+                // ISO timestamps compare lexically: past hours are analysis,
+                // later hours are what's left of the forecast day.
+                if time_str <= this_hour.as_str() {
+                    observed_high = observed_high.max(temp);
+                } else {
+                    remaining_high = Some(remaining_high.map_or(temp, |h: f64| h.max(temp)));
                 }
+                hourly.push(HourlyForecast {
+                    time: time_str.to_string(),
+                    temperature_f: temp,
+                });
             }
         }
 
@@ -143,129 +267,339 @@ impl WeatherClient {
         }
 
         Ok(OpenMeteoDeterministic {
+            local_time: resp.current.and_then(|c| c.time),
             current_temp,
             forecast_high: daily_high,
+            observed_high,
+            remaining_high,
             hourly,
         })
     }
 
-    async fn fetch_open_meteo_ensemble(&self, lat: f64, lon: f64, timezone: &str) -> Option<(EnsembleForecast, Vec<TempBucketProbability>, Vec<f64>)> {
+    /// Today's high from Open-Meteo's HRRR (CONUS, 3 km, hourly runs),
+    /// past hours included. None when HRRR is off or has no hours today.
+    async fn fetch_hrrr_high(&self, lat: f64, lon: f64, timezone: &str) -> Option<f64> {
+        if !self.hrrr {
+            return None;
+        }
         let url = format!(
-            "https://ensemble-api.open-meteo.com/v1/ensemble?latitude={}&longitude={}&hourly=temperature_2m&models=icon_seamless,gfs_seamless,ecmwf_ifs025,ecmwf_aifs025,gem_global&temperature_unit=fahrenheit&timezone={}&forecast_days=2",
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m&models=ncep_hrrr_conus&temperature_unit=fahrenheit&timezone={}&forecast_days=1",
             lat, lon, timezone
         );
+        let data: ForecastResponse = match self.get_json(&url, Provider::OpenMeteo).await {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Open-Meteo HRRR unavailable: {}", e);
+                return None;
+            }
+        };
+        let today = today_in_timezone(timezone);
+        data.hourly
+            .readings()
+            .filter(|(t, _)| t.starts_with(&today))
+            .map(|(_, temp)| temp)
+            .reduce(f64::max)
+    }
 
-        let resp = self.client.get(&url).send().await.ok()?;
-        if !resp.status().is_success() {
-            tracing::warn!("Open-Meteo ensemble -> {}", resp.status());
-            return None;
-        }
+    /// Open-Meteo's snowfall for local day `day`, in inches.
+    async fn fetch_open_meteo_snowfall(&self, lat: f64, lon: f64, timezone: &str, day: &str) -> Result<Option<f64>> {
+        let url = format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=snowfall&precipitation_unit=inch&timezone={}&start_date={}&end_date={}",
+            lat, lon, timezone, day, day
+        );
+        let resp: SnowfallResponse = self.get_json(&url, Provider::OpenMeteo).await?;
+        Ok(resp.hourly.total_on(day))
+    }
 
-        let data: serde_json::Value = resp.json().await.ok()?;
-        let today = today_in_timezone(timezone);
+    /// Snowfall series: the day's total from the deterministic forecast and
+    /// each ensemble member, with current conditions from the temperature
+    /// forecast. NWS and the observation fields stay empty — there's no
+    /// intraday snow reading to price from.
+    async fn snowfall_forecast(&self, city: &CityConfig) -> Result<Option<WeatherSnapshot>> {
+        let today = today_in_timezone(&city.timezone);
+        let (deterministic_result, snowfall_result, ensemble_result, ensemble_run) = tokio::join!(
+            self.fetch_open_meteo_deterministic(city.lat, city.lon, &city.timezone),
+            self.fetch_open_meteo_snowfall(city.lat, city.lon, &city.timezone, &today),
+            self.fetch_open_meteo_ensemble(city.lat, city.lon, &city.timezone, Measure::Snowfall),
+            self.fetch_ensemble_run(),
+        );
 
-        let mut all_highs: Vec<f64> = Vec::new();
+        let det = match deterministic_result {
+            Ok(d) => d,
+            Err(e) => {
+                tracing::error!("Open-Meteo deterministic failed: {}", e);
+                return Ok(None);
+            }
+        };
+        let snowfall = match snowfall_result {
+            Ok(Some(inches)) => inches,
+            Ok(None) => {
+                tracing::error!("Open-Meteo snowfall: no hourly values for {}", today);
+                return Ok(None);
+            }
+            Err(e) => {
+                tracing::error!("Open-Meteo snowfall failed: {}", e);
+                return Ok(None);
+            }
+        };
 
-        // Each model is a separate key under "hourly"
-        let hourly = data["hourly"].as_object()?;
-        let times = hourly.get("time")?.as_array()?;
+        let (ensemble, bucket_probabilities, member_totals) = match ensemble_result {
+            Some((e, b, totals)) => (Some(e), b, totals),
+            None => {
+                tracing::warn!("Open-Meteo snowfall ensemble unavailable, continuing without it");
+                (None, Vec::new(), Vec::new())
+            }
+        };
 
-        // Find indices for today
-        let today_indices: Vec<usize> = times
-            .iter()
-            .enumerate()
-            .filter(|(_, t)| t.as_str().unwrap_or_default().starts_with(&today))
-            .map(|(i, _)| i)
-            .collect();
+        let confidence = match &ensemble {
+            Some(e) if e.std_dev < 0.5 => ForecastConfidence::High,
+            Some(e) if e.std_dev < 1.5 => ForecastConfidence::Medium,
+            Some(_) => ForecastConfidence::Low,
+            None => ForecastConfidence::Medium,
+        };
 
-        for (key, values) in hourly {
-            if key == "time" {
-                continue;
+        Ok(Some(WeatherSnapshot {
+            city: city.name.clone(),
+            current_temp_f: det.current_temp,
+            nws_forecast_high: None,
+            nws_forecast_low: None,
+            nws_short_forecast: None,
+            open_meteo_forecast_high: snowfall,
+            observed_high_f: None,
+            remaining_forecast_high_f: None,
+            hourly_forecasts: Vec::new(),
+            ensemble,
+            bucket_probabilities,
+            ensemble_member_highs: member_totals,
+            ensemble_run,
+            confidence,
+            local_time: det.local_time,
+            measure: Measure::Snowfall,
+            nbm: None,
+            hrrr_high_f: None,
+            offline: self.cache == WeatherCache::Replay,
+            observations: None,
+            alerts: Vec::new(),
+            quorum: None,
+            observed_high_so_far: None,
+        }))
+    }
+
+    async fn fetch_open_meteo_ensemble(&self, lat: f64, lon: f64, timezone: &str, measure: Measure) -> Option<(EnsembleForecast, Vec<TempBucketProbability>, Vec<f64>)> {
+        match self.fetch_ensemble_members(ENSEMBLE_API, lat, lon, timezone, measure).await {
+            Ok(values) => summarize_members(values, measure),
+            Err(e) => {
+                tracing::warn!("Open-Meteo ensemble unavailable: {}", e);
+                None
             }
-            // Keys like "temperature_2m_member01", etc.
-            if !key.starts_with("temperature_2m") {
-                continue;
+        }
+    }
+
+    /// Quorum mode: the same ensemble request against QUORUM_ENSEMBLE_URL.
+    /// None with quorum mode off; a failed fetch still reports the source,
+    /// with no mean, so the brain can refuse to trade on one path alone.
+    async fn fetch_quorum(&self, city: &CityConfig) -> Option<QuorumEnsemble> {
+        let base = self.quorum_url.as_deref()?;
+        let source = base.split_once("://").map_or(base, |(_, rest)| rest).to_string();
+        match self.fetch_ensemble_members(base, city.lat, city.lon, &city.timezone, Measure::HighTemp).await {
+            Ok(values) if !values.is_empty() => Some(QuorumEnsemble {
+                source,
+                mean: Some(values.iter().sum::<f64>() / values.len() as f64),
+                members: values.len(),
+            }),
+            Ok(_) => {
+                tracing::warn!("Quorum ensemble from {}: no members today", source);
+                Some(QuorumEnsemble { source, mean: None, members: 0 })
             }
-            let arr = values.as_array()?;
-            let mut member_high: f64 = f64::NEG_INFINITY;
-            for &idx in &today_indices {
-                if let Some(temp) = arr.get(idx).and_then(|v| v.as_f64()) {
-                    if temp > member_high {
-                        member_high = temp;
+            Err(e) => {
+                tracing::warn!("Quorum ensemble from {} unavailable: {}", source, e);
+                Some(QuorumEnsemble { source, mean: None, members: 0 })
+            }
+        }
+    }
+
+    /// Each ensemble member's high (or snowfall total) for today, from the
+    /// Open-Meteo-compatible API at `base`.
+    async fn fetch_ensemble_members(&self, base: &str, lat: f64, lon: f64, timezone: &str, measure: Measure) -> Result<Vec<f64>> {
+        let today = today_in_timezone(timezone);
+        let url = ensemble_url(base, lat, lon, timezone, &today, self.ensemble_hours, measure);
+        let data: EnsembleResponse = self.get_json(&url, Provider::OpenMeteo).await?;
+        Ok(daily_member_values(&data, &today, measure))
+    }
+
+    /// NBM percentiles for the city's settlement station today. Held under
+    /// the lock while downloading, so cities fetched together share one
+    /// download of the bulletin.
+    async fn fetch_nbm(&self, city: &CityConfig) -> Option<NbmForecast> {
+        if !self.nbm {
+            return None;
+        }
+        let station = city.station.as_deref()?;
+        let mut cached = self.nbm_bulletin.lock().await;
+        if self.cache == WeatherCache::Replay {
+            if cached.is_none() {
+                let url = nbm::bulletin_url(chrono::Utc::now());
+                match self.get_text(&url, Provider::Nws).await {
+                    Ok(text) => *cached = Some((url, std::sync::Arc::new(text))),
+                    Err(e) => {
+                        tracing::warn!("NBM bulletin: {}", e);
+                        return None;
                     }
                 }
             }
-            if member_high > f64::NEG_INFINITY {
-                all_highs.push(member_high);
+            let (_, text) = cached.as_ref()?;
+            return nbm::parse(text, station, &today_in_timezone(&city.timezone));
+        }
+        for run in nbm::candidate_runs(chrono::Utc::now()) {
+            let url = nbm::bulletin_url(run);
+            if let Some((cached_url, text)) = cached.as_ref() {
+                if *cached_url == url {
+                    return nbm::parse(text, station, &today_in_timezone(&city.timezone));
+                }
+            }
+            // NOAA, counted with NWS
+            quota::record(Provider::Nws);
+            let resp = match self.client.get(&url).timeout(std::time::Duration::from_secs(60)).send().await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    tracing::warn!("NBM bulletin request failed: {}", e);
+                    return None;
+                }
+            };
+            if resp.status() == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            if !resp.status().is_success() {
+                tracing::warn!("NBM bulletin -> {}", resp.status());
+                return None;
+            }
+            let text = std::sync::Arc::new(resp.text().await.ok()?);
+            if self.cache == WeatherCache::Record {
+                if let Err(e) = storage::cache_weather_response(&cache_key(&url), &text) {
+                    tracing::warn!("Weather cache write failed: {}", e);
+                }
             }
+            tracing::info!("NBM bulletin {} ({} KB)", url, text.len() / 1024);
+            *cached = Some((url, text.clone()));
+            return nbm::parse(&text, station, &today_in_timezone(&city.timezone));
         }
+        tracing::warn!("No NBM bulletin posted in the last {}h", nbm::RUNS_TRIED);
+        None
+    }
 
-        if all_highs.is_empty() {
-            return None;
+    /// Initialisation time of the latest ensemble run Open-Meteo has ingested.
+    /// GEFS is used as the representative run — it updates most often.
+    async fn fetch_ensemble_run(&self) -> Option<String> {
+        let url = "https://ensemble-api.open-meteo.com/data/ncep_gefs025/static/meta.json";
+        let meta: ModelMeta = match self.get_json(url, Provider::OpenMeteo).await {
+            Ok(meta) => meta,
+            Err(e) => {
+                tracing::warn!("Open-Meteo ensemble meta: {}", e);
+                return None;
+            }
+        };
+        let ts = meta.last_run_initialisation_time?;
+        chrono::DateTime::from_timestamp(ts, 0).map(|t| t.to_rfc3339())
+    }
+}
+
+/// Cache key for a request: the URL without the parameters that pin
+/// today's hours, and one key for whichever NBM bulletin run was fetched.
+fn cache_key(url: &str) -> String {
+    if url.contains("/blend_nbptx.") {
+        return "nbm-bulletin".into();
+    }
+    match url.split_once('?') {
+        Some((base, query)) => {
+            let kept: Vec<&str> = query
+                .split('&')
+                .filter(|p| !p.starts_with("start_hour=") && !p.starts_with("end_hour="))
+                .collect();
+            format!("{}?{}", base, kept.join("&"))
         }
+        None => url.to_string(),
+    }
+}
 
-        all_highs.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        let n = all_highs.len();
-        let mean = all_highs.iter().sum::<f64>() / n as f64;
-        let variance = all_highs.iter().map(|h| (h - mean).powi(2)).sum::<f64>() / n as f64;
-        let std_dev = variance.sqrt();
+/// Spread of the member values and their probability buckets: 2°F wide
+/// for highs, 1 inch from zero for snowfall.
+fn summarize_members(mut values: Vec<f64>, measure: Measure) -> Option<(EnsembleForecast, Vec<TempBucketProbability>, Vec<f64>)> {
+    if values.is_empty() {
+        return None;
+    }
 
-        let percentile = |p: f64| -> f64 {
-            let idx = (p / 100.0 * (n - 1) as f64).round() as usize;
-            all_highs[idx.min(n - 1)]
-        };
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = values.len();
+    let mean = values.iter().sum::<f64>() / n as f64;
+    let variance = values.iter().map(|h| (h - mean).powi(2)).sum::<f64>() / n as f64;
+    let std_dev = variance.sqrt();
 
-        let ensemble = EnsembleForecast {
-            model_count: n,
-            mean_high: mean,
-            min_high: all_highs[0],
-            max_high: all_highs[n - 1],
-            std_dev,
-            p10: percentile(10.0),
-            p25: percentile(25.0),
-            p75: percentile(75.0),
-            p90: percentile(90.0),
-        };
+    let percentile = |p: f64| -> f64 {
+        let idx = (p / 100.0 * (n - 1) as f64).round() as usize;
+        values[idx.min(n - 1)]
+    };
 
-        // Build 2°F temperature buckets
-        let bucket_low = (all_highs[0] / 2.0).floor() as i32 * 2 - 2;
-        let bucket_high = (all_highs[n - 1] / 2.0).ceil() as i32 * 2 + 2;
-
-        let mut buckets: Vec<TempBucketProbability> = Vec::new();
-        let mut temp = bucket_low;
-        while temp < bucket_high {
-            let lower = temp as f64;
-            let upper = (temp + 2) as f64;
-            let count = all_highs.iter().filter(|&&h| h >= lower && h < upper).count();
-            let prob = count as f64 / n as f64;
-            if prob > 0.0 {
-                buckets.push(TempBucketProbability {
-                    label: format!("{}-{}°F", temp, temp + 2),
-                    lower,
-                    upper,
-                    probability: prob,
-                });
-            }
-            temp += 2;
-        }
+    let ensemble = EnsembleForecast {
+        model_count: n,
+        mean_high: mean,
+        min_high: values[0],
+        max_high: values[n - 1],
+        std_dev,
+        p10: percentile(10.0),
+        p25: percentile(25.0),
+        p75: percentile(75.0),
+        p90: percentile(90.0),
+    };
 
-        Some((ensemble, buckets, all_highs))
+    let (width, bucket_low, bucket_high) = match measure {
+        Measure::HighTemp => (2, (values[0] / 2.0).floor() as i32 * 2 - 2, (values[n - 1] / 2.0).ceil() as i32 * 2 + 2),
+        Measure::Snowfall => (1, 0, values[n - 1].floor() as i32 + 1),
+    };
+
+    let mut buckets: Vec<TempBucketProbability> = Vec::new();
+    let mut bound = bucket_low;
+    while bound < bucket_high {
+        let lower = bound as f64;
+        let upper = (bound + width) as f64;
+        let count = values.iter().filter(|&&v| v >= lower && v < upper).count();
+        let prob = count as f64 / n as f64;
+        if prob > 0.0 {
+            buckets.push(TempBucketProbability {
+                label: format!("{}-{}{}", bound, bound + width, measure.unit()),
+                lower,
+                upper,
+                probability: prob,
+            });
+        }
+        bound += width;
     }
+
+    Some((ensemble, buckets, values))
 }
 
 struct OpenMeteoDeterministic {
+    local_time: Option<String>,
     current_temp: f64,
     forecast_high: f64,
+    observed_high: f64,
+    remaining_high: Option<f64>,
     hourly: Vec<HourlyForecast>,
 }
 
 #[async_trait]
 impl WeatherFeed for WeatherClient {
     async fn forecast(&self, city: &CityConfig) -> Result<Option<WeatherSnapshot>> {
-        let (nws_result, deterministic_result, ensemble_result) = tokio::join!(
+        if city.measure == Measure::Snowfall {
+            return self.snowfall_forecast(city).await;
+        }
+        let (nws_result, deterministic_result, ensemble_result, ensemble_run, nbm, hrrr_high_f, quorum) = tokio::join!(
             self.fetch_nws(city.lat, city.lon),
             self.fetch_open_meteo_deterministic(city.lat, city.lon, &city.timezone),
-            self.fetch_open_meteo_ensemble(city.lat, city.lon, &city.timezone),
+            self.fetch_open_meteo_ensemble(city.lat, city.lon, &city.timezone, Measure::HighTemp),
+            self.fetch_ensemble_run(),
+            self.fetch_nbm(city),
+            self.fetch_hrrr_high(city.lat, city.lon, &city.timezone),
+            self.fetch_quorum(city),
         );
 
         let det = match deterministic_result {
@@ -306,11 +640,177 @@ impl WeatherFeed for WeatherClient {
             nws_forecast_low: nws_low,
             nws_short_forecast: nws_short,
             open_meteo_forecast_high: det.forecast_high,
+            observed_high_f: Some(det.observed_high),
+            remaining_forecast_high_f: det.remaining_high,
             hourly_forecasts: det.hourly,
             ensemble,
             bucket_probabilities,
             ensemble_member_highs,
+            ensemble_run,
             confidence,
+            local_time: det.local_time,
+            measure: Measure::HighTemp,
+            nbm,
+            hrrr_high_f,
+            offline: self.cache == WeatherCache::Replay,
+            observations: None,
+            alerts: Vec::new(),
+            quorum,
+            observed_high_so_far: None,
         }))
     }
+
+    /// The settlement station's METARs since local midnight. Local time is
+    /// standard time year-round, the day NWS climate reports cover.
+    async fn observations(&self, city: &CityConfig) -> Result<Option<Observations>> {
+        let Some(station) = city.station.as_deref() else { return Ok(None) };
+        if city.measure != Measure::HighTemp {
+            return Ok(None);
+        }
+        let url = format!("https://aviationweather.gov/api/data/metar?ids={}&format=json&hours=26", station);
+        let reports: Vec<MetarReport> = self.get_json(&url, Provider::Nws).await?;
+
+        let local_now = now_in_timezone(&city.timezone);
+        let midnight = local_now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(*local_now.offset()).single())
+            .ok_or_else(|| anyhow::anyhow!("no local midnight for {}", city.timezone))?
+            .timestamp();
+
+        let mut readings: Vec<(i64, f64)> = reports
+            .iter()
+            // Only the settlement station's own reports count toward its high
+            .filter(|r| r.icao_id.as_deref().is_some_and(|id| id.eq_ignore_ascii_case(station)))
+            .filter_map(|r| Some((r.obs_time?, r.temp_c()? * 9.0 / 5.0 + 32.0)))
+            .filter(|(at, _)| *at >= midnight)
+            .collect();
+        readings.sort_by_key(|(at, _)| *at);
+        let Some(&(latest_at, latest_temp_f)) = readings.last() else { return Ok(None) };
+        let observed_high_f = readings.iter().map(|(_, t)| *t).fold(f64::NEG_INFINITY, f64::max);
+
+        Ok(Some(Observations {
+            city: city.name.clone(),
+            station: Some(station.to_string()),
+            observed_high_f,
+            latest_temp_f,
+            observed_at: chrono::DateTime::from_timestamp(latest_at, 0).map(|t| t.to_rfc3339()).unwrap_or_default(),
+        }))
+    }
+
+    /// NWS alerts in effect at the city's coordinates.
+    async fn alerts(&self, city: &CityConfig) -> Result<Option<Vec<WeatherAlert>>> {
+        let url = format!("https://api.weather.gov/alerts/active?point={:.4},{:.4}", city.lat, city.lon);
+        let resp: NwsAlerts = self.get_json(&url, Provider::Nws).await?;
+        Ok(Some(
+            resp.features
+                .into_iter()
+                .map(|f| WeatherAlert {
+                    event: f.properties.event,
+                    severity: f.properties.severity,
+                    headline: f.properties.headline.unwrap_or_default(),
+                    expires: f.properties.expires,
+                })
+                .collect(),
+        ))
+    }
+
+    async fn daily_high(&self, city: &CityConfig, day: &str) -> Result<Option<f64>> {
+        if day >= today_in_timezone(&city.timezone).as_str() {
+            return Ok(None);
+        }
+        if city.measure == Measure::Snowfall {
+            return self.fetch_open_meteo_snowfall(city.lat, city.lon, &city.timezone, day).await;
+        }
+        let url = format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=temperature_2m&temperature_unit=fahrenheit&timezone={}&start_date={}&end_date={}",
+            city.lat, city.lon, city.timezone, day, day
+        );
+        let resp: ForecastResponse = self.get_json(&url, Provider::OpenMeteo).await?;
+        let high = resp.hourly.readings().map(|(_, t)| t).fold(f64::NEG_INFINITY, f64::max);
+        Ok(high.is_finite().then_some(high))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(url: &str, key: &str) -> Option<String> {
+        url.split_once('?')?
+            .1
+            .split('&')
+            .find_map(|kv| kv.strip_prefix(key)?.strip_prefix('=').map(str::to_string))
+    }
+
+    #[test]
+    fn ensemble_url_asks_for_the_window_only() {
+        let hours = HourWindow { start_hour: 9, end_hour: 19 };
+        let url = ensemble_url(ENSEMBLE_API, 40.78, -73.97, "America/New_York", "2026-10-16", hours, Measure::HighTemp);
+        assert_eq!(query(&url, "start_hour").as_deref(), Some("2026-10-16T09:00"));
+        // end_hour is inclusive on Open-Meteo's side
+        assert_eq!(query(&url, "end_hour").as_deref(), Some("2026-10-16T18:00"));
+        assert_eq!(query(&url, "forecast_days"), None);
+        assert_eq!(query(&url, "hourly").as_deref(), Some("temperature_2m"));
+    }
+
+    #[test]
+    fn ensemble_url_default_window_is_the_whole_day() {
+        let hours = HourWindow { start_hour: 0, end_hour: 24 };
+        let url = ensemble_url(ENSEMBLE_API, 41.96, -87.93, "America/Chicago", "2026-10-16", hours, Measure::HighTemp);
+        assert_eq!(query(&url, "start_hour").as_deref(), Some("2026-10-16T00:00"));
+        assert_eq!(query(&url, "end_hour").as_deref(), Some("2026-10-16T23:00"));
+    }
+
+    #[test]
+    fn ensemble_url_never_windows_snowfall() {
+        let hours = HourWindow { start_hour: 9, end_hour: 19 };
+        let url = ensemble_url(ENSEMBLE_API, 40.78, -73.97, "America/New_York", "2026-10-16", hours, Measure::Snowfall);
+        assert_eq!(query(&url, "start_hour").as_deref(), Some("2026-10-16T00:00"));
+        assert_eq!(query(&url, "end_hour").as_deref(), Some("2026-10-16T23:00"));
+    }
+
+    fn fixture(name: &str) -> EnsembleResponse {
+        let path = format!("{}/tests/fixtures/open-meteo/{}", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn single_model_fixtures_give_control_plus_members() {
+        for (i, model) in ENSEMBLE_MODELS.split(',').enumerate() {
+            let data = fixture(&format!("ensemble_{}.json", model));
+            let highs = daily_member_values(&data, "2026-10-16", Measure::HighTemp);
+            // Each series peaks 2.1°F above its first hour; members step 0.5°F
+            let control = 72.1 + i as f64;
+            let expected: Vec<f64> = (0..4).map(|k| control + k as f64 * 0.5).collect();
+            assert_eq!(highs.len(), expected.len(), "{}", model);
+            for (h, e) in highs.iter().zip(&expected) {
+                assert!((h - e).abs() < 1e-9, "{}: {:?} != {:?}", model, highs, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn multi_model_fixture_skips_models_without_values() {
+        let data = fixture("ensemble_multi_model.json");
+        let mut per_model = std::collections::BTreeMap::new();
+        for (key, _) in data.hourly.members("temperature_2m") {
+            *per_model.entry(key.model.unwrap()).or_insert(0) += 1;
+        }
+        assert_eq!(per_model.len(), 5);
+        // gem_global has run out: its series are all null and add no members
+        let highs = daily_member_values(&data, "2026-10-16", Measure::HighTemp);
+        assert_eq!(highs.len(), 16);
+        // gfs_seamless member02 is missing its warmest hour; its high
+        // comes from the hours it has
+        assert!(highs.iter().any(|h| (h - 73.4).abs() < 1e-9));
+    }
+
+    #[test]
+    fn members_outside_the_day_are_ignored() {
+        let data = fixture("ensemble_icon_seamless.json");
+        assert!(daily_member_values(&data, "2026-10-17", Measure::HighTemp).is_empty());
+        // No snowfall series in a temperature response
+        assert!(daily_member_values(&data, "2026-10-16", Measure::Snowfall).is_empty());
+    }
 }
@@ -0,0 +1,114 @@
+//! National Blend of Models probabilistic text bulletin (NBP) from NOMADS:
+//! percentiles of the daytime max temperature per station. One file covers
+//! every station, so `WeatherClient` keeps the last one it downloaded.
+//!
+//! Each station's block is a header line, then rows of fixed-width columns:
+//!
+//! ```text
+//!  KNYC    NBM V4.2 NBP GUIDANCE   10/16/2026  1300 UTC
+//!  FHR     11| 23| 35| 47| ...
+//!  UTC     00| 12| 00| 12| ...
+//!  TXNP10  68| 51| 70| 53| ...
+//!  TXNP50  72| 55| 74| 57| ...
+//! ```
+//!
+//! TXN columns alternate max and min; the max for a local day is the
+//! column valid at 00Z the next day.
+
+use crate::core::types::{NbmForecast, Quantile};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc};
+
+/// Hourly runs tried, newest first, before giving up.
+pub const RUNS_TRIED: i64 = 6;
+
+/// Bulletin URL for the run issued at `run` (top of an hour, UTC).
+pub fn bulletin_url(run: DateTime<Utc>) -> String {
+    format!(
+        "https://nomads.ncep.noaa.gov/pub/data/nccf/com/blend/prod/blend.{}/{:02}/text/blend_nbptx.t{:02}z",
+        run.format("%Y%m%d"),
+        run.hour(),
+        run.hour()
+    )
+}
+
+/// Runs to try at `now`, newest first. A run takes about an hour to post.
+pub fn candidate_runs(now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let hour = now
+        .with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(now);
+    (1..=RUNS_TRIED).map(|back| hour - Duration::hours(back)).collect()
+}
+
+/// The percentiles of `station`'s max temperature for local day `day`
+/// ("YYYY-MM-DD"), or None when the bulletin has no such block or column.
+pub fn parse(bulletin: &str, station: &str, day: &str) -> Option<NbmForecast> {
+    let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+    let target = Utc.from_utc_datetime(&day.succ_opt()?.and_hms_opt(0, 0, 0)?);
+
+    let mut lines = bulletin.lines().skip_while(|l| !is_header(l, station));
+    let issued = issue_time(lines.next()?)?;
+    let block: Vec<&str> = lines.take_while(|l| !l.trim().is_empty() && !l.contains("GUIDANCE")).collect();
+    let row = |label: &str| block.iter().find(|l| l.split_whitespace().next() == Some(label)).map(|l| cells(l));
+
+    let valid: Vec<DateTime<Utc>> = match row("FHR") {
+        Some(hours) => hours
+            .iter()
+            .map(|h| h.parse::<i64>().ok().map(|h| issued + Duration::hours(h)))
+            .collect::<Option<_>>()?,
+        None => {
+            // Only UTC hours: the date rolls over whenever the hour does
+            let mut at = issued;
+            let mut valid = Vec::new();
+            for h in row("UTC")? {
+                let h: i64 = h.parse().ok()?;
+                let mut next = at - Duration::hours(at.hour() as i64) + Duration::hours(h);
+                if next <= at {
+                    next += Duration::days(1);
+                }
+                valid.push(next);
+                at = next;
+            }
+            valid
+        }
+    };
+    let column = valid.iter().position(|v| *v == target)?;
+
+    let mut quantiles: Vec<Quantile> = block
+        .iter()
+        .filter_map(|l| {
+            let pct: f64 = l.split_whitespace().next()?.strip_prefix("TXNP")?.parse().ok()?;
+            let value: f64 = cells(l).get(column)?.parse().ok()?;
+            // -99 / 999 mark missing values
+            (-90.0..150.0).contains(&value).then_some(Quantile { pct, value })
+        })
+        .collect();
+    quantiles.sort_by(|a, b| a.pct.total_cmp(&b.pct));
+    (quantiles.len() >= 2).then(|| NbmForecast {
+        station: station.to_string(),
+        issued: issued.to_rfc3339(),
+        quantiles,
+    })
+}
+
+fn is_header(line: &str, station: &str) -> bool {
+    line.split_whitespace().next() == Some(station) && line.contains("NBP")
+}
+
+/// "… 10/16/2026  1300 UTC" → 2026-10-16T13:00Z.
+fn issue_time(header: &str) -> Option<DateTime<Utc>> {
+    let words: Vec<&str> = header.split_whitespace().collect();
+    let at = words.iter().position(|w| *w == "UTC")?;
+    let date = NaiveDate::parse_from_str(words.get(at.checked_sub(2)?)?, "%m/%d/%Y").ok()?;
+    let hhmm: u32 = words.get(at - 1)?.parse().ok()?;
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(hhmm / 100, hhmm % 100, 0)?))
+}
+
+/// A row's values, after its label.
+fn cells(line: &str) -> Vec<&str> {
+    line.split(|c: char| c.is_whitespace() || c == '|')
+        .filter(|c| !c.is_empty())
+        .skip(1)
+        .collect()
+}
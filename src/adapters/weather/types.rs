@@ -0,0 +1,287 @@
+//! Open-Meteo, NWS and METAR response structs. Only the fields the bot reads;
+//! anything missing deserializes as empty rather than failing the fetch.
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// `/v1/forecast` with `hourly=temperature_2m` (and `current` for the live
+/// forecast).
+#[derive(Debug, Deserialize)]
+pub struct ForecastResponse {
+    pub current: Option<CurrentConditions>,
+    #[serde(default)]
+    pub hourly: HourlyTemperatures,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrentConditions {
+    pub time: Option<String>,
+    pub temperature_2m: Option<f64>,
+}
+
+/// Parallel arrays; an hour the model has no value for is null.
+#[derive(Debug, Default, Deserialize)]
+pub struct HourlyTemperatures {
+    #[serde(default)]
+    pub time: Vec<String>,
+    #[serde(default)]
+    pub temperature_2m: Vec<Option<f64>>,
+}
+
+impl HourlyTemperatures {
+    /// (time, °F) for every hour with a value.
+    pub fn readings(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.time.iter().zip(&self.temperature_2m).filter_map(|(t, v)| Some((t.as_str(), (*v)?)))
+    }
+}
+
+/// `/v1/forecast` with `hourly=snowfall` in inches.
+#[derive(Debug, Deserialize)]
+pub struct SnowfallResponse {
+    #[serde(default)]
+    pub hourly: HourlySnowfall,
+}
+
+/// Snow that fell in each hour; null where the model has no value.
+#[derive(Debug, Default, Deserialize)]
+pub struct HourlySnowfall {
+    #[serde(default)]
+    pub time: Vec<String>,
+    #[serde(default)]
+    pub snowfall: Vec<Option<f64>>,
+}
+
+impl HourlySnowfall {
+    /// Total over the hours of local day `day` (YYYY-MM-DD) that have a
+    /// value; None if none do.
+    pub fn total_on(&self, day: &str) -> Option<f64> {
+        let values: Vec<f64> = self
+            .time
+            .iter()
+            .zip(&self.snowfall)
+            .filter(|(t, _)| t.starts_with(day))
+            .filter_map(|(_, v)| *v)
+            .collect();
+        (!values.is_empty()).then(|| values.iter().sum())
+    }
+}
+
+/// `/v1/ensemble`: one series per member per requested variable under
+/// `hourly`, e.g. `temperature_2m_member07`.
+#[derive(Debug, Deserialize)]
+pub struct EnsembleResponse {
+    pub hourly: EnsembleHourly,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnsembleHourly {
+    #[serde(default)]
+    pub time: Vec<String>,
+    #[serde(flatten)]
+    pub series: BTreeMap<String, Series>,
+}
+
+/// A member's hourly values, or something else under `hourly` (such as
+/// its units) the bot doesn't read.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Series {
+    Values(Vec<Option<f64>>),
+    Other(IgnoredAny),
+}
+
+/// Which model and member a series key names. Open-Meteo spells them
+/// `temperature_2m` (control), `temperature_2m_member07`, and with several
+/// models requested appends the model: `temperature_2m_member07_gem_global`.
+/// Other variables (`snowfall_member03`) follow the same pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberKey {
+    /// None when only one model was requested
+    pub model: Option<String>,
+    /// None for the control run
+    pub member: Option<u32>,
+}
+
+impl MemberKey {
+    pub fn parse(key: &str, variable: &str) -> Option<Self> {
+        let rest = key.strip_prefix(variable)?;
+        if rest.is_empty() {
+            return Some(Self { model: None, member: None });
+        }
+        let rest = rest.strip_prefix('_')?;
+        let (member, model) = match rest.strip_prefix("member") {
+            Some(tail) => {
+                let digits = tail.chars().take_while(char::is_ascii_digit).count();
+                let member = tail[..digits].parse().ok()?;
+                (Some(member), tail[digits..].strip_prefix('_'))
+            }
+            None => (None, Some(rest)),
+        };
+        Some(Self { model: model.filter(|m| !m.is_empty()).map(String::from), member })
+    }
+}
+
+impl EnsembleHourly {
+    /// Every series of `variable` with its parsed key, skipping other
+    /// variables and keys that don't read as a member.
+    pub fn members<'a>(&'a self, variable: &'a str) -> impl Iterator<Item = (MemberKey, &'a [Option<f64>])> + 'a {
+        self.series.iter().filter_map(move |(key, series)| match series {
+            Series::Values(values) => Some((MemberKey::parse(key, variable)?, values.as_slice())),
+            Series::Other(_) => None,
+        })
+    }
+}
+
+/// `ensemble-api…/static/meta.json` for one model.
+#[derive(Debug, Deserialize)]
+pub struct ModelMeta {
+    /// Unix seconds
+    pub last_run_initialisation_time: Option<i64>,
+}
+
+/// NWS `/points/{lat},{lon}`.
+#[derive(Debug, Deserialize)]
+pub struct NwsPoints {
+    pub properties: NwsPointProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NwsPointProperties {
+    pub forecast: Option<String>,
+}
+
+/// NWS gridpoint forecast: twelve-hour day and night periods.
+#[derive(Debug, Deserialize)]
+pub struct NwsForecast {
+    pub properties: NwsForecastProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NwsForecastProperties {
+    #[serde(default)]
+    pub periods: Vec<NwsPeriod>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NwsPeriod {
+    #[serde(default)]
+    pub is_daytime: bool,
+    pub temperature: Option<f64>,
+    pub short_forecast: Option<String>,
+}
+
+/// NWS `/alerts/active?point=`: a GeoJSON feature per alert.
+#[derive(Debug, Deserialize)]
+pub struct NwsAlerts {
+    #[serde(default)]
+    pub features: Vec<NwsAlertFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NwsAlertFeature {
+    pub properties: NwsAlertProperties,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NwsAlertProperties {
+    pub event: String,
+    #[serde(default)]
+    pub severity: String,
+    pub headline: Option<String>,
+    pub expires: Option<String>,
+}
+
+/// aviationweather.gov `/api/data/metar?format=json`: one entry per report.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetarReport {
+    pub icao_id: Option<String>,
+    /// Unix seconds
+    pub obs_time: Option<i64>,
+    /// Whole °C
+    pub temp: Option<f64>,
+    pub raw_ob: Option<String>,
+}
+
+impl MetarReport {
+    /// Temperature in °C, to the tenth from the remarks T group
+    /// (`T02560178` = 25.6°C) when the report has one.
+    pub fn temp_c(&self) -> Option<f64> {
+        let precise = self.raw_ob.as_deref().and_then(|raw| {
+            raw.split_whitespace().find_map(|word| {
+                let digits = word.strip_prefix('T').filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit()))?;
+                let tenths: f64 = digits[1..4].parse().ok()?;
+                Some(if digits.starts_with('1') { -tenths / 10.0 } else { tenths / 10.0 })
+            })
+        });
+        precise.or(self.temp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(model: Option<&str>, member: Option<u32>) -> Option<MemberKey> {
+        Some(MemberKey { model: model.map(String::from), member })
+    }
+
+    #[test]
+    fn member_keys_in_every_spelling() {
+        let t = "temperature_2m";
+        assert_eq!(MemberKey::parse("temperature_2m", t), key(None, None));
+        assert_eq!(MemberKey::parse("temperature_2m_member07", t), key(None, Some(7)));
+        assert_eq!(MemberKey::parse("temperature_2m_gem_global", t), key(Some("gem_global"), None));
+        assert_eq!(MemberKey::parse("temperature_2m_member07_gem_global", t), key(Some("gem_global"), Some(7)));
+        assert_eq!(MemberKey::parse("temperature_2m_member50_ecmwf_aifs025", t), key(Some("ecmwf_aifs025"), Some(50)));
+        assert_eq!(MemberKey::parse("snowfall_member03", "snowfall"), key(None, Some(3)));
+    }
+
+    #[test]
+    fn other_keys_are_not_members() {
+        let t = "temperature_2m";
+        assert_eq!(MemberKey::parse("time", t), None);
+        assert_eq!(MemberKey::parse("snowfall_member03", t), None);
+        assert_eq!(MemberKey::parse("temperature_2mx", t), None);
+        assert_eq!(MemberKey::parse("temperature_2m_member", t), None);
+    }
+
+    #[test]
+    fn ensemble_response_skips_units_and_other_variables() {
+        let data: EnsembleResponse = serde_json::from_str(
+            r#"{"hourly": {"time": ["2026-10-16T14:00"], "temperature_2m_member01": [71.5],
+                "snowfall_member01": [0.0], "units": "°F"}}"#,
+        )
+        .unwrap();
+        let members: Vec<_> = data.hourly.members("temperature_2m").collect();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].1, &[Some(71.5)]);
+    }
+
+    #[test]
+    fn forecast_tolerates_missing_fields() {
+        let f: ForecastResponse = serde_json::from_str(r#"{"latitude": 40.78}"#).unwrap();
+        assert!(f.current.is_none());
+        assert_eq!(f.hourly.readings().count(), 0);
+        let f: ForecastResponse = serde_json::from_str(
+            r#"{"current": {"time": "2026-10-16T14:00", "temperature_2m": 70.2},
+                "hourly": {"time": ["2026-10-16T14:00", "2026-10-16T15:00"], "temperature_2m": [70.2, null]}}"#,
+        )
+        .unwrap();
+        assert_eq!(f.current.unwrap().temperature_2m, Some(70.2));
+        assert_eq!(f.hourly.readings().collect::<Vec<_>>(), [("2026-10-16T14:00", 70.2)]);
+    }
+
+    #[test]
+    fn snowfall_totals_one_day() {
+        let s: SnowfallResponse = serde_json::from_str(
+            r#"{"hourly": {"time": ["2026-10-16T23:00", "2026-10-17T00:00", "2026-10-17T01:00"],
+                "snowfall": [0.4, 0.3, null]}}"#,
+        )
+        .unwrap();
+        assert_eq!(s.hourly.total_on("2026-10-17"), Some(0.3));
+        assert_eq!(s.hourly.total_on("2026-10-18"), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! Copies the research dataset to the configured bucket (ARCHIVE_BUCKET),
+//! so an ephemeral instance can be thrown away without losing it. Only
+//! files whose size or mtime changed since the last upload are sent, a few
+//! at a time; a failed upload is retried on the next sync.
+
+use crate::core::types::ArchiveConfig;
+use crate::ports::archive::ArchiveSink;
+use crate::storage;
+use futures_util::StreamExt;
+
+/// Uploads in flight at once.
+const CONCURRENCY: usize = 4;
+
+/// Sync when ARCHIVE_INTERVAL_MINUTES has passed since the last one, or
+/// always with `force`. Never fails the caller: errors are logged.
+pub async fn sync_if_due(sink: &dyn ArchiveSink, config: &ArchiveConfig, force: bool) {
+    let state = storage::read_archive_state();
+    let due = force
+        || state
+            .last_sync
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .is_none_or(|last| {
+                chrono::Utc::now().signed_duration_since(last) >= chrono::Duration::minutes(config.interval_minutes as i64)
+            });
+    if !due {
+        return;
+    }
+    match sync(sink, &config.prefix).await {
+        Ok((0, 0)) => tracing::debug!("Archive: nothing changed"),
+        Ok((uploaded, 0)) => tracing::info!("Archive: uploaded {} file(s) to {}", uploaded, config.bucket),
+        Ok((uploaded, failed)) => tracing::warn!(
+            "Archive: uploaded {} file(s) to {}, {} failed (retried next sync)",
+            uploaded, config.bucket, failed
+        ),
+        Err(e) => tracing::warn!("Archive sync failed: {:#}", e),
+    }
+}
+
+/// Upload every changed file under `prefix`; (uploaded, failed).
+pub async fn sync(sink: &dyn ArchiveSink, prefix: &str) -> anyhow::Result<(usize, usize)> {
+    let mut state = storage::read_archive_state();
+    let files = storage::archive_files();
+
+    let mut changed = Vec::new();
+    for path in &files {
+        let Ok(meta) = std::fs::metadata(path) else { continue };
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        let fingerprint = format!("{}-{}", meta.len(), mtime);
+        let local = path.to_string_lossy().replace('\\', "/");
+        if state.uploaded.get(&local) != Some(&fingerprint) {
+            changed.push((local, fingerprint));
+        }
+    }
+
+    let results: Vec<_> = futures_util::stream::iter(changed)
+        .map(|(local, fingerprint)| async move {
+            let result = upload(sink, prefix, &local).await;
+            (local, fingerprint, result)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+
+    let (mut uploaded, mut failed) = (0, 0);
+    for (local, fingerprint, result) in results {
+        match result {
+            Ok(()) => {
+                uploaded += 1;
+                state.uploaded.insert(local, fingerprint);
+            }
+            Err(e) => {
+                failed += 1;
+                tracing::warn!("Archive: {} not uploaded: {:#}", local, e);
+            }
+        }
+    }
+
+    // Pruned snapshots stay in the bucket; only the bookkeeping goes
+    let present: std::collections::BTreeSet<String> =
+        files.iter().map(|p| p.to_string_lossy().replace('\\', "/")).collect();
+    state.uploaded.retain(|path, _| present.contains(path));
+    state.last_sync = Some(chrono::Utc::now().to_rfc3339());
+    storage::write_archive_state(&state)?;
+    Ok((uploaded, failed))
+}
+
+async fn upload(sink: &dyn ArchiveSink, prefix: &str, local: &str) -> anyhow::Result<()> {
+    let body = tokio::fs::read(local).await?;
+    let key = if prefix.is_empty() { local.to_string() } else { format!("{}/{}", prefix, local) };
+    sink.put(&key, body, content_type(local)).await
+}
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit('.').next() {
+        Some("jsonl") => "application/x-ndjson",
+        Some("json") => "application/json",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("zst") => "application/zstd",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+const CAPTURE_ROOT: &str = "brain/debug";
+
+/// JSON keys whose values are masked before a payload hits disk.
+const SENSITIVE_KEYS: [&str; 5] = ["key", "token", "secret", "signature", "authorization"];
+
+static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+static SEQ: AtomicU32 = AtomicU32::new(0);
+
+/// DEBUG_CAPTURE=1 saves every raw API response of this run under
+/// `brain/debug/<run>/`, keeping the newest DEBUG_CAPTURE_KEEP runs (20).
+/// Request headers — where Kalshi's auth lives — are never written.
+fn run_dir() -> Option<&'static Path> {
+    DIR.get_or_init(|| {
+        let on = std::env::var("DEBUG_CAPTURE").is_ok_and(|v| v == "1" || v == "true");
+        if !on {
+            return None;
+        }
+        let dir = Path::new(CAPTURE_ROOT).join(format!(
+            "{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+            std::process::id()
+        ));
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Debug capture disabled — can't create {}: {}", dir.display(), e);
+            return None;
+        }
+        let keep = std::env::var("DEBUG_CAPTURE_KEEP").ok().and_then(|v| v.parse().ok()).unwrap_or(20);
+        rotate(keep);
+        tracing::info!("Debug capture on: raw responses → {}", dir.display());
+        Some(dir)
+    })
+    .as_deref()
+}
+
+/// Record one response. A no-op unless DEBUG_CAPTURE is set; failures only warn.
+pub fn save(source: &str, method: &str, url: &str, status: u16, body: &str) {
+    let Some(dir) = run_dir() else { return };
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("{:04}-{}-{}.json", seq, source, slug(url)));
+
+    let body = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact(&mut json);
+            json
+        }
+        Err(_) => serde_json::Value::String(body.to_string()),
+    };
+    let envelope = serde_json::json!({
+        "captured_at": chrono::Utc::now().to_rfc3339(),
+        "method": method,
+        "url": url,
+        "status": status,
+        "body": body,
+    });
+    let written = serde_json::to_string_pretty(&envelope)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| std::fs::write(&path, s).map_err(Into::into));
+    if let Err(e) = written {
+        tracing::warn!("Debug capture write failed for {}: {}", path.display(), e);
+    }
+}
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                let k = k.to_lowercase();
+                if SENSITIVE_KEYS.iter().any(|s| k.contains(s)) && !v.is_object() && !v.is_array() {
+                    *v = serde_json::Value::String("[redacted]".into());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Filesystem-safe tail of a URL: path and query, trimmed to 80 chars.
+fn slug(url: &str) -> String {
+    let tail = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let tail = tail.split_once('/').map_or("", |(_, path)| path);
+    let slug: String = tail
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .take(80)
+        .collect();
+    slug.trim_matches('_').to_string()
+}
+
+/// Delete all but the newest `keep` run directories (names sort by time).
+fn rotate(keep: usize) {
+    let Ok(entries) = std::fs::read_dir(CAPTURE_ROOT) else { return };
+    let mut runs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    runs.sort();
+    let excess = runs.len().saturating_sub(keep);
+    for old in &runs[..excess] {
+        if let Err(e) = std::fs::remove_dir_all(old) {
+            tracing::warn!("Debug capture rotation failed for {}: {}", old.display(), e);
+        }
+    }
+}
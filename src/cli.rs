@@ -0,0 +1,508 @@
+use crate::core::types::{Action, BacktestResult, Cents, CityConfig, CityTuning, Config, EventScan, OrderState, QuotaLimits, StrategyParams};
+use crate::core::{backtest, dataset, risk, schedule, stats};
+use crate::ports::exchange::Exchange;
+use crate::quota::Provider;
+use crate::{parquet, report, safety};
+use crate::storage;
+use clap::{Parser, Subcommand};
+
+/// `kalshi-bot [COMMAND]`. With no command, one cycle — or, with
+/// RUN_MODE=loop, the loop — so existing cron entries keep working.
+#[derive(Parser)]
+#[command(name = "kalshi-bot", version, about = "Kalshi weather-market trading bot")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// One trading cycle, whatever RUN_MODE says
+    Run,
+    /// Keep trading, one cycle per CYCLE_INTERVAL_MINUTES, until Ctrl-C
+    Loop,
+    /// Evaluate every city's brackets and print the decisions; never orders
+    Scan,
+    /// Trading mode, lock holder, last cycle, P&L, balance, positions and
+    /// pending settlements
+    Status {
+        /// Skip the exchange: brain/ files only
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Morning plan of the day for each city, sent to the notifier
+    Plan,
+    /// Push the research data to ARCHIVE_BUCKET now
+    Archive,
+    /// Cancel every resting order the bot placed
+    CancelAll,
+    /// Cancel resting orders by id
+    Cancel {
+        #[arg(required = true)]
+        order_ids: Vec<String>,
+    },
+    /// Reports from the ledger, journal and state files
+    Stats {
+        #[command(subcommand)]
+        report: StatsReport,
+    },
+    /// Walk-forward backtests over the journal
+    Backtest {
+        #[command(subcommand)]
+        mode: BacktestMode,
+    },
+    /// Next model-run publish times and city refresh triggers
+    Schedule,
+    /// Today's per-ticker rejection counts and lockouts
+    Lockouts,
+    /// Clear rejection lockouts
+    Unlock {
+        #[arg(required_unless_present = "all")]
+        tickers: Vec<String>,
+        /// Every ticker
+        #[arg(long, conflicts_with = "tickers")]
+        all: bool,
+    },
+    /// Settled decisions with their inputs and outcomes, as Parquet
+    Dataset {
+        #[arg(default_value = "brain/dataset.parquet")]
+        out: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum StatsReport {
+    /// Brier score and calibration error per city and lead time
+    ForecastSkill,
+    /// API requests this hour and day vs soft limits
+    Quota,
+    /// Fees vs gross P&L per month
+    Fees,
+    /// Orders by lifecycle state, plus anything still resting
+    Orders,
+    /// Learned net-edge floors (ADAPTIVE_EDGE_FLOOR)
+    EdgeFloors,
+    /// Last cycle per city
+    Cycle,
+    /// Equity curve, Sharpe-like ratio, drawdowns, monthly P&L
+    Analytics,
+}
+
+#[derive(Subcommand)]
+pub enum BacktestMode {
+    /// Best MIN_NET_EDGE_PP on each training window, scored on the next
+    WalkForward {
+        #[arg(default_value_t = 14)]
+        train_days: u32,
+        #[arg(default_value_t = 7)]
+        test_days: u32,
+    },
+    /// The walk-forward backtest as a self-contained HTML report
+    Report {
+        #[arg(default_value_t = 14)]
+        train_days: u32,
+        #[arg(default_value_t = 7)]
+        test_days: u32,
+        #[arg(default_value = "brain/backtest_report.html")]
+        out: String,
+    },
+}
+
+/// Offline commands that read brain/ files and never touch the exchange.
+/// Returns `None` for the rest, which `main` runs with a config.
+pub fn dispatch(command: &Command) -> Option<anyhow::Result<()>> {
+    Some(match command {
+        Command::Stats { report } => match report {
+            StatsReport::ForecastSkill => forecast_skill(),
+            StatsReport::Quota => quota(),
+            StatsReport::Fees => fees(),
+            StatsReport::Orders => orders(),
+            StatsReport::EdgeFloors => edge_floors(),
+            StatsReport::Cycle => cycle(),
+            StatsReport::Analytics => analytics(),
+        },
+        Command::Backtest { mode } => match mode {
+            BacktestMode::WalkForward { train_days, test_days } => walk_forward(*train_days, *test_days),
+            BacktestMode::Report { train_days, test_days, out } => backtest_report(*train_days, *test_days, out),
+        },
+        Command::Schedule => print_schedule(),
+        Command::Lockouts => lockouts(),
+        Command::Unlock { tickers, all } => unlock(tickers, *all),
+        Command::Dataset { out } => export_dataset(out),
+        _ => return None,
+    })
+}
+
+/// The live price caps, per city, without building the whole `Config`.
+fn price_caps(params: &StrategyParams) -> anyhow::Result<backtest::PriceCaps> {
+    let mut cities = CityConfig::all();
+    for city in &mut cities {
+        city.tuning = CityTuning::from_env(&city.series_ticker)?;
+    }
+    Ok(backtest::PriceCaps::new(params, &cities))
+}
+
+fn walk_forward(train_days: u32, test_days: u32) -> anyhow::Result<()> {
+    let params = StrategyParams::from_env();
+    let live = params.min_net_edge_pp;
+    let folds = backtest::walk_forward(&storage::read_journal()?, train_days, test_days, live, &price_caps(&params)?);
+    if folds.is_empty() {
+        println!(
+            "Not enough settled history: need more than {} days of journaled edges with outcomes.",
+            train_days
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<23} {:<23} {:>6} {:>12} {:>14} {:>14}",
+        "train", "test", "edge", "in-sample", "out-of-sample", format!("live {:.0}pp", live)
+    );
+    let (mut oos, mut base) = (BacktestResult::default(), BacktestResult::default());
+    for f in &folds {
+        println!(
+            "{:<23} {:<23} {:>4.0}pp {:>3}x {:>7} {:>3}x {:>9} {:>3}x {:>9}",
+            format!("{}..{}", f.train.0, f.train.1),
+            format!("{}..{}", f.test.0, f.test.1),
+            f.min_net_edge_pp,
+            f.in_sample.trades, f.in_sample.pnl_cents,
+            f.out_of_sample.trades, f.out_of_sample.pnl_cents,
+            f.baseline.trades, f.baseline.pnl_cents,
+        );
+        for (total, fold) in [(&mut oos, &f.out_of_sample), (&mut base, &f.baseline)] {
+            total.trades += fold.trades;
+            total.wins += fold.wins;
+            total.pnl_cents += fold.pnl_cents;
+        }
+    }
+    let won = |r: &BacktestResult| r.win_rate().map_or_else(|| "-".into(), |w| format!("{:.0}%", w * 100.0));
+    println!(
+        "Out-of-sample total: {} trades, {}, {} won (live {:.0}pp threshold: {} trades, {}, {} won)",
+        oos.trades, oos.pnl_cents, won(&oos), live, base.trades, base.pnl_cents, won(&base)
+    );
+    Ok(())
+}
+
+/// Walk-forward backtest rendered as a self-contained HTML file.
+fn backtest_report(train_days: u32, test_days: u32, out: &str) -> anyhow::Result<()> {
+    let params = StrategyParams::from_env();
+    let live = params.min_net_edge_pp;
+    let journal = storage::read_journal()?;
+    let folds = backtest::walk_forward(&journal, train_days, test_days, live, &price_caps(&params)?);
+    if folds.is_empty() {
+        println!(
+            "Not enough settled history: need more than {} days of journaled edges with outcomes.",
+            train_days
+        );
+        return Ok(());
+    }
+    std::fs::write(out, report::backtest_html(&folds, &stats::forecast_skill(&journal), live))?;
+    println!("Wrote {} ({} folds)", out, folds.len());
+    Ok(())
+}
+
+/// Settled decisions with their inputs and outcomes, as Parquet.
+fn export_dataset(out: &str) -> anyhow::Result<()> {
+    let rows = dataset::rows(&storage::read_journal()?, &storage::read_ledger()?);
+    if rows.is_empty() {
+        println!("No journaled decisions on settled markets yet.");
+        return Ok(());
+    }
+    std::fs::write(out, parquet::encode(&dataset::columns(&rows))?)?;
+    let with_inputs = rows.iter().filter(|r| r.member_count.is_some()).count();
+    println!("Wrote {} ({} rows, {} with forecast and market inputs)", out, rows.len(), with_inputs);
+    Ok(())
+}
+
+fn forecast_skill() -> anyhow::Result<()> {
+    let journal = storage::read_journal()?;
+    let rows = stats::forecast_skill(&journal);
+    if rows.is_empty() {
+        println!("No settled forecasts in the journal yet.");
+        return Ok(());
+    }
+
+    println!("{:<16} {:<8} {:>9} {:>7} {:>7}", "city", "lead", "forecasts", "brier", "ece");
+    for r in &rows {
+        println!(
+            "{:<16} {:<8} {:>9} {:>7.4} {:>7.4}",
+            r.city, r.lead_time, r.forecasts, r.brier, r.ece
+        );
+    }
+    Ok(())
+}
+
+fn fees() -> anyhow::Result<()> {
+    let rows = stats::fee_drag(&storage::read_ledger()?, &storage::read_journal()?);
+    if rows.is_empty() {
+        println!("No settled trades in the ledger yet.");
+        return Ok(());
+    }
+
+    println!("{:<8} {:>6} {:>10} {:>8} {:>8} {:>10} {:>7}", "month", "trades", "gross", "fees", "est.", "net", "drag");
+    for f in &rows {
+        let drag = f.drag_pct().map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "n/a".into());
+        println!(
+            "{:<8} {:>6} {:>10} {:>8} {:>8} {:>10} {:>7}",
+            f.period, f.trades, f.gross_pnl_cents, f.fees_cents(), f.estimated_fees_cents,
+            f.gross_pnl_cents - f.fees_cents(), drag
+        );
+    }
+    Ok(())
+}
+
+/// Learned net-edge floors per bucket, whether or not ADAPTIVE_EDGE_FLOOR
+/// is on, so they can be reviewed before enabling it.
+fn edge_floors() -> anyhow::Result<()> {
+    let params = StrategyParams::from_env();
+    let floors = backtest::edge_floors(
+        &storage::read_journal()?,
+        params.min_net_edge_pp,
+        params.adaptive_edge_prior_samples,
+        &price_caps(&params)?,
+    );
+    if floors.is_empty() {
+        println!("No settled edges with city, market kind and confidence in the journal yet.");
+        return Ok(());
+    }
+
+    println!("{:<16} {:<8} {:<7} {:>7} {:>8} {:>7}", "city", "kind", "conf", "samples", "learned", "floor");
+    for f in &floors {
+        let learned = f.learned_pp.map(|pp| format!("{:.0}pp", pp)).unwrap_or_else(|| "none".into());
+        println!(
+            "{:<16} {:<8} {:<7} {:>7} {:>8} {:>5.1}pp",
+            f.city, f.market_kind, f.confidence.label(), f.samples, learned, f.floor_pp
+        );
+    }
+    println!("Fixed floor (MIN_NET_EDGE_PP): {:.1}pp", params.min_net_edge_pp);
+    Ok(())
+}
+
+/// Orders per lifecycle state, then every order still on the book.
+fn orders() -> anyhow::Result<()> {
+    let orders = storage::read_orders();
+    if orders.is_empty() {
+        println!("No orders tracked yet.");
+        return Ok(());
+    }
+
+    for state in OrderState::ALL {
+        let n = orders.values().filter(|o| o.state == state).count();
+        if n > 0 {
+            println!("{:<17} {:>5}", state.label(), n);
+        }
+    }
+    for o in orders.values().filter(|o| o.state.is_resting()) {
+        let since = o.history.last().map(|c| c.at.as_str()).unwrap_or_default();
+        println!("  {} {} {}/{} filled, {} since {}", o.order_id, o.ticker, o.filled, o.shares, o.state, since);
+    }
+    Ok(())
+}
+
+/// Equity curve figures and monthly P&L from the settled ledger rows.
+fn analytics() -> anyhow::Result<()> {
+    let a = stats::analytics(&storage::read_ledger()?);
+    if a.daily.is_empty() {
+        println!("No settled trades in the ledger yet.");
+        return Ok(());
+    }
+
+    let ratio = |r: Option<f64>| r.map(|r| format!("{:.2}", r)).unwrap_or_else(|| "n/a".into());
+    println!(
+        "Sharpe-like (daily P&L, ×√365): {} last {} trading days, {} all time",
+        ratio(a.rolling_sharpe), stats::SHARPE_WINDOW_DAYS, ratio(a.sharpe)
+    );
+    println!("Longest losing streak: {}", a.longest_losing_streak);
+    println!(
+        "Max drawdown: {} | longest {} days, current {} days",
+        a.max_drawdown_cents, a.longest_drawdown_days, a.current_drawdown_days
+    );
+
+    println!();
+    println!("{:<8} {:>6} {:>7} {:>10} {:>10}", "month", "trades", "W/L", "P&L", "max dd");
+    for m in &a.monthly {
+        println!(
+            "{:<8} {:>6} {:>7} {:>10} {:>10}",
+            m.month, m.trades, format!("{}/{}", m.wins, m.losses), m.pnl_cents, m.max_drawdown_cents
+        );
+    }
+
+    println!();
+    println!("{:<10} {:>10} {:>10}", "day", "P&L", "equity");
+    for d in &a.daily {
+        println!("{:<10} {:>10} {:>10}", d.date, d.pnl_cents, d.equity_cents);
+    }
+    Ok(())
+}
+
+/// The last cycle, city by city.
+fn cycle() -> anyhow::Result<()> {
+    let Some(summary) = storage::read_cycle_summary() else {
+        println!("No cycle summary yet.");
+        return Ok(());
+    };
+    println!("{} → {}: {} ({} trades)", summary.started_at, summary.finished_at, summary.outcome, summary.trades);
+    for c in &summary.cities {
+        let edge = c.best_edge_pp.map(|pp| format!("{:+.1}pp", pp)).unwrap_or_else(|| "-".into());
+        let total = c.latencies_ms.get("total").map(|ms| format!("{}ms", ms)).unwrap_or_default();
+        println!("  {:<16} {:<8} {:>8} {:>8}", c.city, c.action, edge, total);
+        if !c.pass_reasons.is_empty() {
+            let tally: Vec<String> = c.pass_reasons.iter().map(|(r, n)| format!("{}×{}", r, n)).collect();
+            println!("    passes: {}", tally.join(", "));
+        }
+        if let Some(e) = &c.error {
+            println!("    error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn quota() -> anyhow::Result<()> {
+    let limits = QuotaLimits::from_env();
+    println!("{:<11} {:>10} {:>10}   soft limits", "provider", "this hour", "today");
+    for provider in Provider::ALL {
+        let usage = storage::api_usage(provider);
+        let soft = match provider {
+            Provider::Kalshi => format!("{}/hour", limits.kalshi_hourly),
+            Provider::OpenMeteo => format!("{}/hour, {}/day", limits.open_meteo_hourly, limits.open_meteo_daily),
+            Provider::Nws => "-".to_string(),
+        };
+        println!("{:<11} {:>10} {:>10}   {}", provider.label(), usage.hour, usage.day, soft);
+    }
+    Ok(())
+}
+
+/// `kalshi-bot lockouts`: today's per-ticker rejection counts.
+fn lockouts() -> anyhow::Result<()> {
+    let state = storage::read_risk_state();
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let rows: Vec<_> = state.tickers.iter().filter(|(_, r)| r.day == today).collect();
+    if rows.is_empty() {
+        println!("No rejections today.");
+        return Ok(());
+    }
+    println!("{:<32} {:>5} {:<26} last error", "ticker", "count", "locked");
+    for (ticker, r) in rows {
+        println!("{:<32} {:>5} {:<26} {}", ticker, r.count, r.locked_at.as_deref().unwrap_or("-"), r.last_error);
+    }
+    Ok(())
+}
+
+/// `kalshi-bot unlock <TICKER>... | --all`: clear rejection lockouts.
+fn unlock(tickers: &[String], all: bool) -> anyhow::Result<()> {
+    let mut state = storage::read_risk_state();
+    if all {
+        println!("Cleared {} ticker(s).", state.tickers.len());
+        state.tickers.clear();
+    } else {
+        for ticker in tickers {
+            match state.tickers.remove(ticker) {
+                Some(_) => println!("Unlocked {}.", ticker),
+                None => println!("{} has no rejections recorded.", ticker),
+            }
+        }
+    }
+    storage::write_risk_state(&state)
+}
+
+/// `kalshi-bot status`: the Telegram /status text plus who holds the lock,
+/// the loss SPRT, the account as the exchange reports it (unless
+/// `exchange` is None) and the ledger rows still waiting to settle.
+pub async fn status(config: &Config, exchange: Option<&dyn Exchange>) -> anyhow::Result<()> {
+    println!("{}", safety::status()?);
+    match safety::lock_holder(&config.lockfile_path) {
+        Some((pid, age)) => println!(
+            "Lock: {} held by pid {}, heartbeat {}s ago",
+            config.lockfile_path, pid.map_or_else(|| "?".into(), |p| p.to_string()), age.as_secs()
+        ),
+        None => println!("Lock: free, no instance running"),
+    }
+    let ledger = storage::read_ledger()?;
+    if let Some(t) = risk::loss_sprt(&ledger, &config.strategy) {
+        println!("Loss SPRT: log-LR {:.2} of {:.2} to veto ({}W/{}L since the edge last held)", t.llr, t.upper, t.wins, t.losses);
+    }
+
+    if let Some(exchange) = exchange {
+        match (exchange.balance().await, exchange.positions().await) {
+            (Ok(balance), Ok(positions)) => {
+                let exposure: Cents = positions.iter().map(|p| p.exposure_cents).sum();
+                println!();
+                println!("Balance {}, open exposure {} across {} position(s)", balance, exposure, positions.len());
+                if !positions.is_empty() {
+                    println!("  {:<26} {:>4} {:>9} {:>9}", "ticker", "side", "contracts", "exposure");
+                    for p in &positions {
+                        let side = format!("{:?}", p.side).to_uppercase();
+                        println!("  {:<26} {:>4} {:>9} {:>9}", p.ticker, side, p.count, p.exposure_cents);
+                    }
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => println!("\nExchange: unavailable — {:#}", e),
+        }
+    }
+
+    let pending: Vec<_> = ledger.iter().filter(|r| r.is_pending()).collect();
+    println!();
+    if pending.is_empty() {
+        println!("Pending settlements: none");
+        return Ok(());
+    }
+    println!(
+        "Pending settlements: {} row(s), {} contracts, {} at risk",
+        pending.len(),
+        pending.iter().map(|r| r.shares).sum::<u32>(),
+        risk::open_worst_case_loss(&ledger)
+    );
+    println!("  {:<26} {:>4} {:>6} {:>5}  placed", "ticker", "side", "shares", "price");
+    for r in pending {
+        let placed = r.timestamp.get(..16).unwrap_or(&r.timestamp).replace('T', " ");
+        println!("  {:<26} {:>4} {:>6} {:>4}¢  {}", r.ticker, r.side.to_uppercase(), r.shares, r.price, placed);
+    }
+    Ok(())
+}
+
+/// `kalshi-bot scan`: one table per event, a row per bracket.
+pub fn print_scan(scans: &[EventScan]) {
+    if scans.is_empty() {
+        println!("No events to evaluate.");
+        return;
+    }
+    for scan in scans {
+        println!("{} — {}", scan.city, scan.event_ticker);
+        println!("  {:<10} {:>7} {:>7} {:>7} {:>9}  decision", "bracket", "yes ask", "no ask", "model", "net edge");
+        for (market, d) in &scan.brackets {
+            let cents = |c: Option<u32>| c.map_or_else(|| "-".into(), |c| format!("{}¢", c));
+            let model = d.model_probability.map_or_else(|| "-".into(), |p| format!("{:.0}%", p * 100.0));
+            let edge = d.edge.as_ref().map_or_else(|| "-".into(), |e| format!("{:+.1}pp", e.net_pp));
+            let decision = match (d.action, d.pass_reason) {
+                (Action::Buy, _) => format!(
+                    "BUY {} {}x @ {}",
+                    d.side.as_ref().map(|s| format!("{:?}", s).to_uppercase()).unwrap_or_default(),
+                    d.shares.unwrap_or(0),
+                    cents(d.max_price_cents)
+                ),
+                (_, Some(reason)) => format!("pass ({})", reason),
+                _ => "pass".into(),
+            };
+            println!(
+                "  {:<10} {:>7} {:>7} {:>7} {:>9}  {}",
+                market.ticker.split('-').next_back().unwrap_or(&market.ticker),
+                cents(market.yes_ask), cents(market.no_ask), model, edge, decision
+            );
+        }
+    }
+}
+
+/// Next model-run publish times and when each city will next refresh.
+fn print_schedule() -> anyhow::Result<()> {
+    let now = chrono::Utc::now();
+    println!("Model runs (publish times, UTC):");
+    for m in &schedule::MODELS {
+        println!("  {:<11} next {}", m.name, m.next_publish(now).format("%Y-%m-%d %H:%M"));
+    }
+    println!("City refresh triggers:");
+    for city in CityConfig::all() {
+        let (next, model) = schedule::next_trigger(&city, now);
+        println!("  {:<11} next {} ({}, +{}min)", city.name, next.format("%Y-%m-%d %H:%M"), model, city.refresh_offset_minutes);
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::core::types::Config;
+use crate::storage;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Keeps the running config and re-reads it when config.toml changes on
+/// disk, for loop mode: an edit takes effect from the next cycle. An edit
+/// that doesn't parse or validate is logged and ignored, so the loop keeps
+/// trading the last good config. Environment variables can't change under
+/// a running process, so they still win over whatever the file says.
+pub struct ConfigWatcher {
+    state: Mutex<(Arc<Config>, Option<SystemTime>)>,
+}
+
+impl ConfigWatcher {
+    pub fn new(config: Config) -> Self {
+        Self { state: Mutex::new((Arc::new(config), storage::config_modified())) }
+    }
+
+    pub fn current(&self) -> Arc<Config> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let modified = storage::config_modified();
+        if modified != state.1 {
+            state.1 = modified;
+            match state.0.reload() {
+                Ok((config, kept)) => {
+                    if !kept.is_empty() {
+                        tracing::warn!("Config change to {} needs a restart — keeping the running value", kept.join(", "));
+                    }
+                    tracing::info!("Reloaded config: {} cities, applies from this cycle", config.cities.len());
+                    state.0 = Arc::new(config);
+                }
+                Err(e) => tracing::warn!("Config file changed but was rejected, keeping the running config: {:#}", e),
+            }
+        }
+        state.0.clone()
+    }
+}
@@ -0,0 +1,105 @@
+//! Trade-frequency monitor. Counts, per UTC day, how many city evaluations
+//! end in a buy decision and how many send an order, and flags a day that
+//! strays far from the trailing baseline. A sudden jump or drop usually
+//! means a data regression (ensemble members silently empty, a feed stuck on
+//! old values) rather than a change in the markets. Pure: the engine loads
+//! and saves the state and sends the alerts.
+
+use crate::core::types::{ActivityLimits, ActivityState, CitySummary};
+
+/// A day whose activity is out of line with the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityAlert {
+    /// "buys" or "trades"
+    pub metric: &'static str,
+    /// True when far above the baseline, false when far below
+    pub spike: bool,
+    pub actual: u32,
+    /// What the baseline rate predicts for today's evaluations
+    pub expected: f64,
+    pub evaluated: u32,
+    pub baseline_days: u32,
+}
+
+impl ActivityAlert {
+    /// Stable key, so each alert fires once a day.
+    pub fn key(&self) -> String {
+        format!("{}_{}", self.metric, if self.spike { "spike" } else { "drop" })
+    }
+}
+
+impl std::fmt::Display for ActivityAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} today across {} evaluations, {:.1} expected from the last {} days — {}",
+            self.actual,
+            self.metric,
+            self.evaluated,
+            self.expected,
+            self.baseline_days,
+            if self.spike { "trading far more than usual" } else { "trading far less than usual" }
+        )
+    }
+}
+
+/// Add one cycle's city summaries to `day`, and drop days the baseline no
+/// longer needs.
+pub fn record(state: &mut ActivityState, day: &str, cities: &[CitySummary], trades: u32, keep_days: u32) {
+    let counts = state.days.entry(day.to_string()).or_default();
+    counts.cycles += 1;
+    for city in cities.iter().filter(|c| c.action != "skipped") {
+        counts.evaluated += 1;
+        if city.action == "traded" || city.action.starts_with("buy") {
+            counts.buys += 1;
+        }
+    }
+    counts.trades += trades;
+
+    if let Some(cutoff) = chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.checked_sub_days(chrono::Days::new(keep_days as u64)))
+    {
+        let cutoff = cutoff.format("%Y-%m-%d").to_string();
+        state.days.retain(|d, _| *d >= cutoff);
+    }
+}
+
+/// Today's buys and trades against the rates of the `baseline_days`
+/// before it. Alerts already sent today are left out.
+pub fn check(state: &ActivityState, day: &str, limits: &ActivityLimits) -> Vec<ActivityAlert> {
+    let Some(today) = state.days.get(day) else { return Vec::new() };
+    let baseline: Vec<_> = state
+        .days
+        .range(..day.to_string())
+        .rev()
+        .take(limits.baseline_days as usize)
+        .map(|(_, d)| d)
+        .collect();
+    let evaluated: u32 = baseline.iter().map(|d| d.evaluated).sum();
+    if evaluated == 0 || today.evaluated == 0 {
+        return Vec::new();
+    }
+
+    let metrics = [
+        ("buys", today.buys, baseline.iter().map(|d| d.buys).sum::<u32>()),
+        ("trades", today.trades, baseline.iter().map(|d| d.trades).sum::<u32>()),
+    ];
+    metrics
+        .into_iter()
+        .filter_map(|(metric, actual, past)| {
+            let expected = past as f64 / evaluated as f64 * today.evaluated as f64;
+            let spike = actual as f64 >= limits.min_count && actual as f64 > expected * limits.alert_ratio;
+            let drop = expected >= limits.min_count && (actual as f64) * limits.alert_ratio < expected;
+            (spike || drop).then_some(ActivityAlert {
+                metric,
+                spike,
+                actual,
+                expected,
+                evaluated: today.evaluated,
+                baseline_days: baseline.len() as u32,
+            })
+        })
+        .filter(|a| !today.alerted.contains(&a.key()))
+        .collect()
+}
@@ -0,0 +1,254 @@
+use crate::core::risk;
+use crate::core::rules_brain;
+use crate::core::types::{
+    event_of, Action, BacktestResult, BacktestTrade, Cents, CityConfig, EdgeFloor, ForecastConfidence, JournalEntry, PassReason,
+    Side, StrategyParams, WalkForwardFold,
+};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Net-edge thresholds tried on each training window, in pp.
+pub const EDGE_GRID: [f64; 15] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 12.0, 14.0, 16.0, 18.0, 20.0];
+
+/// The most the live engine pays per contract in each city: MAX_PRICE_CENTS,
+/// or the city's MAX_PRICE_CENTS_<SERIES>.
+pub struct PriceCaps {
+    default: u32,
+    cities: HashMap<String, u32>,
+}
+
+impl PriceCaps {
+    pub fn new(base: &StrategyParams, cities: &[CityConfig]) -> Self {
+        Self {
+            default: base.max_price_cents,
+            cities: cities.iter().map(|c| (c.name.clone(), c.strategy(base).max_price_cents)).collect(),
+        }
+    }
+
+    fn get(&self, city: &str) -> u32 {
+        self.cities.get(city).copied().unwrap_or(self.default)
+    }
+}
+
+/// A journaled bracket evaluation that a lower or higher edge threshold
+/// could have turned into a trade, joined to its settled outcome.
+struct Candidate {
+    day: NaiveDate,
+    city: String,
+    market_kind: Option<String>,
+    confidence: Option<ForecastConfidence>,
+    ticker: String,
+    event: String,
+    net_pp: f64,
+    price_cents: u32,
+    won: bool,
+}
+
+/// Decisions that reached the edge threshold — buys, and passes for edge
+/// alone — on markets with a recorded outcome. Other vetoes (spread,
+/// liquidity, ...) were decided on live data that isn't replayable, so
+/// those decisions stay out.
+fn candidates(journal: &[JournalEntry]) -> Vec<Candidate> {
+    let outcomes: HashMap<&str, &str> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::MarketOutcome(o) if !o.is_void() => Some((o.ticker.as_str(), o.result.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    journal
+        .iter()
+        .filter_map(|e| {
+            let JournalEntry::Decision(d) = e else { return None };
+            let edge = d.edge.as_ref()?;
+            let reached = d.action == Action::Buy || d.pass_reason == Some(PassReason::EdgeTooSmall);
+            let result = outcomes.get(d.ticker.as_str())?;
+            let day = NaiveDate::parse_from_str(d.timestamp.get(..10)?, "%Y-%m-%d").ok()?;
+            reached.then(|| Candidate {
+                day,
+                city: d.city.clone(),
+                market_kind: d.market_kind.clone(),
+                confidence: d.confidence.clone(),
+                ticker: d.ticker.clone(),
+                event: if d.event_ticker.is_empty() { event_of(&d.ticker) } else { &d.event_ticker }.to_string(),
+                net_pp: edge.net_pp,
+                price_cents: edge.price_cents,
+                won: matches!((&edge.side, *result), (Side::Yes, "yes") | (Side::No, "no")),
+            })
+        })
+        .collect()
+}
+
+/// Replay candidates in journal order at one threshold. Like the live
+/// engine, an event is traded at most once and each city's price cap holds.
+fn replay<'a>(candidates: impl Iterator<Item = &'a Candidate>, min_net_edge_pp: f64, caps: &PriceCaps) -> Vec<BacktestTrade> {
+    let mut traded: HashSet<&str> = HashSet::new();
+    let mut trades = Vec::new();
+    for c in candidates {
+        if c.net_pp < min_net_edge_pp || c.price_cents > caps.get(&c.city) || !traded.insert(c.event.as_str()) {
+            continue;
+        }
+        let shares = rules_brain::size_from_edge(c.net_pp / 100.0);
+        trades.push(BacktestTrade {
+            day: c.day.to_string(),
+            city: c.city.clone(),
+            ticker: c.ticker.clone(),
+            net_pp: c.net_pp,
+            shares,
+            won: c.won,
+            pnl_cents: c.pnl(shares),
+        });
+    }
+    trades
+}
+
+fn simulate<'a>(candidates: impl Iterator<Item = &'a Candidate>, min_net_edge_pp: f64, caps: &PriceCaps) -> BacktestResult {
+    BacktestResult::from_trades(&replay(candidates, min_net_edge_pp, caps))
+}
+
+impl Candidate {
+    /// Settled P&L of `shares` contracts, net of the estimated taker fee.
+    fn pnl(&self, shares: u32) -> Cents {
+        let gross = if self.won {
+            Cents::for_contracts(shares, 100 - self.price_cents)
+        } else {
+            -Cents::for_contracts(shares, self.price_cents)
+        };
+        gross - risk::taker_fee_cents(shares, self.price_cents)
+    }
+}
+
+/// Learn a net-edge floor per city / market kind / confidence bucket: the
+/// lowest `EDGE_GRID` threshold at which one contract on every settled
+/// candidate at or above it made money, shrunk toward `prior_pp` with
+/// `prior_samples` of pseudo-weight so a thin bucket stays near the fixed
+/// floor. A bucket where no threshold paid learns the top of the grid.
+/// Decisions journaled before the bucket fields existed are skipped.
+pub fn edge_floors(journal: &[JournalEntry], prior_pp: f64, prior_samples: f64, caps: &PriceCaps) -> Vec<EdgeFloor> {
+    let mut buckets: BTreeMap<(String, String, ForecastConfidence), Vec<Candidate>> = BTreeMap::new();
+    for c in candidates(journal) {
+        if let (Some(kind), Some(confidence)) = (c.market_kind.clone(), c.confidence.clone()) {
+            buckets.entry((c.city.clone(), kind, confidence)).or_default().push(c);
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|((city, market_kind, confidence), cs)| {
+            let cap = caps.get(&city);
+            let learned_pp = EDGE_GRID.iter().copied().find(|&pp| {
+                let pnl: Cents = cs.iter().filter(|c| c.net_pp >= pp && c.price_cents <= cap).map(|c| c.pnl(1)).sum();
+                pnl > Cents::ZERO
+            });
+            let n = cs.len() as f64;
+            let learned = learned_pp.unwrap_or(EDGE_GRID[EDGE_GRID.len() - 1]);
+            EdgeFloor {
+                city,
+                market_kind,
+                confidence,
+                samples: cs.len() as u32,
+                learned_pp,
+                floor_pp: (n * learned + prior_samples.max(0.0) * prior_pp) / (n + prior_samples.max(0.0)),
+            }
+        })
+        .collect()
+}
+
+/// Walk-forward validation of the net-edge threshold: pick the best
+/// `EDGE_GRID` value on `train_days` of journal history, score it on the
+/// following `test_days`, then roll both windows forward by `test_days`.
+/// Ties go to the higher threshold.
+pub fn walk_forward(
+    journal: &[JournalEntry],
+    train_days: u32,
+    test_days: u32,
+    live_min_net_edge_pp: f64,
+    caps: &PriceCaps,
+) -> Vec<WalkForwardFold> {
+    let candidates = candidates(journal);
+    let (Some(first), Some(last)) = (
+        candidates.iter().map(|c| c.day).min(),
+        candidates.iter().map(|c| c.day).max(),
+    ) else {
+        return Vec::new();
+    };
+    let (train_days, test_days) = (train_days.max(1) as i64, test_days.max(1) as i64);
+
+    let window = |from: NaiveDate, days: i64| {
+        let to = from + chrono::Duration::days(days);
+        candidates.iter().filter(move |c| c.day >= from && c.day < to)
+    };
+    let label = |from: NaiveDate, days: i64| {
+        (from.to_string(), (from + chrono::Duration::days(days - 1)).to_string())
+    };
+
+    let mut folds = Vec::new();
+    let mut train_start = first;
+    loop {
+        let test_start = train_start + chrono::Duration::days(train_days);
+        if test_start > last {
+            break;
+        }
+        let (best_pp, in_sample) = EDGE_GRID
+            .iter()
+            .map(|&pp| (pp, simulate(window(train_start, train_days), pp, caps)))
+            .fold((EDGE_GRID[0], None::<BacktestResult>), |best, (pp, r)| match best.1 {
+                Some(b) if b.pnl_cents > r.pnl_cents => best,
+                _ => (pp, Some(r)),
+            });
+
+        let out_of_sample_trades = replay(window(test_start, test_days), best_pp, caps);
+        let baseline_trades = replay(window(test_start, test_days), live_min_net_edge_pp, caps);
+        folds.push(WalkForwardFold {
+            train: label(train_start, train_days),
+            test: label(test_start, test_days),
+            min_net_edge_pp: best_pp,
+            in_sample: in_sample.unwrap_or_default(),
+            out_of_sample: BacktestResult::from_trades(&out_of_sample_trades),
+            baseline: BacktestResult::from_trades(&baseline_trades),
+            out_of_sample_trades,
+            baseline_trades,
+        });
+        train_start += chrono::Duration::days(test_days);
+    }
+    folds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(city: &str, event: &str, price_cents: u32) -> Candidate {
+        Candidate {
+            day: NaiveDate::from_ymd_opt(2026, 2, 12).unwrap(),
+            city: city.into(),
+            market_kind: Some("between".into()),
+            confidence: Some(ForecastConfidence::High),
+            ticker: format!("{}-B40.5", event),
+            event: event.into(),
+            net_pp: 10.0,
+            price_cents,
+            won: true,
+        }
+    }
+
+    #[test]
+    fn replay_holds_each_city_to_its_own_price_cap() {
+        let mut cities = CityConfig::all();
+        cities[0].tuning.max_price_cents = Some(60);
+        let caps = PriceCaps::new(&StrategyParams::default(), &cities);
+        let tuned = cities[0].name.clone();
+        let other = cities[1].name.clone();
+
+        let cs = [
+            candidate(&tuned, "KXA-26FEB12", 55),
+            candidate(&tuned, "KXA-26FEB13", 61),
+            candidate(&other, "KXB-26FEB12", 50),
+            candidate(&other, "KXB-26FEB13", 55),
+            candidate("Nowhere", "KXC-26FEB12", 55),
+        ];
+        let traded: Vec<String> = replay(cs.iter(), 5.0, &caps).into_iter().map(|t| t.ticker).collect();
+        assert_eq!(traded, ["KXA-26FEB12-B40.5", "KXB-26FEB12-B40.5"]);
+    }
+}
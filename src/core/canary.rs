@@ -0,0 +1,83 @@
+//! Canary sizing: a new strategy version trades small until its settled
+//! trades bear out what the model expected of them. Pure functions over
+//! the journal and ledger; the engine persists the ramp in
+//! brain/canary.json.
+
+use crate::core::risk;
+use crate::core::types::{Action, CanaryLimits, CanaryState, Cents, JournalEntry, LedgerRow, Side};
+use std::collections::{HashMap, HashSet};
+
+/// A fresh ramp for `version`, first seen at `now`.
+pub fn start(version: &str, now: &str, limits: &CanaryLimits) -> CanaryState {
+    CanaryState {
+        version: version.to_string(),
+        since: now.to_string(),
+        settled: 0,
+        expected_cents: 0.0,
+        realized_cents: Cents::ZERO,
+        fraction: limits.fraction,
+        graduated: false,
+    }
+}
+
+/// `state` re-scored on the version's settled trades. A trade belongs to
+/// the version of the last buy decision on its ticker before it; its
+/// expectation is the decision's probability for the side bought against
+/// the price paid, and both sides are net of the fee (reported, else the
+/// taker estimate).
+pub fn ramp(state: &CanaryState, limits: &CanaryLimits, journal: &[JournalEntry], ledger: &[LedgerRow]) -> CanaryState {
+    let mut settled: HashMap<&str, Cents> = HashMap::new();
+    let mut open: HashSet<&str> = HashSet::new();
+    for r in ledger.iter().filter(|r| !r.order_id.is_empty()) {
+        if r.is_settled() {
+            *settled.entry(r.order_id.as_str()).or_insert(Cents::ZERO) += r.pnl_cents;
+        } else if r.is_open() {
+            open.insert(r.order_id.as_str());
+        }
+    }
+
+    let mut last_buy: HashMap<&str, (Option<&str>, Option<f64>)> = HashMap::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let (mut count, mut expected, mut variance, mut realized) = (0u32, 0.0, 0.0, Cents::ZERO);
+    for entry in journal {
+        match entry {
+            JournalEntry::Decision(d) if d.action == Action::Buy => {
+                last_buy.insert(d.ticker.as_str(), (d.strategy_version.as_deref(), d.model_probability));
+            }
+            JournalEntry::Trade(t) => {
+                let Some(&(version, p_yes)) = last_buy.get(t.ticker.as_str()) else { continue };
+                if version != Some(state.version.as_str()) || t.timestamp < state.since || !seen.insert(t.order_id.as_str()) {
+                    continue;
+                }
+                let (Some(&pnl), Some(p_yes)) = (settled.get(t.order_id.as_str()), p_yes) else { continue };
+                if open.contains(t.order_id.as_str()) {
+                    continue;
+                }
+                let p = if t.side == Side::Yes { p_yes } else { 1.0 - p_yes };
+                let shares = t.shares as f64;
+                let fee = t.fee_cents.unwrap_or_else(|| risk::taker_fee_cents(t.shares, t.price_cents));
+                count += 1;
+                expected += shares * (p * 100.0 - t.price_cents as f64) - fee.0 as f64;
+                variance += shares * shares * p * (1.0 - p) * 100.0 * 100.0;
+                realized += pnl - fee;
+            }
+            _ => {}
+        }
+    }
+
+    let confirmed = realized.0 as f64 >= expected - limits.tolerance_sd * variance.sqrt();
+    let progress = (count as f64 / limits.trades.max(1) as f64).min(1.0);
+    CanaryState {
+        settled: count,
+        expected_cents: expected,
+        realized_cents: realized,
+        fraction: if confirmed { limits.fraction + (1.0 - limits.fraction) * progress } else { limits.fraction },
+        graduated: confirmed && count >= limits.trades,
+        ..state.clone()
+    }
+}
+
+/// `shares` cut to `fraction`, never below one contract.
+pub fn size(shares: u32, fraction: f64) -> u32 {
+    ((shares as f64 * fraction).floor() as u32).clamp(1, shares.max(1))
+}
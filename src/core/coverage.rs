@@ -0,0 +1,65 @@
+//! Whether an event's brackets tile the temperature line, and how much of
+//! the model's distribution lands where no bracket pays. Bounds follow
+//! `MarketType::yes_at`, the ones ensemble members are counted against, so
+//! a gap here is probability the brackets' YES prices can't add up to.
+//! Pure: the engine logs and exports the result.
+
+use crate::core::types::{MarketState, MarketType};
+
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Ranges between two brackets that neither covers
+    pub gaps: Vec<(f64, f64)>,
+    /// Tickers whose ranges overlap another bracket's
+    pub overlaps: Vec<(String, String)>,
+    /// Nothing covers below the lowest strike / above the highest
+    pub open_below: bool,
+    pub open_above: bool,
+    /// Share of ensemble members no bracket resolves YES on; None without
+    /// members
+    pub uncovered_mass: Option<f64>,
+}
+
+pub fn check(brackets: &[MarketState], members: &[f64]) -> Coverage {
+    let mut ranges: Vec<(f64, f64, &str)> = brackets
+        .iter()
+        .filter_map(|b| {
+            let (lower, upper) = match MarketType::from_market(b)? {
+                MarketType::Below(t) => (f64::NEG_INFINITY, t),
+                MarketType::Above(t) => (t, f64::INFINITY),
+                MarketType::Between(lo, hi) => (lo, hi),
+            };
+            Some((lower, upper, b.ticker.as_str()))
+        })
+        .collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+    let mut coverage = Coverage {
+        open_below: ranges.first().is_none_or(|r| r.0.is_finite()),
+        open_above: ranges.iter().all(|r| r.1.is_finite()),
+        ..Default::default()
+    };
+    // Sweep left to right, remembering how far the covered line reaches
+    let mut reach: Option<(f64, &str)> = None;
+    for &(lower, upper, ticker) in &ranges {
+        if let Some((end, prev)) = reach {
+            if lower > end {
+                coverage.gaps.push((end, lower));
+            } else if lower < end {
+                coverage.overlaps.push((prev.to_string(), ticker.to_string()));
+            }
+        }
+        if reach.is_none_or(|(end, _)| upper > end) {
+            reach = Some((upper, ticker));
+        }
+    }
+
+    if !members.is_empty() && !ranges.is_empty() {
+        let uncovered = members
+            .iter()
+            .filter(|h| !brackets.iter().filter_map(MarketType::from_market).any(|mt| mt.yes_at(**h)))
+            .count();
+        coverage.uncovered_mass = Some(uncovered as f64 / members.len() as f64);
+    }
+    coverage
+}
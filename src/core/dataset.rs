@@ -0,0 +1,258 @@
+//! Research dataset: every journaled decision on a market that has since
+//! settled, one row each — the forecast and market inputs the brain saw
+//! (features) next to how the market resolved and what the trade made
+//! (labels). Flat columns, so an offline model can train on it directly;
+//! `crate::parquet` writes it out, and `STRATEGY=ml` feeds a trained model
+//! the same `FEATURES` live.
+
+use crate::core::risk;
+use crate::core::types::{event_of, Action, Cents, DecisionInputs, JournalEntry, LedgerRow, Side};
+use std::collections::HashMap;
+
+/// Model inputs, in the order `features` lays them out: dataset column
+/// names, so a model trained on these columns runs unchanged.
+pub const FEATURES: [&str; 17] = [
+    "minutes_to_expiry",
+    "member_count",
+    "ensemble_mean",
+    "ensemble_std",
+    "ensemble_p10",
+    "ensemble_p90",
+    "nws_forecast_high",
+    "observed_high",
+    "floor_strike",
+    "cap_strike",
+    "yes_bid",
+    "yes_ask",
+    "no_bid",
+    "no_ask",
+    "volume_24h",
+    "open_interest",
+    "hours_since_midnight_utc",
+];
+
+/// The `FEATURES` row for a decision at `timestamp` (RFC 3339); a missing
+/// value is NaN.
+pub fn features(inputs: &DecisionInputs, minutes_to_expiry: Option<f64>, timestamp: &str) -> Vec<f32> {
+    let opt = |v: Option<f64>| v.map_or(f32::NAN, |v| v as f32);
+    let cents = |v: Option<u32>| v.map_or(f32::NAN, |v| v as f32);
+    vec![
+        opt(minutes_to_expiry),
+        inputs.member_count as f32,
+        opt(inputs.ensemble_mean),
+        opt(inputs.ensemble_std),
+        opt(inputs.ensemble_p10),
+        opt(inputs.ensemble_p90),
+        opt(inputs.nws_forecast_high),
+        opt(inputs.observed_high),
+        opt(inputs.floor_strike),
+        opt(inputs.cap_strike),
+        cents(inputs.yes_bid),
+        cents(inputs.yes_ask),
+        cents(inputs.no_bid),
+        cents(inputs.no_ask),
+        inputs.volume_24h as f32,
+        inputs.open_interest as f32,
+        opt(hours_since_midnight(timestamp)),
+    ]
+}
+
+fn hours_since_midnight(timestamp: &str) -> Option<f64> {
+    use chrono::Timelike;
+    let t = chrono::DateTime::parse_from_rfc3339(timestamp).ok()?.with_timezone(&chrono::Utc);
+    Some(t.num_seconds_from_midnight() as f64 / 3600.0)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DatasetRow {
+    pub timestamp: String,
+    pub city: String,
+    pub event_ticker: String,
+    pub ticker: String,
+    pub market_kind: Option<String>,
+    pub confidence: Option<String>,
+    pub action: String,
+    pub side: Option<String>,
+    pub pass_reason: Option<String>,
+    pub minutes_to_expiry: Option<f64>,
+    pub model_probability: Option<f64>,
+    /// Ask on the side the edge was measured on
+    pub price_cents: Option<i64>,
+    pub raw_edge_pp: Option<f64>,
+    pub net_edge_pp: Option<f64>,
+    pub member_count: Option<i64>,
+    pub ensemble_mean: Option<f64>,
+    pub ensemble_std: Option<f64>,
+    pub ensemble_p10: Option<f64>,
+    pub ensemble_p90: Option<f64>,
+    pub nws_forecast_high: Option<f64>,
+    pub observed_high: Option<f64>,
+    pub floor_strike: Option<f64>,
+    pub cap_strike: Option<f64>,
+    pub yes_bid: Option<i64>,
+    pub yes_ask: Option<i64>,
+    pub no_bid: Option<i64>,
+    pub no_ask: Option<i64>,
+    pub volume_24h: Option<i64>,
+    pub open_interest: Option<i64>,
+    pub hours_since_midnight_utc: Option<f64>,
+    /// "yes" or "no"
+    pub outcome: String,
+    pub yes_won: bool,
+    /// One contract on the edge's side at `price_cents`, held to
+    /// settlement, net of the estimated taker fee
+    pub pnl_per_contract_cents: Option<i64>,
+    /// Settled ledger P&L of the orders this decision placed; buys only
+    pub realized_pnl_cents: Option<i64>,
+}
+
+/// One row per decision whose market settled yes or no. A trade is
+/// credited to the last buy decision on its ticker before it.
+pub fn rows(journal: &[JournalEntry], ledger: &[LedgerRow]) -> Vec<DatasetRow> {
+    let outcomes: HashMap<&str, &str> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::MarketOutcome(o) if !o.is_void() => Some((o.ticker.as_str(), o.result.as_str())),
+            _ => None,
+        })
+        .collect();
+    let settled: HashMap<&str, Cents> = ledger
+        .iter()
+        .filter(|r| r.is_settled() && !r.order_id.is_empty())
+        .map(|r| (r.order_id.as_str(), r.pnl_cents))
+        .collect();
+
+    let mut rows: Vec<DatasetRow> = Vec::new();
+    let mut orders: Vec<Vec<&str>> = Vec::new();
+    let mut last_buy: HashMap<&str, usize> = HashMap::new();
+    for entry in journal {
+        match entry {
+            JournalEntry::Decision(d) => {
+                let Some(&result) = outcomes.get(d.ticker.as_str()) else { continue };
+                let edge = d.edge.as_ref();
+                let inputs = d.inputs.as_ref();
+                let yes_won = result == "yes";
+                if d.action == Action::Buy {
+                    last_buy.insert(d.ticker.as_str(), rows.len());
+                }
+                rows.push(DatasetRow {
+                    timestamp: d.timestamp.clone(),
+                    city: d.city.clone(),
+                    event_ticker: if d.event_ticker.is_empty() { event_of(&d.ticker) } else { &d.event_ticker }.to_string(),
+                    ticker: d.ticker.clone(),
+                    market_kind: d.market_kind.clone(),
+                    confidence: d.confidence.as_ref().map(|c| format!("{:?}", c).to_lowercase()),
+                    action: format!("{:?}", d.action).to_lowercase(),
+                    side: d.side.as_ref().map(|s| format!("{:?}", s).to_lowercase()),
+                    pass_reason: d.pass_reason.map(|r| r.label().to_string()),
+                    minutes_to_expiry: d.minutes_to_expiry,
+                    model_probability: d.model_probability,
+                    price_cents: edge.map(|e| e.price_cents as i64),
+                    raw_edge_pp: edge.map(|e| e.raw_pp),
+                    net_edge_pp: edge.map(|e| e.net_pp),
+                    member_count: inputs.map(|i| i.member_count as i64),
+                    ensemble_mean: inputs.and_then(|i| i.ensemble_mean),
+                    ensemble_std: inputs.and_then(|i| i.ensemble_std),
+                    ensemble_p10: inputs.and_then(|i| i.ensemble_p10),
+                    ensemble_p90: inputs.and_then(|i| i.ensemble_p90),
+                    nws_forecast_high: inputs.and_then(|i| i.nws_forecast_high),
+                    observed_high: inputs.and_then(|i| i.observed_high),
+                    floor_strike: inputs.and_then(|i| i.floor_strike),
+                    cap_strike: inputs.and_then(|i| i.cap_strike),
+                    yes_bid: inputs.and_then(|i| i.yes_bid).map(i64::from),
+                    yes_ask: inputs.and_then(|i| i.yes_ask).map(i64::from),
+                    no_bid: inputs.and_then(|i| i.no_bid).map(i64::from),
+                    no_ask: inputs.and_then(|i| i.no_ask).map(i64::from),
+                    volume_24h: inputs.map(|i| i.volume_24h as i64),
+                    open_interest: inputs.map(|i| i.open_interest as i64),
+                    hours_since_midnight_utc: hours_since_midnight(&d.timestamp),
+                    outcome: result.to_string(),
+                    yes_won,
+                    pnl_per_contract_cents: edge.map(|e| {
+                        let won = (e.side == Side::Yes) == yes_won;
+                        let gross = if won { 100 - e.price_cents as i64 } else { -(e.price_cents as i64) };
+                        gross - risk::taker_fee_cents(1, e.price_cents).0
+                    }),
+                    realized_pnl_cents: None,
+                });
+                orders.push(Vec::new());
+            }
+            JournalEntry::Trade(t) => {
+                if let Some(&i) = last_buy.get(t.ticker.as_str()) {
+                    orders[i].push(t.order_id.as_str());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (row, placed) in rows.iter_mut().zip(&orders) {
+        let pnl: Vec<Cents> = placed.iter().filter_map(|id| settled.get(id).copied()).collect();
+        if !pnl.is_empty() {
+            row.realized_pnl_cents = Some(pnl.into_iter().sum::<Cents>().0);
+        }
+    }
+    rows
+}
+
+/// A column of the dataset, values in row order.
+pub enum Values {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+    Text(Vec<Option<String>>),
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub values: Values,
+}
+
+/// The rows as named columns, in `DatasetRow` field order.
+pub fn columns(rows: &[DatasetRow]) -> Vec<Column> {
+    fn text(name: &'static str, rows: &[DatasetRow], f: impl Fn(&DatasetRow) -> Option<String>) -> Column {
+        Column { name, values: Values::Text(rows.iter().map(f).collect()) }
+    }
+    fn float(name: &'static str, rows: &[DatasetRow], f: impl Fn(&DatasetRow) -> Option<f64>) -> Column {
+        Column { name, values: Values::Float(rows.iter().map(f).collect()) }
+    }
+    fn int(name: &'static str, rows: &[DatasetRow], f: impl Fn(&DatasetRow) -> Option<i64>) -> Column {
+        Column { name, values: Values::Int(rows.iter().map(f).collect()) }
+    }
+    vec![
+        text("timestamp", rows, |r| Some(r.timestamp.clone())),
+        text("city", rows, |r| Some(r.city.clone())),
+        text("event_ticker", rows, |r| Some(r.event_ticker.clone())),
+        text("ticker", rows, |r| Some(r.ticker.clone())),
+        text("market_kind", rows, |r| r.market_kind.clone()),
+        text("confidence", rows, |r| r.confidence.clone()),
+        text("action", rows, |r| Some(r.action.clone())),
+        text("side", rows, |r| r.side.clone()),
+        text("pass_reason", rows, |r| r.pass_reason.clone()),
+        float("minutes_to_expiry", rows, |r| r.minutes_to_expiry),
+        float("model_probability", rows, |r| r.model_probability),
+        int("price_cents", rows, |r| r.price_cents),
+        float("raw_edge_pp", rows, |r| r.raw_edge_pp),
+        float("net_edge_pp", rows, |r| r.net_edge_pp),
+        int("member_count", rows, |r| r.member_count),
+        float("ensemble_mean", rows, |r| r.ensemble_mean),
+        float("ensemble_std", rows, |r| r.ensemble_std),
+        float("ensemble_p10", rows, |r| r.ensemble_p10),
+        float("ensemble_p90", rows, |r| r.ensemble_p90),
+        float("nws_forecast_high", rows, |r| r.nws_forecast_high),
+        float("observed_high", rows, |r| r.observed_high),
+        float("floor_strike", rows, |r| r.floor_strike),
+        float("cap_strike", rows, |r| r.cap_strike),
+        int("yes_bid", rows, |r| r.yes_bid),
+        int("yes_ask", rows, |r| r.yes_ask),
+        int("no_bid", rows, |r| r.no_bid),
+        int("no_ask", rows, |r| r.no_ask),
+        int("volume_24h", rows, |r| r.volume_24h),
+        int("open_interest", rows, |r| r.open_interest),
+        float("hours_since_midnight_utc", rows, |r| r.hours_since_midnight_utc),
+        text("outcome", rows, |r| Some(r.outcome.clone())),
+        Column { name: "yes_won", values: Values::Bool(rows.iter().map(|r| Some(r.yes_won)).collect()) },
+        int("pnl_per_contract_cents", rows, |r| r.pnl_per_contract_cents),
+        int("realized_pnl_cents", rows, |r| r.realized_pnl_cents),
+    ]
+}
@@ -0,0 +1,202 @@
+use crate::core::types::*;
+
+/// Integer temperature grid covering every ensemble member and bracket strike,
+/// padded by 2°F on each side.
+pub fn temperature_grid(member_highs: &[f64], brackets: &[MarketState]) -> Vec<i32> {
+    let strikes = brackets
+        .iter()
+        .flat_map(|b| [b.floor_strike, b.cap_strike])
+        .flatten();
+    let values: Vec<f64> = member_highs.iter().copied().chain(strikes).collect();
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let lo = values.iter().cloned().fold(f64::INFINITY, f64::min).floor() as i32 - 2;
+    let hi = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max).ceil() as i32 + 2;
+    (lo..=hi).collect()
+}
+
+/// Model CDF: P(high < x) at each grid temperature, from raw member highs.
+pub fn member_cdf(member_highs: &[f64], grid: &[i32]) -> Vec<f64> {
+    let total = member_highs.len() as f64;
+    grid.iter()
+        .map(|&x| {
+            if total == 0.0 {
+                return 0.0;
+            }
+            member_highs.iter().filter(|h| **h < x as f64).count() as f64 / total
+        })
+        .collect()
+}
+
+/// Drop or winsorize the `k` lowest and highest member highs so one rogue
+/// member can't move a near-threshold probability by a whole 1/n. Returns
+/// the members unchanged when trimming would leave fewer than three.
+pub fn trim_members(member_highs: &[f64], k: usize, mode: TrimMode) -> Vec<f64> {
+    let n = member_highs.len();
+    if k == 0 || n < 2 * k + 3 {
+        return member_highs.to_vec();
+    }
+    let mut sorted = member_highs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    match mode {
+        TrimMode::Drop => sorted[k..n - k].to_vec(),
+        TrimMode::Winsorize => {
+            let (lo, hi) = (sorted[k], sorted[n - k - 1]);
+            member_highs.iter().map(|h| h.clamp(lo, hi)).collect()
+        }
+    }
+}
+
+/// P(value < x) from a few quantiles: linear between neighbouring
+/// quantiles, and beyond the outermost ones along the nearest segment's
+/// slope, clamped to 0–1. None with fewer than two distinct quantiles.
+pub fn quantile_cdf(quantiles: &[Quantile], x: f64) -> Option<f64> {
+    let mut points: Vec<(f64, f64)> = quantiles.iter().map(|q| (q.value, q.pct / 100.0)).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup_by(|a, b| a.0 == b.0);
+    if points.len() < 2 {
+        return None;
+    }
+    let segment = points
+        .windows(2)
+        .position(|w| x < w[1].0)
+        .unwrap_or(points.len() - 2);
+    let ((x0, p0), (x1, p1)) = (points[segment], points[segment + 1]);
+    Some((p0 + (x - x0) * (p1 - p0) / (x1 - x0)).clamp(0.0, 1.0))
+}
+
+/// Keep a forecast probability off 0 and 1. Each bound is the configured
+/// one or, from `members` ensemble members, the rule-of-succession
+/// 1/(n+2), whichever sits further from the edge: 0 of 10 members is not
+/// certainty. With no members only the configured bounds apply.
+pub fn bound_probability(p: f64, members: usize, floor: f64, ceiling: f64) -> f64 {
+    let succession = if members > 0 { 1.0 / (members as f64 + 2.0) } else { 0.0 };
+    let lo = floor.max(succession);
+    let hi = ceiling.min(1.0 - succession);
+    p.max(lo).min(hi)
+}
+
+/// Whether a tail probability (YES within `tail` of 0 or 1) is backed by
+/// sources outside the ensemble: at least one point forecast, and every
+/// one of them on the side the probability favours. Probabilities away
+/// from the tails pass unchecked.
+pub fn tail_confirmed(yes: f64, tail: f64, market_type: &MarketType, sources: &[f64]) -> bool {
+    if tail <= 0.0 || (yes > tail && yes < 1.0 - tail) {
+        return true;
+    }
+    let says_yes = yes >= 1.0 - tail;
+    !sources.is_empty() && sources.iter().all(|v| market_type.yes_at(*v) == says_yes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rules_brain::compute_ensemble_yes_from_members;
+
+    /// 20 members spread over 75–78.8°F and one rogue run at 95°F.
+    fn members_with_outlier() -> Vec<f64> {
+        let mut m: Vec<f64> = (0..20).map(|i| 75.0 + i as f64 * 0.2).collect();
+        m.push(95.0);
+        m
+    }
+
+    #[test]
+    fn dropping_tails_removes_the_rogue_members_vote() {
+        let raw = members_with_outlier();
+        let above = MarketType::Above(80.0);
+        // The outlier alone is the whole YES probability: 1/21
+        assert!((compute_ensemble_yes_from_members(&raw, &above) - 1.0 / 21.0).abs() < 1e-12);
+        let trimmed = trim_members(&raw, 1, TrimMode::Drop);
+        assert_eq!(trimmed.len(), 19);
+        assert_eq!(compute_ensemble_yes_from_members(&trimmed, &above), 0.0);
+    }
+
+    #[test]
+    fn winsorizing_keeps_the_count_and_pulls_tails_in() {
+        let raw = members_with_outlier();
+        let trimmed = trim_members(&raw, 1, TrimMode::Winsorize);
+        assert_eq!(trimmed.len(), raw.len());
+        // The 95°F run is clamped to the next member down, the lowest to
+        // the next one up
+        assert_eq!(trimmed[20], raw[19]);
+        assert_eq!(trimmed[0], raw[1]);
+        assert_eq!(compute_ensemble_yes_from_members(&trimmed, &MarketType::Above(80.0)), 0.0);
+        // Inner members are left where they were, in their original order
+        assert_eq!(trimmed[1..20], raw[1..20]);
+    }
+
+    #[test]
+    fn trimming_away_from_the_tails_moves_nothing() {
+        let raw = members_with_outlier();
+        let between = MarketType::Between(76.0, 78.0);
+        let before = compute_ensemble_yes_from_members(&raw, &between);
+        let after = compute_ensemble_yes_from_members(&trim_members(&raw, 1, TrimMode::Winsorize), &between);
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn trim_leaves_small_or_untrimmed_ensembles_alone() {
+        let raw = members_with_outlier();
+        assert_eq!(trim_members(&raw, 0, TrimMode::Drop), raw);
+        // k=2 on 6 members would leave 2; at least 3 must remain
+        let small = [70.0, 71.0, 72.0, 73.0, 74.0, 90.0];
+        assert_eq!(trim_members(&small, 2, TrimMode::Drop), small);
+        assert_eq!(trim_members(&small, 1, TrimMode::Drop), [71.0, 72.0, 73.0, 74.0]);
+        assert!(trim_members(&[], 1, TrimMode::Drop).is_empty());
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn bounds_keep_unanimous_members_off_certainty() {
+        // 0 or all of 10 members: succession 1/12 beats the 2% floor
+        assert!(close(bound_probability(0.0, 10, 0.02, 0.98), 1.0 / 12.0));
+        assert!(close(bound_probability(1.0, 10, 0.02, 0.98), 11.0 / 12.0));
+        // With 100 members 1/102 sits inside the configured bounds
+        assert!(close(bound_probability(0.0, 100, 0.02, 0.98), 0.02));
+        assert!(close(bound_probability(1.0, 100, 0.02, 0.98), 0.98));
+    }
+
+    #[test]
+    fn bounds_with_one_member_or_none() {
+        // A single member is a coin flip at best: 1/3 to 2/3
+        assert!(close(bound_probability(0.0, 1, 0.02, 0.98), 1.0 / 3.0));
+        assert!(close(bound_probability(1.0, 1, 0.02, 0.98), 2.0 / 3.0));
+        // No members (bucket or sigmoid fallback): configured bounds only
+        assert!(close(bound_probability(0.0, 0, 0.02, 0.98), 0.02));
+        assert!(close(bound_probability(1.0, 0, 0.02, 0.98), 0.98));
+        assert_eq!(bound_probability(0.0, 0, 0.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn bounds_leave_interior_probabilities_alone() {
+        for members in [0, 1, 10, 51] {
+            assert_eq!(bound_probability(0.5, members, 0.02, 0.98), 0.5);
+        }
+        assert_eq!(bound_probability(0.3, 10, 0.02, 0.98), 0.3);
+    }
+
+    #[test]
+    fn tails_need_every_outside_source_to_agree() {
+        let above = MarketType::Above(80.0);
+        // 95% YES: NWS at 83 and Open-Meteo at 82 both clear the strike
+        assert!(tail_confirmed(0.95, 0.10, &above, &[83.0, 82.0]));
+        // One source below the strike is enough to refuse
+        assert!(!tail_confirmed(0.95, 0.10, &above, &[83.0, 79.0]));
+        // No outside source at all can't confirm
+        assert!(!tail_confirmed(0.95, 0.10, &above, &[]));
+        // 3% YES: every source must put the high at or under the strike
+        assert!(tail_confirmed(0.03, 0.10, &above, &[77.0, 78.5]));
+        assert!(!tail_confirmed(0.03, 0.10, &above, &[77.0, 81.0]));
+    }
+
+    #[test]
+    fn tail_check_skips_the_middle_and_can_be_off() {
+        let between = MarketType::Between(74.0, 76.0);
+        assert!(tail_confirmed(0.5, 0.10, &between, &[]));
+        assert!(tail_confirmed(0.95, 0.0, &between, &[90.0]));
+    }
+}
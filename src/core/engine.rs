@@ -1,43 +1,165 @@
-use crate::core::{risk, stats, types::*};
+use crate::core::implied::ImpliedDistribution;
+use crate::core::requote::{self, OrderAction, Quote};
+use crate::flags::{CycleFlags, Feature, FlagsWatcher};
+use crate::core::{activity, canary, coverage, distribution, execution, exits, nowcast, risk, rules_brain, schedule, stats, types::*};
 use crate::ports::brain::Brain;
-use crate::ports::exchange::Exchange;
+use crate::ports::exchange::{Exchange, ExchangeError};
+use crate::ports::notifier::Notifier;
+use crate::overrides::{Override, Overrides, OverridesWatcher};
 use crate::ports::weather_feed::WeatherFeed;
-use crate::storage;
+use crate::prompt::{Prompt, PromptWatcher};
+use crate::quota::{self, Provider};
+use crate::safety;
+use crate::storage::{self, QueuedWrite};
 use anyhow::Result;
-use chrono::Timelike;
+use chrono::{Datelike, Timelike};
+use futures_util::stream::{self, StreamExt};
 
+/// Orderbook requests in flight at once while scanning an event.
+const ORDERBOOK_CONCURRENCY: usize = 4;
+
+/// Bot rows placed within this many hours are reconciled against fills.
+const RECONCILE_LOOKBACK_HOURS: i64 = 48;
+
+/// Per-cycle state threaded through the city loop.
+struct CycleState {
+    /// Capital not yet committed this cycle
+    available_cents: Cents,
+    /// Tickers whose resting quotes were re-quoted (maker mode)
+    requoted: std::collections::HashSet<String>,
+    /// Model-vs-market gauges for each city evaluated this cycle
+    forecast_metrics: Vec<Metric>,
+    /// The city being run, filled in as it goes
+    city: CitySummary,
+    /// brain/overrides.toml as of this cycle
+    overrides: Overrides,
+    /// Per-ticker rejection counts and lockouts
+    risk: RiskState,
+    /// brain/flags.toml as of this cycle, partial rollouts rolled
+    flags: CycleFlags,
+    /// Size multiplier while the strategy version is on canary; 1 = full
+    canary_fraction: f64,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_cycle(
     exchange: &dyn Exchange,
     brain: &dyn Brain,
     weather_feed: &dyn WeatherFeed,
+    notifier: &dyn Notifier,
+    prompt: &PromptWatcher,
+    overrides: &OverridesWatcher,
+    flags: &FlagsWatcher,
     config: &Config,
 ) -> Result<()> {
-    // 1. CANCEL stale resting orders from previous cycles
+    let started_at = chrono::Utc::now().to_rfc3339();
+
+    // 0. FLUSH storage writes that failed in earlier cycles
+    let mut unflushed = storage::flush_write_queue()?;
+    if unflushed > 0 {
+        tracing::warn!("{} storage write(s) still unflushed", unflushed);
+    }
+
+    // KILLSWITCH — an operator-created brain/KILL pulls every bot order and
+    // stops the cycle before anything new is placed
+    if safety::kill_switch_engaged() {
+        let resting = exchange.resting_orders().await?;
+        let ours: Vec<&RestingOrder> = resting.iter().filter(|o| o.is_ours()).collect();
+        tracing::warn!(
+            "Killswitch {} present — cancelling {} bot order(s), not trading",
+            safety::KILL_SWITCH, ours.len()
+        );
+        return cancel_orders(exchange, &ours, CancelReason::Killswitch).await;
+    }
+
+    // CLOSE — the operator asked to go flat: pull the bot's orders, sell
+    // every position, and stay paused until resumed. Anything that couldn't
+    // be sold is tried again next cycle.
+    if safety::close_requested() {
+        let resting = exchange.resting_orders().await?;
+        let ours: Vec<&RestingOrder> = resting.iter().filter(|o| o.is_ours()).collect();
+        tracing::warn!("Close requested — cancelling {} bot order(s) and selling every position", ours.len());
+        cancel_orders(exchange, &ours, CancelReason::Operator).await?;
+        let unsold = exit_positions(exchange, config, exits::all).await?;
+        safety::set_paused(true)?;
+        let body = if unsold == 0 {
+            safety::set_close_request(false)?;
+            "Orders cancelled and positions sold. Paused until /resume.".to_string()
+        } else {
+            format!("Orders cancelled; {} position(s) still held — retrying next cycle. Paused.", unsold)
+        };
+        if let Err(e) = notifier.notify("Close-out", &body).await {
+            tracing::warn!("Close-out notification failed: {}", e);
+        }
+    }
+
+    // 1. CANCEL stale resting orders from previous cycles — only the bot's own
+    // unless CANCEL_ALL_RESTING is set, so manual orders are left alone. In
+    // maker mode the bot's quotes stay up to be re-quoted in place.
     let resting = exchange.resting_orders().await?;
-    for order in &resting {
-        exchange.cancel_order(&order.order_id).await?;
-        storage::cancel_trade(&order.order_id)?;
-        tracing::info!("Canceled stale order: {} (ledger marked cancelled)", order.order_id);
+    storage::poll_orders(&resting);
+    let (stale, kept): (Vec<&RestingOrder>, Vec<&RestingOrder>) = resting
+        .iter()
+        .partition(|o| if o.is_ours() { !config.maker_mode } else { config.cancel_all_resting });
+    if !kept.is_empty() {
+        let quotes = kept.iter().filter(|o| o.is_ours()).count();
+        tracing::info!(
+            "Leaving {} resting order(s): {} bot quote(s) to re-quote, {} not placed by the bot",
+            kept.len(), quotes, kept.len() - quotes
+        );
+    }
+    cancel_orders(exchange, &stale, CancelReason::StaleCycle).await?;
+
+    // RECONCILE — the ledger follows the exchange's fills rather than the
+    // order responses, so partial fills and orders that filled before they
+    // were cancelled are booked as they executed
+    if !config.paper_ledger() {
+        if let Err(e) = reconcile_fills(exchange, &kept).await {
+            tracing::warn!("Fill reconciliation failed: {:#} — retrying next cycle", e);
+        }
     }
 
     // 2. SETTLE — check ALL pending trades (may have multiple cities)
     let mut ledger = storage::read_ledger()?;
-    let pending_entries: Vec<(String, String)> = ledger.iter().rev()
-        .filter(|r| r.result == "pending")
-        .map(|r| (r.ticker.clone(), r.timestamp.clone()))
+    // Several rows can share a ticker (scale-ins); each is settled by its
+    // own order_id, and settlements are fetched once per ticker.
+    let pending_entries: Vec<LedgerRow> = ledger.iter().rev()
+        .filter(|r| r.is_pending())
+        .cloned()
         .collect();
+    let mut settlements_by_ticker: std::collections::HashMap<String, Vec<Settlement>> =
+        std::collections::HashMap::new();
 
-    for (pending_ticker, pending_timestamp) in &pending_entries {
-        let settlements = exchange.settlements(pending_ticker).await?;
-        if let Some(s) = settlements.first() {
-            storage::settle_last_trade(s)?;
+    for pending in &pending_entries {
+        let pending_ticker = &pending.ticker;
+        let pending_timestamp = &pending.timestamp;
+        if !settlements_by_ticker.contains_key(pending_ticker) {
+            let fetched = exchange.settlements(pending_ticker).await?;
+            warn_on_fill_mismatch(pending_ticker, &pending_entries, &fetched);
+            settlements_by_ticker.insert(pending_ticker.clone(), fetched);
+        }
+        let settlements = &settlements_by_ticker[pending_ticker];
+        if let Some(s) = settlements.iter().find(|s| settles_row(s, pending)) {
+            // Partial data (e.g. during exchange maintenance): settle once
+            // the result is readable rather than guess a side
+            if s.market_result.is_none() {
+                tracing::warn!("Settlement for {} has no market result yet — retrying next cycle", s.ticker);
+                continue;
+            }
+            storage::write_durable(QueuedWrite::Settle {
+                order_id: pending.order_id.clone(),
+                settlement: s.clone(),
+            })?;
             ledger = storage::read_ledger()?;
             let settled_stats = stats::compute(&ledger);
             storage::write_stats(&settled_stats)?;
             tracing::info!(
-                "Settled: {} (market_result={}) | {} {}¢",
-                s.result.to_uppercase(), s.market_result, s.ticker, s.pnl_cents
+                "Settled: {} (market_result={}) | {} {}",
+                s.result.to_uppercase(), s.market_result.map_or("unknown", |r| r.label()), s.ticker, s.pnl_cents
             );
+            if s.market_result == Some(MarketResult::Void) {
+                alert_void(notifier, pending, s).await;
+            }
         } else {
             // No settlement found — check if pending entry is stale (>30 min old)
             if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(pending_timestamp) {
@@ -49,11 +171,14 @@ pub async fn run_cycle(
                         count: 0,
                         price_cents: 0,
                         result: "unknown".into(),
-                        pnl_cents: 0,
+                        pnl_cents: Cents::ZERO,
                         settled_time: chrono::Utc::now().to_rfc3339(),
-                        market_result: "unknown".into(),
+                        market_result: None,
                     };
-                    storage::settle_last_trade(&zombie)?;
+                    storage::write_durable(QueuedWrite::Settle {
+                        order_id: pending.order_id.clone(),
+                        settlement: zombie,
+                    })?;
                     ledger = storage::read_ledger()?;
                     tracing::warn!(
                         "Zombie cleanup: pending entry for {} was {}min old, marked unknown",
@@ -64,65 +189,620 @@ pub async fn run_cycle(
         }
     }
 
+    // Resolve outcomes for journaled brackets, so blocked trades and the
+    // model's probabilities can be scored in stats
+    resolve_market_outcomes(exchange).await;
+
+    // EXIT — sell positions held past MAX_HOLDING_MINUTES. Ahead of the risk
+    // checks: getting out only lowers risk, so a veto doesn't block it.
+    let cycle_flags = flags.current().roll();
+    if config.max_holding_minutes > 0 {
+        exit_positions(exchange, config, |positions, ledger, now| {
+            // Exits flagged off for a city leave its positions alone
+            let flagged: Vec<Position> = positions
+                .iter()
+                .filter(|p| {
+                    let city = config.cities.iter().find(|c| p.ticker.starts_with(&format!("{}-", c.series_ticker)));
+                    cycle_flags.on(Feature::Exits, city)
+                })
+                .cloned()
+                .collect();
+            exits::overdue(&flagged, ledger, config.max_holding_minutes, now)
+        })
+        .await?;
+        ledger = storage::read_ledger()?;
+    }
+
     // 3. RISK — deterministic checks in Rust
     let computed_stats = stats::compute(&ledger);
     let balance = exchange.balance().await?;
+    let still_resting = exchange.resting_orders().await?;
+    let available = risk::available_capital(balance, &still_resting, &ledger, config.paper_ledger());
+    if available < balance {
+        tracing::info!("Available capital {} of {} balance (rest locked in orders/pending)", available, balance);
+    }
 
-    if let Some(veto) = risk::check(&computed_stats, balance, config) {
-        tracing::info!("Risk veto: {}", veto);
+    let open_risk = risk::open_worst_case_loss(&ledger);
+    if config.daily_loss_includes_open && open_risk > Cents::ZERO {
+        tracing::info!(
+            "Daily loss check: {} settled today + {} worst case on open positions (limit {})",
+            computed_stats.today_pnl_cents, open_risk, config.max_daily_loss_cents
+        );
+    }
+    let loss_sprt = risk::loss_sprt(&ledger, &config.strategy);
+    if let Some(t) = loss_sprt.filter(|t| t.llr > 0.0) {
+        tracing::info!(
+            "Loss SPRT: log-LR {:.2} of {:.2} to veto ({}W/{}L since the edge last held)",
+            t.llr, t.upper, t.wins, t.losses
+        );
+    }
+    if let Some(veto) = risk::check(&computed_stats, available, open_risk, loss_sprt, config) {
+        tracing::warn!(
+            veto = veto.veto.label(), observed = veto.observed, limit = veto.limit,
+            "Risk veto: {}", veto
+        );
+        if let Err(e) = storage::append_journal(&JournalEntry::RiskVeto(veto.clone())) {
+            tracing::warn!("Risk veto journal write failed: {}", e);
+        }
+        write_metrics(config, Some(veto.veto), &[]);
+        write_summary(&started_at, veto.veto.label(), 0, Vec::new());
         return Ok(());
     }
 
+    // QUOTAS — back off before a provider starts refusing requests
+    let kalshi_usage = storage::api_usage(Provider::Kalshi);
+    let open_meteo_usage = storage::api_usage(Provider::OpenMeteo);
+    let skip_scan = kalshi_usage.hour >= config.quota.kalshi_hourly;
+    if skip_scan {
+        tracing::warn!(
+            "Kalshi soft limit: {} requests this hour (limit {}) — skipping market scan",
+            kalshi_usage.hour, config.quota.kalshi_hourly
+        );
+    }
+    let new_runs_only = open_meteo_usage.hour >= config.quota.open_meteo_hourly
+        || open_meteo_usage.day >= config.quota.open_meteo_daily;
+    if new_runs_only && !config.model_schedule {
+        tracing::warn!(
+            "Open-Meteo soft limit: {} this hour / {} today (limits {}/{}) — refreshing only on new model runs",
+            open_meteo_usage.hour, open_meteo_usage.day, config.quota.open_meteo_hourly, config.quota.open_meteo_daily
+        );
+    }
+
     // 4+ PER-CITY EVALUATION
+    let mut state = CycleState {
+        available_cents: available,
+        requoted: std::collections::HashSet::new(),
+        forecast_metrics: Vec::new(),
+        city: CitySummary::default(),
+        overrides: overrides.current(),
+        risk: storage::read_risk_state(),
+        flags: cycle_flags,
+        canary_fraction: canary_fraction(notifier, &ledger, config).await,
+    };
+    let mut city_summaries = Vec::with_capacity(config.cities.len());
     let positions = exchange.positions().await?;
-    let prompt_md = storage::read_prompt()?;
+    let prompt = prompt.current();
     let mut trades_this_cycle = 0u32;
 
-    for city in &config.cities {
+    let paused = safety::paused();
+    if paused {
+        tracing::warn!("Paused ({} present) — settling and exiting only, no new entries", safety::PAUSE);
+    }
+    let cities: &[CityConfig] = if skip_scan || paused { &[] } else { &config.cities };
+    for city in cities {
         tracing::info!("━━━ {} ({}) ━━━", city.name, city.series_ticker);
 
-        match run_city(exchange, brain, weather_feed, config, city, &ledger, &positions, &prompt_md).await {
+        state.city = CitySummary { city: city.name.clone(), action: "skipped".into(), ..Default::default() };
+        let timer = std::time::Instant::now();
+        let result = run_city(exchange, brain, weather_feed, config, city, &ledger, &positions, &prompt, new_runs_only, &mut state).await;
+        let mut summary = std::mem::take(&mut state.city);
+        summary.latencies_ms.insert("total".into(), timer.elapsed().as_millis() as u64);
+        if let Err(e) = &result {
+            summary.error = Some(format!("{:#}", e));
+        }
+        if matches!(result, Ok(true)) {
+            summary.action = "traded".into();
+        }
+        city_summaries.push(summary);
+
+        match result {
             Ok(traded) => {
                 if traded {
                     trades_this_cycle += 1;
                     ledger = storage::read_ledger()?;
                 }
             }
-            Err(e) => {
-                tracing::error!("[{}] Failed: {} — continuing", city.name, e);
-            }
+            Err(e) => match exchange_error(&e) {
+                Some(ExchangeError::Auth(_)) => {
+                    tracing::error!("[{}] Auth failure: {:#} — aborting cycle", city.name, e);
+                    write_summary(&started_at, "auth_failure", trades_this_cycle, city_summaries);
+                    return Err(e);
+                }
+                Some(ExchangeError::InsufficientFunds(_)) => {
+                    tracing::warn!("[{}] Insufficient funds: {:#} — no more orders this cycle", city.name, e);
+                    break;
+                }
+                _ => {
+                    tracing::error!("[{}] Failed: {:#} — continuing", city.name, e);
+                }
+            },
         }
     }
 
+    // Maker mode: quotes on events nobody re-quoted this cycle are stale
+    if config.maker_mode {
+        let resting = exchange.resting_orders().await?;
+        let stale: Vec<&RestingOrder> = resting
+            .iter()
+            .filter(|o| o.is_ours() && !state.requoted.contains(&o.ticker))
+            .collect();
+        cancel_orders(exchange, &stale, CancelReason::Requote).await?;
+    }
+
+    let journal = storage::read_journal()?;
+    let mut cycle_stats = stats::compute(&ledger);
+    cycle_stats.pass_reasons = stats::pass_reason_counts(&journal);
+    cycle_stats.rule_attribution = stats::rule_attribution(&journal);
+    cycle_stats.fee_drag = stats::fee_drag(&ledger, &journal);
+    cycle_stats.analytics = Some(stats::analytics(&ledger));
+    storage::write_stats(&cycle_stats)?;
+
+    if let Err(e) = storage::prune_snapshots(config.snapshot_retention_days) {
+        tracing::warn!("Snapshot pruning failed: {}", e);
+    }
+
+    if config.activity.baseline_days > 0 && !paused && !skip_scan {
+        monitor_activity(notifier, &config.activity, &city_summaries, trades_this_cycle).await;
+    }
+    write_summary(&started_at, if paused { "paused" } else { "ok" }, trades_this_cycle, city_summaries);
+    unflushed = storage::queued_writes()?;
+    storage::write_health(unflushed)?;
+
+    if let Err(e) = storage::record_api_usage(&quota::take()) {
+        tracing::warn!("API usage write failed: {}", e);
+    }
+    write_metrics(config, None, &state.forecast_metrics);
+
     tracing::info!("Cycle complete: {}/{} cities traded", trades_this_cycle, config.cities.len());
     Ok(())
 }
 
-async fn run_city(
+/// Sell what `select` picks out of the held positions at the best bid. In
+/// paper-ledger mode the sale is booked at that bid without an order, as
+/// paper buys are. Rows stay open until a sell executes; one left resting
+/// is cancelled with the stale orders next cycle and tried again. Returns
+/// how many exits didn't sell in full.
+async fn exit_positions(
+    exchange: &dyn Exchange,
+    config: &Config,
+    select: impl Fn(&[Position], &[LedgerRow], chrono::DateTime<chrono::Utc>) -> Vec<exits::Exit>,
+) -> Result<usize> {
+    let mut unsold = 0;
+    let ledger = storage::read_ledger()?;
+    let paper = config.paper_ledger();
+    let positions = if paper { risk::paper_positions(&ledger) } else { exchange.positions().await? };
+
+    for exit in select(&positions, &ledger, chrono::Utc::now()) {
+        let book = match exchange.orderbook(&exit.ticker).await {
+            Ok(book) => book,
+            Err(e) => {
+                tracing::warn!("Exit {}: orderbook fetch failed: {:#} — holding", exit.ticker, e);
+                unsold += 1;
+                continue;
+            }
+        };
+        let Some((price, shares)) = exits::exit_price(&book, &exit.side, exit.shares) else {
+            tracing::warn!(
+                "Exit {} {:?}: held {}min but nobody is bidding — holding",
+                exit.ticker, exit.side, exit.held_minutes
+            );
+            unsold += 1;
+            continue;
+        };
+        tracing::info!(
+            "Exit ({}): {} {:?} {}/{}x @ {}¢ after {}min",
+            exit.reason.label(), exit.ticker, exit.side, shares, exit.shares, price, exit.held_minutes
+        );
+
+        if !paper {
+            let order = OrderRequest { ticker: exit.ticker.clone(), side: exit.side.clone(), shares, price_cents: price };
+            match exchange.sell_position(&order).await {
+                Ok(result) if result.status == "executed" => {}
+                Ok(result) => {
+                    tracing::warn!("Exit order {} on {} is {} — rows stay open", result.order_id, exit.ticker, result.status);
+                    unsold += 1;
+                    continue;
+                }
+                Err(e) => {
+                    if let Some(ExchangeError::Auth(_)) = exchange_error(&e) {
+                        return Err(e);
+                    }
+                    tracing::error!("Exit order on {} failed: {:#} — holding", exit.ticker, e);
+                    unsold += 1;
+                    continue;
+                }
+            }
+        }
+
+        if shares < exit.shares {
+            unsold += 1;
+        }
+        for row in exits::closed_rows(&exit.rows, shares) {
+            storage::write_durable(QueuedWrite::Exit {
+                order_id: row.order_id.clone(),
+                pnl_cents: exits::exit_pnl(row, price),
+            })?;
+        }
+    }
+    Ok(unsold)
+}
+
+/// Today's forecast for `city`, with what the settlement station has
+/// recorded so far attached. None when the forecast can't be had.
+async fn fetch_weather(weather_feed: &dyn WeatherFeed, city: &CityConfig) -> Option<WeatherSnapshot> {
+    let mut weather = match weather_feed.forecast(city).await {
+        Ok(w) => w,
+        Err(e) => {
+            tracing::warn!("[{}] Weather forecast failed: {}", city.name, e);
+            None
+        }
+    };
+    if let Some(w) = weather.as_mut() {
+        match weather_feed.observations(city).await {
+            Ok(Some(obs)) => {
+                tracing::info!(
+                    "[{}] {} observed: high so far {:.1}°F, latest {:.1}°F at {}",
+                    city.name, obs.station.as_deref().unwrap_or("station"), obs.observed_high_f, obs.latest_temp_f, obs.observed_at
+                );
+                w.observed_high_so_far = Some(obs.observed_high_f);
+                w.observations = Some(obs);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("[{}] Station observations unavailable: {}", city.name, e),
+        }
+        match weather_feed.alerts(city).await {
+            Ok(Some(alerts)) => {
+                for a in &alerts {
+                    tracing::info!(
+                        "[{}] Alert: {} ({}){}",
+                        city.name, a.event, a.severity, a.expires.as_deref().map(|t| format!(" until {}", t)).unwrap_or_default()
+                    );
+                }
+                w.alerts = alerts;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("[{}] Weather alerts unavailable: {}", city.name, e),
+        }
+    }
+    weather
+}
+
+/// Evaluate every city the way a cycle would — same event, weather, trims,
+/// flags, overrides, lockouts and brain — without cancelling, settling,
+/// ordering or writing anything. Account-level risk checks and sizing caps
+/// aren't applied, so a BUY here is what the brain wants, not an order.
+#[allow(clippy::too_many_arguments)]
+pub async fn scan(
+    exchange: &dyn Exchange,
+    brain: &dyn Brain,
+    weather_feed: &dyn WeatherFeed,
+    prompt: &PromptWatcher,
+    overrides: &OverridesWatcher,
+    flags: &FlagsWatcher,
+    config: &Config,
+) -> Result<Vec<EventScan>> {
+    let ledger = storage::read_ledger()?;
+    let positions = exchange.positions().await?;
+    let resting = exchange.resting_orders().await?;
+    let prompt = prompt.current();
+    let overrides = overrides.current();
+    let flags = flags.current().roll();
+    let risk_state = storage::read_risk_state();
+
+    let mut scans = Vec::with_capacity(config.cities.len());
+    for city in &config.cities {
+        if let Some(o) = overrides.for_city(city, chrono::Utc::now()).filter(|o| o.pass) {
+            tracing::info!("[{}] Operator override: pass{} — skipping", city.name, o.note_suffix());
+            continue;
+        }
+        let context = ScanContext {
+            ledger: &ledger,
+            positions: &positions,
+            resting: &resting,
+            prompt: &prompt,
+            overrides: &overrides,
+            risk: &risk_state,
+            features: flags.switches(city),
+        };
+        match scan_city(exchange, brain, weather_feed, config, city, &context).await {
+            Ok(Some(scan)) => scans.push(scan),
+            Ok(None) => {}
+            Err(e) if matches!(exchange_error(&e), Some(ExchangeError::Auth(_))) => return Err(e),
+            Err(e) => tracing::error!("[{}] Scan failed: {:#} — continuing", city.name, e),
+        }
+    }
+    Ok(scans)
+}
+
+/// What `scan` reads once and shares across cities.
+struct ScanContext<'a> {
+    ledger: &'a [LedgerRow],
+    positions: &'a [Position],
+    resting: &'a [RestingOrder],
+    prompt: &'a Prompt,
+    overrides: &'a Overrides,
+    risk: &'a RiskState,
+    features: FeatureSwitches,
+}
+
+async fn scan_city(
+    exchange: &dyn Exchange,
+    brain: &dyn Brain,
+    weather_feed: &dyn WeatherFeed,
+    config: &Config,
+    city: &CityConfig,
+    ctx: &ScanContext<'_>,
+) -> Result<Option<EventScan>> {
+    let brackets = tradable_brackets(exchange, config, city).await?;
+    if brackets.is_empty() {
+        return Ok(None);
+    }
+    let event_ticker = brackets[0].event_ticker.clone();
+
+    let mut weather = fetch_weather(weather_feed, city).await;
+    let params = config.seasons.params(&city.strategy(&config.strategy), &city.name, &city.series_ticker, local_now(&city.timezone).month());
+    if let Some(w) = weather.as_mut() {
+        if !ctx.features.ensemble {
+            drop_ensemble(w);
+        }
+        trim_ensemble(w, &params, &brackets);
+        apply_nowcast(w, &params);
+    }
+
+    let books = fetch_orderbooks(exchange, city, &brackets, "skipping bracket").await?;
+    if books.is_empty() {
+        anyhow::bail!("no orderbook loaded for any of {} brackets on {}", brackets.len(), event_ticker);
+    }
+
+    let now = chrono::Utc::now();
+    let fair_overrides = books
+        .iter()
+        .filter_map(|(m, _)| Some((m.ticker.clone(), ctx.overrides.for_ticker(&m.ticker, now)?.fair_yes?)))
+        .collect();
+    let context = EventContext {
+        prompt_md: ctx.prompt.text.clone(),
+        stats: stats::compute(ctx.ledger),
+        last_n_trades: ctx.ledger.iter().rev().take(20).cloned().collect(),
+        event_ticker: event_ticker.clone(),
+        brackets: books,
+        weather,
+        implied: ImpliedDistribution::from_brackets(&brackets),
+        exposure: risk::exposure(&event_ticker, ctx.positions, ctx.resting, ctx.ledger, config.paper_ledger()),
+        fair_overrides,
+        features: ctx.features,
+    };
+    let decisions = brain.decide_event(&context).await?;
+    if decisions.len() != context.brackets.len() {
+        anyhow::bail!("Brain returned {} decisions for {} brackets", decisions.len(), context.brackets.len());
+    }
+
+    let today = now.format("%Y-%m-%d").to_string();
+    let brackets = context
+        .brackets
+        .into_iter()
+        .zip(decisions)
+        .map(|((market, _), mut decision)| {
+            if decision.action == Action::Buy {
+                if ctx.overrides.for_ticker(&market.ticker, now).is_some_and(|o| o.pass) {
+                    stand_down(&mut decision, PassReason::OperatorOverride);
+                } else if risk::lockout(ctx.risk, &market.ticker, &today).is_some() {
+                    stand_down(&mut decision, PassReason::TickerLocked);
+                }
+            }
+            (market, decision)
+        })
+        .collect();
+    Ok(Some(EventScan { city: city.name.clone(), event_ticker, brackets }))
+}
+
+/// Turn a BUY into a PASS for `reason`, keeping the edge and probability
+/// it was based on.
+fn stand_down(decision: &mut TradeDecision, reason: PassReason) {
+    decision.action = Action::Pass;
+    decision.side = None;
+    decision.shares = None;
+    decision.max_price_cents = None;
+    decision.pass_reason = Some(reason);
+}
+
+/// This cycle's canary size multiplier. A new strategy version restarts
+/// the ramp (and tells the operator); until it graduates, the ramp is
+/// re-scored on the journal and saved. Best-effort: a journal or state
+/// problem trades at the canary fraction rather than full size.
+async fn canary_fraction(notifier: &dyn Notifier, ledger: &[LedgerRow], config: &Config) -> f64 {
+    let limits = &config.canary;
+    if limits.trades == 0 {
+        return 1.0;
+    }
+    let mut state = storage::read_canary();
+    if state.as_ref().is_none_or(|s| s.version != config.strategy_version) {
+        let fresh = canary::start(&config.strategy_version, &chrono::Utc::now().to_rfc3339(), limits);
+        let body = format!(
+            "Strategy version {} (was {}): orders at {:.0}% size until {} trades have settled in line with the model.",
+            fresh.version,
+            state.as_ref().map_or("none", |s| s.version.as_str()),
+            fresh.fraction * 100.0,
+            limits.trades
+        );
+        tracing::warn!("Canary: {}", body);
+        if let Err(e) = notifier.notify("New strategy version", &body).await {
+            tracing::warn!("Canary notification failed: {}", e);
+        }
+        state = Some(fresh);
+    }
+    let Some(state) = state else { return limits.fraction };
+    if state.graduated {
+        return 1.0;
+    }
+    let next = match storage::read_journal() {
+        Ok(journal) => canary::ramp(&state, limits, &journal, ledger),
+        Err(e) => {
+            tracing::warn!("Canary: journal read failed: {} — holding at {:.0}%", e, limits.fraction * 100.0);
+            return limits.fraction;
+        }
+    };
+    if next.graduated {
+        tracing::info!("Canary: strategy {} at full size after {} settled trades", next.version, next.settled);
+    } else {
+        tracing::info!(
+            "Canary: strategy {} at {:.0}% size — {}/{} settled trades, {} realized vs {:+.0}¢ expected",
+            next.version, next.fraction * 100.0, next.settled, limits.trades, next.realized_cents, next.expected_cents
+        );
+    }
+    if let Err(e) = storage::write_canary(&next) {
+        tracing::warn!("Canary state write failed: {}", e);
+    }
+    if next.graduated { 1.0 } else { next.fraction }
+}
+
+/// Ask the brain for a fresh view of the brackets held on this event and
+/// sell what `exits::against_model` picks out. Nothing is journaled: these
+/// aren't entry decisions, and the entries were scored when they were made.
+#[allow(clippy::too_many_arguments)]
+async fn manage_exits(
     exchange: &dyn Exchange,
     brain: &dyn Brain,
     weather_feed: &dyn WeatherFeed,
     config: &Config,
     city: &CityConfig,
+    brackets: &[MarketState],
     ledger: &[LedgerRow],
     positions: &[Position],
-    prompt_md: &str,
-) -> Result<bool> {
-    // MARKETS — fetch all brackets for nearest event
+    prompt: &Prompt,
+    state: &mut CycleState,
+) -> Result<()> {
+    let Some(mut weather) = fetch_weather(weather_feed, city).await else {
+        tracing::warn!("[{}] No weather to re-price held positions — holding", city.name);
+        return Ok(());
+    };
+    let params = config.seasons.params(&city.strategy(&config.strategy), &city.name, &city.series_ticker, local_now(&city.timezone).month());
+    let features = state.flags.switches(city);
+    if !features.ensemble {
+        drop_ensemble(&mut weather);
+    }
+    trim_ensemble(&mut weather, &params, brackets);
+    apply_nowcast(&mut weather, &params);
+
+    let held = brackets.iter().filter(|b| ledger.iter().any(|r| r.is_open() && r.ticker == b.ticker));
+    let books = fetch_orderbooks(exchange, city, held, "holding").await?;
+    if books.is_empty() {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now();
+    let event_ticker = brackets[0].event_ticker.clone();
+    let fair_overrides = books
+        .iter()
+        .filter_map(|(m, _)| Some((m.ticker.clone(), state.overrides.for_ticker(&m.ticker, now)?.fair_yes?)))
+        .collect();
+    let context = EventContext {
+        prompt_md: prompt.text.clone(),
+        stats: stats::compute(ledger),
+        last_n_trades: ledger.iter().rev().take(20).cloned().collect(),
+        event_ticker: event_ticker.clone(),
+        brackets: books,
+        weather: Some(weather),
+        implied: ImpliedDistribution::from_brackets(brackets),
+        exposure: risk::exposure(&event_ticker, positions, &[], ledger, config.paper_ledger()),
+        fair_overrides,
+        features,
+    };
+    let decisions = brain.decide_event(&context).await?;
+    let model_yes: std::collections::BTreeMap<&str, f64> = context
+        .brackets
+        .iter()
+        .zip(&decisions)
+        .filter_map(|((m, _), d)| Some((m.ticker.as_str(), d.model_probability?)))
+        .collect();
+    for (ticker, yes) in &model_yes {
+        tracing::info!("[{}] Holding {}: model now {:.1}% YES", city.name, ticker, yes * 100.0);
+    }
+    let best_bid = |ticker: &str, side: &Side| {
+        let (_, book) = context.brackets.iter().find(|(m, _)| m.ticker == ticker)?;
+        exits::exit_price(book, side, 1).map(|(price, _)| price)
+    };
+
+    exit_positions(exchange, config, |positions, ledger, now| {
+        exits::against_model(
+            positions, ledger, |t| model_yes.get(t).copied(), best_bid, config.take_profit_pp, config.stop_loss_pp, now,
+        )
+    })
+    .await?;
+    Ok(())
+}
+
+/// Apply the configured ensemble trim in place, logging how far it moved
+/// each bracket's member probability.
+fn trim_ensemble(weather: &mut WeatherSnapshot, params: &StrategyParams, brackets: &[MarketState]) {
+    let raw = &weather.ensemble_member_highs;
+    let trimmed = distribution::trim_members(raw, params.ensemble_trim, params.ensemble_trim_mode);
+    if trimmed == *raw {
+        return;
+    }
+    let shifts: Vec<String> = brackets
+        .iter()
+        .filter_map(|m| {
+            let mt = MarketType::from_market(m)?;
+            let before = rules_brain::compute_ensemble_yes_from_members(raw, &mt);
+            let after = rules_brain::compute_ensemble_yes_from_members(&trimmed, &mt);
+            let label = m.ticker.split('-').next_back().unwrap_or(&m.ticker);
+            ((after - before).abs() >= 0.005).then(|| format!("{} {:+.1}pp", label, (after - before) * 100.0))
+        })
+        .collect();
+    tracing::info!(
+        "[{}] Ensemble trim ({:?}, k={}): {} → {} members{}",
+        weather.city, params.ensemble_trim_mode, params.ensemble_trim, raw.len(), trimmed.len(),
+        if shifts.is_empty() { String::new() } else { format!(" | {}", shifts.join(", ")) }
+    );
+    weather.ensemble_member_highs = trimmed;
+}
+
+/// ENSEMBLE flagged off: decide as if the ensemble hadn't loaded.
+fn drop_ensemble(weather: &mut WeatherSnapshot) {
+    weather.ensemble = None;
+    weather.ensemble_member_highs.clear();
+    weather.bucket_probabilities.clear();
+}
+
+/// Blend the observation-trend nowcast into the remaining-day high and
+/// the ensemble members, after trimming.
+fn apply_nowcast(weather: &mut WeatherSnapshot, params: &StrategyParams) {
+    let Some(n) = nowcast::nowcast(weather, params) else { return };
+    tracing::info!(
+        "[{}] Nowcast: bias {:+.1}°F, trend {:+.2}°F/h vs forecast, peak in {:.0}h → {:+.1}°F × weight {:.2} = {:+.1}°F",
+        weather.city, n.bias_f, n.rate_excess_f_per_h, n.hours_to_peak, n.adjustment_f, n.weight, n.shift_f()
+    );
+    nowcast::blend(weather, &n);
+}
+
+/// Brackets still far enough from expiry, moving on to the next event when
+/// the nearest one's high is likely already known. Empty, with the reason
+/// logged, when there's nothing to evaluate.
+async fn tradable_brackets(exchange: &dyn Exchange, config: &Config, city: &CityConfig) -> Result<Vec<MarketState>> {
     let brackets = exchange.active_markets_for_series(&city.series_ticker).await?;
     if brackets.is_empty() {
         tracing::info!("[{}] No active markets", city.name);
-        return Ok(false);
+        return Ok(Vec::new());
     }
 
-    let mut brackets: Vec<MarketState> = brackets
+    let brackets: Vec<MarketState> = brackets
         .into_iter()
         .filter(|m| m.minutes_to_expiry >= config.min_minutes_to_expiry)
         .collect();
 
     if brackets.is_empty() {
         tracing::info!("[{}] All brackets too close to expiry", city.name);
-        return Ok(false);
+        return Ok(Vec::new());
     }
 
     // Skip events where the daily high is likely already known
@@ -136,117 +816,395 @@ async fn run_city(
             .collect();
         if next.is_empty() {
             tracing::info!("[{}] No next event available", city.name);
+        }
+        return Ok(next);
+    }
+    Ok(brackets)
+}
+
+/// Orderbooks for `markets`, a few at a time. A bracket whose book won't
+/// load is logged with `on_failure` and left out; only bad credentials
+/// abort.
+async fn fetch_orderbooks<'a>(
+    exchange: &dyn Exchange,
+    city: &CityConfig,
+    markets: impl IntoIterator<Item = &'a MarketState>,
+    on_failure: &str,
+) -> Result<Vec<(MarketState, Orderbook)>> {
+    let fetched: Vec<(&MarketState, Result<Orderbook>)> = stream::iter(markets)
+        .map(|market| async move { (market, exchange.orderbook(&market.ticker).await) })
+        .buffered(ORDERBOOK_CONCURRENCY)
+        .collect()
+        .await;
+    let mut books = Vec::with_capacity(fetched.len());
+    for (market, book) in fetched {
+        match book {
+            Ok(book) => books.push((market.clone(), book)),
+            Err(e) if matches!(exchange_error(&e), Some(ExchangeError::Auth(_))) => return Err(e),
+            Err(e) => tracing::warn!("[{}] {}: orderbook fetch failed: {:#} — {}", city.name, market.ticker, e, on_failure),
+        }
+    }
+    Ok(books)
+}
+
+/// One city's nearest event as `run_city` works it, once it has brackets:
+/// what pricing, risk gating and placement all read.
+struct CityRun<'a> {
+    exchange: &'a dyn Exchange,
+    config: &'a Config,
+    city: &'a CityConfig,
+    ledger: &'a [LedgerRow],
+    positions: &'a [Position],
+    event_ticker: String,
+    brackets: Vec<MarketState>,
+    maker_mode: bool,
+    /// The event's TWAP plan in progress, if any
+    twap: Option<TwapPlan>,
+}
+
+/// The bracket pricing settled on, with the weather it was priced from.
+struct Pick {
+    market: MarketState,
+    orderbook: Orderbook,
+    decision: TradeDecision,
+    side: Side,
+    weather: Option<WeatherSnapshot>,
+    /// When the weather and books behind the decision were fetched
+    context_at: std::time::Instant,
+}
+
+/// What risk gating cleared for a pick.
+struct SizedOrder {
+    shares: u32,
+    /// What the order works towards; with TWAP `shares` is one slice of it
+    target: u32,
+    price: u32,
+    /// The ask on the pick's side when it was priced
+    ask: Option<u32>,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_city(
+    exchange: &dyn Exchange,
+    brain: &dyn Brain,
+    weather_feed: &dyn WeatherFeed,
+    config: &Config,
+    city: &CityConfig,
+    ledger: &[LedgerRow],
+    positions: &[Position],
+    prompt: &Prompt,
+    new_runs_only: bool,
+    state: &mut CycleState,
+) -> Result<bool> {
+    // TRADING HOURS — no new entries outside the city's local window
+    if let Some(window) = city.trading_hours {
+        let hour = local_now(&city.timezone).hour();
+        if !window.contains(hour) {
+            tracing::info!("[{}] Outside trading hours ({} local, window {}) — skipping", city.name, hour, window);
             return Ok(false);
         }
-        brackets = next;
+    }
+
+    // OVERRIDES — the operator can stand a city down from overrides.toml
+    let city_override = state.overrides.for_city(city, chrono::Utc::now()).cloned();
+    if let Some(o) = city_override.as_ref().filter(|o| o.pass) {
+        tracing::warn!("[{}] Operator override: pass{} — skipping", city.name, o.note_suffix());
+        state.city.action = "operator pass".into();
+        return Ok(false);
+    }
+
+    // FLAGS — subsystems rolled back or partly rolled out in flags.toml
+    let features = state.flags.switches(city);
+    let flagged_off = state.flags.off_for(city);
+    if !flagged_off.is_empty() {
+        tracing::info!("[{}] Flagged off this cycle: {}", city.name, flagged_off.join(", "));
+    }
+    let maker_mode = config.maker_mode && features.maker_mode;
+
+    // MARKETS — fetch all brackets for nearest event
+    let timer = std::time::Instant::now();
+    let brackets = tradable_brackets(exchange, config, city).await?;
+    state.city.latencies_ms.insert("markets".into(), timer.elapsed().as_millis() as u64);
+    if brackets.is_empty() {
+        return Ok(false);
     }
 
     let event_ticker = brackets[0].event_ticker.clone();
+    state.city.event_ticker = Some(event_ticker.clone());
     tracing::info!(
         "[{}] {} brackets for event {} (expiry in {:.1}min)",
         city.name, brackets.len(), event_ticker, brackets[0].minutes_to_expiry
     );
 
+    // TWAP — a plan in progress owns this event: its earlier slices are the
+    // position, pending rows and recent trade the checks below skip on
+    let twap = if config.twap_interval_minutes > 0 && !maker_mode {
+        storage::twap_plan(&event_ticker)
+    } else {
+        None
+    };
+    if let Some(plan) = &twap {
+        if !plan.is_due(chrono::Utc::now()) {
+            tracing::info!(
+                "[{}] TWAP on {}: {}/{} filled, next slice at {}",
+                city.name, plan.ticker, plan.filled_shares, plan.total_shares, plan.next_slice_at
+            );
+            return Ok(false);
+        }
+    }
+
+    // EXITS — a held event takes no new entries, but with TAKE_PROFIT_PP or
+    // STOP_LOSS_PP its positions are re-priced and may be sold
+    if twap.is_none()
+        && features.exits
+        && (config.take_profit_pp > 0.0 || config.stop_loss_pp > 0.0)
+        && ledger.iter().any(|r| r.is_open() && r.event_ticker == event_ticker)
+    {
+        manage_exits(exchange, brain, weather_feed, config, city, &brackets, ledger, positions, prompt, state).await?;
+    }
+
     // EVENT-LEVEL POSITION CHECK
-    if positions.iter().any(|p| brackets.iter().any(|b| b.ticker == p.ticker)) {
+    if twap.is_none() && positions.iter().any(|p| brackets.iter().any(|b| b.ticker == p.ticker)) {
         tracing::warn!("[{}] Existing position on event {} — skipping", city.name, event_ticker);
         return Ok(false);
     }
+    // Paper trades never show up as positions, and a fill can lag them live.
+    // Maker quotes stay pending on purpose so they can be re-quoted.
+    if twap.is_none() && !maker_mode && ledger.iter().any(|r| r.is_pending() && r.event_ticker == event_ticker) {
+        tracing::warn!("[{}] Pending trade on event {} — skipping", city.name, event_ticker);
+        return Ok(false);
+    }
 
-    // WEATHER
-    let weather = match weather_feed.forecast(city).await {
-        Ok(w) => w,
-        Err(e) => {
-            tracing::warn!("[{}] Weather forecast failed: {}", city.name, e);
-            None
+    // TRADE COOLDOWN — positions can lag a fill, e.g. right after a restart
+    if let Some(last) = storage::last_trade(&event_ticker).filter(|_| twap.is_none()) {
+        let age_min = (chrono::Utc::now() - last).num_minutes();
+        if config.trade_cooldown_minutes > 0 && age_min < config.trade_cooldown_minutes as i64 {
+            tracing::warn!(
+                "[{}] Traded event {} {}min ago (cooldown {}min) — skipping",
+                city.name, event_ticker, age_min, config.trade_cooldown_minutes
+            );
+            return Ok(false);
         }
-    };
-
-    // EVALUATE all brackets
-    let mut buy_candidates: Vec<(MarketState, Orderbook, TradeDecision)> = Vec::new();
-    let mut scan_lines: Vec<String> = Vec::new();
+    }
 
-    for market in &brackets {
-        let orderbook = exchange.orderbook(&market.ticker).await?;
+    // MAX HOLDING — once the event's first entry is older than the holding
+    // limit its window is over, so a forced exit isn't bought straight back
+    if config.max_holding_minutes > 0 && features.exits && twap.is_none() {
+        let first_entry = ledger
+            .iter()
+            .filter(|r| r.event_ticker == event_ticker && !r.is_cancelled())
+            .filter_map(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).ok())
+            .min();
+        if let Some(first) = first_entry {
+            let age_min = (chrono::Utc::now() - first.with_timezone(&chrono::Utc)).num_minutes();
+            if age_min >= config.max_holding_minutes as i64 {
+                tracing::info!(
+                    "[{}] First entered {} {}min ago (MAX_HOLDING_MINUTES={}) — no new entries",
+                    city.name, event_ticker, age_min, config.max_holding_minutes
+                );
+                return Ok(false);
+            }
+        }
+    }
 
-        let context = DecisionContext {
-            prompt_md: prompt_md.to_string(),
-            stats: stats::compute(ledger),
-            last_n_trades: ledger.iter().rev().take(20).cloned().collect(),
-            market: market.clone(),
-            orderbook: orderbook.clone(),
-            weather: weather.clone(),
-        };
+    // SCHEDULE — only refresh once a new model run is out, except near
+    // expiry where pricing follows observations, not runs
+    let now = chrono::Utc::now();
+    if ((config.model_schedule && twap.is_none()) || new_runs_only)
+        && brackets[0].minutes_to_expiry > rules_brain::NEAR_EXPIRY_MINUTES
+        && !schedule::refresh_due(city, storage::last_refresh(&city.name), now)
+    {
+        let (next, model) = schedule::next_trigger(city, now);
+        tracing::info!(
+            "[{}] No new model run since last refresh — next trigger {} ({})",
+            city.name, next.format("%H:%M UTC"), model
+        );
+        return Ok(false);
+    }
 
-        let decision = brain.decide(&context).await?;
+    let run = CityRun { exchange, config, city, ledger, positions, event_ticker, brackets, maker_mode, twap };
+    let Some(pick) = price_event(&run, brain, weather_feed, prompt, features, now, state).await? else {
+        return Ok(false);
+    };
+    let Some(order) = size_order(&run, &pick, city_override.as_ref(), now, state).await? else {
+        return Ok(false);
+    };
+    place_order(run, pick, order, state).await
+}
 
-        let market_type = MarketType::from_market(market);
-        let mt_label = match &market_type {
-            Some(MarketType::Above(t)) => format!(">{:.0}°", t),
-            Some(MarketType::Below(t)) => format!("<{:.0}°", t),
-            Some(MarketType::Between(lo, hi)) => format!("{:.0}-{:.0}°", lo, hi),
-            None => "???".into(),
-        };
+/// PRICING — fetch the weather and every bracket's book, have the brain
+/// decide each one, journal and log the scan, and pick the bracket to buy:
+/// the best edge, or the one a TWAP plan is working. None when nothing is
+/// worth buying.
+async fn price_event(
+    run: &CityRun<'_>,
+    brain: &dyn Brain,
+    weather_feed: &dyn WeatherFeed,
+    prompt: &Prompt,
+    features: FeatureSwitches,
+    now: chrono::DateTime<chrono::Utc>,
+    state: &mut CycleState,
+) -> Result<Option<Pick>> {
+    let CityRun { exchange, config, city, ledger, positions, event_ticker, brackets, maker_mode, .. } = run;
 
-        let ens_pct = if let (Some(w), Some(mt)) = (&weather, &market_type) {
-            if !w.ensemble_member_highs.is_empty() {
-                let total = w.ensemble_member_highs.len() as f64;
-                let count = match mt {
-                    MarketType::Above(t) => w.ensemble_member_highs.iter().filter(|h| **h > *t).count(),
-                    MarketType::Below(t) => w.ensemble_member_highs.iter().filter(|h| **h < *t).count(),
-                    MarketType::Between(lo, hi) => w.ensemble_member_highs.iter().filter(|h| **h >= *lo && **h < *hi).count(),
-                };
-                format!("{:.0}%", count as f64 / total * 100.0)
-            } else {
-                "n/a".into()
-            }
-        } else {
-            "n/a".into()
-        };
+    // WEATHER — the decision's inputs are as old as this fetch
+    let context_at = std::time::Instant::now();
+    let timer = std::time::Instant::now();
+    let mut weather = fetch_weather(weather_feed, city).await;
+    state.city.latencies_ms.insert("weather".into(), timer.elapsed().as_millis() as u64);
+    if weather.is_some() {
+        if let Err(e) = storage::record_refresh(&city.name, now) {
+            tracing::warn!("[{}] Refresh state write failed: {}", city.name, e);
+        }
+    }
 
-        let mkt_pct = market.yes_ask.map(|a| format!("{:.0}%", a)).unwrap_or_else(|| "n/a".into());
-        let edge_pp = decision.edge_magnitude * 100.0;
+    let implied = ImpliedDistribution::from_brackets(brackets);
+    if let Some(d) = &implied {
+        tracing::info!(
+            "[{}] Market-implied mean {:.1}{} over {} brackets (overround {:.2})",
+            city.name, d.mean(), city.measure.unit(), d.bins.len(), d.overround
+        );
+    }
 
-        let action_label = match decision.action {
-            Action::Buy => {
-                let side = decision.side.as_ref().map(|s| format!("{:?}", s).to_uppercase()).unwrap_or_default();
-                format!("BUY {}", side)
-            }
-            Action::Pass => "PASS".into(),
-        };
+    // Replayed weather stays out of the research archive
+    if let Some(w) = &weather {
+        if w.offline {
+            tracing::warn!("[{}] OFFLINE DATA — weather replayed from brain/weather_cache/", city.name);
+        } else if let Err(e) = storage::archive_snapshot(w) {
+            tracing::warn!("[{}] Snapshot archive failed: {}", city.name, e);
+        }
+    }
+
+    check_forecast(config, city, event_ticker, brackets, weather.as_ref(), state);
+
+    // Trim after archiving so snapshots keep every member
+    let month = local_now(&city.timezone).month();
+    for p in config.seasons.matching(&city.name, &city.series_ticker, month) {
+        tracing::info!("[{}] Seasonal profile for month {}{}", city.name, month, p.note.as_deref().map(|n| format!(": {}", n)).unwrap_or_default());
+    }
+    let tuning = city.tuning.summary();
+    if !tuning.is_empty() {
+        tracing::info!("[{}] City tuning: {}", city.name, tuning);
+    }
+    let params = config.seasons.params(&city.strategy(&config.strategy), &city.name, &city.series_ticker, month);
+    if let Some(w) = weather.as_mut() {
+        if !features.ensemble {
+            drop_ensemble(w);
+        }
+        trim_ensemble(w, &params, brackets);
+        apply_nowcast(w, &params);
+    }
+
+    // EVALUATE all brackets
+    let mut buy_candidates: Vec<(MarketState, Orderbook, TradeDecision)> = Vec::new();
+    let mut scan_lines: Vec<String> = Vec::new();
+    let mut pass_tally: std::collections::BTreeMap<PassReason, u32> = std::collections::BTreeMap::new();
+
+    // A bracket whose book won't load sits this cycle out rather than
+    // failing the event
+    let timer = std::time::Instant::now();
+    let books = fetch_orderbooks(*exchange, city, brackets, "skipping bracket").await?;
+    if books.is_empty() {
+        anyhow::bail!("no orderbook loaded for any of {} brackets on {}", brackets.len(), event_ticker);
+    }
+    // Maker quotes, resting or pending, are re-quoted below rather than
+    // doubled up on, so they aren't shown to the brain as exposure
+    let resting: Vec<RestingOrder> = exchange
+        .resting_orders()
+        .await?
+        .into_iter()
+        .filter(|o| !(*maker_mode && o.is_ours()))
+        .collect();
+    state.city.latencies_ms.insert("orderbooks".into(), timer.elapsed().as_millis() as u64);
+    let exposure = risk::exposure(
+        event_ticker, positions, &resting, ledger, config.paper_ledger() && !maker_mode,
+    );
+    if !exposure.positions.is_empty() || !exposure.resting.is_empty() {
+        tracing::info!(
+            "[{}] Already on {}: {} position(s), {} resting order(s)",
+            city.name, event_ticker, exposure.positions.len(), exposure.resting.len()
+        );
+    }
+    let fair_overrides: std::collections::BTreeMap<String, f64> = books
+        .iter()
+        .filter_map(|(m, _)| {
+            let o = state.overrides.for_ticker(&m.ticker, now)?;
+            let fair = o.fair_yes?;
+            tracing::warn!("[{}] {}: operator fair value {:.0}% YES{}", city.name, m.ticker, fair * 100.0, o.note_suffix());
+            Some((m.ticker.clone(), fair))
+        })
+        .collect();
+    let context = EventContext {
+        prompt_md: prompt.text.clone(),
+        stats: stats::compute(ledger),
+        last_n_trades: ledger.iter().rev().take(20).cloned().collect(),
+        event_ticker: event_ticker.clone(),
+        brackets: books,
+        weather: weather.clone(),
+        implied: implied.clone(),
+        exposure,
+        fair_overrides,
+        features,
+    };
+    let timer = std::time::Instant::now();
+    let decisions = brain.decide_event(&context).await?;
+    state.city.latencies_ms.insert("brain".into(), timer.elapsed().as_millis() as u64);
+    if decisions.len() != context.brackets.len() {
+        anyhow::bail!(
+            "[{}] Brain returned {} decisions for {} brackets",
+            city.name, decisions.len(), context.brackets.len()
+        );
+    }
+    state.forecast_metrics.extend(forecast_metrics(city, weather.as_ref(), implied.as_ref(), &decisions));
+    state.city.best_edge_pp = decisions.iter().filter_map(|d| d.edge.as_ref().map(|e| e.net_pp)).max_by(f64::total_cmp);
+
+    let today = now.format("%Y-%m-%d").to_string();
+    for ((market, orderbook), mut decision) in context.brackets.into_iter().zip(decisions) {
+        let ticker_override = state.overrides.for_ticker(&market.ticker, now);
+        if let Some(o) = ticker_override.filter(|o| o.pass && decision.action == Action::Buy) {
+            tracing::warn!("[{}] {}: operator override: pass{}", city.name, market.ticker, o.note_suffix());
+            stand_down(&mut decision, PassReason::OperatorOverride);
+        }
+        if let Some(r) = risk::lockout(&state.risk, &market.ticker, &today).filter(|_| decision.action == Action::Buy) {
+            tracing::warn!("[{}] {}: locked out after {} rejections ({})", city.name, market.ticker, r.count, r.last_error);
+            stand_down(&mut decision, PassReason::TickerLocked);
+        }
+
+        if let Err(e) = storage::append_journal(&JournalEntry::Decision(DecisionRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            city: city.name.clone(),
+            event_ticker: event_ticker.clone(),
+            ticker: market.ticker.clone(),
+            action: decision.action,
+            side: decision.side.clone(),
+            pass_reason: decision.pass_reason,
+            edge_magnitude: decision.edge_magnitude,
+            counterfactual: decision.counterfactual.clone(),
+            model_probability: decision.model_probability,
+            minutes_to_expiry: Some(market.minutes_to_expiry),
+            edge: decision.edge.clone(),
+            market_kind: MarketType::from_market(&market).map(|mt| mt.kind().to_string()),
+            confidence: weather.as_ref().map(|w| w.confidence.clone()),
+            prompt_version: brain.uses_prompt().then(|| prompt.version.clone()),
+            strategy_version: Some(config.strategy_version.clone()),
+            operator_override: ticker_override.map(describe_override),
+            inputs: Some(Box::new(DecisionInputs::new(&market, weather.as_ref()))),
+        })) {
+            tracing::warn!("[{}] Journal write failed: {}", city.name, e);
+        }
+        if let Some(reason) = decision.pass_reason {
+            *pass_tally.entry(reason).or_insert(0u32) += 1;
+        }
 
-        scan_lines.push(format!(
-            "  {:<12} ({:<8}): ens={:<5} mkt={:<5} edge={:+.1}pp → {}",
-            market.ticker.split('-').last().unwrap_or(&market.ticker),
-            mt_label, ens_pct, mkt_pct, edge_pp, action_label
-        ));
+        scan_lines.push(scan_line(&market, &decision, weather.as_ref(), city.measure));
 
         if decision.action == Action::Buy {
-            buy_candidates.push((market.clone(), orderbook, decision));
+            buy_candidates.push((market, orderbook, decision));
         }
     }
 
-    // Bracket coverage sanity check
     if let Some(w) = &weather {
-        if !w.ensemble_member_highs.is_empty() {
-            let total = w.ensemble_member_highs.len();
-            let mut covered = 0usize;
-            for market in &brackets {
-                if let Some(mt) = MarketType::from_market(market) {
-                    covered += match &mt {
-                        MarketType::Above(t) => w.ensemble_member_highs.iter().filter(|h| **h > *t).count(),
-                        MarketType::Below(t) => w.ensemble_member_highs.iter().filter(|h| **h < *t).count(),
-                        MarketType::Between(lo, hi) => w.ensemble_member_highs.iter().filter(|h| **h >= *lo && **h < *hi).count(),
-                    };
-                }
-            }
-            let pct = covered as f64 / total as f64 * 100.0;
-            if pct < 90.0 {
-                tracing::warn!("[{}] Bracket coverage: {:.0}% ({}/{})", city.name, pct, covered, total);
-            } else {
-                tracing::info!("[{}] Bracket coverage: {:.0}% ({}/{})", city.name, pct, covered, total);
-            }
-        }
+        journal_curve(city, event_ticker, brackets, w, implied.as_ref());
     }
 
     tracing::info!("[{}] Bracket scan for {}:", city.name, event_ticker);
@@ -254,19 +1212,313 @@ async fn run_city(
         tracing::info!("{}", line);
     }
 
+    if !pass_tally.is_empty() {
+        let tally: Vec<String> = pass_tally.iter().map(|(r, n)| format!("{}×{}", r, n)).collect();
+        tracing::info!("[{}] Pass reasons: {}", city.name, tally.join(", "));
+    }
+    state.city.action = "pass".into();
+    state.city.pass_reasons = pass_tally;
+
     if buy_candidates.is_empty() {
         tracing::info!("[{}] PASS: No bracket has sufficient edge", city.name);
-        return Ok(false);
+        if let Some(plan) = &run.twap {
+            stop_twap(city, plan, "edge gone");
+        }
+        return Ok(None);
     }
 
     buy_candidates.sort_by(|a, b| {
         b.2.edge_magnitude.partial_cmp(&a.2.edge_magnitude).unwrap()
     });
 
-    let (best_market, _best_ob, best_decision) = &buy_candidates[0];
-    let side = best_decision.side.clone().unwrap_or(Side::Yes);
-    let shares = best_decision.shares.unwrap_or(1).min(config.max_shares);
-    let price = best_decision.max_price_cents.unwrap_or(50).clamp(1, 99);
+    // A TWAP plan keeps working its own bracket, as long as that still has edge
+    let best = match &run.twap {
+        Some(plan) => buy_candidates
+            .iter()
+            .position(|(m, _, d)| m.ticker == plan.ticker && d.side.as_ref() == Some(&plan.side)),
+        None => Some(0),
+    };
+    let Some(best) = best else {
+        if let Some(plan) = &run.twap {
+            stop_twap(city, plan, "edge gone on its bracket");
+        }
+        return Ok(None);
+    };
+    let (market, orderbook, decision) = buy_candidates.swap_remove(best);
+    let side = decision.side.clone().unwrap_or(Side::Yes);
+    state.city.action = format!("buy {:?}", side).to_lowercase();
+    Ok(Some(Pick { market, orderbook, decision, side, weather, context_at }))
+}
+
+/// CONSISTENCY and COVERAGE — warnings about the forecast against the
+/// event's brackets, before any trimming. Nothing here vetoes a trade.
+fn check_forecast(
+    config: &Config,
+    city: &CityConfig,
+    event_ticker: &str,
+    brackets: &[MarketState],
+    weather: Option<&WeatherSnapshot>,
+    state: &mut CycleState,
+) {
+    // Buckets and raw members are the same ensemble, so their YES
+    // probabilities should agree
+    if let Some((ticker, members, buckets)) = weather
+        .filter(|_| config.bucket_check_pp > 0.0)
+        .and_then(|w| rules_brain::bucket_divergence(w, brackets))
+    {
+        let gap_pp = (members - buckets).abs() * 100.0;
+        if gap_pp > config.bucket_check_pp {
+            tracing::warn!(
+                "[{}] {}: ensemble buckets say {:.1}% YES but members say {:.1}% ({:.1}pp apart, tolerance {:.1}pp) — bucket construction?",
+                city.name, ticker, buckets * 100.0, members * 100.0, gap_pp, config.bucket_check_pp
+            );
+        }
+        state.forecast_metrics.push(Metric {
+            name: "weatherbot_bucket_divergence_pp",
+            help: "Largest gap between member-counted and bucket-interpolated YES probability, in points",
+            labels: vec![("city", city.name.clone())],
+            value: gap_pp,
+        });
+    }
+
+    // The brackets should tile the line; model mass outside all of them is
+    // probability no YES price accounts for
+    if config.coverage_alert_mass > 0.0 {
+        let members = weather.map(|w| w.ensemble_member_highs.as_slice()).unwrap_or_default();
+        let cov = coverage::check(brackets, members);
+        for (lower, upper) in &cov.gaps {
+            tracing::warn!("[{}] {}: no bracket covers {:.1}–{:.1}{}", city.name, event_ticker, lower, upper, city.measure.unit());
+        }
+        if cov.open_below || cov.open_above {
+            tracing::info!(
+                "[{}] {}: brackets leave the {} tail uncovered",
+                city.name, event_ticker,
+                match (cov.open_below, cov.open_above) { (true, true) => "lower and upper", (true, false) => "lower", _ => "upper" }
+            );
+        }
+        for (a, b) in &cov.overlaps {
+            tracing::info!("[{}] {} overlaps {}", city.name, a, b);
+        }
+        if let Some(mass) = cov.uncovered_mass {
+            if mass > config.coverage_alert_mass {
+                tracing::warn!(
+                    "[{}] {:.0}% of ensemble members fall outside every bracket on {} (alert above {:.0}%) — YES prices won't sum to the model",
+                    city.name, mass * 100.0, event_ticker, config.coverage_alert_mass * 100.0
+                );
+            }
+            state.forecast_metrics.push(Metric {
+                name: "weatherbot_uncovered_mass",
+                help: "Share of ensemble members no bracket of the event resolves YES on",
+                labels: vec![("city", city.name.clone())],
+                value: mass,
+            });
+        }
+    }
+}
+
+/// One bracket's row in the scan log: the ensemble's and market's YES, the
+/// edge, and what was decided.
+fn scan_line(market: &MarketState, decision: &TradeDecision, weather: Option<&WeatherSnapshot>, measure: Measure) -> String {
+    let market_type = MarketType::from_market(market);
+    let mt_label = market_type.as_ref().map_or_else(|| "???".into(), |mt| mt.label(measure));
+    let ens_pct = match (weather, &market_type) {
+        (Some(w), Some(mt)) if !w.ensemble_member_highs.is_empty() => {
+            format!("{:.0}%", rules_brain::compute_ensemble_yes_from_members(&w.ensemble_member_highs, mt) * 100.0)
+        }
+        _ => "n/a".into(),
+    };
+    let mkt_pct = market.yes_ask.map(|a| format!("{:.0}%", a)).unwrap_or_else(|| "n/a".into());
+    let action_label = match decision.action {
+        Action::Buy => {
+            let side = decision.side.as_ref().map(|s| format!("{:?}", s).to_uppercase()).unwrap_or_default();
+            format!("BUY {}", side)
+        }
+        Action::Pass => match decision.pass_reason {
+            Some(reason) => format!("PASS ({})", reason),
+            None => "PASS".into(),
+        },
+    };
+    format!(
+        "  {:<12} ({:<8}): ens={:<5} mkt={:<5} edge={:+.1}pp → {}",
+        market.ticker.split('-').next_back().unwrap_or(&market.ticker),
+        mt_label, ens_pct, mkt_pct, decision.edge_magnitude * 100.0, action_label
+    )
+}
+
+/// Probability curve artifact: model CDF vs market-implied CDF.
+fn journal_curve(
+    city: &CityConfig,
+    event_ticker: &str,
+    brackets: &[MarketState],
+    weather: &WeatherSnapshot,
+    implied: Option<&ImpliedDistribution>,
+) {
+    if weather.ensemble_member_highs.is_empty() {
+        return;
+    }
+    let grid = distribution::temperature_grid(&weather.ensemble_member_highs, brackets);
+    let model = distribution::member_cdf(&weather.ensemble_member_highs, &grid);
+    let points = grid
+        .iter()
+        .enumerate()
+        .map(|(i, &temp_f)| CurvePoint {
+            temp_f,
+            model_cdf: model[i],
+            market_cdf: implied.map(|d| d.cdf(temp_f as f64)),
+        })
+        .collect();
+    if let Err(e) = storage::append_journal(&JournalEntry::ProbabilityCurve(CurveRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        city: city.name.clone(),
+        event_ticker: event_ticker.to_string(),
+        points,
+    })) {
+        tracing::warn!("[{}] Curve journal write failed: {}", city.name, e);
+    }
+}
+
+/// RISK GATING — size the pick: the brain's shares, then each cap in turn
+/// (city max, operator pins, canary, capital, position limit, city
+/// exposure) and a TWAP slice of what the ask can fill. None when a cap
+/// leaves nothing to send.
+async fn size_order(
+    run: &CityRun<'_>,
+    pick: &Pick,
+    city_override: Option<&Override>,
+    now: chrono::DateTime<chrono::Utc>,
+    state: &mut CycleState,
+) -> Result<Option<SizedOrder>> {
+    let CityRun { exchange, config, city, ledger, positions, maker_mode, .. } = run;
+    let market = &pick.market;
+
+    let shares = pick.decision.shares.unwrap_or(1).min(city.max_shares(config));
+    let pinned = [city_override, state.overrides.for_ticker(&market.ticker, now)]
+        .into_iter()
+        .flatten()
+        .filter_map(|o| o.max_shares)
+        .min();
+    let shares = match pinned {
+        Some(pin) if pin < shares => {
+            tracing::info!("[{}] Sizing {}x → {}x, pinned by overrides.toml", city.name, shares, pin);
+            pin
+        }
+        _ => shares,
+    };
+    if shares == 0 {
+        tracing::info!("[{}] overrides.toml pins {} to 0 contracts — skipping", city.name, market.ticker);
+        return Ok(None);
+    }
+    // CANARY — a new strategy version trades small until its settled
+    // trades bear the model out
+    let shares = if state.canary_fraction < 1.0 {
+        let cut = canary::size(shares, state.canary_fraction);
+        if cut < shares {
+            tracing::info!(
+                "[{}] Sizing {}x → {}x, strategy {} on canary at {:.0}%",
+                city.name, shares, cut, config.strategy_version, state.canary_fraction * 100.0
+            );
+        }
+        cut
+    } else {
+        shares
+    };
+    let price = pick.decision.max_price_cents.unwrap_or(50).clamp(1, 99);
+
+    let affordable = risk::affordable_shares(state.available_cents, price);
+    if affordable == 0 {
+        tracing::info!("[{}] Available capital {} can't cover 1x @ {}¢ — skipping", city.name, state.available_cents, price);
+        return Ok(None);
+    }
+    let shares = if affordable < shares {
+        tracing::info!(
+            "[{}] Sizing {}x → {}x to fit available capital {}",
+            city.name, shares, affordable, state.available_cents
+        );
+        affordable
+    } else {
+        shares
+    };
+
+    // POSITION LIMIT — never send what the exchange would reject. In maker
+    // mode our own resting quote on this ticker is about to be amended, so
+    // it doesn't count against the headroom.
+    let resting = exchange.resting_orders().await?;
+    let counted: Vec<RestingOrder> = resting
+        .into_iter()
+        .filter(|o| !(*maker_mode && o.is_ours()))
+        .collect();
+    let headroom = risk::position_headroom(
+        market, config.market_position_limit_cents, positions, &counted, ledger, config.paper_ledger(), price,
+    );
+    if headroom == 0 {
+        tracing::info!("[{}] {} at its position limit — skipping", city.name, market.ticker);
+        return Ok(None);
+    }
+    let shares = if headroom < shares {
+        tracing::info!(
+            "[{}] Sizing {}x → {}x to stay under the {} position limit",
+            city.name, shares, headroom, market.ticker
+        );
+        headroom
+    } else {
+        shares
+    };
+
+    // CITY EXPOSURE — every city is scanned each cycle; keep one city's
+    // forecast from taking the capital the others would use
+    let shares = if config.city_max_exposure_cents > Cents::ZERO {
+        let used = risk::city_exposure(city, positions, &counted, ledger, config.paper_ledger());
+        let room = config.city_max_exposure_cents.saturating_sub_to_zero(used);
+        let fit = (room.0 / price as i64).min(u32::MAX as i64) as u32;
+        if fit == 0 {
+            tracing::info!(
+                "[{}] City exposure {} at its {} cap — skipping",
+                city.name, used, config.city_max_exposure_cents
+            );
+            return Ok(None);
+        }
+        if fit < shares {
+            tracing::info!(
+                "[{}] Sizing {}x → {}x to stay under the {} city exposure cap ({} used)",
+                city.name, shares, fit, config.city_max_exposure_cents, used
+            );
+        }
+        shares.min(fit)
+    } else {
+        shares
+    };
+
+    // TWAP — more than the ask can fill: send what's there now, the rest
+    // in later cycles. A plan in progress sizes from what it has left.
+    let ask = match pick.side {
+        Side::Yes => market.yes_ask,
+        Side::No => market.no_ask,
+    };
+    let target = run.twap.as_ref().map_or(shares, |p| shares.min(p.remaining()));
+    let depth = ask.map_or(0, |a| pick.orderbook.ask_depth(&pick.side, a));
+    let twap_on = config.twap_interval_minutes > 0 && !maker_mode;
+    let shares = if twap_on && depth > 0 && depth < target {
+        tracing::info!(
+            "[{}] TWAP: {}x wanted, {}x at the ask — sending a {}x slice",
+            city.name, target, depth, depth
+        );
+        depth
+    } else {
+        target
+    };
+
+    Ok(Some(SizedOrder { shares, target, price, ask }))
+}
+
+/// PLACEMENT — last checks on the sized order (a position appearing
+/// meanwhile, cached weather, quotes gone stale), then send it: a paper
+/// ledger row, a maker re-quote, or a live order with one retry at what
+/// the balance covers. Ledger rows are written only after the exchange
+/// accepts.
+async fn place_order(run: CityRun<'_>, pick: Pick, order: SizedOrder, state: &mut CycleState) -> Result<bool> {
+    let CityRun { exchange, config, city, ledger, event_ticker, brackets, maker_mode, twap, .. } = run;
+    let Pick { market: best_market, decision: best_decision, side, weather, context_at, .. } = pick;
+    let SizedOrder { shares, target, price, ask } = order;
 
     tracing::info!(
         "[{}] Best: {} | edge={:.1}pp | {:?} {}x @ {}¢",
@@ -277,91 +1529,824 @@ async fn run_city(
 
     // FINAL POSITION CHECK (race condition guard)
     let fresh_positions = exchange.positions().await?;
-    if fresh_positions.iter().any(|p| brackets.iter().any(|b| b.ticker == p.ticker)) {
+    if twap.is_none() && fresh_positions.iter().any(|p| brackets.iter().any(|b| b.ticker == p.ticker)) {
         tracing::warn!("[{}] Position appeared during evaluation — aborting", city.name);
         return Ok(false);
     }
 
+    // Cached weather never backs a real order
+    if !config.paper_trade && weather.as_ref().is_some_and(|w| w.offline) {
+        anyhow::bail!("[{}] OFFLINE DATA — refusing a live order priced on cached weather", city.name);
+    }
+
+    // FRESHNESS — a slow brain call or API retries can leave the quotes the
+    // decision was priced on behind the market. Past QUOTE_SLA_SECS the
+    // book is read again, and an ask that moved past the limit aborts.
+    let age = context_at.elapsed();
+    state.city.latencies_ms.insert("context_age".into(), age.as_millis() as u64);
+    let ask = if config.quote_sla_secs > 0 && age.as_secs() >= config.quote_sla_secs {
+        let book = exchange.orderbook(&best_market.ticker).await?;
+        match book.asks(&side).first().map(|(p, _)| *p) {
+            Some(fresh) if fresh <= price => {
+                tracing::info!(
+                    "[{}] Context {:.1}s old (SLA {}s) — refetched {}: ask {}¢, limit {}¢ still holds",
+                    city.name, age.as_secs_f64(), config.quote_sla_secs, best_market.ticker, fresh, price
+                );
+                Some(fresh)
+            }
+            fresh => {
+                tracing::warn!(
+                    "[{}] Context {:.1}s old (SLA {}s) and {} ask is now {} vs limit {}¢ — aborting",
+                    city.name, age.as_secs_f64(), config.quote_sla_secs, best_market.ticker,
+                    fresh.map_or("gone".to_string(), |a| format!("{}¢", a)), price
+                );
+                return Ok(false);
+            }
+        }
+    } else {
+        ask
+    };
+
     // EXECUTE — order FIRST, ledger SECOND
     let current_stats = stats::compute(ledger);
+    let mut placed_shares = 0;
 
-    if config.paper_trade {
+    if config.paper_ledger() {
         let paper_id = format!("paper-{}", chrono::Utc::now().timestamp_millis());
         tracing::info!(
             "[{}] PAPER: {:?} {}x @ {}¢ | {} ({})",
             city.name, side, shares, price, best_market.ticker, paper_id
         );
-        storage::append_ledger(&LedgerRow {
-            timestamp: chrono::Utc::now().to_rfc3339(),
+        storage::write_durable(QueuedWrite::AppendLedger {
+            row: LedgerRow {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                ticker: best_market.ticker.clone(),
+                side: format!("{:?}", side).to_lowercase(),
+                shares,
+                price,
+                result: "pending".into(),
+                pnl_cents: Cents::ZERO,
+                cumulative_cents: current_stats.total_pnl_cents,
+                order_id: paper_id.clone(),
+                cancel_reason: None,
+                event_ticker: event_ticker.clone(),
+                city: city.name.clone(),
+            },
+        })?;
+        journal_trade(city, &event_ticker, &best_market.ticker, &paper_id, &side, shares, price, true, None, weather.as_ref());
+        record_trade(city, &event_ticker);
+        state.available_cents = state.available_cents.saturating_sub_to_zero(Cents::for_contracts(shares, price));
+        placed_shares = shares;
+    } else if maker_mode {
+        let quote = Quote {
             ticker: best_market.ticker.clone(),
-            side: format!("{:?}", side).to_lowercase(),
+            side: side.clone(),
+            price_cents: price,
             shares,
-            price,
-            result: "pending".into(),
-            pnl_cents: 0,
-            cumulative_cents: current_stats.total_pnl_cents,
-            order_id: paper_id,
-        })?;
+        };
+        return requote_event(exchange, city, &brackets, quote, current_stats.total_pnl_cents, weather.as_ref(), config.paper_trade, config.rejection_lockout, state).await;
     } else {
-        let order_result = exchange
-            .place_order(&OrderRequest {
-                ticker: best_market.ticker.clone(),
-                side: side.clone(),
-                shares,
-                price_cents: price,
-            })
+        let executor = execution::executor_for(config);
+        let mut shares = shares;
+        let mut order_result = executor
+            .execute(
+                exchange,
+                &OrderRequest {
+                    ticker: best_market.ticker.clone(),
+                    side: side.clone(),
+                    shares,
+                    price_cents: price,
+                },
+                ask,
+            )
             .await;
 
-        match order_result {
-            Ok(result) => {
-                tracing::info!(
-                    "[{}] LIVE: {:?} {}x @ {}¢ | {} (order {} status: {})",
-                    city.name, side, shares, price, best_market.ticker, result.order_id, result.status
+        // Insufficient funds: shrink to what the balance covers and retry once
+        let underfunded = matches!(
+            order_result.as_ref().err().and_then(exchange_error),
+            Some(ExchangeError::InsufficientFunds(_))
+        );
+        if underfunded {
+            let balance = exchange.balance().await?;
+            let affordable = risk::affordable_shares(balance, price);
+            if affordable >= 1 && affordable < shares {
+                tracing::warn!(
+                    "[{}] Insufficient funds for {}x @ {}¢ (balance {}) — retrying with {}x",
+                    city.name, shares, price, balance, affordable
                 );
-                if let Err(e) = storage::append_ledger(&LedgerRow {
+                if let Err(e) = storage::append_journal(&JournalEntry::Downsized(DownsizeRecord {
                     timestamp: chrono::Utc::now().to_rfc3339(),
+                    city: city.name.clone(),
                     ticker: best_market.ticker.clone(),
-                    side: format!("{:?}", side).to_lowercase(),
-                    shares,
-                    price,
-                    result: "pending".into(),
-                    pnl_cents: 0,
-                    cumulative_cents: current_stats.total_pnl_cents,
-                    order_id: result.order_id.clone(),
-                }) {
-                    tracing::error!(
-                        "CRITICAL: Order {} placed but ledger write failed: {}",
-                        result.order_id,
-                        e
+                    requested_shares: shares,
+                    placed_shares: affordable,
+                    price_cents: price,
+                    balance_cents: balance,
+                })) {
+                    tracing::warn!("[{}] Journal write failed: {}", city.name, e);
+                }
+                shares = affordable;
+                order_result = executor
+                    .execute(
+                        exchange,
+                        &OrderRequest {
+                            ticker: best_market.ticker.clone(),
+                            side: side.clone(),
+                            shares,
+                            price_cents: price,
+                        },
+                        ask,
+                    )
+                    .await;
+            } else {
+                tracing::warn!(
+                    "[{}] Insufficient funds: balance {} covers {}x @ {}¢ — skipping",
+                    city.name, balance, affordable, price
+                );
+                return Ok(false);
+            }
+        }
+
+        match order_result {
+            Ok(placed) => {
+                if placed.is_empty() {
+                    tracing::info!("[{}] Nothing filled on {} — no trade", city.name, best_market.ticker);
+                    return Ok(false);
+                }
+                record_trade(city, &event_ticker);
+                let venue = if config.paper_trade { "SIM" } else { "LIVE" };
+                for order in &placed {
+                    tracing::info!(
+                        "[{}] {}: {:?} {}x @ {}¢ | {} (order {})",
+                        city.name, venue, side, order.shares, order.price_cents, best_market.ticker, order.order_id
                     );
-                    return Err(e.into());
+                    if let Err(e) = storage::write_durable(QueuedWrite::AppendLedger {
+                        row: LedgerRow {
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                            ticker: best_market.ticker.clone(),
+                            side: format!("{:?}", side).to_lowercase(),
+                            shares: order.shares,
+                            price: order.price_cents,
+                            result: "pending".into(),
+                            pnl_cents: Cents::ZERO,
+                            cumulative_cents: current_stats.total_pnl_cents,
+                            order_id: order.order_id.clone(),
+                            cancel_reason: None,
+                            event_ticker: event_ticker.clone(),
+                            city: city.name.clone(),
+                        },
+                    }) {
+                        tracing::error!(
+                            "CRITICAL: Order {} placed but ledger write failed: {}",
+                            order.order_id,
+                            e
+                        );
+                        return Err(e);
+                    }
+                    journal_trade(city, &event_ticker, &best_market.ticker, &order.order_id, &side, order.shares, order.price_cents, config.paper_trade, order.fee_cents, weather.as_ref());
+                    state.available_cents = state.available_cents.saturating_sub_to_zero(Cents::for_contracts(order.shares, order.price_cents));
+                    placed_shares += order.shares;
                 }
             }
+            Err(e) if matches!(exchange_error(&e), Some(ExchangeError::MarketClosed(_))) => {
+                tracing::warn!("[{}] Market closed before order landed: {:#}", city.name, e);
+                storage::record_rejected(&best_market.ticker, shares);
+                note_rejection(state, city, &best_market.ticker, &e, config.rejection_lockout);
+                return Ok(false);
+            }
             Err(e) => {
-                tracing::error!("[{}] Order placement failed: {}", city.name, e);
+                tracing::error!("[{}] Order placement failed: {:#}", city.name, e);
+                storage::record_rejected(&best_market.ticker, shares);
+                note_rejection(state, city, &best_market.ticker, &e, config.rejection_lockout);
                 return Err(e);
             }
         }
     }
 
+    let twap_on = config.twap_interval_minutes > 0 && !maker_mode;
+    if twap_on && (twap.is_some() || shares < target) {
+        advance_twap(city, twap, &event_ticker, &best_market, &side, target, placed_shares, config.twap_interval_minutes);
+    }
+
     Ok(true)
 }
 
+/// Record a slice against the event's TWAP plan, starting one if this was
+/// the first. A plan with nothing left is dropped. Best-effort: a lost plan
+/// only means the rest of the size isn't worked.
+#[allow(clippy::too_many_arguments)]
+fn advance_twap(
+    city: &CityConfig,
+    plan: Option<TwapPlan>,
+    event_ticker: &str,
+    market: &MarketState,
+    side: &Side,
+    target: u32,
+    placed: u32,
+    interval_minutes: u64,
+) {
+    let now = chrono::Utc::now();
+    let mut plan = plan.unwrap_or_else(|| TwapPlan {
+        event_ticker: event_ticker.to_string(),
+        ticker: market.ticker.clone(),
+        side: side.clone(),
+        total_shares: target,
+        filled_shares: 0,
+        slices: 0,
+        started_at: now.to_rfc3339(),
+        next_slice_at: now.to_rfc3339(),
+    });
+    plan.filled_shares += placed;
+    plan.slices += 1;
+    plan.next_slice_at = (now + chrono::Duration::minutes(interval_minutes as i64)).to_rfc3339();
+
+    let written = if plan.remaining() == 0 {
+        tracing::info!("[{}] TWAP on {} done: {}x in {} slices", city.name, plan.ticker, plan.filled_shares, plan.slices);
+        storage::write_twap_plan(event_ticker, None)
+    } else {
+        tracing::info!(
+            "[{}] TWAP on {}: {}/{} filled, next slice at {}",
+            city.name, plan.ticker, plan.filled_shares, plan.total_shares, plan.next_slice_at
+        );
+        storage::write_twap_plan(event_ticker, Some(&plan))
+    };
+    if let Err(e) = written {
+        tracing::warn!("[{}] TWAP plan write failed: {}", city.name, e);
+    }
+}
+
+/// Drop a TWAP plan whose edge is gone; what already filled stays.
+fn stop_twap(city: &CityConfig, plan: &TwapPlan, reason: &str) {
+    tracing::info!(
+        "[{}] TWAP on {} stopped ({}): {}/{} filled in {} slices",
+        city.name, plan.ticker, reason, plan.filled_shares, plan.total_shares, plan.slices
+    );
+    if let Err(e) = storage::write_twap_plan(&plan.event_ticker, None) {
+        tracing::warn!("[{}] TWAP plan write failed: {}", city.name, e);
+    }
+}
+
+/// An overrides.toml entry as journaled: `pass, max_shares=5 (note)`.
+fn describe_override(o: &crate::overrides::Override) -> String {
+    let mut parts = Vec::new();
+    if o.pass {
+        parts.push("pass".to_string());
+    }
+    if let Some(n) = o.max_shares {
+        parts.push(format!("max_shares={}", n));
+    }
+    if let Some(p) = o.fair_yes {
+        parts.push(format!("fair_yes={}", p));
+    }
+    format!("{}{}", parts.join(", "), o.note_suffix())
+}
+
+/// Best-effort: a failed write only weakens the restart guard.
+fn record_trade(city: &CityConfig, event_ticker: &str) {
+    if let Err(e) = storage::record_trade(event_ticker, chrono::Utc::now()) {
+        tracing::warn!("[{}] Trade cooldown write failed: {}", city.name, e);
+    }
+}
+
+/// Tell the operator a market was voided: the row is refunded rather than
+/// scored, and a void usually means a station data problem worth a look.
+/// Best-effort.
+async fn alert_void(notifier: &dyn Notifier, row: &LedgerRow, settlement: &Settlement) {
+    tracing::warn!("Market {} voided — order {} refunded, not scored", row.ticker, row.order_id);
+    let body = format!(
+        "{} {}x @ {}¢ (order {}) refunded at zero P&L and left out of win-rate stats.\nSettled {}.",
+        row.side, row.shares, row.price, row.order_id, settlement.settled_time
+    );
+    if let Err(e) = notifier.notify(&format!("Market voided: {}", row.ticker), &body).await {
+        tracing::warn!("Void alert failed: {}", e);
+    }
+}
+
+/// Count this cycle's evaluations toward today's activity and tell the
+/// operator once a day if buys or trades have strayed far from the
+/// baseline. Best-effort.
+async fn monitor_activity(notifier: &dyn Notifier, limits: &ActivityLimits, cities: &[CitySummary], trades: u32) {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let mut state = storage::read_activity();
+    activity::record(&mut state, &today, cities, trades, limits.baseline_days);
+    for alert in activity::check(&state, &today, limits) {
+        tracing::warn!(metric = alert.metric, "Activity anomaly: {}", alert);
+        let body = format!("{}.\nCheck the weather feeds and the journal's pass reasons before trusting the bot's edge.", alert);
+        match notifier.notify("Trade frequency anomaly", &body).await {
+            Ok(()) => {
+                if let Some(day) = state.days.get_mut(&today) {
+                    day.alerted.push(alert.key());
+                }
+            }
+            Err(e) => tracing::warn!("Activity alert failed: {}", e),
+        }
+    }
+    if let Err(e) = storage::write_activity(&state) {
+        tracing::warn!("Activity write failed: {}", e);
+    }
+}
+
+/// Look up settled results for tickers with a journaled counterfactual or
+/// model probability.
+/// Best-effort: failures are logged and retried next cycle.
+async fn resolve_market_outcomes(exchange: &dyn Exchange) {
+    const MAX_LOOKUPS_PER_CYCLE: usize = 20;
+
+    let journal = match storage::read_journal() {
+        Ok(j) => j,
+        Err(e) => {
+            tracing::warn!("Journal read failed: {}", e);
+            return;
+        }
+    };
+    let resolved: std::collections::HashSet<&str> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::MarketOutcome(o) => Some(o.ticker.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let cutoff = (chrono::Utc::now() - chrono::Duration::hours(12)).to_rfc3339();
+    let mut unresolved: Vec<&str> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::Decision(d)
+                if (d.counterfactual.is_some() || d.model_probability.is_some()) && d.timestamp < cutoff =>
+            {
+                Some(d.ticker.as_str())
+            }
+            _ => None,
+        })
+        .filter(|t| !resolved.contains(t))
+        .collect();
+    unresolved.sort_unstable();
+    unresolved.dedup();
+
+    for ticker in unresolved.into_iter().take(MAX_LOOKUPS_PER_CYCLE) {
+        match exchange.market_result(ticker).await {
+            Ok(Some(result)) => {
+                let record = OutcomeRecord {
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                    ticker: ticker.to_string(),
+                    result: result.label().to_string(),
+                };
+                if let Err(e) = storage::append_journal(&JournalEntry::MarketOutcome(record)) {
+                    tracing::warn!("Outcome journal write failed for {}: {}", ticker, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Market result lookup failed for {}: {:#}", ticker, e),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn journal_trade(
+    city: &CityConfig,
+    event_ticker: &str,
+    ticker: &str,
+    order_id: &str,
+    side: &Side,
+    shares: u32,
+    price_cents: u32,
+    paper: bool,
+    fee_cents: Option<Cents>,
+    weather: Option<&WeatherSnapshot>,
+) {
+    let record = TradeRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        city: city.name.clone(),
+        event_ticker: event_ticker.to_string(),
+        ticker: ticker.to_string(),
+        order_id: order_id.to_string(),
+        side: side.clone(),
+        shares,
+        price_cents,
+        paper,
+        model_run: weather.and_then(|w| w.ensemble_run.clone()),
+        member_count: weather.map_or(0, |w| w.ensemble_member_highs.len()),
+        fee_cents,
+    };
+    if let Err(e) = storage::append_journal(&JournalEntry::Trade(record)) {
+        tracing::warn!("[{}] Trade journal write failed: {}", city.name, e);
+    }
+}
+
+fn exchange_error(e: &anyhow::Error) -> Option<&ExchangeError> {
+    e.downcast_ref::<ExchangeError>()
+}
+
+/// Count an order the exchange refused for reasons that will recur on the
+/// next try (bad price, paused or missing market) toward the ticker's
+/// lockout. Rate limits, outages and funds aren't the market's fault.
+fn note_rejection(state: &mut CycleState, city: &CityConfig, ticker: &str, e: &anyhow::Error, limit: u32) {
+    if !matches!(
+        exchange_error(e),
+        Some(ExchangeError::Validation(_) | ExchangeError::MarketClosed(_) | ExchangeError::NotFound(_))
+    ) {
+        return;
+    }
+    if risk::record_rejection(&mut state.risk, ticker, &format!("{:#}", e), chrono::Utc::now(), limit) {
+        tracing::error!(
+            "[{}] {} rejected {} times today — locked out until `kalshi-bot unlock {}` or the UTC day rolls over",
+            city.name, ticker, limit, ticker
+        );
+    }
+    if let Err(e) = storage::write_risk_state(&state.risk) {
+        tracing::warn!("Risk state write failed: {}", e);
+    }
+}
+
+/// `kalshi-bot cancel <order_id>...`: cancel the given resting orders and
+/// mark their ledger rows as operator cancellations.
+pub async fn cancel_by_operator(exchange: &dyn Exchange, order_ids: &[String]) -> Result<()> {
+    let resting = exchange.resting_orders().await?;
+    let targets: Vec<&RestingOrder> = resting.iter().filter(|o| order_ids.contains(&o.order_id)).collect();
+    for id in order_ids.iter().filter(|id| !targets.iter().any(|o| &o.order_id == *id)) {
+        tracing::warn!("Order {} is not resting — nothing to cancel", id);
+    }
+    cancel_orders(exchange, &targets, CancelReason::Operator).await
+}
+
+/// `kalshi-bot cancel-all`: every resting order the bot placed. Manual
+/// orders are left alone, as in the cycle.
+pub async fn cancel_all_by_operator(exchange: &dyn Exchange) -> Result<()> {
+    let resting = exchange.resting_orders().await?;
+    let ours: Vec<&RestingOrder> = resting.iter().filter(|o| o.is_ours()).collect();
+    tracing::info!("Cancelling {} bot order(s)", ours.len());
+    cancel_orders(exchange, &ours, CancelReason::Operator).await
+}
+
+/// Cancel resting orders in one batch and mark their ledger rows cancelled.
+/// Orders that are already gone were filled or cancelled elsewhere. The book
+/// is re-read afterwards: an order the exchange still shows resting fails
+/// the call, since a new order on top of it would double the exposure.
+async fn cancel_orders(exchange: &dyn Exchange, orders: &[&RestingOrder], reason: CancelReason) -> Result<()> {
+    if orders.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = orders.iter().map(|o| o.order_id.clone()).collect();
+    let results = exchange.cancel_orders(&ids).await?;
+
+    let mut failed = None;
+    for (id, result) in ids.iter().zip(results) {
+        match result {
+            Ok(()) => {
+                storage::write_durable(QueuedWrite::Cancel { order_id: id.clone(), reason })?;
+                tracing::info!("Canceled order: {} ({}, ledger marked cancelled)", id, reason);
+            }
+            Err(e) if matches!(exchange_error(&e), Some(ExchangeError::NotFound(_))) => {
+                tracing::warn!("Order {} already gone (filled or canceled elsewhere)", id);
+            }
+            Err(e) => {
+                tracing::error!("Cancel of order {} failed: {:#}", id, e);
+                failed.get_or_insert(e);
+            }
+        }
+    }
+
+    let still_resting: Vec<String> = exchange
+        .resting_orders()
+        .await?
+        .into_iter()
+        .filter(|o| o.remaining_count > 0 && ids.contains(&o.order_id))
+        .map(|o| o.order_id)
+        .collect();
+    if !still_resting.is_empty() {
+        anyhow::bail!("{} order(s) still resting after cancel: {}", still_resting.len(), still_resting.join(", "));
+    }
+    match failed {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Maker mode: bring this event's resting quotes in line with `quote` using
+/// the fewest order actions, so untouched orders keep queue priority.
+#[allow(clippy::too_many_arguments)]
+async fn requote_event(
+    exchange: &dyn Exchange,
+    city: &CityConfig,
+    brackets: &[MarketState],
+    quote: Quote,
+    cumulative_cents: Cents,
+    weather: Option<&WeatherSnapshot>,
+    paper: bool,
+    lockout: u32,
+    state: &mut CycleState,
+) -> Result<bool> {
+    let resting = exchange.resting_orders().await?;
+    let ours: Vec<&RestingOrder> = resting
+        .iter()
+        .filter(|o| o.is_ours() && brackets.iter().any(|b| b.ticker == o.ticker))
+        .collect();
+    state.requoted.extend(brackets.iter().map(|b| b.ticker.clone()));
+    let event_ticker = brackets.first().map(|b| b.event_ticker.clone()).unwrap_or_default();
+
+    let actions = requote::plan(std::slice::from_ref(&quote), &ours);
+    let mut changed = false;
+    for action in actions {
+        match action {
+            OrderAction::Keep { order_id } => {
+                tracing::info!("[{}] Quote {} unchanged — keeping queue position", city.name, order_id);
+            }
+            OrderAction::Cancel { order_id } => {
+                if let Some(order) = ours.iter().find(|o| o.order_id == order_id) {
+                    cancel_orders(exchange, &[*order], CancelReason::Requote).await?;
+                }
+            }
+            OrderAction::Amend { order_id, price_cents, shares } => {
+                let request = OrderRequest {
+                    ticker: quote.ticker.clone(),
+                    side: quote.side.clone(),
+                    shares,
+                    price_cents,
+                };
+                match exchange.amend_order(&order_id, &request).await {
+                    Ok(result) => {
+                        tracing::info!(
+                            "[{}] Amended {} → {}x @ {}¢ (order {})",
+                            city.name, order_id, shares, price_cents, result.order_id
+                        );
+                        storage::write_durable(QueuedWrite::Amend {
+                            order_id,
+                            new_order_id: result.order_id,
+                            shares,
+                            price: price_cents,
+                        })?;
+                        changed = true;
+                    }
+                    Err(e) if matches!(exchange_error(&e), Some(ExchangeError::NotFound(_))) => {
+                        tracing::warn!("[{}] Quote {} gone before amend (filled?) — leaving for settlement", city.name, order_id);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            OrderAction::Create(q) => {
+                let result = match exchange
+                    .place_order(&OrderRequest {
+                        ticker: q.ticker.clone(),
+                        side: q.side.clone(),
+                        shares: q.shares,
+                        price_cents: q.price_cents,
+                    })
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(e) => {
+                        note_rejection(state, city, &q.ticker, &e, lockout);
+                        return Err(e);
+                    }
+                };
+                tracing::info!(
+                    "[{}] {} quote: {:?} {}x @ {}¢ | {} (order {})",
+                    city.name, if paper { "SIM" } else { "LIVE" }, q.side, q.shares, q.price_cents, q.ticker, result.order_id
+                );
+                storage::write_durable(QueuedWrite::AppendLedger {
+                    row: LedgerRow {
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                        ticker: q.ticker.clone(),
+                        side: format!("{:?}", q.side).to_lowercase(),
+                        shares: q.shares,
+                        price: q.price_cents,
+                        result: "pending".into(),
+                        pnl_cents: Cents::ZERO,
+                        cumulative_cents,
+                        order_id: result.order_id.clone(),
+                        cancel_reason: None,
+                        event_ticker: event_ticker.clone(),
+                        city: city.name.clone(),
+                    },
+                })?;
+                journal_trade(city, &event_ticker, &q.ticker, &result.order_id, &q.side, q.shares, q.price_cents, paper, result.fee_cents, weather);
+                state.available_cents = state.available_cents.saturating_sub_to_zero(Cents::for_contracts(q.shares, q.price_cents));
+                changed = true;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Save this cycle's brain/cycle_summary.json; best-effort, like the metrics.
+fn write_summary(started_at: &str, outcome: &str, trades: u32, cities: Vec<CitySummary>) {
+    let summary = CycleSummary {
+        started_at: started_at.to_string(),
+        finished_at: chrono::Utc::now().to_rfc3339(),
+        outcome: outcome.to_string(),
+        trades,
+        cities,
+    };
+    if let Err(e) = storage::write_cycle_summary(&summary) {
+        tracing::warn!("Cycle summary write failed: {}", e);
+    }
+}
+
+/// Rewrite brain/metrics.prom. `active_veto` is the risk limit that stopped
+/// this cycle, if any.
+fn write_metrics(config: &Config, active_veto: Option<RiskVeto>, forecast: &[Metric]) {
+    let mut metrics = quota_metrics(config);
+    metrics.extend_from_slice(forecast);
+    match storage::read_journal() {
+        Ok(journal) => metrics.extend(risk_metrics(&journal, active_veto)),
+        Err(e) => tracing::warn!("Journal read for risk metrics failed: {}", e),
+    }
+    if let Err(e) = storage::write_metrics(&metrics) {
+        tracing::warn!("Metrics write failed: {}", e);
+    }
+}
+
+/// Veto counts from the journal plus a 0/1 gauge per code, so alerts can
+/// tell "out of money" from "on a losing streak".
+fn risk_metrics(journal: &[JournalEntry], active_veto: Option<RiskVeto>) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    for veto in RiskVeto::ALL {
+        let count = journal
+            .iter()
+            .filter(|e| matches!(e, JournalEntry::RiskVeto(v) if v.veto == veto))
+            .count();
+        metrics.push(Metric {
+            name: "weatherbot_risk_vetoes",
+            help: "Cycles stopped by each risk limit",
+            labels: vec![("veto", veto.label().to_string())],
+            value: count as f64,
+        });
+        metrics.push(Metric {
+            name: "weatherbot_risk_veto_active",
+            help: "1 if this risk limit stopped the latest cycle",
+            labels: vec![("veto", veto.label().to_string())],
+            value: if active_veto == Some(veto) { 1.0 } else { 0.0 },
+        });
+    }
+    metrics
+}
+
+/// Model-vs-market gauges for one city: the ensemble the strategy priced
+/// against (after trim and nowcast), the market-implied mean, and the best
+/// net edge across the event's brackets. A city not evaluated this cycle
+/// drops out until it is.
+fn forecast_metrics(
+    city: &CityConfig,
+    weather: Option<&WeatherSnapshot>,
+    implied: Option<&ImpliedDistribution>,
+    decisions: &[TradeDecision],
+) -> Vec<Metric> {
+    let gauge = |name, help, value| Metric { name, help, labels: vec![("city", city.name.clone())], value };
+    let mut metrics = Vec::new();
+    if let Some(members) = weather.map(|w| &w.ensemble_member_highs).filter(|m| !m.is_empty()) {
+        let n = members.len() as f64;
+        let mean = members.iter().sum::<f64>() / n;
+        let std = (members.iter().map(|h| (h - mean).powi(2)).sum::<f64>() / n).sqrt();
+        metrics.push(gauge("weatherbot_ensemble_mean_f", "Mean of the ensemble daily-high members", mean));
+        metrics.push(gauge("weatherbot_ensemble_std_f", "Standard deviation of the ensemble daily-high members", std));
+    }
+    if let Some(d) = implied {
+        metrics.push(gauge("weatherbot_market_implied_mean_f", "Daily-high mean implied by bracket prices", d.mean()));
+    }
+    let best = decisions
+        .iter()
+        .filter_map(|d| d.edge.as_ref().map(|e| e.net_pp))
+        .max_by(|a, b| a.total_cmp(b));
+    if let Some(net_pp) = best {
+        metrics.push(gauge("weatherbot_best_edge_pp", "Best net edge across the event's brackets, in points", net_pp));
+    }
+    metrics
+}
+
+/// Requests per provider this hour/day alongside the soft limits.
+fn quota_metrics(config: &Config) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    for provider in Provider::ALL {
+        let usage = storage::api_usage(provider);
+        for (window, value) in [("hour", usage.hour), ("day", usage.day)] {
+            metrics.push(Metric {
+                name: "weatherbot_api_requests",
+                help: "API requests made in the current UTC window",
+                labels: vec![("provider", provider.label().to_string()), ("window", window.to_string())],
+                value: value as f64,
+            });
+        }
+    }
+    for (provider, window, limit) in [
+        (Provider::Kalshi, "hour", config.quota.kalshi_hourly),
+        (Provider::OpenMeteo, "hour", config.quota.open_meteo_hourly),
+        (Provider::OpenMeteo, "day", config.quota.open_meteo_daily),
+    ] {
+        metrics.push(Metric {
+            name: "weatherbot_api_soft_limit",
+            help: "Request count at which polling degrades",
+            labels: vec![("provider", provider.label().to_string()), ("window", window.to_string())],
+            value: limit as f64,
+        });
+    }
+    metrics
+}
+
+/// A settlement can only close a row placed before it settled.
+fn settles_row(settlement: &Settlement, row: &LedgerRow) -> bool {
+    let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+    match (parse(&settlement.settled_time), parse(&row.timestamp)) {
+        (Some(settled), Some(placed)) => placed <= settled,
+        _ => true,
+    }
+}
+
+/// Bring the bot's recent ledger rows in line with what actually executed.
+/// Once an order is off the book its row takes the filled count and average
+/// price; a row cancelled after a partial fill is reopened for the filled
+/// part, and a pending row whose order never filled is cancelled. Fills on
+/// orders the ledger doesn't know (placed by hand) are only logged.
+async fn reconcile_fills(exchange: &dyn Exchange, on_book: &[&RestingOrder]) -> Result<()> {
+    let ledger = storage::read_ledger()?;
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(RECONCILE_LOOKBACK_HOURS);
+    let placed = |row: &LedgerRow| {
+        chrono::DateTime::parse_from_rfc3339(&row.timestamp)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+    let rows: Vec<&LedgerRow> = ledger
+        .iter()
+        .filter(|r| !r.order_id.is_empty() && (r.is_open() || r.result == "cancelled"))
+        .filter(|r| placed(r).is_some_and(|t| t >= cutoff))
+        .collect();
+    let tickers: std::collections::BTreeSet<&str> = rows.iter().map(|r| r.ticker.as_str()).collect();
+
+    for ticker in tickers {
+        let ticker_rows: Vec<&LedgerRow> = rows.iter().copied().filter(|r| r.ticker == ticker).collect();
+        let Some(since) = ticker_rows.iter().filter_map(|r| placed(r)).min() else { continue };
+        let fills = exchange.fills(ticker, since - chrono::Duration::minutes(1)).await?;
+
+        // order_id → (contracts, cost in cents)
+        let mut by_order: std::collections::HashMap<&str, (u32, u64)> = std::collections::HashMap::new();
+        for f in fills.iter().filter(|f| f.buy) {
+            let (count, cost) = by_order.entry(f.order_id.as_str()).or_default();
+            *count += f.count;
+            *cost += f.count as u64 * f.price_cents as u64;
+        }
+
+        for row in ticker_rows {
+            if on_book.iter().any(|o| o.order_id == row.order_id) {
+                continue;
+            }
+            let (filled, cost) = by_order.get(row.order_id.as_str()).copied().unwrap_or_default();
+            if filled == 0 {
+                if row.is_pending() {
+                    storage::write_durable(QueuedWrite::Cancel {
+                        order_id: row.order_id.clone(),
+                        reason: CancelReason::Unfilled,
+                    })?;
+                    tracing::warn!("Reconcile: {} order {} left the book unfilled — row cancelled", ticker, row.order_id);
+                }
+                continue;
+            }
+            let price = ((cost + filled as u64 / 2) / filled as u64) as u32;
+            if row.result != "cancelled" && filled == row.shares && price == row.price {
+                continue;
+            }
+            storage::write_durable(QueuedWrite::Fill { order_id: row.order_id.clone(), shares: filled, price })?;
+            tracing::warn!(
+                "Reconcile: {} order {} filled {}x @ {}¢ — ledger had {}x @ {}¢ ({})",
+                ticker, row.order_id, filled, price, row.shares, row.price, row.result
+            );
+        }
+
+        let untracked: u32 = fills
+            .iter()
+            .filter(|f| f.buy && !ledger.iter().any(|r| r.order_id == f.order_id))
+            .map(|f| f.count)
+            .sum();
+        if untracked > 0 {
+            tracing::info!("{}: {} contract(s) filled on orders the bot didn't place — left off the ledger", ticker, untracked);
+        }
+    }
+    Ok(())
+}
+
+/// Kalshi settles the whole position at once. If its contract count differs
+/// from the pending shares on the ledger, some orders only partly filled and
+/// the per-row P&L will be off — flag it rather than guess which row.
+fn warn_on_fill_mismatch(ticker: &str, pending: &[LedgerRow], settlements: &[Settlement]) {
+    let Some(s) = settlements.first().filter(|s| s.count > 0) else { return };
+    let ledger_shares: u32 = pending.iter().filter(|r| r.ticker == ticker).map(|r| r.shares).sum();
+    if ledger_shares != s.count {
+        tracing::warn!(
+            "Settlement for {} covers {} contracts but ledger has {} pending — partial fills?",
+            ticker, s.count, ledger_shares
+        );
+    }
+}
+
 fn should_skip_settled_event(event_ticker: &str, timezone: &str) -> bool {
-    let parts: Vec<&str> = event_ticker.split('-').collect();
-    if parts.len() < 2 || parts[1].len() < 6 {
+    let Some(event_date) = event_date(event_ticker) else {
         return false;
-    }
-    let date_part = parts[1];
-    let year_suffix: u32 = match date_part[..2].parse() { Ok(y) => y, Err(_) => return false };
-    let month_str = &date_part[2..5];
-    let day: u32 = match date_part[5..].parse() { Ok(d) => d, Err(_) => return false };
-    let month: u32 = match month_str {
-        "JAN" => 1, "FEB" => 2, "MAR" => 3, "APR" => 4, "MAY" => 5, "JUN" => 6,
-        "JUL" => 7, "AUG" => 8, "SEP" => 9, "OCT" => 10, "NOV" => 11, "DEC" => 12,
-        _ => return false,
     };
-    let event_date = format!("20{:02}-{:02}-{:02}", year_suffix, month, day);
+    let local_now = local_now(timezone);
+    let today = local_now.format("%Y-%m-%d").to_string();
+    event_date < today || (event_date == today && local_now.hour() >= 17)
+}
+
+/// Wall-clock time in a city's timezone, by fixed standard-time offset.
+fn local_now(timezone: &str) -> chrono::DateTime<chrono::FixedOffset> {
     let offset_hours: i32 = match timezone {
         "America/New_York" | "US/Eastern" | "America/Indiana/Indianapolis" => -5,
         "America/Chicago" | "US/Central" => -6,
@@ -370,7 +2355,5 @@ fn should_skip_settled_event(event_ticker: &str, timezone: &str) -> bool {
         _ => 0,
     };
     let offset = chrono::FixedOffset::east_opt(offset_hours * 3600).unwrap();
-    let local_now = chrono::Utc::now().with_timezone(&offset);
-    let today = local_now.format("%Y-%m-%d").to_string();
-    event_date < today || (event_date == today && local_now.hour() >= 17)
+    chrono::Utc::now().with_timezone(&offset)
 }
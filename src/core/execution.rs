@@ -0,0 +1,451 @@
+use crate::core::types::*;
+use crate::ports::exchange::{Exchange, ExchangeError};
+use crate::storage;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// An order that reached the exchange and should get its own ledger row.
+#[derive(Debug, Clone)]
+pub struct PlacedOrder {
+    pub order_id: String,
+    pub shares: u32,
+    pub price_cents: u32,
+    pub fee_cents: Option<Cents>,
+}
+
+/// How a buy decision is worked on the exchange. `limit_cents` is the most
+/// the strategy will pay; `ask_cents` is the current ask on that side.
+#[async_trait]
+pub trait Executor: Send + Sync {
+    async fn execute(
+        &self,
+        exchange: &dyn Exchange,
+        order: &OrderRequest,
+        ask_cents: Option<u32>,
+    ) -> Result<Vec<PlacedOrder>>;
+}
+
+pub fn executor_for(config: &Config) -> Box<dyn Executor> {
+    match config.execution_tactic {
+        ExecutionTactic::Cross => Box::new(CrossSpread),
+        ExecutionTactic::Improve => Box::new(PriceImprovement {
+            wait: std::time::Duration::from_secs(config.improve_wait_secs),
+        }),
+        ExecutionTactic::Post => Box::new(PostInside {
+            wait: std::time::Duration::from_secs(config.post_wait_secs),
+            reprices: config.post_reprices,
+        }),
+    }
+}
+
+/// Post straight at the strategy's limit price.
+pub struct CrossSpread;
+
+#[async_trait]
+impl Executor for CrossSpread {
+    async fn execute(
+        &self,
+        exchange: &dyn Exchange,
+        order: &OrderRequest,
+        _ask_cents: Option<u32>,
+    ) -> Result<Vec<PlacedOrder>> {
+        let result = exchange.place_order(order).await?;
+        journal_attempt(order, 1, "cross", &result.order_id, order.price_cents, order.shares, &result.status);
+        Ok(vec![PlacedOrder {
+            order_id: result.order_id,
+            shares: order.shares,
+            price_cents: order.price_cents,
+            fee_cents: result.fee_cents,
+        }])
+    }
+}
+
+/// Rest one tick below the ask for `wait`, hoping for a passive fill, then
+/// cancel the remainder and cross at the limit price.
+pub struct PriceImprovement {
+    pub wait: std::time::Duration,
+}
+
+#[async_trait]
+impl Executor for PriceImprovement {
+    async fn execute(
+        &self,
+        exchange: &dyn Exchange,
+        order: &OrderRequest,
+        ask_cents: Option<u32>,
+    ) -> Result<Vec<PlacedOrder>> {
+        let passive_price = match ask_cents {
+            Some(ask) if ask > 1 && ask - 1 < order.price_cents => ask - 1,
+            // Already at or below the ask — nothing to improve on
+            _ => return CrossSpread.execute(exchange, order, ask_cents).await,
+        };
+
+        let passive = OrderRequest {
+            ticker: order.ticker.clone(),
+            side: order.side.clone(),
+            shares: order.shares,
+            price_cents: passive_price,
+        };
+        let placed = exchange.place_order(&passive).await?;
+        tracing::info!(
+            "Price improvement: resting {}x @ {}¢ (ask {}¢) for {}s | order {}",
+            passive.shares, passive_price, passive_price + 1, self.wait.as_secs(), placed.order_id
+        );
+
+        tokio::time::sleep(self.wait).await;
+
+        let remaining = match cancel_remainder(exchange, &placed.order_id).await {
+            Ok(remaining) => remaining,
+            Err(e) => {
+                tracing::error!(
+                    "Price improvement: can't tell how much of order {} filled: {:#} — booking {}x for reconcile",
+                    placed.order_id, e, passive.shares
+                );
+                return Ok(vec![book_unknown(exchange, order, 1, "passive", placed.order_id, passive_price, passive.shares).await]);
+            }
+        };
+        let filled = order.shares.saturating_sub(remaining);
+        let outcome = match (filled, remaining) {
+            (_, 0) => "filled",
+            (0, _) => "unfilled",
+            _ => "partial",
+        };
+        journal_attempt(order, 1, "passive", &placed.order_id, passive_price, filled, outcome);
+
+        let mut orders = Vec::new();
+        if filled > 0 {
+            orders.push(PlacedOrder {
+                order_id: placed.order_id,
+                shares: filled,
+                price_cents: passive_price,
+                // Filled while resting — the create response predates it
+                fee_cents: None,
+            });
+        }
+        if remaining == 0 {
+            return Ok(orders);
+        }
+
+        let cross = OrderRequest {
+            ticker: order.ticker.clone(),
+            side: order.side.clone(),
+            shares: remaining,
+            price_cents: order.price_cents,
+        };
+        match exchange.place_order(&cross).await {
+            Ok(result) => {
+                journal_attempt(order, 2, "cross", &result.order_id, cross.price_cents, remaining, &result.status);
+                orders.push(PlacedOrder {
+                    order_id: result.order_id,
+                    shares: remaining,
+                    price_cents: cross.price_cents,
+                    fee_cents: result.fee_cents,
+                });
+                Ok(orders)
+            }
+            // The passive fill is real and must still reach the ledger
+            Err(e) if !orders.is_empty() => {
+                tracing::error!("Crossing {}x after partial passive fill failed: {:#}", remaining, e);
+                Ok(orders)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Maker only: rest inside the spread for `wait`, then cancel what's left
+/// and rest it again at the current book's price, `reprices` times. What
+/// never fills is left cancelled; the taker fee is never paid.
+pub struct PostInside {
+    pub wait: std::time::Duration,
+    pub reprices: u32,
+}
+
+impl PostInside {
+    /// One tick above the best bid, kept below the ask and at or under the
+    /// limit. Joins the bid when the spread is a single tick. None when
+    /// even that would cross.
+    fn price(book: &Orderbook, side: &Side, limit_cents: u32) -> Option<u32> {
+        let bid = book.bids(side).first().map(|(p, _)| *p);
+        let ask = book.asks(side).first().map(|(p, _)| *p);
+        let inside = match (bid, ask) {
+            (Some(bid), Some(ask)) if bid + 1 < ask => bid + 1,
+            (Some(bid), _) => bid,
+            (None, Some(ask)) => ask.saturating_sub(1),
+            (None, None) => limit_cents,
+        };
+        let price = inside.min(limit_cents);
+        (price >= 1 && ask.is_none_or(|a| price < a)).then_some(price)
+    }
+}
+
+#[async_trait]
+impl Executor for PostInside {
+    async fn execute(
+        &self,
+        exchange: &dyn Exchange,
+        order: &OrderRequest,
+        _ask_cents: Option<u32>,
+    ) -> Result<Vec<PlacedOrder>> {
+        let mut orders = Vec::new();
+        let mut remaining = order.shares;
+        for attempt in 1..=self.reprices + 1 {
+            let book = match exchange.orderbook(&order.ticker).await {
+                Ok(book) => book,
+                Err(e) if orders.is_empty() => return Err(e),
+                // Keep what already filled
+                Err(e) => {
+                    tracing::warn!("Post {}: orderbook fetch failed: {:#} — keeping {} filled", order.ticker, e, order.shares - remaining);
+                    break;
+                }
+            };
+            let Some(price) = Self::price(&book, &order.side, order.price_cents) else {
+                tracing::info!("Post {}: no room inside the spread under {}¢ — stopping", order.ticker, order.price_cents);
+                break;
+            };
+            let resting = OrderRequest {
+                ticker: order.ticker.clone(),
+                side: order.side.clone(),
+                shares: remaining,
+                price_cents: price,
+            };
+            let placed = match exchange.place_order(&resting).await {
+                Ok(placed) => placed,
+                Err(e) if orders.is_empty() => return Err(e),
+                Err(e) => {
+                    tracing::error!("Repricing {}x on {} failed: {:#}", remaining, order.ticker, e);
+                    break;
+                }
+            };
+            tracing::info!(
+                "Post {}/{}: resting {}x @ {}¢ for {}s | order {}",
+                attempt, self.reprices + 1, remaining, price, self.wait.as_secs(), placed.order_id
+            );
+
+            tokio::time::sleep(self.wait).await;
+
+            let left = match cancel_remainder(exchange, &placed.order_id).await {
+                Ok(left) => left,
+                // Earlier fills and this order must still reach the ledger
+                Err(e) => {
+                    tracing::error!(
+                        "Post {}: can't tell how much of order {} filled: {:#} — booking {}x for reconcile",
+                        order.ticker, placed.order_id, e, remaining
+                    );
+                    orders.push(book_unknown(exchange, order, attempt, "post", placed.order_id, price, remaining).await);
+                    remaining = 0;
+                    break;
+                }
+            };
+            let filled = remaining.saturating_sub(left);
+            let outcome = match (filled, left) {
+                (_, 0) => "filled",
+                (0, _) => "unfilled",
+                _ => "partial",
+            };
+            journal_attempt(order, attempt, "post", &placed.order_id, price, filled, outcome);
+            if filled > 0 {
+                orders.push(PlacedOrder {
+                    order_id: placed.order_id,
+                    shares: filled,
+                    price_cents: price,
+                    fee_cents: None,
+                });
+            }
+            remaining = left;
+            if remaining == 0 {
+                break;
+            }
+        }
+        if remaining > 0 {
+            tracing::info!("Post {}: {}x of {}x unfilled — cancelled", order.ticker, remaining, order.shares);
+        }
+        Ok(orders)
+    }
+}
+
+/// Cancel what's left of `order_id` after its wait: the contracts that
+/// were still resting, zero when it filled. Err when the exchange can't say
+/// or the cancel fails — the order may still be live.
+async fn cancel_remainder(exchange: &dyn Exchange, order_id: &str) -> Result<u32> {
+    let left = exchange
+        .resting_orders()
+        .await?
+        .into_iter()
+        .find(|o| o.order_id == order_id)
+        .map(|o| o.remaining_count)
+        .unwrap_or(0);
+    if left == 0 {
+        return Ok(0);
+    }
+    match exchange.cancel_order(order_id).await {
+        Ok(()) => Ok(left),
+        // Gone between the check and the cancel — it filled
+        Err(e) if matches!(e.downcast_ref::<ExchangeError>(), Some(ExchangeError::NotFound(_))) => Ok(0),
+        Err(e) => Err(e),
+    }
+}
+
+/// `order_id` reached the exchange but how much of it filled couldn't be
+/// read back. Cancel it, best-effort, and book all `shares` as "unknown":
+/// reconcile brings the ledger row in line with the exchange's fills next
+/// cycle, or cancels it if nothing filled.
+async fn book_unknown(
+    exchange: &dyn Exchange,
+    order: &OrderRequest,
+    attempt: u32,
+    stage: &str,
+    order_id: String,
+    price_cents: u32,
+    shares: u32,
+) -> PlacedOrder {
+    if let Err(e) = exchange.cancel_order(&order_id).await {
+        if !matches!(e.downcast_ref::<ExchangeError>(), Some(ExchangeError::NotFound(_))) {
+            tracing::warn!("Cancelling order {} on {} failed: {:#} — it may still be resting", order_id, order.ticker, e);
+        }
+    }
+    journal_attempt(order, attempt, stage, &order_id, price_cents, shares, "unknown");
+    PlacedOrder {
+        order_id,
+        shares,
+        price_cents,
+        fee_cents: None,
+    }
+}
+
+fn journal_attempt(order: &OrderRequest, attempt: u32, stage: &str, order_id: &str, price_cents: u32, shares: u32, outcome: &str) {
+    let record = ExecutionRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        ticker: order.ticker.clone(),
+        order_id: order_id.to_string(),
+        attempt,
+        stage: stage.to_string(),
+        side: order.side.clone(),
+        price_cents,
+        shares,
+        outcome: outcome.to_string(),
+    };
+    // Tests work executors against stub exchanges; keep them out of the journal
+    if cfg!(test) {
+        return;
+    }
+    if let Err(e) = storage::append_journal(&JournalEntry::Execution(record)) {
+        tracing::warn!("Execution journal write failed for {}: {}", order.ticker, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// An exchange with one bracket quoted 40¢ bid / 45¢ ask on YES. Each
+    /// order placed rests with `left[n]` contracts unfilled (none past the
+    /// end), and `resting_orders` fails after `resting_ok` calls.
+    #[derive(Default)]
+    struct Stub {
+        left: Vec<u32>,
+        resting_ok: usize,
+        placed: Mutex<Vec<(String, u32)>>,
+        resting_calls: Mutex<usize>,
+        cancelled: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Exchange for Stub {
+        async fn active_markets_for_series(&self, _series_ticker: &str) -> Result<Vec<MarketState>> {
+            unimplemented!()
+        }
+        async fn orderbook(&self, _ticker: &str) -> Result<Orderbook> {
+            Ok(Orderbook { yes: vec![(40, 10)], no: vec![(55, 10)] })
+        }
+        async fn resting_orders(&self) -> Result<Vec<RestingOrder>> {
+            let mut calls = self.resting_calls.lock().unwrap();
+            *calls += 1;
+            if *calls > self.resting_ok {
+                return Err(ExchangeError::Transport("timed out".into()).into());
+            }
+            let placed = self.placed.lock().unwrap();
+            let n = placed.len() - 1;
+            let (order_id, _) = &placed[n];
+            let left = self.left.get(n).copied().unwrap_or(0);
+            Ok((left > 0)
+                .then(|| RestingOrder {
+                    order_id: order_id.clone(),
+                    client_order_id: format!("{}{}", BOT_ORDER_PREFIX, order_id),
+                    ticker: "KXHIGHNY-26FEB12-T39".into(),
+                    side: Side::Yes,
+                    price_cents: 41,
+                    remaining_count: left,
+                })
+                .into_iter()
+                .collect())
+        }
+        async fn cancel_order(&self, order_id: &str) -> Result<()> {
+            self.cancelled.lock().unwrap().push(order_id.to_string());
+            Ok(())
+        }
+        async fn place_order(&self, order: &OrderRequest) -> Result<OrderResult> {
+            let mut placed = self.placed.lock().unwrap();
+            let order_id = format!("o{}", placed.len() + 1);
+            placed.push((order_id.clone(), order.shares));
+            Ok(OrderResult { order_id, status: "resting".into(), fee_cents: None })
+        }
+        async fn amend_order(&self, _order_id: &str, _order: &OrderRequest) -> Result<OrderResult> {
+            unimplemented!()
+        }
+        async fn sell_position(&self, _order: &OrderRequest) -> Result<OrderResult> {
+            unimplemented!()
+        }
+        async fn positions(&self) -> Result<Vec<Position>> {
+            unimplemented!()
+        }
+        async fn settlements(&self, _ticker: &str) -> Result<Vec<Settlement>> {
+            unimplemented!()
+        }
+        async fn fills(&self, _ticker: &str, _since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Fill>> {
+            unimplemented!()
+        }
+        async fn balance(&self) -> Result<Cents> {
+            unimplemented!()
+        }
+        async fn market_result(&self, _ticker: &str) -> Result<Option<MarketResult>> {
+            unimplemented!()
+        }
+    }
+
+    fn buy(shares: u32) -> OrderRequest {
+        OrderRequest { ticker: "KXHIGHNY-26FEB12-T39".into(), side: Side::Yes, shares, price_cents: 50 }
+    }
+
+    fn booked(orders: &[PlacedOrder]) -> Vec<(&str, u32, u32)> {
+        orders.iter().map(|o| (o.order_id.as_str(), o.shares, o.price_cents)).collect()
+    }
+
+    #[tokio::test]
+    async fn improvement_books_the_passive_order_when_its_fill_is_unknown() {
+        let exchange = Stub::default();
+        let executor = PriceImprovement { wait: std::time::Duration::ZERO };
+        let orders = executor.execute(&exchange, &buy(5), Some(45)).await.unwrap();
+        assert_eq!(booked(&orders), [("o1", 5, 44)]);
+        assert_eq!(*exchange.cancelled.lock().unwrap(), ["o1"]);
+    }
+
+    #[tokio::test]
+    async fn improvement_crosses_what_the_passive_order_left() {
+        let exchange = Stub { left: vec![3], resting_ok: 1, ..Default::default() };
+        let executor = PriceImprovement { wait: std::time::Duration::ZERO };
+        let orders = executor.execute(&exchange, &buy(5), Some(45)).await.unwrap();
+        assert_eq!(booked(&orders), [("o1", 2, 44), ("o2", 3, 50)]);
+    }
+
+    #[tokio::test]
+    async fn post_keeps_earlier_fills_when_a_reprice_goes_unread() {
+        let exchange = Stub { left: vec![3], resting_ok: 1, ..Default::default() };
+        let executor = PostInside { wait: std::time::Duration::ZERO, reprices: 2 };
+        let orders = executor.execute(&exchange, &buy(5), Some(45)).await.unwrap();
+        assert_eq!(booked(&orders), [("o1", 2, 41), ("o2", 3, 41)]);
+        assert_eq!(*exchange.cancelled.lock().unwrap(), ["o1", "o2"]);
+        assert_eq!(exchange.placed.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,156 @@
+//! Selling before settlement. Pure: the engine fetches positions and books
+//! and places the sells.
+
+use crate::core::risk;
+use crate::core::types::{Cents, LedgerRow, Orderbook, Position, Side};
+
+/// Why a position is being sold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// Held longer than MAX_HOLDING_MINUTES
+    MaxHolding,
+    /// The operator asked to close everything
+    Operator,
+    /// The bid caught up with the model (TAKE_PROFIT_PP)
+    TakeProfit,
+    /// The model turned against the entry (STOP_LOSS_PP)
+    StopLoss,
+}
+
+impl ExitReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExitReason::MaxHolding => "max_holding",
+            ExitReason::Operator => "operator",
+            ExitReason::TakeProfit => "take_profit",
+            ExitReason::StopLoss => "stop_loss",
+        }
+    }
+}
+
+/// A held position to sell and the open ledger rows it closes.
+#[derive(Debug, Clone)]
+pub struct Exit {
+    pub ticker: String,
+    pub side: Side,
+    pub shares: u32,
+    /// Oldest first
+    pub rows: Vec<LedgerRow>,
+    pub held_minutes: i64,
+    pub reason: ExitReason,
+}
+
+/// Positions whose oldest open ledger row is at least `max_holding_minutes`
+/// old. A position with no open row (placed by hand) has no entry time and
+/// is never forced out.
+pub fn overdue(
+    positions: &[Position],
+    ledger: &[LedgerRow],
+    max_holding_minutes: u64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<Exit> {
+    if max_holding_minutes == 0 {
+        return Vec::new();
+    }
+    positions
+        .iter()
+        .filter_map(|p| {
+            let exit = exit_for(p, ledger, now, ExitReason::MaxHolding);
+            (!exit.rows.is_empty() && exit.held_minutes >= max_holding_minutes as i64).then_some(exit)
+        })
+        .collect()
+}
+
+/// Every position, for closing out on the operator's word — including ones
+/// the ledger has no row for.
+pub fn all(positions: &[Position], ledger: &[LedgerRow], now: chrono::DateTime<chrono::Utc>) -> Vec<Exit> {
+    positions.iter().map(|p| exit_for(p, ledger, now, ExitReason::Operator)).collect()
+}
+
+/// Positions the model says to get out of. `model_yes` is the model's YES
+/// probability per ticker and `best_bid` what the position's side would
+/// sell for now. Stop loss: the model values the side `stop_loss_pp` or more
+/// below the average entry price. Take profit: the bid is within
+/// `take_profit_pp` of the model's value and selling there beats the entry
+/// after the fee. Positions without open rows, or a fresh model view, are
+/// left alone; 0 turns either rule off.
+pub fn against_model(
+    positions: &[Position],
+    ledger: &[LedgerRow],
+    model_yes: impl Fn(&str) -> Option<f64>,
+    best_bid: impl Fn(&str, &Side) -> Option<u32>,
+    take_profit_pp: f64,
+    stop_loss_pp: f64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<Exit> {
+    positions
+        .iter()
+        .filter_map(|p| {
+            let yes = model_yes(&p.ticker)?;
+            let value = match p.side {
+                Side::Yes => yes,
+                Side::No => 1.0 - yes,
+            } * 100.0;
+            let mut exit = exit_for(p, ledger, now, ExitReason::StopLoss);
+            let shares: u32 = exit.rows.iter().map(|r| r.shares).sum();
+            if shares == 0 {
+                return None;
+            }
+            let entry = exit.rows.iter().map(|r| r.price as f64 * r.shares as f64).sum::<f64>() / shares as f64;
+            if stop_loss_pp > 0.0 && value <= entry - stop_loss_pp {
+                return Some(exit);
+            }
+            let bid = best_bid(&p.ticker, &p.side)?;
+            let gain: Cents = exit.rows.iter().map(|r| exit_pnl(r, bid)).sum();
+            if take_profit_pp > 0.0 && bid as f64 >= value - take_profit_pp && gain > Cents::ZERO {
+                exit.reason = ExitReason::TakeProfit;
+                return Some(exit);
+            }
+            None
+        })
+        .collect()
+}
+
+/// `p` with its open rows; held time is 0 without any.
+fn exit_for(p: &Position, ledger: &[LedgerRow], now: chrono::DateTime<chrono::Utc>, reason: ExitReason) -> Exit {
+    let mut rows: Vec<LedgerRow> = ledger
+        .iter()
+        .filter(|r| r.is_open() && r.ticker == p.ticker && r.side() == Some(p.side.clone()))
+        .cloned()
+        .collect();
+    rows.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let held_minutes = rows
+        .first()
+        .and_then(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).ok())
+        .map_or(0, |entered| (now - entered.with_timezone(&chrono::Utc)).num_minutes());
+    Exit { ticker: p.ticker.clone(), side: p.side.clone(), shares: p.count, rows, held_minutes, reason }
+}
+
+/// Sell at the best bid, no more than it shows. None with no bid.
+pub fn exit_price(book: &Orderbook, side: &Side, shares: u32) -> Option<(u32, u32)> {
+    let (price, depth) = book.bids(side).into_iter().next()?;
+    Some((price, shares.min(depth)))
+}
+
+/// Rows the sold contracts close, oldest first. A row only partly covered
+/// stays open for the next exit.
+pub fn closed_rows(rows: &[LedgerRow], sold: u32) -> Vec<&LedgerRow> {
+    let mut left = sold;
+    rows.iter()
+        .take_while(|r| {
+            let fits = r.shares <= left;
+            if fits {
+                left -= r.shares;
+            }
+            fits
+        })
+        .collect()
+}
+
+/// What selling a row's contracts at `exit_cents` made or lost, after the
+/// estimated taker fee on the sale.
+pub fn exit_pnl(row: &LedgerRow, exit_cents: u32) -> Cents {
+    Cents::for_contracts(row.shares, exit_cents)
+        - Cents::for_contracts(row.shares, row.price)
+        - risk::taker_fee_cents(row.shares, exit_cents)
+}
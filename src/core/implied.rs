@@ -0,0 +1,137 @@
+use crate::core::types::*;
+
+/// One bracket's share of the market-implied temperature distribution.
+/// Open-ended tails use ±infinity for the missing bound.
+#[derive(Debug, Clone)]
+pub struct ImpliedBin {
+    pub ticker: String,
+    pub lower: f64,
+    pub upper: f64,
+    pub probability: f64,
+}
+
+/// Market-implied distribution of the daily high, built from every bracket
+/// of one event. Probabilities are vig-free and sum to 1.
+#[derive(Debug, Clone)]
+pub struct ImpliedDistribution {
+    pub bins: Vec<ImpliedBin>,
+    /// Sum of raw YES asks across the ladder (1.0 = no vig).
+    pub overround: f64,
+}
+
+impl ImpliedDistribution {
+    /// Build from an event's brackets. Overlapping brackets (e.g. an "above"
+    /// contract spanning several "between" buckets) are dropped in favour of
+    /// the narrower ones so each temperature is counted once; the surviving
+    /// mid prices are then normalized to remove the vig.
+    pub fn from_brackets(brackets: &[MarketState]) -> Option<Self> {
+        let mut candidates: Vec<(ImpliedBin, Option<u32>)> = brackets
+            .iter()
+            .filter_map(|b| {
+                let (lower, upper) = match MarketType::from_market(b)? {
+                    MarketType::Below(t) => (f64::NEG_INFINITY, t),
+                    MarketType::Above(t) => (t, f64::INFINITY),
+                    MarketType::Between(lo, hi) => (lo, hi),
+                };
+                Some((
+                    ImpliedBin {
+                        ticker: b.ticker.clone(),
+                        lower,
+                        upper,
+                        probability: yes_mid(b)?,
+                    },
+                    b.yes_ask,
+                ))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            let wa = a.0.upper - a.0.lower;
+            let wb = b.0.upper - b.0.lower;
+            wa.partial_cmp(&wb).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut bins: Vec<ImpliedBin> = Vec::new();
+        let mut asks: Vec<Option<u32>> = Vec::new();
+        for (bin, ask) in candidates {
+            let overlaps = bins
+                .iter()
+                .any(|k| bin.lower < k.upper && k.lower < bin.upper);
+            if overlaps {
+                tracing::debug!("Implied: dropping overlapping bracket {}", bin.ticker);
+                continue;
+            }
+            bins.push(bin);
+            asks.push(ask);
+        }
+
+        let total: f64 = bins.iter().map(|b| b.probability).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        for b in &mut bins {
+            b.probability /= total;
+        }
+        bins.sort_by(|a, b| a.lower.partial_cmp(&b.lower).unwrap_or(std::cmp::Ordering::Equal));
+
+        let overround = asks.iter().flatten().map(|a| *a as f64 / 100.0).sum();
+
+        Some(Self { bins, overround })
+    }
+
+    /// P(high < x). Mass inside a finite bin is spread uniformly; tail mass
+    /// only counts once x is past the tail's finite edge.
+    pub fn cdf(&self, x: f64) -> f64 {
+        self.bins
+            .iter()
+            .map(|b| {
+                if x >= b.upper {
+                    b.probability
+                } else if x <= b.lower || b.lower.is_infinite() || b.upper.is_infinite() {
+                    0.0
+                } else {
+                    b.probability * (x - b.lower) / (b.upper - b.lower)
+                }
+            })
+            .sum()
+    }
+
+    /// Vig-free probability the market assigns to a contract's YES outcome.
+    pub fn probability(&self, market_type: &MarketType) -> f64 {
+        let p = match market_type {
+            MarketType::Above(t) => 1.0 - self.cdf(*t),
+            MarketType::Below(t) => self.cdf(*t),
+            MarketType::Between(lo, hi) => self.cdf(*hi) - self.cdf(*lo),
+        };
+        p.clamp(0.0, 1.0)
+    }
+
+    /// Mean of the implied distribution, using bin midpoints and placing tail
+    /// mass 1°F beyond the finite edge.
+    pub fn mean(&self) -> f64 {
+        self.bins
+            .iter()
+            .map(|b| {
+                let mid = if b.lower.is_infinite() {
+                    b.upper - 1.0
+                } else if b.upper.is_infinite() {
+                    b.lower + 1.0
+                } else {
+                    (b.lower + b.upper) / 2.0
+                };
+                mid * b.probability
+            })
+            .sum()
+    }
+}
+
+/// YES mid price as a probability, falling back to whichever side is quoted.
+fn yes_mid(m: &MarketState) -> Option<f64> {
+    let cents = match (m.yes_bid, m.yes_ask) {
+        (Some(b), Some(a)) => (b + a) as f64 / 2.0,
+        (None, Some(a)) => a as f64,
+        (Some(b), None) => b as f64,
+        (None, None) => return None,
+    };
+    Some(cents / 100.0)
+}
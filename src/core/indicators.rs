@@ -1,10 +1,25 @@
 use crate::core::types::*;
 
+/// Absolute °F gap between the NWS forecast high and `reference`.
+pub fn nws_diff(weather: &WeatherSnapshot, reference: f64) -> Option<f64> {
+    weather.nws_forecast_high.map(|nws_high| (nws_high - reference).abs())
+}
+
+/// NWS vs the ensemble mean, falling back to the Open-Meteo deterministic
+/// high when there is no ensemble.
+pub fn source_disagreement(weather: &WeatherSnapshot) -> Option<f64> {
+    let reference = weather
+        .ensemble
+        .as_ref()
+        .map(|e| e.mean_high)
+        .unwrap_or(weather.open_meteo_forecast_high);
+    nws_diff(weather, reference)
+}
+
 /// Compares NWS forecast high vs Open-Meteo forecast high.
 pub fn forecast_agreement(weather: &WeatherSnapshot) -> String {
-    match weather.nws_forecast_high {
-        Some(nws_high) => {
-            let diff = (nws_high - weather.open_meteo_forecast_high).abs();
+    match (weather.nws_forecast_high, nws_diff(weather, weather.open_meteo_forecast_high)) {
+        (Some(nws_high), Some(diff)) => {
             if diff <= 1.0 {
                 format!(
                     "Strong agreement: NWS {:.0}°F vs Open-Meteo {:.0}°F (within 1°F)",
@@ -22,7 +37,7 @@ pub fn forecast_agreement(weather: &WeatherSnapshot) -> String {
                 )
             }
         }
-        None => format!(
+        _ => format!(
             "NWS unavailable. Open-Meteo forecast high: {:.0}°F",
             weather.open_meteo_forecast_high
         ),
@@ -44,3 +59,18 @@ pub fn ensemble_summary(ensemble: &EnsembleForecast) -> String {
         ensemble.p90,
     )
 }
+
+/// Point forecasts of the day's value that don't come from the Open-Meteo
+/// ensemble: NWS, the NBM median and HRRR, whichever the snapshot has.
+pub fn independent_forecasts(weather: &WeatherSnapshot) -> Vec<(&'static str, f64)> {
+    let nbm_median = weather.nbm.as_ref().and_then(|nbm| {
+        nbm.quantiles
+            .iter()
+            .min_by(|a, b| (a.pct - 50.0).abs().total_cmp(&(b.pct - 50.0).abs()))
+            .map(|q| q.value)
+    });
+    [("NWS", weather.nws_forecast_high), ("NBM", nbm_median), ("HRRR", weather.hrrr_high_f)]
+        .into_iter()
+        .filter_map(|(name, v)| Some((name, v?)))
+        .collect()
+}
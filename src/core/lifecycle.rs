@@ -0,0 +1,54 @@
+use crate::core::types::{OrderRecord, OrderState, RestingOrder, StateChange};
+
+impl OrderRecord {
+    /// A new order at `Intent`, before anything is sent.
+    pub fn intent(order_id: &str, ticker: &str, shares: u32) -> Self {
+        Self {
+            order_id: order_id.to_string(),
+            ticker: ticker.to_string(),
+            shares,
+            filled: 0,
+            state: OrderState::Intent,
+            history: vec![StateChange {
+                state: OrderState::Intent,
+                at: chrono::Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+
+    /// Move to `next` if the state machine allows it. Illegal transitions
+    /// are logged and leave the record untouched.
+    pub fn advance(&mut self, next: OrderState) -> bool {
+        if !self.state.can_become(next) {
+            tracing::warn!("Order {}: ignoring {} → {} transition", self.order_id, self.state, next);
+            return false;
+        }
+        match next {
+            OrderState::Filled => self.filled = self.shares,
+            OrderState::Rejected => self.filled = 0,
+            _ => {}
+        }
+        self.state = next;
+        self.history.push(StateChange {
+            state: next,
+            at: chrono::Utc::now().to_rfc3339(),
+        });
+        true
+    }
+}
+
+/// Where exchange polling says a resting order now is. An order missing from
+/// the book that the bot didn't cancel has filled — cancels are recorded
+/// when the bot makes them, before the next poll.
+pub fn poll(record: &OrderRecord, on_book: Option<&RestingOrder>) -> Option<(OrderState, u32)> {
+    if !record.state.is_resting() {
+        return None;
+    }
+    match on_book {
+        None => Some((OrderState::Filled, record.shares)),
+        Some(o) => {
+            let filled = record.shares.saturating_sub(o.remaining_count);
+            (filled > record.filled).then_some((OrderState::PartiallyFilled, filled))
+        }
+    }
+}
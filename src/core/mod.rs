@@ -1,6 +1,22 @@
+pub mod activity;
+pub mod backtest;
+pub mod canary;
+pub mod coverage;
+pub mod dataset;
+pub mod distribution;
 pub mod engine;
+pub mod execution;
+pub mod exits;
+pub mod implied;
 pub mod indicators;
+pub mod lifecycle;
+pub mod nowcast;
+pub mod plan;
+pub mod prefetch;
+pub mod requote;
 pub mod risk;
 pub mod rules_brain;
+pub mod schedule;
+pub mod seasons;
 pub mod stats;
 pub mod types;
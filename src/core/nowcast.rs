@@ -0,0 +1,84 @@
+use crate::core::types::{StrategyParams, WeatherSnapshot};
+
+/// Local hours over which the nowcast weight ramps from 0 up to
+/// `nowcast_weight`: little is known about the day at 9am, most of it
+/// by mid-afternoon when highs usually land.
+const RAMP_START_HOUR: f64 = 9.0;
+const RAMP_END_HOUR: f64 = 15.0;
+
+/// Largest shift the nowcast may apply to the remaining-day high, °F.
+const MAX_ADJUSTMENT_F: f64 = 5.0;
+
+/// How today's readings are running against the hourly forecast, and what
+/// that implies for the rest of the day.
+#[derive(Debug, Clone, Copy)]
+pub struct Nowcast {
+    /// Current reading minus the forecast for this hour
+    pub bias_f: f64,
+    /// Observed warming over the last `nowcast_hours` minus forecast warming
+    pub rate_excess_f_per_h: f64,
+    /// Hours from now until the forecast's remaining peak
+    pub hours_to_peak: f64,
+    /// Bias carried forward plus the rate excess extrapolated to the peak
+    pub adjustment_f: f64,
+    /// Share of `adjustment_f` blended in at this time of day
+    pub weight: f64,
+}
+
+impl Nowcast {
+    pub fn shift_f(&self) -> f64 {
+        self.adjustment_f * self.weight
+    }
+}
+
+/// Extrapolate the recent observed trend to the remaining-day high. None
+/// when the nowcast is off, the snapshot has no local time, or there's
+/// not `nowcast_hours` of history today yet.
+pub fn nowcast(weather: &WeatherSnapshot, params: &StrategyParams) -> Option<Nowcast> {
+    if params.nowcast_weight <= 0.0 || params.nowcast_hours == 0 {
+        return None;
+    }
+    let local_time = weather.local_time.as_deref()?;
+    let hour_prefix = local_time.get(..13)?;
+    let hour: f64 = local_time.get(11..13)?.parse().ok()?;
+    let hours = &weather.hourly_forecasts;
+    let now = hours.iter().position(|h| h.time.starts_with(hour_prefix))?;
+    let start = now.checked_sub(params.nowcast_hours)?;
+
+    let k = params.nowcast_hours as f64;
+    let forecast_now = hours[now].temperature_f;
+    let observed_rate = (weather.current_temp_f - hours[start].temperature_f) / k;
+    let forecast_rate = (forecast_now - hours[start].temperature_f) / k;
+    let rate_excess = observed_rate - forecast_rate;
+
+    let peak = hours[now + 1..]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.temperature_f.total_cmp(&b.1.temperature_f))
+        .map_or(0.0, |(i, _)| (i + 1) as f64);
+
+    let bias = weather.current_temp_f - forecast_now;
+    let adjustment = (bias + rate_excess * peak).clamp(-MAX_ADJUSTMENT_F, MAX_ADJUSTMENT_F);
+    let progress = ((hour - RAMP_START_HOUR) / (RAMP_END_HOUR - RAMP_START_HOUR)).clamp(0.0, 1.0);
+
+    Some(Nowcast {
+        bias_f: bias,
+        rate_excess_f_per_h: rate_excess,
+        hours_to_peak: peak,
+        adjustment_f: adjustment,
+        weight: params.nowcast_weight.min(1.0) * progress,
+    })
+}
+
+/// Shift the remaining-day high and every ensemble member by the weighted
+/// nowcast. Members never drop below the high already observed.
+pub fn blend(weather: &mut WeatherSnapshot, nowcast: &Nowcast) {
+    let shift = nowcast.shift_f();
+    if let Some(h) = weather.remaining_forecast_high_f.as_mut() {
+        *h += shift;
+    }
+    let floor = weather.observed_high_so_far.unwrap_or(f64::NEG_INFINITY);
+    for m in weather.ensemble_member_highs.iter_mut() {
+        *m = (*m + shift).max(floor);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::core::{distribution, rules_brain, stats};
+use crate::core::types::*;
+use crate::ports::exchange::Exchange;
+use crate::ports::notifier::Notifier;
+use crate::ports::weather_feed::WeatherFeed;
+use anyhow::Result;
+
+/// Brackets below this fair value are left out of the plan.
+const LIKELY_BRACKET_MIN: f64 = 0.02;
+
+/// Morning pre-open routine: pull the overnight runs, price the likely
+/// brackets for each city and send a plan of the day. Never trades.
+pub async fn run_plan(
+    exchange: &dyn Exchange,
+    weather_feed: &dyn WeatherFeed,
+    notifier: &dyn Notifier,
+    config: &Config,
+) -> Result<()> {
+    let date = chrono::Utc::now().format("%Y-%m-%d");
+    for city in &config.cities {
+        let report = match city_plan(exchange, weather_feed, city, &config.strategy).await {
+            Ok(report) => report,
+            Err(e) => {
+                tracing::error!("[{}] Plan failed: {:#} — continuing", city.name, e);
+                continue;
+            }
+        };
+        let subject = format!("Plan of the day — {} ({})", city.name, date);
+        if let Err(e) = notifier.notify(&subject, &report).await {
+            tracing::warn!("[{}] Plan notification failed: {:#}", city.name, e);
+        }
+    }
+
+    match crate::storage::read_ledger() {
+        Ok(ledger) => {
+            let subject = format!("Performance ({})", date);
+            if let Err(e) = notifier.notify(&subject, &performance(&ledger)).await {
+                tracing::warn!("Performance notification failed: {:#}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Performance report skipped, ledger unreadable: {:#}", e),
+    }
+    Ok(())
+}
+
+/// Equity-curve figures for the morning report: risk-adjusted P&L, streaks
+/// and drawdown, and the last few months.
+fn performance(ledger: &[LedgerRow]) -> String {
+    let a = stats::analytics(ledger);
+    let Some(last) = a.daily.last() else {
+        return "No settled trades yet.".into();
+    };
+    let ratio = |r: Option<f64>| r.map(|r| format!("{:.2}", r)).unwrap_or_else(|| "n/a".into());
+    let mut lines = vec![
+        format!("Equity {} (last trading day {}: {})", last.equity_cents, last.date, last.pnl_cents),
+        format!(
+            "Sharpe-like {} ({}d) / {} (all)",
+            ratio(a.rolling_sharpe), stats::SHARPE_WINDOW_DAYS, ratio(a.sharpe)
+        ),
+        format!(
+            "Max drawdown {} | longest {}d, current {}d | longest losing streak {}",
+            a.max_drawdown_cents, a.longest_drawdown_days, a.current_drawdown_days, a.longest_losing_streak
+        ),
+    ];
+    for m in a.monthly.iter().rev().take(3) {
+        lines.push(format!("  {}: {} over {} trades ({}W/{}L)", m.month, m.pnl_cents, m.trades, m.wins, m.losses));
+    }
+    lines.join("\n")
+}
+
+async fn city_plan(
+    exchange: &dyn Exchange,
+    weather_feed: &dyn WeatherFeed,
+    city: &CityConfig,
+    params: &StrategyParams,
+) -> Result<String> {
+    let Some(weather) = weather_feed.forecast(city).await? else {
+        return Ok("No forecast available.".into());
+    };
+
+    let members = distribution::trim_members(&weather.ensemble_member_highs, params.ensemble_trim, params.ensemble_trim_mode);
+
+    let mut lines = Vec::new();
+    let what = match weather.measure {
+        Measure::HighTemp => "high",
+        Measure::Snowfall => "snowfall",
+    };
+    let unit = weather.measure.unit();
+    match &weather.ensemble {
+        Some(e) => lines.push(format!(
+            "Ensemble {} {:.1}{} (p10 {:.1} / p90 {:.1}, σ {:.1}) from {} members",
+            what, e.mean_high, unit, e.p10, e.p90, e.std_dev, members.len()
+        )),
+        None => lines.push(format!("Open-Meteo {} {:.1}{} (no ensemble)", what, weather.open_meteo_forecast_high, unit)),
+    }
+    if let Some(nws) = weather.nws_forecast_high {
+        lines.push(format!("NWS high {:.0}°F — {}", nws, weather.nws_short_forecast.as_deref().unwrap_or("")));
+    }
+    if let Some(run) = &weather.ensemble_run {
+        lines.push(format!("Run {} | confidence {:?}", run, weather.confidence));
+    }
+
+    // Price the listed brackets of the nearest event; before they're listed,
+    // fall back to the forecast's own buckets.
+    let markets = exchange.active_markets_for_series(&city.series_ticker).await?;
+    let event = markets.first().map(|m| m.event_ticker.clone());
+    let brackets: Vec<&MarketState> = markets.iter().filter(|m| Some(&m.event_ticker) == event.as_ref()).collect();
+
+    if brackets.is_empty() {
+        lines.push("No brackets listed yet — forecast buckets:".into());
+        for b in weather.bucket_probabilities.iter().filter(|b| b.probability >= LIKELY_BRACKET_MIN) {
+            lines.push(format!("  {:<10} fair {:>3.0}%", b.label, b.probability * 100.0));
+        }
+        return Ok(lines.join("\n"));
+    }
+
+    lines.push(format!("Event {}:", brackets[0].event_ticker));
+    for market in brackets {
+        let Some(mt) = MarketType::from_market(market) else { continue };
+        let fair = if !members.is_empty() {
+            rules_brain::compute_ensemble_yes_from_members(&members, &mt)
+        } else {
+            rules_brain::compute_ensemble_yes_from_buckets(&weather.bucket_probabilities, &mt)
+        };
+        if fair < LIKELY_BRACKET_MIN {
+            continue;
+        }
+        let fair_cents = (fair * 100.0).round() as u32;
+        let quote = match market.yes_ask {
+            Some(ask) => format!("ask {:>2}¢ edge {:+}¢", ask, fair_cents as i64 - ask as i64),
+            None => "no ask".into(),
+        };
+        lines.push(format!("  {:<10} fair {:>2}¢  {}", mt.label(weather.measure), fair_cents, quote));
+    }
+    Ok(lines.join("\n"))
+}
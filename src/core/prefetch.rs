@@ -0,0 +1,81 @@
+use crate::core::schedule;
+use crate::core::types::*;
+use crate::ports::weather_feed::WeatherFeed;
+use crate::quota::Provider;
+use crate::safety;
+use crate::storage;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Starts every city's forecast fetch at startup so weather is ready by the
+/// time the cycle has cancelled, settled and checked risk. Each prefetched
+/// snapshot is handed out once; later calls go straight to the inner feed.
+pub struct PrefetchFeed<F> {
+    inner: Arc<F>,
+    pending: Mutex<HashMap<String, JoinHandle<Result<Option<WeatherSnapshot>>>>>,
+}
+
+impl<F: WeatherFeed + 'static> PrefetchFeed<F> {
+    /// Spawn fetches for the cities the cycle is likely to refresh: all of
+    /// them, minus cities with no new model run (MODEL_SCHEDULE). None at
+    /// all once Open-Meteo is past a soft limit, so the engine's own
+    /// back-off decides what gets fetched, or under the killswitch, which
+    /// ends the cycle before any weather is needed.
+    pub fn start(inner: Arc<F>, config: &Config) -> Self {
+        let open_meteo = storage::api_usage(Provider::OpenMeteo);
+        let over_quota = open_meteo.hour >= config.quota.open_meteo_hourly
+            || open_meteo.day >= config.quota.open_meteo_daily;
+
+        let mut pending = HashMap::new();
+        if !over_quota && !safety::kill_switch_engaged() {
+            let now = chrono::Utc::now();
+            for city in &config.cities {
+                if config.model_schedule && !schedule::refresh_due(city, storage::last_refresh(&city.name), now) {
+                    continue;
+                }
+                let feed = inner.clone();
+                let city = city.clone();
+                let name = city.name.clone();
+                pending.insert(name, tokio::spawn(async move { feed.forecast(&city).await }));
+            }
+            tracing::info!("Prefetching weather for {} of {} cities", pending.len(), config.cities.len());
+        }
+
+        Self {
+            inner,
+            pending: Mutex::new(pending),
+        }
+    }
+}
+
+#[async_trait]
+impl<F: WeatherFeed + 'static> WeatherFeed for PrefetchFeed<F> {
+    async fn forecast(&self, city: &CityConfig) -> Result<Option<WeatherSnapshot>> {
+        let handle = self.pending.lock().expect("prefetch lock poisoned").remove(&city.name);
+        match handle {
+            Some(handle) => match handle.await {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::warn!("[{}] Weather prefetch task failed: {} — fetching again", city.name, e);
+                    self.inner.forecast(city).await
+                }
+            },
+            None => self.inner.forecast(city).await,
+        }
+    }
+
+    async fn observations(&self, city: &CityConfig) -> Result<Option<Observations>> {
+        self.inner.observations(city).await
+    }
+
+    async fn alerts(&self, city: &CityConfig) -> Result<Option<Vec<WeatherAlert>>> {
+        self.inner.alerts(city).await
+    }
+
+    async fn daily_high(&self, city: &CityConfig, day: &str) -> Result<Option<f64>> {
+        self.inner.daily_high(city, day).await
+    }
+}
@@ -0,0 +1,65 @@
+use crate::core::types::*;
+
+/// An order the strategy wants resting on the book.
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub ticker: String,
+    pub side: Side,
+    pub price_cents: u32,
+    pub shares: u32,
+}
+
+/// One step to move the book from what's resting to what's desired.
+#[derive(Debug, Clone)]
+pub enum OrderAction {
+    /// Already right — leave it and its queue position alone
+    Keep { order_id: String },
+    /// Same ticker and side, new price or smaller size
+    Amend { order_id: String, price_cents: u32, shares: u32 },
+    Cancel { order_id: String },
+    Create(Quote),
+}
+
+/// Diff desired quotes against the bot's resting orders, matching on
+/// (ticker, side). Size increases at the same price are added as a new
+/// order so the existing one keeps its queue priority; shrinking in place
+/// keeps it too. Extra resting orders on a matched key are cancelled.
+pub fn plan(desired: &[Quote], resting: &[&RestingOrder]) -> Vec<OrderAction> {
+    let mut actions = Vec::new();
+    let mut used: Vec<&str> = Vec::new();
+
+    for quote in desired {
+        let existing = resting
+            .iter()
+            .find(|o| o.ticker == quote.ticker && o.side == quote.side && !used.contains(&o.order_id.as_str()));
+        let Some(order) = existing else {
+            actions.push(OrderAction::Create(quote.clone()));
+            continue;
+        };
+        used.push(&order.order_id);
+
+        if order.price_cents != quote.price_cents || order.remaining_count > quote.shares {
+            actions.push(OrderAction::Amend {
+                order_id: order.order_id.clone(),
+                price_cents: quote.price_cents,
+                shares: quote.shares,
+            });
+        } else {
+            actions.push(OrderAction::Keep { order_id: order.order_id.clone() });
+            if order.remaining_count < quote.shares {
+                actions.push(OrderAction::Create(Quote {
+                    shares: quote.shares - order.remaining_count,
+                    ..quote.clone()
+                }));
+            }
+        }
+    }
+
+    for order in resting {
+        if !used.contains(&order.order_id.as_str()) {
+            actions.push(OrderAction::Cancel { order_id: order.order_id.clone() });
+        }
+    }
+
+    actions
+}
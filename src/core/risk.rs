@@ -1,24 +1,312 @@
-use crate::core::types::{Config, Stats};
+use crate::core::types::{
+    event_of, CityConfig, Cents, Config, Exposure, LedgerRow, LossSprt, MarketState, Position, RestingOrder, RiskState,
+    RiskVeto, StrategyParams, Stats, TickerRejections, VetoDetails,
+};
 
+/// `open_risk` is the worst-case loss still riding on open positions;
+/// it only counts toward the daily limit with `daily_loss_includes_open`.
+/// With `loss_sprt` (see `loss_sprt()`) the SPRT replaces the
+/// consecutive-loss limit.
 pub fn check(
     stats: &Stats,
-    balance_cents: u64,
+    balance: Cents,
+    open_risk: Cents,
+    loss_sprt: Option<LossSprt>,
     config: &Config,
-) -> Option<String> {
-    if balance_cents < config.min_balance_cents {
-        return Some(format!(
-            "Balance {}¢ < {}¢ minimum",
-            balance_cents, config.min_balance_cents
-        ));
+) -> Option<VetoDetails> {
+    let veto = |veto, observed: i64, limit: i64| VetoDetails {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        veto,
+        observed,
+        limit,
+    };
+    if balance < config.min_balance_cents {
+        return Some(veto(RiskVeto::LowBalance, balance.0, config.min_balance_cents.0));
     }
-    if stats.today_pnl_cents <= -config.max_daily_loss_cents {
-        return Some(format!("Daily loss: {}¢", stats.today_pnl_cents));
+    let today_loss = if config.daily_loss_includes_open {
+        stats.today_pnl_cents - open_risk
+    } else {
+        stats.today_pnl_cents
+    };
+    if today_loss <= -config.max_daily_loss_cents {
+        return Some(veto(RiskVeto::DailyLoss, today_loss.0, -config.max_daily_loss_cents.0));
     }
-    if stats.current_streak <= -(config.max_consecutive_losses as i32) {
-        return Some(format!(
-            "{}× consecutive losses",
-            stats.current_streak.abs()
+    if let Some(t) = loss_sprt {
+        if t.llr >= t.upper {
+            return Some(veto(RiskVeto::LossSprt, (t.llr * 100.0).round() as i64, (t.upper * 100.0).round() as i64));
+        }
+    } else if stats.current_streak <= -(config.max_consecutive_losses as i32) {
+        return Some(veto(
+            RiskVeto::LossStreak,
+            stats.current_streak.unsigned_abs() as i64,
+            config.max_consecutive_losses as i64,
         ));
     }
     None
 }
+
+/// Wald's SPRT over settled wins and losses, oldest first: each result
+/// adds its log-likelihood under `loss_sprt_fail_win_prob` over
+/// `loss_sprt_win_prob`. A losing run the assumed win rate readily
+/// explains stays under the upper bound, while mostly-losing stretches
+/// with the odd win still add up to a veto. Crossing the lower bound
+/// accepts the edge and restarts the test, so old wins don't bank credit
+/// against a later collapse. None when off.
+pub fn loss_sprt(ledger: &[LedgerRow], params: &StrategyParams) -> Option<LossSprt> {
+    let (p0, p1) = (params.loss_sprt_win_prob, params.loss_sprt_fail_win_prob);
+    if p0 <= 0.0 {
+        return None;
+    }
+    let (alpha, beta) = (params.loss_sprt_alpha, params.loss_sprt_beta);
+    let mut t = LossSprt {
+        llr: 0.0,
+        upper: ((1.0 - beta) / alpha).ln(),
+        lower: (beta / (1.0 - alpha)).ln(),
+        wins: 0,
+        losses: 0,
+    };
+    for r in ledger.iter().filter(|r| r.is_settled()) {
+        match r.result.as_str() {
+            "win" => {
+                t.llr += (p1 / p0).ln();
+                t.wins += 1;
+            }
+            "loss" => {
+                t.llr += ((1.0 - p1) / (1.0 - p0)).ln();
+                t.losses += 1;
+            }
+            _ => continue,
+        }
+        if t.llr <= t.lower {
+            (t.llr, t.wins, t.losses) = (0.0, 0, 0);
+        }
+    }
+    Some(t)
+}
+
+/// Worst-case loss on pending ledger rows: every open contract expires
+/// worthless. Settlement can be hours away, so without this a bad morning
+/// of entries doesn't touch the daily limit until night.
+pub fn open_worst_case_loss(ledger: &[LedgerRow]) -> Cents {
+    ledger
+        .iter()
+        .filter(|r| r.is_pending())
+        .map(|r| Cents::for_contracts(r.shares, r.price))
+        .sum()
+}
+
+/// Estimated Kalshi taker fee for an order: ceil of
+/// 7% × contracts × P × (1−P), in whole cents.
+pub fn taker_fee_cents(shares: u32, price_cents: u32) -> Cents {
+    let price = price_cents.clamp(1, 99) as i64;
+    Cents((7 * shares as i64 * price * (100 - price) + 9_999) / 10_000)
+}
+
+/// The same fee for a single contract, unrounded, as a fraction of the
+/// $1 payout — the units of a probability edge, so it can be subtracted
+/// from one directly.
+pub fn taker_fee_edge(price_cents: u32) -> f64 {
+    let p = price_cents.clamp(1, 99) as f64 / 100.0;
+    0.07 * p * (1.0 - p)
+}
+
+/// Whole contracts a balance can buy at `price_cents`, including the
+/// estimated taker fee per contract.
+pub fn affordable_shares(balance: Cents, price_cents: u32) -> u32 {
+    let per_contract = Cents::for_contracts(1, price_cents.clamp(1, 99)) + taker_fee_cents(1, price_cents);
+    (balance.0.max(0) / per_contract.0).min(u32::MAX as i64) as u32
+}
+
+/// Contracts at `price_cents` that still fit under the market's position
+/// limit, after what the bot already holds, has resting and (paper) has
+/// pending on that ticker.
+pub fn position_headroom(
+    market: &MarketState,
+    fallback_limit: Cents,
+    positions: &[Position],
+    resting: &[RestingOrder],
+    ledger: &[LedgerRow],
+    paper_trade: bool,
+    price_cents: u32,
+) -> u32 {
+    let limit = market.position_limit_cents.unwrap_or(fallback_limit);
+    let held: Cents = positions
+        .iter()
+        .filter(|p| p.ticker == market.ticker)
+        .map(|p| p.exposure_cents)
+        .sum();
+    let resting_cost: Cents = resting
+        .iter()
+        .filter(|o| o.ticker == market.ticker)
+        .map(|o| Cents::for_contracts(o.remaining_count, o.price_cents))
+        .sum();
+    let pending_cost: Cents = if paper_trade {
+        ledger
+            .iter()
+            .filter(|r| r.is_pending() && r.ticker == market.ticker)
+            .map(|r| Cents::for_contracts(r.shares, r.price))
+            .sum()
+    } else {
+        Cents::ZERO
+    };
+    let room = limit.saturating_sub_to_zero(held + resting_cost + pending_cost);
+    (room.0 / price_cents.max(1) as i64).min(u32::MAX as i64) as u32
+}
+
+/// Everything riding on one city: held positions and resting orders on its
+/// series and, in paper mode, its pending rows. Cities' highs move together
+/// with the same fronts, so this caps how much one forecast can lose.
+pub fn city_exposure(
+    city: &CityConfig,
+    positions: &[Position],
+    resting: &[RestingOrder],
+    ledger: &[LedgerRow],
+    paper_trade: bool,
+) -> Cents {
+    let prefix = format!("{}-", city.series_ticker);
+    let held: Cents = positions
+        .iter()
+        .filter(|p| p.ticker.starts_with(&prefix))
+        .map(|p| p.exposure_cents)
+        .sum();
+    let resting_cost: Cents = resting
+        .iter()
+        .filter(|o| o.ticker.starts_with(&prefix))
+        .map(|o| Cents::for_contracts(o.remaining_count, o.price_cents))
+        .sum();
+    let pending_cost: Cents = if paper_trade {
+        ledger
+            .iter()
+            .filter(|r| r.is_pending() && (r.city == city.name || r.ticker.starts_with(&prefix)))
+            .map(|r| Cents::for_contracts(r.shares, r.price))
+            .sum()
+    } else {
+        Cents::ZERO
+    };
+    held + resting_cost + pending_cost
+}
+
+/// What a brain deciding on `event_ticker` should know is already on:
+/// that event's positions and resting orders, plus account-wide totals.
+/// In paper mode pending rows stand in for the positions the exchange
+/// never reports.
+pub fn exposure(
+    event_ticker: &str,
+    positions: &[Position],
+    resting: &[RestingOrder],
+    ledger: &[LedgerRow],
+    paper_trade: bool,
+) -> Exposure {
+    let mut held: Vec<Position> = positions.to_vec();
+    if paper_trade {
+        held.extend(paper_positions(ledger));
+    }
+    Exposure {
+        portfolio_held_cents: held.iter().map(|p| p.exposure_cents).sum(),
+        portfolio_resting_cents: resting
+            .iter()
+            .map(|o| Cents::for_contracts(o.remaining_count, o.price_cents))
+            .sum(),
+        portfolio_markets: held.iter().map(|p| p.ticker.as_str()).collect::<std::collections::HashSet<_>>().len(),
+        positions: held.into_iter().filter(|p| event_of(&p.ticker) == event_ticker).collect(),
+        resting: resting.iter().filter(|o| event_of(&o.ticker) == event_ticker).cloned().collect(),
+    }
+}
+
+/// Pending paper rows as the positions they would be.
+pub fn paper_positions(ledger: &[LedgerRow]) -> Vec<Position> {
+    ledger
+        .iter()
+        .filter(|r| r.is_pending())
+        .filter_map(|r| {
+            Some(Position {
+                ticker: r.ticker.clone(),
+                side: r.side()?,
+                count: r.shares,
+                exposure_cents: Cents::for_contracts(r.shares, r.price),
+            })
+        })
+        .collect()
+}
+
+/// Capital the sizer may commit: balance minus the cost of resting orders
+/// and, in paper mode, the worst-case loss of pending paper trades (which
+/// the exchange balance never sees). Live fills are already netted out of
+/// the exchange balance, so pending live rows aren't subtracted twice.
+pub fn available_capital(
+    balance: Cents,
+    resting: &[RestingOrder],
+    ledger: &[LedgerRow],
+    paper_trade: bool,
+) -> Cents {
+    let resting_cost: Cents = resting
+        .iter()
+        .map(|o| Cents::for_contracts(o.remaining_count, o.price_cents))
+        .sum();
+    let pending_cost: Cents = if paper_trade {
+        open_worst_case_loss(ledger)
+    } else {
+        Cents::ZERO
+    };
+    balance.saturating_sub_to_zero(resting_cost + pending_cost)
+}
+
+/// Count a rejection of `ticker` on `today` (UTC, YYYY-MM-DD); a count left
+/// from an earlier day starts over. True when this rejection reaches
+/// `limit` and locks the ticker. A `limit` of 0 never locks.
+pub fn record_rejection(state: &mut RiskState, ticker: &str, error: &str, now: chrono::DateTime<chrono::Utc>, limit: u32) -> bool {
+    let today = now.format("%Y-%m-%d").to_string();
+    state.tickers.retain(|_, r| r.day == today);
+    let entry = state.tickers.entry(ticker.to_string()).or_insert_with(|| TickerRejections {
+        day: today,
+        count: 0,
+        last_error: String::new(),
+        locked_at: None,
+    });
+    entry.count += 1;
+    entry.last_error = error.to_string();
+    if limit > 0 && entry.count >= limit && entry.locked_at.is_none() {
+        entry.locked_at = Some(now.to_rfc3339());
+        return true;
+    }
+    false
+}
+
+/// The lockout on `ticker`, if one was set on `today`.
+pub fn lockout<'a>(state: &'a RiskState, ticker: &str, today: &str) -> Option<&'a TickerRejections> {
+    state.tickers.get(ticker).filter(|r| r.day == today && r.locked_at.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(result: &str) -> LedgerRow {
+        LedgerRow {
+            timestamp: "2026-02-12T15:00:00+00:00".into(),
+            ticker: "KXHIGHNY-26FEB12-B40.5".into(),
+            side: "yes".into(),
+            shares: 1,
+            price: 40,
+            result: result.into(),
+            pnl_cents: Cents::ZERO,
+            cumulative_cents: Cents::ZERO,
+            order_id: String::new(),
+            cancel_reason: None,
+            event_ticker: "KXHIGHNY-26FEB12".into(),
+            city: "New York".into(),
+        }
+    }
+
+    #[test]
+    fn loss_sprt_skips_scratch_exits() {
+        let params = StrategyParams::default();
+        let sprt = |results: &[&str]| loss_sprt(&results.iter().map(|r| row(r)).collect::<Vec<_>>(), &params).unwrap();
+        let with = sprt(&["loss", "scratch", "scratch", "loss"]);
+        let without = sprt(&["loss", "loss"]);
+        assert_eq!((with.wins, with.losses), (0, 2));
+        assert_eq!(with.llr, without.llr);
+        // (1 − 0.35) / (1 − 0.6) per loss
+        assert!((with.llr - 2.0 * (0.65f64 / 0.4).ln()).abs() < 1e-12);
+    }
+}
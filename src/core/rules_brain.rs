@@ -1,25 +1,115 @@
+use crate::core::seasons::Seasons;
+use crate::core::{distribution, indicators, risk};
 use crate::core::types::*;
 use crate::ports::brain::Brain;
 use anyhow::Result;
 use async_trait::async_trait;
 
+/// Inside this window the ensemble is stale relative to what the station has
+/// already recorded, so pricing switches to observations.
+pub const NEAR_EXPIRY_MINUTES: f64 = 60.0;
+
 /// Pure deterministic brain — no LLM, no network calls.
 /// Compares ensemble probability to market implied probability.
-pub struct RulesBrain;
+pub struct RulesBrain {
+    params: StrategyParams,
+    /// Per-city tuning, applied to `params` before the seasons
+    cities: Vec<CityConfig>,
+    /// Seasonal patches applied to `params` per city and month
+    seasons: Seasons,
+    /// Learned floors, used instead of `min_net_edge_pp` when
+    /// `adaptive_edge_floor` is set
+    edge_floors: Vec<EdgeFloor>,
+}
 
 impl RulesBrain {
-    pub fn new() -> Self {
-        Self
+    pub fn new(params: StrategyParams) -> Self {
+        Self { params, cities: Vec::new(), seasons: Seasons::default(), edge_floors: Vec::new() }
+    }
+
+    pub fn with_cities(mut self, cities: Vec<CityConfig>) -> Self {
+        self.cities = cities;
+        self
+    }
+
+    pub fn with_seasons(mut self, seasons: Seasons) -> Self {
+        self.seasons = seasons;
+        self
+    }
+
+    pub fn with_edge_floors(mut self, floors: Vec<EdgeFloor>) -> Self {
+        self.edge_floors = floors;
+        self
+    }
+
+    /// Net edge, in pp, a bracket must clear. Buckets with no history fall
+    /// back to the fixed floor.
+    fn min_net_edge_pp(&self, params: &StrategyParams, city: &str, market_type: Option<&MarketType>, confidence: &ForecastConfidence) -> f64 {
+        if !params.adaptive_edge_floor {
+            return params.min_net_edge_pp;
+        }
+        market_type
+            .and_then(|mt| {
+                self.edge_floors
+                    .iter()
+                    .find(|f| f.city == city && f.market_kind == mt.kind() && f.confidence == *confidence)
+            })
+            .map_or(params.min_net_edge_pp, |f| f.floor_pp)
     }
 }
 
+/// Rules whose vetoes get a counterfactual: what would we have traded
+/// if only this rule were switched off?
+const COUNTERFACTUAL_RULES: [PassReason; 8] = [
+    PassReason::ExtremePrice,
+    PassReason::PriceCap,
+    PassReason::SpreadTooWide,
+    PassReason::Illiquid,
+    PassReason::SourceDisagreement,
+    PassReason::NoTradeZone,
+    PassReason::TailUnconfirmed,
+    PassReason::NoQuorum,
+];
+
 #[async_trait]
 impl Brain for RulesBrain {
     async fn decide(&self, ctx: &DecisionContext) -> Result<TradeDecision> {
+        let mut decision = self.evaluate(ctx, None);
+
+        if let Some(rule) = decision.pass_reason.filter(|r| COUNTERFACTUAL_RULES.contains(r)) {
+            // Re-run with the vetoing rule waived; mute its logging so the
+            // counterfactual doesn't read like a real decision.
+            let waived = tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+                self.evaluate(ctx, Some(rule))
+            });
+            if waived.action == Action::Buy {
+                decision.counterfactual = Some(Counterfactual {
+                    rule,
+                    side: waived.side.unwrap_or(Side::Yes),
+                    shares: waived.shares.unwrap_or(1),
+                    price_cents: waived.max_price_cents.unwrap_or(50),
+                });
+            }
+        }
+
+        Ok(decision)
+    }
+}
+
+impl RulesBrain {
+    /// The strategy pipeline. `waived` switches off one veto rule, used to
+    /// price what that rule blocked.
+    fn evaluate(&self, ctx: &DecisionContext, waived: Option<PassReason>) -> TradeDecision {
         let weather = match &ctx.weather {
             Some(w) => w,
-            None => return Ok(pass("No weather data available")),
+            None => return pass(PassReason::NoWeather, "No weather data available"),
         };
+        let series = ctx.market.event_ticker.split('-').next().unwrap_or_default();
+        let base = match self.cities.iter().find(|c| c.series_ticker == series) {
+            Some(city) => city.strategy(&self.params),
+            None => self.params.clone(),
+        };
+        let params = &self.seasons.params(&base, &weather.city, series, local_month(weather));
 
         // Use API strike fields via MarketType::from_market()
         let market_type = MarketType::from_market(&ctx.market);
@@ -32,7 +122,7 @@ impl Brain for RulesBrain {
 
         let market_implied = match ctx.market.yes_ask {
             Some(ask) => ask as f64 / 100.0,
-            None => return Ok(pass("No yes_ask price available")),
+            None => return pass(PassReason::NoQuote, "No yes_ask price available"),
         };
 
         let no_ask = ctx.market.no_ask.unwrap_or(100);
@@ -43,18 +133,34 @@ impl Brain for RulesBrain {
             yes_ask, no_ask, market_implied * 100.0
         );
 
-        // Skip extreme prices — likely settled or stale
-        if market_implied > 0.90 || market_implied < 0.10 {
-            return Ok(pass(&format!(
-                "Extreme price: yes_ask={}¢ (implied {:.0}%) — likely settled or stale",
-                yes_ask, market_implied * 100.0
-            )));
-        }
-
-        // Compute ensemble YES probability — prefer raw member highs, fall back to buckets
-        let ensemble_yes = match &market_type {
-            Some(mt) => {
-                if !weather.ensemble_member_highs.is_empty() {
+        // Compute ensemble YES probability — near expiry price from observations,
+        // otherwise prefer raw member highs, fall back to buckets
+        let near_expiry = ctx.market.minutes_to_expiry <= NEAR_EXPIRY_MINUTES;
+        let high_so_far = weather.observed_high_so_far;
+        let mut observation_priced = false;
+        let ensemble_yes = match (&market_type, ctx.fair_override) {
+            (Some(_), Some(prob)) => {
+                tracing::info!("Fair value override: {:.1}% YES", prob * 100.0);
+                Some(prob)
+            }
+            (Some(mt), None) => {
+                if let Some(prob) = high_so_far.and_then(|h| decided_by_observed(h, mt)) {
+                    // The running high already settled it, whatever the forecast
+                    observation_priced = true;
+                    tracing::info!(
+                        "High so far {:.1}°F already decides {:?} → {:.0}% YES",
+                        high_so_far.unwrap_or_default(), mt, prob * 100.0
+                    );
+                    Some(prob)
+                } else if let (true, Some(observed)) = (near_expiry, high_so_far) {
+                    let prob = observation_yes_probability(observed, weather.remaining_forecast_high_f, mt);
+                    observation_priced = true;
+                    tracing::info!(
+                        "Near expiry ({:.0}min) — observation pricing: observed high {:.1}°F, remaining forecast {:?} → {:.1}% YES | {:?}",
+                        ctx.market.minutes_to_expiry, observed, weather.remaining_forecast_high_f, prob * 100.0, mt
+                    );
+                    Some(prob)
+                } else if !weather.ensemble_member_highs.is_empty() {
                     // Exact computation from raw ensemble members
                     let prob = compute_ensemble_yes_from_members(&weather.ensemble_member_highs, mt);
                     let total = weather.ensemble_member_highs.len();
@@ -64,15 +170,21 @@ impl Brain for RulesBrain {
                         matching, total, prob * 100.0, mt
                     );
                     Some(prob)
-                } else if weather.ensemble.is_some() && !weather.bucket_probabilities.is_empty() {
+                } else if ctx.features.bucket_fallback
+                    && weather.ensemble.is_some()
+                    && !weather.bucket_probabilities.is_empty()
+                {
                     // Fallback: bucket interpolation
                     let prob = compute_ensemble_yes_from_buckets(&weather.bucket_probabilities, mt);
                     tracing::info!("Ensemble YES (bucket fallback): {:.1}%", prob * 100.0);
                     Some(prob)
                 } else {
-                    // Last resort: sigmoid from point estimate
+                    // Last resort: sigmoid from point estimate, scaled for
+                    // temperatures
                     match mt {
-                        MarketType::Above(threshold) => {
+                        MarketType::Above(threshold)
+                            if ctx.features.sigmoid_fallback && weather.measure == Measure::HighTemp =>
+                        {
                             let diff = weather.open_meteo_forecast_high - threshold;
                             let prob = 1.0 / (1.0 + (-diff / 2.0_f64).exp());
                             tracing::info!(
@@ -82,117 +194,355 @@ impl Brain for RulesBrain {
                             Some(prob)
                         }
                         _ => {
-                            tracing::info!("No ensemble data and non-Above market type — cannot estimate");
+                            tracing::info!("No ensemble data and no fallback for this market — cannot estimate");
                             None
                         }
                     }
                 }
             }
-            None => {
+            (None, _) => {
                 tracing::info!("Cannot determine market type from strike fields or ticker");
                 None
             }
         };
 
+        // Blend in the NBM percentiles, which also stand in for a missing
+        // ensemble. Observation pricing and operator values are left alone.
+        let ensemble_yes = match (&market_type, &weather.nbm) {
+            (Some(mt), Some(nbm)) if params.nbm_weight > 0.0 && !observation_priced && ctx.fair_override.is_none() => {
+                match nbm_yes_probability(nbm, mt) {
+                    Some(nbm_yes) => {
+                        let w = params.nbm_weight.min(1.0);
+                        let blended = ensemble_yes.map_or(nbm_yes, |e| (1.0 - w) * e + w * nbm_yes);
+                        tracing::info!(
+                            "NBM {} ({}): {:.1}% YES × weight {:.2} → model {:.1}% YES",
+                            nbm.station, nbm.issued, nbm_yes * 100.0, w, blended * 100.0
+                        );
+                        Some(blended)
+                    }
+                    None => ensemble_yes,
+                }
+            }
+            _ => ensemble_yes,
+        };
+
+        // Same day: lean on the HRRR high more as expiry nears
+        let ensemble_yes = match (&market_type, weather.hrrr_high_f) {
+            (Some(mt), Some(hrrr)) if params.hrrr_weight > 0.0 && !observation_priced && ctx.fair_override.is_none() => {
+                let window = params.hrrr_window_hours * 60.0;
+                let progress = if window > 0.0 { (1.0 - ctx.market.minutes_to_expiry / window).clamp(0.0, 1.0) } else { 0.0 };
+                let w = params.hrrr_weight.min(1.0) * progress;
+                if w > 0.0 {
+                    let hrrr_yes = hrrr_yes_probability(hrrr, high_so_far, params.hrrr_sd_f, mt);
+                    let blended = ensemble_yes.map_or(hrrr_yes, |e| (1.0 - w) * e + w * hrrr_yes);
+                    tracing::info!(
+                        "HRRR high {:.1}°F ({:.0}min to expiry): {:.1}% YES × weight {:.2} → model {:.1}% YES",
+                        hrrr, ctx.market.minutes_to_expiry, hrrr_yes * 100.0, w, blended * 100.0
+                    );
+                    Some(blended)
+                } else {
+                    ensemble_yes
+                }
+            }
+            _ => ensemble_yes,
+        };
+
+        // The forecast never claims certainty; observations and operator
+        // values can
+        let mut bias_yes = 0.0;
+        let ensemble_yes = match ensemble_yes {
+            Some(p) if !observation_priced && ctx.fair_override.is_none() => {
+                let bounded = distribution::bound_probability(
+                    p, weather.ensemble_member_highs.len(), params.prob_floor, params.prob_ceiling,
+                );
+                if bounded != p {
+                    tracing::info!("Model {:.1}% YES bounded to {:.1}%", p * 100.0, bounded * 100.0);
+                }
+                bias_yes = bounded - p;
+                Some(bounded)
+            }
+            other => other,
+        };
+
+        if let (Some(ens_yes), Some(mt), Some(implied)) = (ensemble_yes, &market_type, &ctx.implied) {
+            let dist_yes = implied.probability(mt);
+            tracing::info!(
+                "Distribution view: model {:.1}% vs vig-free implied {:.1}% → {:+.1}pp",
+                ens_yes * 100.0, dist_yes * 100.0, (ens_yes - dist_yes) * 100.0
+            );
+        }
+
+        // Skip extreme prices — likely settled or stale. Checked after pricing
+        // so the probability still reaches the journal for calibration.
+        if waived != Some(PassReason::ExtremePrice) && !(0.10..=0.90).contains(&market_implied) {
+            return pass(PassReason::ExtremePrice, &format!(
+                "Extreme price: yes_ask={}¢ (implied {:.0}%) — likely settled or stale",
+                yes_ask, market_implied * 100.0
+            )).with_probability(ensemble_yes);
+        }
+
+        // Sources disagree — the ensemble can't be trusted for this city.
+        // Observations near expiry don't depend on either forecast.
+        let max_gap = params.max_source_disagreement_f;
+        if let Some(gap) = indicators::source_disagreement(weather) {
+            if waived != Some(PassReason::SourceDisagreement) && max_gap > 0.0 && gap > max_gap && !observation_priced {
+                return pass(PassReason::SourceDisagreement, &format!(
+                    "NWS {:.0}°F vs ensemble mean differ by {:.1}°F (max {:.1}°F) — city vetoed",
+                    weather.nws_forecast_high.unwrap_or_default(), gap, max_gap
+                )).with_probability(ensemble_yes);
+            }
+        }
+
+        // Quorum mode: the ensemble has to come out the same both ways
+        if let Some(q) = &weather.quorum {
+            if waived != Some(PassReason::NoQuorum) && !observation_priced && ctx.fair_override.is_none() {
+                let tolerance = params.quorum_tolerance_f;
+                match (weather.ensemble.as_ref().map(|e| e.mean_high), q.mean) {
+                    (Some(a), Some(b)) if (a - b).abs() <= tolerance => {}
+                    (Some(a), Some(b)) => {
+                        return pass(PassReason::NoQuorum, &format!(
+                            "Ensemble mean {:.1}°F vs {:.1}°F from {} ({} members) differ by {:.1}°F (tolerance {:.1}°F)",
+                            a, b, q.source, q.members, (a - b).abs(), tolerance
+                        )).with_probability(ensemble_yes);
+                    }
+                    (a, _) => {
+                        let missing = if a.is_none() { "primary ensemble".to_string() } else { q.source.clone() };
+                        return pass(PassReason::NoQuorum, &format!("No quorum: {} unavailable", missing))
+                            .with_probability(ensemble_yes);
+                    }
+                }
+            }
+        }
+
+        // Near 0 or 1 the ensemble alone isn't enough: a source outside it
+        // has to be on the same side
+        if let (Some(ens_yes), Some(mt)) = (ensemble_yes, &market_type) {
+            let sources = indicators::independent_forecasts(weather);
+            let values: Vec<f64> = sources.iter().map(|(_, v)| *v).collect();
+            if waived != Some(PassReason::TailUnconfirmed)
+                && !observation_priced
+                && ctx.fair_override.is_none()
+                && !distribution::tail_confirmed(ens_yes, params.tail_confirm_prob, mt, &values)
+            {
+                let seen: Vec<String> = sources.iter().map(|(name, v)| format!("{} {}", name, weather.measure.strike(*v))).collect();
+                return pass(PassReason::TailUnconfirmed, &format!(
+                    "Model {:.1}% YES is a tail and {} — not confirmed",
+                    ens_yes * 100.0,
+                    if seen.is_empty() { "no source outside the ensemble is available".to_string() } else { format!("not every source outside it agrees ({})", seen.join(", ")) }
+                )).with_probability(ensemble_yes);
+            }
+        }
+
+        // Near 50/50 with a wide ensemble, small model biases show up as
+        // edge that isn't there — sit out whatever the computed edge.
+        let zone = params.no_trade_zone_pp / 100.0;
+        if let (Some(ens_yes), Some(ensemble)) = (ensemble_yes, &weather.ensemble) {
+            if waived != Some(PassReason::NoTradeZone)
+                && zone > 0.0
+                && !observation_priced
+                && (ens_yes - 0.5).abs() <= zone
+                && ensemble.std_dev >= params.no_trade_zone_min_std_f
+            {
+                return pass(PassReason::NoTradeZone, &format!(
+                    "Model {:.1}% is within {:.0}pp of 50% with ensemble σ {:.1}°F (≥ {:.1}°F) — no-trade zone",
+                    ens_yes * 100.0, params.no_trade_zone_pp, ensemble.std_dev, params.no_trade_zone_min_std_f
+                )).with_probability(ensemble_yes);
+            }
+        }
+
         if let Some(ens_yes) = ensemble_yes {
             let edge_yes = ens_yes - market_implied;
             let edge_no = (1.0 - ens_yes) - (no_ask as f64 / 100.0);
 
+            // Observations aren't subject to ensemble spread
             let confidence_multiplier = match weather.confidence {
+                _ if observation_priced => 1.0,
                 ForecastConfidence::High => 1.0,
-                ForecastConfidence::Medium => 0.8,
-                ForecastConfidence::Low => 0.5,
+                ForecastConfidence::Medium => params.confidence_medium_scale,
+                ForecastConfidence::Low => params.confidence_low_scale,
             };
 
             let adj_edge_yes = edge_yes * confidence_multiplier;
             let adj_edge_no = edge_no * confidence_multiplier;
 
-            let (side, _raw_edge, adj_edge, price) = if adj_edge_yes >= adj_edge_no {
+            let (side, raw_edge, adj_edge, price) = if adj_edge_yes >= adj_edge_no {
                 (Side::Yes, edge_yes, adj_edge_yes, yes_ask)
             } else {
                 (Side::No, edge_no, adj_edge_no, no_ask)
             };
 
-            // Fee-aware: subtract estimated taker fee from edge
-            let fee_pp = estimate_fee_pp(1, price);
-            let net_edge = adj_edge - fee_pp;
+            // Fee-aware: subtract the estimated taker fee, as an edge, and
+            // what rounding it up to the cent adds over the order
+            let fee_edge = risk::taker_fee_edge(price);
+            let order_shares = size_from_edge(adj_edge - fee_edge).max(1);
+            let charged = risk::taker_fee_cents(order_shares, price).0 as f64 / order_shares as f64 / 100.0;
+            let rounding = charged - fee_edge;
+            let net_edge = adj_edge - fee_edge - rounding;
+            let side_bias = if side == Side::Yes { bias_yes } else { -bias_yes };
+            let breakdown = EdgeBreakdown {
+                side: side.clone(),
+                price_cents: price,
+                raw_pp: (raw_edge - side_bias) * 100.0,
+                bias_pp: side_bias * 100.0,
+                confidence_pp: (adj_edge - raw_edge) * 100.0,
+                fee_pp: -fee_edge * 100.0,
+                rounding_pp: -rounding * 100.0,
+                net_pp: net_edge * 100.0,
+            };
 
             tracing::info!(
                 "Edge: YES={:+.1}pp NO={:+.1}pp (adj YES={:+.1}pp NO={:+.1}pp) → best={:?} | Gross edge: {:.1}pp, fee: ~{:.1}pp, net edge: {:.1}pp",
                 edge_yes * 100.0, edge_no * 100.0,
                 adj_edge_yes * 100.0, adj_edge_no * 100.0, side,
-                adj_edge * 100.0, fee_pp * 100.0, net_edge * 100.0
+                adj_edge * 100.0, charged * 100.0, net_edge * 100.0
+            );
+            tracing::debug!(
+                raw_pp = breakdown.raw_pp,
+                bias_pp = breakdown.bias_pp,
+                confidence_pp = breakdown.confidence_pp,
+                fee_pp = breakdown.fee_pp,
+                rounding_pp = breakdown.rounding_pp,
+                net_pp = breakdown.net_pp,
+                dominant = breakdown.dominant(),
+                "Edge breakdown"
             );
 
-            if net_edge < 0.05 {
-                return Ok(pass(&format!(
-                    "Edge too small: {:.1}pp adj on {:?}. Ensemble YES={:.0}% vs market={:.0}%. {:?} confidence.",
-                    adj_edge * 100.0, side, ens_yes * 100.0, market_implied * 100.0, weather.confidence
-                )));
+            // Already on this event elsewhere — one view of the high, one bet
+            if let Some(held) = ctx.exposure.conflict(&ctx.market.ticker, &side) {
+                return pass(PassReason::AlreadyExposed, &format!(
+                    "Edge {:.1}pp on {:?} but already exposed to {}: {}",
+                    net_edge * 100.0, side, ctx.market.event_ticker, held
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
             }
 
-            if price > 50 {
-                return Ok(pass(&format!(
-                    "Edge {:.1}pp on {:?} but price {}¢ > 50¢ cap",
-                    adj_edge * 100.0, side, price
-                )));
+            let floor_pp = self.min_net_edge_pp(params, &weather.city, market_type.as_ref(), &weather.confidence);
+            if net_edge < floor_pp / 100.0 {
+                return pass(PassReason::EdgeTooSmall, &format!(
+                    "Edge too small: {:.1}pp net on {:?} (floor {:.1}pp). Ensemble YES={:.0}% vs market={:.0}%. {:?} confidence.",
+                    net_edge * 100.0, side, floor_pp, ens_yes * 100.0, market_implied * 100.0, weather.confidence
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
             }
 
-            let shares = size_from_edge(net_edge);
-            let max_price = spread_aware_price(&ctx.market, &ctx.orderbook, &side);
+            if waived != Some(PassReason::PriceCap) && price > params.max_price_cents {
+                return pass(PassReason::PriceCap, &format!(
+                    "Edge {:.1}pp on {:?} but price {}¢ > {}¢ cap",
+                    adj_edge * 100.0, side, price, params.max_price_cents
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
+            }
+
+            let mut shares = size_from_edge(net_edge);
+            if let (Some(ensemble), Some(mt), false) = (&weather.ensemble, &market_type, observation_priced) {
+                let scale = params.size_scale(ensemble, mt, net_edge);
+                if scale != 1.0 {
+                    let scaled = ((shares as f64 * scale).round() as u32).max(1);
+                    tracing::info!(
+                        "Percentile sizing: strike vs P10={:.1} P25={:.1} P75={:.1} P90={:.1} → ×{:.2} ({}x → {}x)",
+                        ensemble.p10, ensemble.p25, ensemble.p75, ensemble.p90, scale, shares, scaled
+                    );
+                    shares = scaled;
+                }
+            }
+            let side_fair = if side == Side::Yes { ens_yes } else { 1.0 - ens_yes };
+            let fair_cents = (side_fair * 100.0).floor() as u32;
+            let Some(max_price) = spread_aware_price(&ctx.market, &ctx.orderbook, &side, fair_cents, floor_pp) else {
+                return pass(PassReason::NoQuote, &format!(
+                    "Edge {:.1}pp on {:?} but nobody is offering {:?} — empty book on that side",
+                    adj_edge * 100.0, side, side
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
+            };
 
-            if max_price > 50 {
-                return Ok(pass(&format!(
-                    "Edge {:.1}pp on {:?} but spread-aware price {}¢ > 50¢",
-                    adj_edge * 100.0, side, max_price
-                )));
+            if waived != Some(PassReason::SpreadTooWide) && max_price > params.max_price_cents {
+                return pass(PassReason::SpreadTooWide, &format!(
+                    "Edge {:.1}pp on {:?} but spread-aware price {}¢ > {}¢",
+                    adj_edge * 100.0, side, max_price, params.max_price_cents
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
             }
 
-            // Liquidity filter — skip brackets with no real market activity
-            if ctx.market.volume_24h < 10 && ctx.market.open_interest < 10 {
-                return Ok(pass(&format!(
-                    "Net edge {:.1}pp on {:?} but illiquid: vol_24h={}, OI={}",
-                    net_edge * 100.0, side, ctx.market.volume_24h, ctx.market.open_interest
-                )));
+            // Liquidity filter — skip brackets with no real market activity.
+            // Thresholds are relaxed right after open when volume hasn't built yet.
+            let (min_vol, min_oi) = params.liquidity_thresholds(ctx.market.minutes_since_open);
+            if waived != Some(PassReason::Illiquid)
+                && ctx.market.volume_24h < min_vol
+                && ctx.market.open_interest < min_oi
+            {
+                return pass(PassReason::Illiquid, &format!(
+                    "Net edge {:.1}pp on {:?} but illiquid: vol_24h={} (min {}), OI={} (min {})",
+                    net_edge * 100.0, side, ctx.market.volume_24h, min_vol, ctx.market.open_interest, min_oi
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
+            }
+
+            // A busy market can still have an empty book on one side — NO
+            // on tail brackets especially — so check the side being bought
+            let min_depth = params.liquidity_min_side_depth;
+            let depth = ctx.orderbook.side_depth(&side, params.liquidity_depth_window_cents);
+            if waived != Some(PassReason::Illiquid) && min_depth > 0 && depth < min_depth {
+                return pass(PassReason::Illiquid, &format!(
+                    "Net edge {:.1}pp on {:?} but only {} contracts offered within {}¢ of the {:?} ask (min {})",
+                    net_edge * 100.0, side, depth, params.liquidity_depth_window_cents, side, min_depth
+                )).with_probability(ensemble_yes).with_edge(breakdown.clone());
             }
 
             let reasoning = format!(
-                "Ensemble YES={:.0}% vs market={:.0}% → {:.1}pp net edge on {:?} (gross {:.1}pp - fee ~{:.1}pp, {:?} confidence). {}x @ {}¢. vol_24h={} OI={}",
+                "Ensemble YES={:.0}% vs market={:.0}% → {:.1}pp net edge on {:?} (gross {:.1}pp - fee ~{:.1}pp, {:?} confidence). {}x @ {}¢. vol_24h={} OI={}. Portfolio: {} held across {} market(s), {} resting",
                 ens_yes * 100.0, market_implied * 100.0,
-                net_edge * 100.0, side, adj_edge * 100.0, fee_pp * 100.0, weather.confidence,
+                net_edge * 100.0, side, adj_edge * 100.0, charged * 100.0, weather.confidence,
                 shares, max_price, ctx.market.volume_24h, ctx.market.open_interest,
+                ctx.exposure.portfolio_held_cents, ctx.exposure.portfolio_markets, ctx.exposure.portfolio_resting_cents,
             );
 
-            return Ok(TradeDecision {
+            return TradeDecision {
                 action: Action::Buy,
                 side: Some(side),
                 shares: Some(shares),
                 max_price_cents: Some(max_price),
                 reasoning,
                 edge_magnitude: net_edge.abs(),
-            });
+                pass_reason: None,
+                counterfactual: None,
+                model_probability: Some(ens_yes),
+                edge: Some(breakdown),
+            };
         }
 
-        Ok(pass(&format!(
+        let reason = if market_type.is_none() {
+            PassReason::UnknownMarketType
+        } else {
+            PassReason::NoProbability
+        };
+        pass(reason, &format!(
             "Cannot determine ensemble probability for '{}'",
             ctx.market.title
-        )))
+        ))
     }
 }
 
-fn pass(reason: &str) -> TradeDecision {
+/// Month of the city's local day, from the snapshot's local time.
+fn local_month(weather: &WeatherSnapshot) -> u32 {
+    weather
+        .local_time
+        .as_deref()
+        .and_then(|t| t.get(5..7))
+        .and_then(|m| m.parse().ok())
+        .unwrap_or_else(|| chrono::Datelike::month(&chrono::Utc::now()))
+}
+
+pub fn pass(reason: PassReason, detail: &str) -> TradeDecision {
     TradeDecision {
         action: Action::Pass,
         side: None,
         shares: None,
         max_price_cents: None,
-        reasoning: reason.to_string(),
+        reasoning: detail.to_string(),
         edge_magnitude: 0.0,
+        pass_reason: Some(reason),
+        counterfactual: None,
+        model_probability: None,
+        edge: None,
     }
 }
 
 /// Compute ensemble YES probability from raw member highs — exact, no interpolation.
-fn compute_ensemble_yes_from_members(member_highs: &[f64], market_type: &MarketType) -> f64 {
+pub fn compute_ensemble_yes_from_members(member_highs: &[f64], market_type: &MarketType) -> f64 {
     let total = member_highs.len() as f64;
     if total == 0.0 {
         return 0.0;
@@ -205,8 +555,108 @@ fn compute_ensemble_yes_from_members(member_highs: &[f64], market_type: &MarketT
     count as f64 / total
 }
 
+/// YES probability from the NBM percentiles; None when there are too few
+/// to interpolate.
+pub fn nbm_yes_probability(nbm: &NbmForecast, market_type: &MarketType) -> Option<f64> {
+    let cdf = |x: f64| distribution::quantile_cdf(&nbm.quantiles, x);
+    Some(match market_type {
+        MarketType::Above(t) => 1.0 - cdf(*t)?,
+        MarketType::Below(t) => cdf(*t)?,
+        MarketType::Between(lo, hi) => (cdf(*hi)? - cdf(*lo)?).max(0.0),
+    })
+}
+
+/// YES settled by the running high (`WeatherSnapshot::observed_high_so_far`):
+/// a bracket the day's high is already above (or, for the upper bound, at)
+/// can't come back. None while the rest of the day still matters.
+fn decided_by_observed(observed_high: f64, market_type: &MarketType) -> Option<f64> {
+    match market_type {
+        MarketType::Above(t) if observed_high > *t => Some(1.0),
+        MarketType::Below(t) if observed_high >= *t => Some(0.0),
+        MarketType::Between(_, hi) if observed_high >= *hi => Some(0.0),
+        _ => None,
+    }
+}
+
+/// Near-expiry YES probability from what the station has already recorded.
+/// The final high can't be below the observed high; the remaining uplift is
+/// whatever the forecast still expects above it, with a 1°F+ error band.
+fn observation_yes_probability(observed_high: f64, remaining_high: Option<f64>, market_type: &MarketType) -> f64 {
+    let uplift = remaining_high.map_or(0.0, |h| (h - observed_high).max(0.0));
+    let expected_final = observed_high + uplift;
+    let sd = 1.0 + uplift * 0.5;
+
+    // P(final high < x)
+    let below = |x: f64| -> f64 {
+        if x <= observed_high {
+            0.0
+        } else {
+            normal_cdf((x - expected_final) / sd)
+        }
+    };
+
+    match market_type {
+        MarketType::Above(t) => 1.0 - below(*t),
+        MarketType::Below(t) => below(*t),
+        MarketType::Between(lo, hi) => (below(*hi) - below(*lo)).max(0.0),
+    }
+}
+
+/// YES probability from the HRRR high with a normal error of `sd`. The
+/// final high can't end below what's already been observed.
+fn hrrr_yes_probability(hrrr_high: f64, observed_high: Option<f64>, sd: f64, market_type: &MarketType) -> f64 {
+    let floor = observed_high.unwrap_or(f64::NEG_INFINITY);
+    let expected = hrrr_high.max(floor);
+    let sd = sd.max(0.1);
+    // P(final high < x)
+    let below = |x: f64| -> f64 {
+        if x <= floor {
+            0.0
+        } else {
+            normal_cdf((x - expected) / sd)
+        }
+    };
+    match market_type {
+        MarketType::Above(t) => 1.0 - below(*t),
+        MarketType::Below(t) => below(*t),
+        MarketType::Between(lo, hi) => (below(*hi) - below(*lo)).max(0.0),
+    }
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26, |error| < 1.5e-7).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// Where the bucket fallback disagrees most with counting raw members,
+/// over `markets`: (ticker, members YES, buckets YES). Both come from the
+/// same ensemble, so a wide gap points at how the buckets were built.
+/// None unless the snapshot has both.
+pub fn bucket_divergence(weather: &WeatherSnapshot, markets: &[MarketState]) -> Option<(String, f64, f64)> {
+    if weather.ensemble_member_highs.is_empty() || weather.bucket_probabilities.is_empty() {
+        return None;
+    }
+    markets
+        .iter()
+        .filter_map(|m| {
+            let mt = MarketType::from_market(m)?;
+            let members = compute_ensemble_yes_from_members(&weather.ensemble_member_highs, &mt);
+            let buckets = compute_ensemble_yes_from_buckets(&weather.bucket_probabilities, &mt);
+            Some((m.ticker.clone(), members, buckets))
+        })
+        .max_by(|a, b| (a.1 - a.2).abs().total_cmp(&(b.1 - b.2).abs()))
+}
+
 /// Fallback: compute ensemble YES probability from 2°F temperature buckets.
-fn compute_ensemble_yes_from_buckets(buckets: &[TempBucketProbability], market_type: &MarketType) -> f64 {
+pub fn compute_ensemble_yes_from_buckets(buckets: &[TempBucketProbability], market_type: &MarketType) -> f64 {
     match market_type {
         MarketType::Above(threshold) => {
             let mut prob = 0.0;
@@ -248,40 +698,28 @@ fn compute_ensemble_yes_from_buckets(buckets: &[TempBucketProbability], market_t
     }
 }
 
-/// Estimate Kalshi taker fee as a fraction (percentage points).
-/// Kalshi charges: fee_per_contract = min(price, 100-price) * fee_rate
-/// where fee_rate is ~7% for taker (crossing the spread).
-/// Returns fee as a fraction of notional (i.e., divide by 100 to get per-cent).
-fn estimate_fee_pp(shares: u32, price_cents: u32) -> f64 {
-    let fee_rate = 0.07; // 7% taker fee
-    let capped_price = price_cents.min(100 - price_cents) as f64;
-    let fee_per_contract = capped_price * fee_rate;
-    // Convert to percentage points: fee in cents / 100 cents per dollar
-    fee_per_contract * shares as f64 / (shares as f64 * 100.0)
-}
-
-fn size_from_edge(_edge: f64) -> u32 {
+pub fn size_from_edge(_edge: f64) -> u32 {
     50
 }
 
-fn spread_aware_price(market: &MarketState, orderbook: &Orderbook, side: &Side) -> u32 {
+/// Limit price for `side`: the ask when the spread is tight or the ask is
+/// deep, else the mid. None when nobody offers the side. With no bid there
+/// is no mid to speak of, so the ask is taken only up to `fair_cents` less
+/// the edge floor — never a price averaged with a placeholder.
+fn spread_aware_price(market: &MarketState, orderbook: &Orderbook, side: &Side, fair_cents: u32, floor_pp: f64) -> Option<u32> {
     let (bid, ask, ob_levels) = match side {
-        Side::Yes => (
-            market.yes_bid.unwrap_or(1),
-            market.yes_ask.unwrap_or(99),
-            &orderbook.yes,
-        ),
-        Side::No => (
-            market.no_bid.unwrap_or(1),
-            market.no_ask.unwrap_or(99),
-            &orderbook.no,
-        ),
+        Side::Yes => (market.yes_bid, market.yes_ask?, &orderbook.yes),
+        Side::No => (market.no_bid, market.no_ask?, &orderbook.no),
+    };
+    let Some(bid) = bid.filter(|&b| b > 0) else {
+        let cap = fair_cents.saturating_sub(floor_pp.ceil() as u32).max(1);
+        return Some(ask.min(cap));
     };
 
     let spread = ask.saturating_sub(bid);
 
     if spread <= 4 {
-        return ask;
+        return Some(ask);
     }
 
     let ask_depth: u32 = ob_levels
@@ -291,8 +729,95 @@ fn spread_aware_price(market: &MarketState, orderbook: &Orderbook, side: &Side)
         .sum();
 
     if ask_depth >= 10 {
-        ask
+        Some(ask)
     } else {
-        (bid + ask) / 2
+        Some((bid + ask) / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::stats;
+
+    fn context(members: Vec<f64>, confidence: ForecastConfidence, yes_ask: u32) -> DecisionContext {
+        let weather: WeatherSnapshot = serde_json::from_value(serde_json::json!({
+            "city": "New York",
+            "current_temp_f": 70.0,
+            "nws_forecast_high": 84.0,
+            "open_meteo_forecast_high": 84.0,
+            "remaining_forecast_high_f": 84.0,
+            "hourly_forecasts": [],
+            "bucket_probabilities": [],
+            "ensemble_member_highs": members,
+            "confidence": confidence,
+        }))
+        .unwrap();
+        DecisionContext {
+            prompt_md: String::new(),
+            stats: stats::compute(&[]),
+            last_n_trades: Vec::new(),
+            market: MarketState {
+                ticker: "KXHIGHNY-26OCT16-T80".into(),
+                event_ticker: "KXHIGHNY-26OCT16".into(),
+                title: String::new(),
+                yes_bid: Some(yes_ask - 2),
+                yes_ask: Some(yes_ask),
+                no_bid: Some(98 - yes_ask),
+                no_ask: Some(102 - yes_ask),
+                last_price: None,
+                volume: 5000,
+                volume_24h: 2000,
+                open_interest: 1000,
+                expiration_time: String::new(),
+                minutes_to_expiry: 600.0,
+                minutes_since_open: None,
+                floor_strike: Some(80.0),
+                cap_strike: None,
+                strike_type: "greater".into(),
+                position_limit_cents: None,
+            },
+            orderbook: Orderbook { yes: vec![(yes_ask - 2, 100)], no: vec![(98 - yes_ask, 100)] },
+            weather: Some(weather),
+            implied: None,
+            exposure: Exposure::default(),
+            fair_override: None,
+            features: FeatureSwitches::default(),
+        }
+    }
+
+    fn breakdown(ctx: &DecisionContext) -> EdgeBreakdown {
+        RulesBrain::new(StrategyParams::default()).evaluate(ctx, None).edge.expect("priced far enough for an edge")
+    }
+
+    fn assert_sums(e: &EdgeBreakdown) {
+        let sum = e.raw_pp + e.bias_pp + e.confidence_pp + e.fee_pp + e.rounding_pp;
+        assert!((sum - e.net_pp).abs() < 1e-9, "{:?} sums to {}", e, sum);
+    }
+
+    #[test]
+    fn breakdown_carries_the_probability_bound() {
+        // Every one of 10 members clears 80°F: 100% bounded to 11/12
+        let e = breakdown(&context((0..10).map(|i| 82.0 + i as f64 * 0.4).collect(), ForecastConfidence::High, 60));
+        assert_eq!(e.side, Side::Yes);
+        assert!((e.raw_pp - 40.0).abs() < 1e-9);
+        assert!((e.bias_pp + 100.0 / 12.0).abs() < 1e-9);
+        assert_eq!(e.confidence_pp, 0.0);
+        assert_eq!(e.dominant(), "bias");
+        assert_sums(&e);
+    }
+
+    #[test]
+    fn breakdown_carries_confidence_and_fee_rounding() {
+        // 7 of 10 members above 80°F: no bound, Medium confidence shrinks it
+        let members = (0..10).map(|i| if i < 7 { 83.0 } else { 78.0 }).collect();
+        let e = breakdown(&context(members, ForecastConfidence::Medium, 41));
+        assert_eq!(e.side, Side::Yes);
+        assert_eq!(e.bias_pp, 0.0);
+        assert!(e.confidence_pp < 0.0);
+        assert!(e.fee_pp < 0.0);
+        // 50 contracts at 41¢ owe 84.665¢ of fee and are charged 85¢
+        assert!((e.rounding_pp + 0.335 / 50.0).abs() < 1e-9, "{}", e.rounding_pp);
+        assert_sums(&e);
     }
 }
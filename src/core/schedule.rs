@@ -0,0 +1,78 @@
+use crate::core::types::CityConfig;
+use chrono::{DateTime, Duration, Utc};
+
+/// When an ensemble model's runs start and roughly how long after init they
+/// show up on Open-Meteo.
+pub struct ModelSchedule {
+    pub name: &'static str,
+    /// UTC init hours
+    pub cycle_hours: &'static [u32],
+    pub publish_delay_minutes: i64,
+}
+
+/// The models behind `WeatherClient`'s ensemble request.
+pub const MODELS: [ModelSchedule; 5] = [
+    ModelSchedule { name: "ICON", cycle_hours: &[0, 6, 12, 18], publish_delay_minutes: 240 },
+    ModelSchedule { name: "GFS", cycle_hours: &[0, 6, 12, 18], publish_delay_minutes: 300 },
+    ModelSchedule { name: "GEM", cycle_hours: &[0, 12], publish_delay_minutes: 330 },
+    ModelSchedule { name: "ECMWF AIFS", cycle_hours: &[0, 6, 12, 18], publish_delay_minutes: 390 },
+    ModelSchedule { name: "ECMWF IFS", cycle_hours: &[0, 6, 12, 18], publish_delay_minutes: 450 },
+];
+
+impl ModelSchedule {
+    /// Publish times of this model's runs from two days before `now`
+    /// through the day after.
+    fn publish_times(&self, now: DateTime<Utc>) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        let midnight = now.date_naive().and_hms_opt(0, 0, 0).expect("valid midnight").and_utc();
+        (-2..=1i64).flat_map(move |day| {
+            self.cycle_hours.iter().map(move |h| {
+                midnight
+                    + Duration::days(day)
+                    + Duration::hours(*h as i64)
+                    + Duration::minutes(self.publish_delay_minutes)
+            })
+        })
+    }
+
+    pub fn latest_publish(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.publish_times(now).filter(|t| *t <= now).max()
+    }
+
+    pub fn next_publish(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.publish_times(now)
+            .filter(|t| *t > now)
+            .min()
+            .expect("a run publishes within a day")
+    }
+}
+
+/// A city needs a forecast refresh when any model has published a run
+/// (plus the city's stagger offset) since its last refresh.
+pub fn refresh_due(city: &CityConfig, last_refresh: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    let Some(last) = last_refresh else { return true };
+    let offset = Duration::minutes(city.refresh_offset_minutes as i64);
+    MODELS
+        .iter()
+        .filter_map(|m| m.latest_publish(now - offset))
+        .any(|published| published + offset > last)
+}
+
+/// When loop mode should start its next cycle: `interval_minutes` from now,
+/// or sooner if a new model run makes some city due first (MODEL_SCHEDULE).
+pub fn next_cycle(now: DateTime<Utc>, interval_minutes: u64, cities: &[CityConfig], model_schedule: bool) -> DateTime<Utc> {
+    let regular = now + Duration::minutes(interval_minutes as i64);
+    if !model_schedule {
+        return regular;
+    }
+    cities.iter().map(|c| next_trigger(c, now).0).fold(regular, DateTime::min)
+}
+
+/// Next time any model's new run makes this city due.
+pub fn next_trigger(city: &CityConfig, now: DateTime<Utc>) -> (DateTime<Utc>, &'static str) {
+    let offset = Duration::minutes(city.refresh_offset_minutes as i64);
+    MODELS
+        .iter()
+        .map(|m| (m.next_publish(now - offset) + offset, m.name))
+        .min_by_key(|(t, _)| *t)
+        .expect("MODELS is non-empty")
+}
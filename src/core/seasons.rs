@@ -0,0 +1,145 @@
+//! Seasonal strategy profiles: brain/seasons.toml patches the strategy
+//! parameters for some cities during some months, so edge floors and
+//! confidence scales can follow the season (Miami summers, Chicago
+//! winters). Read with the config, so again on a loop-mode config reload;
+//! profiles apply in file order, a later match overriding an earlier one.
+//!
+//! ```toml
+//! [[profile]]
+//! cities = ["Miami"]            # names or series tickers; omit for every city
+//! season = "summer"             # winter (Dec–Feb), spring, summer, fall
+//! min_net_edge_pp = 8.0
+//! confidence_low_scale = 0.3
+//!
+//! [[profile]]
+//! cities = ["KXHIGHCHI"]
+//! months = [1, 2]
+//! max_source_disagreement_f = 6.0
+//! ```
+
+use crate::core::types::StrategyParams;
+use serde::Deserialize;
+
+pub const PATH: &str = "brain/seasons.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Seasons {
+    #[serde(default)]
+    pub profile: Vec<Profile>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// City names or series tickers; empty matches every city
+    #[serde(default)]
+    pub cities: Vec<String>,
+    pub season: Option<String>,
+    /// 1–12, added to the season's months
+    #[serde(default)]
+    pub months: Vec<u32>,
+    /// Why, for the logs
+    pub note: Option<String>,
+    pub min_net_edge_pp: Option<f64>,
+    pub confidence_medium_scale: Option<f64>,
+    pub confidence_low_scale: Option<f64>,
+    pub max_source_disagreement_f: Option<f64>,
+    pub no_trade_zone_pp: Option<f64>,
+    pub no_trade_zone_min_std_f: Option<f64>,
+    pub tail_confirm_prob: Option<f64>,
+    pub prob_floor: Option<f64>,
+    pub prob_ceiling: Option<f64>,
+    pub nowcast_weight: Option<f64>,
+    pub hrrr_sd_f: Option<f64>,
+    pub size_iqr_scale: Option<f64>,
+    pub size_tail_scale: Option<f64>,
+    pub ensemble_trim: Option<usize>,
+}
+
+/// Meteorological seasons, northern hemisphere.
+fn season_months(season: &str) -> Option<[u32; 3]> {
+    match season.to_ascii_lowercase().as_str() {
+        "winter" => Some([12, 1, 2]),
+        "spring" => Some([3, 4, 5]),
+        "summer" => Some([6, 7, 8]),
+        "fall" | "autumn" => Some([9, 10, 11]),
+        _ => None,
+    }
+}
+
+impl Seasons {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let seasons: Self = toml::from_str(text)?;
+        for (i, p) in seasons.profile.iter().enumerate() {
+            if let Some(s) = p.season.as_deref().filter(|s| season_months(s).is_none()) {
+                anyhow::bail!("profile {}: unknown season {:?} — winter, spring, summer or fall", i + 1, s);
+            }
+            if let Some(m) = p.months.iter().find(|m| !(1..=12).contains(*m)) {
+                anyhow::bail!("profile {}: month {} is outside 1..=12", i + 1, m);
+            }
+            if p.season.is_none() && p.months.is_empty() {
+                anyhow::bail!("profile {}: needs a season or months", i + 1);
+            }
+        }
+        Ok(seasons)
+    }
+
+    /// brain/seasons.toml, or no profiles when the file is absent. A file
+    /// that doesn't parse is an error: it would silently trade the base
+    /// parameters.
+    pub fn load() -> anyhow::Result<Self> {
+        match std::fs::read_to_string(PATH) {
+            Ok(text) => Self::parse(&text).map_err(|e| anyhow::anyhow!("{}: {}", PATH, e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Profiles for the city (by name or series ticker) in `month`, in
+    /// file order.
+    pub fn matching<'a>(&'a self, city: &'a str, series: &'a str, month: u32) -> impl Iterator<Item = &'a Profile> + 'a {
+        self.profile.iter().filter(move |p| p.matches(city, series, month))
+    }
+
+    /// `base` with every matching profile applied.
+    pub fn params(&self, base: &StrategyParams, city: &str, series: &str, month: u32) -> StrategyParams {
+        let mut params = base.clone();
+        for p in self.matching(city, series, month) {
+            p.apply(&mut params);
+        }
+        params
+    }
+}
+
+impl Profile {
+    fn matches(&self, city: &str, series: &str, month: u32) -> bool {
+        let city_ok = self.cities.is_empty() || self.cities.iter().any(|c| c.eq_ignore_ascii_case(city) || c == series);
+        let month_ok = self.months.contains(&month)
+            || self.season.as_deref().and_then(season_months).is_some_and(|m| m.contains(&month));
+        city_ok && month_ok
+    }
+
+    fn apply(&self, p: &mut StrategyParams) {
+        let set = |field: &mut f64, value: Option<f64>| {
+            if let Some(v) = value {
+                *field = v;
+            }
+        };
+        set(&mut p.min_net_edge_pp, self.min_net_edge_pp);
+        set(&mut p.confidence_medium_scale, self.confidence_medium_scale);
+        set(&mut p.confidence_low_scale, self.confidence_low_scale);
+        set(&mut p.max_source_disagreement_f, self.max_source_disagreement_f);
+        set(&mut p.no_trade_zone_pp, self.no_trade_zone_pp);
+        set(&mut p.no_trade_zone_min_std_f, self.no_trade_zone_min_std_f);
+        set(&mut p.tail_confirm_prob, self.tail_confirm_prob);
+        set(&mut p.prob_floor, self.prob_floor);
+        set(&mut p.prob_ceiling, self.prob_ceiling);
+        set(&mut p.nowcast_weight, self.nowcast_weight);
+        set(&mut p.hrrr_sd_f, self.hrrr_sd_f);
+        set(&mut p.size_iqr_scale, self.size_iqr_scale);
+        set(&mut p.size_tail_scale, self.size_tail_scale);
+        if let Some(trim) = self.ensemble_trim {
+            p.ensemble_trim = trim;
+        }
+    }
+}
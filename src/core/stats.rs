@@ -1,25 +1,31 @@
-use crate::core::types::{LedgerRow, Stats};
+use crate::core::risk;
+use crate::core::types::{
+    Analytics, CalibrationBin, CancelReason, Cents, DailyPnl, FeeDrag, ForecastSkill, JournalEntry, LedgerRow, MonthlyPnl, PassReason,
+    RuleAttribution, Side, Stats,
+};
+use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub fn compute(ledger: &[LedgerRow]) -> Stats {
     let done: Vec<&LedgerRow> = ledger
         .iter()
-        .filter(|r| r.result == "win" || r.result == "loss")
+        .filter(|r| r.is_settled())
         .collect();
 
     let wins = done.iter().filter(|r| r.result == "win").count() as u32;
     let losses = done.iter().filter(|r| r.result == "loss").count() as u32;
     let total = wins + losses;
-    let total_pnl: i64 = done.iter().map(|r| r.pnl_cents).sum();
+    let total_pnl: Cents = done.iter().map(|r| r.pnl_cents).sum();
 
     let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-    let today_pnl: i64 = done
+    let today_pnl: Cents = done
         .iter()
         .filter(|r| r.timestamp.starts_with(&today))
         .map(|r| r.pnl_cents)
         .sum();
 
     let mut streak: i32 = 0;
-    for row in done.iter().rev() {
+    for row in done.iter().rev().filter(|r| !r.is_scratch()) {
         let is_win = row.result == "win";
         if streak == 0 {
             streak = if is_win { 1 } else { -1 };
@@ -30,12 +36,12 @@ pub fn compute(ledger: &[LedgerRow]) -> Stats {
         }
     }
 
-    let win_pnl: Vec<i64> = done
+    let win_pnl: Vec<Cents> = done
         .iter()
         .filter(|r| r.result == "win")
         .map(|r| r.pnl_cents)
         .collect();
-    let loss_pnl: Vec<i64> = done
+    let loss_pnl: Vec<Cents> = done
         .iter()
         .filter(|r| r.result == "loss")
         .map(|r| r.pnl_cents)
@@ -55,22 +61,54 @@ pub fn compute(ledger: &[LedgerRow]) -> Stats {
         current_streak: streak,
         max_drawdown_cents: max_drawdown(&done),
         avg_win_cents: if wins > 0 {
-            win_pnl.iter().sum::<i64>() as f64 / wins as f64
+            win_pnl.iter().copied().sum::<Cents>().0 as f64 / wins as f64
         } else {
             0.0
         },
         avg_loss_cents: if losses > 0 {
-            loss_pnl.iter().sum::<i64>() as f64 / losses as f64
+            loss_pnl.iter().copied().sum::<Cents>().0 as f64 / losses as f64
         } else {
             0.0
         },
+        pass_reasons: Vec::new(),
+        rule_attribution: Vec::new(),
+        fee_drag: Vec::new(),
+        cancel_reasons: cancel_reason_counts(ledger),
+        voided: ledger.iter().filter(|r| r.is_void()).count() as u32,
+        analytics: None,
     }
 }
 
-fn max_drawdown(trades: &[&LedgerRow]) -> i64 {
-    let mut peak: i64 = 0;
-    let mut running: i64 = 0;
-    let mut worst: i64 = 0;
+/// Count cancelled ledger rows by reason, most frequent first.
+fn cancel_reason_counts(ledger: &[LedgerRow]) -> Vec<(Option<CancelReason>, u32)> {
+    let mut counts: BTreeMap<Option<CancelReason>, u32> = BTreeMap::new();
+    for row in ledger.iter().filter(|r| r.is_cancelled()) {
+        *counts.entry(row.cancel_reason).or_insert(0) += 1;
+    }
+    let mut sorted: Vec<(Option<CancelReason>, u32)> = counts.into_iter().collect();
+    sorted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    sorted
+}
+
+/// Count PASS decisions in the journal by reason, most frequent first.
+pub fn pass_reason_counts(journal: &[JournalEntry]) -> Vec<(PassReason, u32)> {
+    let mut counts: BTreeMap<PassReason, u32> = BTreeMap::new();
+    for entry in journal {
+        if let JournalEntry::Decision(d) = entry {
+            if let Some(reason) = d.pass_reason {
+                *counts.entry(reason).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut sorted: Vec<(PassReason, u32)> = counts.into_iter().collect();
+    sorted.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    sorted
+}
+
+fn max_drawdown(trades: &[&LedgerRow]) -> Cents {
+    let mut peak = Cents::ZERO;
+    let mut running = Cents::ZERO;
+    let mut worst = Cents::ZERO;
     for t in trades {
         running += t.pnl_cents;
         if running > peak {
@@ -83,3 +121,458 @@ fn max_drawdown(trades: &[&LedgerRow]) -> i64 {
     }
     worst
 }
+
+/// Settle every counterfactual in the journal against recorded market
+/// outcomes and total the P&L per (month, rule). Each ticker counts once
+/// per rule — the first blocked evaluation — since the same bracket is
+/// re-scanned every cycle.
+pub fn rule_attribution(journal: &[JournalEntry]) -> Vec<RuleAttribution> {
+    let outcomes: HashMap<&str, &str> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::MarketOutcome(o) if !o.is_void() => Some((o.ticker.as_str(), o.result.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let mut seen: std::collections::HashSet<(&str, PassReason)> = std::collections::HashSet::new();
+    let mut table: BTreeMap<(String, PassReason), RuleAttribution> = BTreeMap::new();
+
+    for entry in journal {
+        let JournalEntry::Decision(d) = entry else { continue };
+        let Some(cf) = &d.counterfactual else { continue };
+        let Some(result) = outcomes.get(d.ticker.as_str()) else { continue };
+        if !seen.insert((d.ticker.as_str(), cf.rule)) {
+            continue;
+        }
+
+        let won = matches!((&cf.side, *result), (Side::Yes, "yes") | (Side::No, "no"));
+        let pnl = if won {
+            Cents::for_contracts(cf.shares, 100 - cf.price_cents)
+        } else {
+            -Cents::for_contracts(cf.shares, cf.price_cents)
+        };
+
+        let month = d.timestamp.get(..7).unwrap_or_default().to_string();
+        let row = table.entry((month.clone(), cf.rule)).or_insert(RuleAttribution {
+            month,
+            rule: cf.rule,
+            blocked: 0,
+            wins: 0,
+            losses: 0,
+            pnl_cents: Cents::ZERO,
+        });
+        row.blocked += 1;
+        if won {
+            row.wins += 1;
+        } else {
+            row.losses += 1;
+        }
+        row.pnl_cents += pnl;
+    }
+
+    table.into_values().collect()
+}
+
+/// Lead-time bucket for a forecast made `minutes` before expiry.
+fn lead_time_bucket(minutes: Option<f64>) -> &'static str {
+    match minutes {
+        None => "unknown",
+        Some(m) if m < 60.0 => "<1h",
+        Some(m) if m < 360.0 => "1-6h",
+        Some(m) if m < 1440.0 => "6-24h",
+        Some(_) => "24h+",
+    }
+}
+
+/// Brier score and expected calibration error of journaled model
+/// probabilities against settled outcomes, per city and lead-time bucket,
+/// plus an "all" row per city. A bracket is re-priced every cycle, so only
+/// its latest forecast within each lead-time bucket counts.
+pub fn forecast_skill(journal: &[JournalEntry]) -> Vec<ForecastSkill> {
+    const BINS: usize = 10;
+
+    let outcomes: HashMap<&str, bool> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::MarketOutcome(o) if !o.is_void() => Some((o.ticker.as_str(), o.result == "yes")),
+            _ => None,
+        })
+        .collect();
+
+    let mut latest: HashMap<(&str, &str, &'static str), (f64, bool)> = HashMap::new();
+    for entry in journal {
+        let JournalEntry::Decision(d) = entry else { continue };
+        let Some(p) = d.model_probability else { continue };
+        let Some(&yes) = outcomes.get(d.ticker.as_str()) else { continue };
+        let lead = lead_time_bucket(d.minutes_to_expiry);
+        latest.insert((d.city.as_str(), d.ticker.as_str(), lead), (p.clamp(0.0, 1.0), yes));
+    }
+
+    let mut groups: BTreeMap<(String, String), Vec<(f64, bool)>> = BTreeMap::new();
+    for ((city, _, lead), pair) in latest {
+        groups.entry((city.to_string(), lead.to_string())).or_default().push(pair);
+        groups.entry((city.to_string(), "all".to_string())).or_default().push(pair);
+    }
+
+    groups
+        .into_iter()
+        .map(|((city, lead_time), pairs)| {
+            let n = pairs.len() as f64;
+            let outcome = |yes: bool| if yes { 1.0 } else { 0.0 };
+            let brier = pairs.iter().map(|(p, yes)| (p - outcome(*yes)).powi(2)).sum::<f64>() / n;
+
+            let mut bins = [(0.0_f64, 0.0_f64, 0u32); BINS];
+            for (p, yes) in &pairs {
+                let i = ((p * BINS as f64) as usize).min(BINS - 1);
+                bins[i].0 += p;
+                bins[i].1 += outcome(*yes);
+                bins[i].2 += 1;
+            }
+            let ece = bins
+                .iter()
+                .filter(|(_, _, count)| *count > 0)
+                // weight (count / n) × |mean p − hit rate| = |Σp − Σhits| / n
+                .map(|(p_sum, hit_sum, _)| (p_sum - hit_sum).abs() / n)
+                .sum();
+
+            ForecastSkill {
+                city,
+                lead_time,
+                forecasts: pairs.len() as u32,
+                brier,
+                ece,
+                calibration: bins
+                    .iter()
+                    .filter(|(_, _, count)| *count > 0)
+                    .map(|(p_sum, hit_sum, count)| CalibrationBin {
+                        forecasts: *count,
+                        mean_probability: p_sum / *count as f64,
+                        hit_rate: hit_sum / *count as f64,
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Fee drag per month plus an "all" row, over settled ledger rows. Fills
+/// with a fee in the trade journal use it; the rest fall back to the taker
+/// estimate, which overstates fees on maker fills.
+pub fn fee_drag(ledger: &[LedgerRow], journal: &[JournalEntry]) -> Vec<FeeDrag> {
+    let actual: HashMap<&str, Cents> = journal
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::Trade(t) => Some((t.order_id.as_str(), t.fee_cents?)),
+            _ => None,
+        })
+        .collect();
+
+    let mut table: BTreeMap<String, FeeDrag> = BTreeMap::new();
+    for row in ledger.iter().filter(|r| r.is_settled()) {
+        let month = row.timestamp.get(..7).unwrap_or_default().to_string();
+        let fee = actual.get(row.order_id.as_str()).copied();
+        for period in [month, "all".to_string()] {
+            let drag = table.entry(period.clone()).or_insert(FeeDrag {
+                period,
+                trades: 0,
+                gross_pnl_cents: Cents::ZERO,
+                actual_fees_cents: Cents::ZERO,
+                estimated_fees_cents: Cents::ZERO,
+            });
+            drag.trades += 1;
+            drag.gross_pnl_cents += row.pnl_cents;
+            match fee {
+                Some(cents) => drag.actual_fees_cents += cents,
+                None => drag.estimated_fees_cents += risk::taker_fee_cents(row.shares, row.price),
+            }
+        }
+    }
+
+    // "all" sorts after the "YYYY-MM" keys
+    table.into_values().collect()
+}
+
+/// Trading days in the rolling Sharpe-like ratio.
+pub const SHARPE_WINDOW_DAYS: usize = 30;
+
+/// Equity curve, monthly P&L and drawdown figures over settled rows.
+pub fn analytics(ledger: &[LedgerRow]) -> Analytics {
+    let done: Vec<&LedgerRow> = ledger.iter().filter(|r| r.is_settled()).collect();
+
+    let mut by_day: BTreeMap<&str, Cents> = BTreeMap::new();
+    for row in &done {
+        *by_day.entry(row.timestamp.get(..10).unwrap_or_default()).or_insert(Cents::ZERO) += row.pnl_cents;
+    }
+    let mut equity = Cents::ZERO;
+    let daily: Vec<DailyPnl> = by_day
+        .into_iter()
+        .map(|(date, pnl)| {
+            equity += pnl;
+            DailyPnl { date: date.to_string(), pnl_cents: pnl, equity_cents: equity }
+        })
+        .collect();
+
+    let mut months: BTreeMap<&str, Vec<&LedgerRow>> = BTreeMap::new();
+    for row in &done {
+        months.entry(row.timestamp.get(..7).unwrap_or_default()).or_default().push(row);
+    }
+    let monthly = months
+        .into_iter()
+        .map(|(month, rows)| MonthlyPnl {
+            month: month.to_string(),
+            trades: rows.iter().filter(|r| r.result == "win" || r.result == "loss").count() as u32,
+            wins: rows.iter().filter(|r| r.result == "win").count() as u32,
+            losses: rows.iter().filter(|r| r.result == "loss").count() as u32,
+            pnl_cents: rows.iter().map(|r| r.pnl_cents).sum(),
+            max_drawdown_cents: max_drawdown(&rows),
+        })
+        .collect();
+
+    let mut longest_losing_streak = 0;
+    let mut run = 0;
+    for row in &done {
+        match row.result.as_str() {
+            "loss" => {
+                run += 1;
+                longest_losing_streak = longest_losing_streak.max(run);
+            }
+            "win" => run = 0,
+            _ => {}
+        }
+    }
+
+    let pnl: Vec<f64> = daily.iter().map(|d| d.pnl_cents.0 as f64).collect();
+    let (longest_drawdown_days, current_drawdown_days) = drawdown_days(&daily);
+    Analytics {
+        rolling_sharpe: sharpe(&pnl[pnl.len().saturating_sub(SHARPE_WINDOW_DAYS)..]),
+        sharpe: sharpe(&pnl),
+        longest_losing_streak,
+        max_drawdown_cents: max_drawdown(&done),
+        longest_drawdown_days,
+        current_drawdown_days,
+        daily,
+        monthly,
+    }
+}
+
+/// Mean over sample standard deviation, annualized by √365 — a Sharpe
+/// ratio on P&L rather than returns, with no risk-free rate.
+fn sharpe(daily_pnl: &[f64]) -> Option<f64> {
+    if daily_pnl.len() < 2 {
+        return None;
+    }
+    let n = daily_pnl.len() as f64;
+    let mean = daily_pnl.iter().sum::<f64>() / n;
+    let var = daily_pnl.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (var > 0.0).then(|| mean / var.sqrt() * 365f64.sqrt())
+}
+
+/// Longest and current peak-to-recovery spans of the daily equity curve,
+/// in calendar days. The curve starts at a zero-P&L peak before the first
+/// trading day.
+fn drawdown_days(daily: &[DailyPnl]) -> (i64, i64) {
+    let date = |d: &DailyPnl| chrono::NaiveDate::parse_from_str(&d.date, "%Y-%m-%d").ok();
+    let Some(first) = daily.first().and_then(date) else { return (0, 0) };
+    let mut peak = Cents::ZERO;
+    let mut peak_date = first - chrono::Duration::days(1);
+    let mut longest = 0;
+    let mut current = 0;
+    for day in daily {
+        let Some(d) = date(day) else { continue };
+        if day.equity_cents >= peak {
+            if current > 0 {
+                longest = longest.max((d - peak_date).num_days());
+            }
+            peak = day.equity_cents;
+            peak_date = d;
+            current = 0;
+        } else {
+            current = (d - peak_date).num_days();
+            longest = longest.max(current);
+        }
+    }
+    (longest, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::{Action, DecisionRecord, OutcomeRecord};
+
+    fn row(day: &str, result: &str, pnl: i64) -> LedgerRow {
+        LedgerRow {
+            timestamp: format!("{}T15:00:00+00:00", day),
+            ticker: "KXHIGHNY-26FEB12-B40.5".into(),
+            side: "yes".into(),
+            shares: 1,
+            price: 40,
+            result: result.into(),
+            pnl_cents: Cents(pnl),
+            cumulative_cents: Cents::ZERO,
+            order_id: format!("o-{}-{}", day, result),
+            cancel_reason: None,
+            event_ticker: "KXHIGHNY-26FEB12".into(),
+            city: "New York".into(),
+        }
+    }
+
+    fn decision(ticker: &str, p: Option<f64>, minutes_to_expiry: f64) -> JournalEntry {
+        JournalEntry::Decision(DecisionRecord {
+            timestamp: "2026-02-12T15:00:00+00:00".into(),
+            city: "New York".into(),
+            event_ticker: "KXHIGHNY-26FEB12".into(),
+            ticker: ticker.into(),
+            action: Action::Pass,
+            side: None,
+            pass_reason: None,
+            edge_magnitude: 0.0,
+            counterfactual: None,
+            model_probability: p,
+            minutes_to_expiry: Some(minutes_to_expiry),
+            edge: None,
+            market_kind: None,
+            confidence: None,
+            prompt_version: None,
+            strategy_version: None,
+            operator_override: None,
+            inputs: None,
+        })
+    }
+
+    fn outcome(ticker: &str, result: &str) -> JournalEntry {
+        JournalEntry::MarketOutcome(OutcomeRecord {
+            timestamp: "2026-02-13T06:00:00+00:00".into(),
+            ticker: ticker.into(),
+            result: result.into(),
+        })
+    }
+
+    #[test]
+    fn forecast_skill_scores_the_latest_forecast_per_lead_time() {
+        let journal = [
+            decision("A", Some(0.8), 120.0),
+            decision("A", Some(0.9), 30.0),
+            decision("B", Some(0.3), 30.0),
+            // Re-priced in the same bucket: only 0.2 counts
+            decision("D", Some(0.6), 200.0),
+            decision("D", Some(0.2), 200.0),
+            decision("V", Some(0.75), 45.0),
+            decision("N", None, 45.0),
+            outcome("A", "yes"),
+            outcome("B", "no"),
+            outcome("D", "yes"),
+            outcome("V", "void"),
+            outcome("N", "yes"),
+        ];
+        let skill = forecast_skill(&journal);
+        let rows: Vec<(&str, u32)> = skill.iter().map(|s| (s.lead_time.as_str(), s.forecasts)).collect();
+        assert_eq!(rows, [("1-6h", 2), ("<1h", 2), ("all", 4)]);
+
+        // 1-6h: A at 0.8 won, D at 0.2 won
+        // Brier ((0.2)² + (0.8)²) / 2; ECE (|0.8 − 1| + |0.2 − 1|) / 2
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(skill[0].brier, 0.34), "{}", skill[0].brier);
+        assert!(close(skill[0].ece, 0.5), "{}", skill[0].ece);
+        // <1h: A at 0.9 won, B at 0.3 lost
+        assert!(close(skill[1].brier, 0.05), "{}", skill[1].brier);
+        assert!(close(skill[1].ece, 0.2), "{}", skill[1].ece);
+        // all: (0.01 + 0.04 + 0.09 + 0.64) / 4, and (0.1 + 0.2 + 0.3 + 0.8) / 4
+        assert!(close(skill[2].brier, 0.195), "{}", skill[2].brier);
+        assert!(close(skill[2].ece, 0.35), "{}", skill[2].ece);
+
+        let bins: Vec<(u32, f64, f64)> = skill[2].calibration.iter().map(|b| (b.forecasts, b.mean_probability, b.hit_rate)).collect();
+        assert_eq!(bins, [(1, 0.2, 1.0), (1, 0.3, 0.0), (1, 0.8, 1.0), (1, 0.9, 1.0)]);
+    }
+
+    #[test]
+    fn ece_pools_forecasts_within_a_bin() {
+        // Four forecasts in the 0.6–0.7 bin, mean 0.65, half of them hit:
+        // one bin weighted 1 × |0.65 − 0.5|
+        let journal = [
+            decision("A", Some(0.6), 30.0),
+            decision("B", Some(0.6), 30.0),
+            decision("C", Some(0.7 - 1e-9), 30.0),
+            decision("D", Some(0.7 - 1e-9), 30.0),
+            outcome("A", "yes"),
+            outcome("B", "no"),
+            outcome("C", "yes"),
+            outcome("D", "no"),
+        ];
+        let all = forecast_skill(&journal).into_iter().find(|s| s.lead_time == "all").unwrap();
+        assert_eq!(all.calibration.len(), 1);
+        assert!((all.ece - 0.15).abs() < 1e-8, "{}", all.ece);
+    }
+
+    #[test]
+    fn analytics_equity_curve_and_drawdowns() {
+        let ledger = [
+            row("2026-02-01", "win", 100),
+            row("2026-02-02", "loss", -150),
+            row("2026-02-02", "loss", -50),
+            row("2026-02-03", "cancelled", 0),
+            row("2026-02-04", "win", 120),
+            row("2026-02-05", "win", 200),
+            row("2026-03-01", "loss", -80),
+        ];
+        let a = analytics(&ledger);
+
+        let daily: Vec<(&str, i64, i64)> =
+            a.daily.iter().map(|d| (d.date.as_str(), d.pnl_cents.0, d.equity_cents.0)).collect();
+        assert_eq!(
+            daily,
+            [
+                ("2026-02-01", 100, 100),
+                ("2026-02-02", -200, -100),
+                ("2026-02-04", 120, 20),
+                ("2026-02-05", 200, 220),
+                ("2026-03-01", -80, 140),
+            ]
+        );
+        let monthly: Vec<(&str, u32, u32, u32, i64, i64)> = a
+            .monthly
+            .iter()
+            .map(|m| (m.month.as_str(), m.trades, m.wins, m.losses, m.pnl_cents.0, m.max_drawdown_cents.0))
+            .collect();
+        assert_eq!(monthly, [("2026-02", 5, 3, 2, 220, 200), ("2026-03", 1, 0, 1, -80, 80)]);
+
+        // Trade by trade: 100 → −100 is the worst fall
+        assert_eq!(a.max_drawdown_cents, Cents(200));
+        assert_eq!(a.longest_losing_streak, 2);
+        // Peak 100 on 02-01 regained 02-05 (4 days); peak 220 on 02-05
+        // still not regained on 03-01 (24 days)
+        assert_eq!((a.longest_drawdown_days, a.current_drawdown_days), (24, 24));
+        // Daily P&L mean 28, sample SD √26720, × √365
+        assert!((a.sharpe.unwrap() - 3.272_549_593).abs() < 1e-8, "{:?}", a.sharpe);
+        assert_eq!(a.rolling_sharpe, a.sharpe);
+    }
+
+    #[test]
+    fn drawdown_days_reset_at_a_new_high() {
+        let ledger = [
+            row("2026-02-01", "loss", -50),
+            row("2026-02-03", "win", 80),
+            row("2026-02-04", "win", 10),
+        ];
+        let a = analytics(&ledger);
+        // Below the starting peak from 01-31 until 02-03
+        assert_eq!((a.longest_drawdown_days, a.current_drawdown_days), (3, 0));
+        assert_eq!(a.max_drawdown_cents, Cents(50));
+        assert_eq!(analytics(&ledger[..1]).sharpe, None);
+    }
+
+    #[test]
+    fn scratch_exits_neither_extend_nor_break_a_streak() {
+        let ledger = [
+            row("2026-02-10", "win", 60),
+            row("2026-02-11", "loss", -40),
+            row("2026-02-12", "scratch", 0),
+            row("2026-02-13", "loss", -40),
+            row("2026-02-14", "scratch", 0),
+        ];
+        let stats = compute(&ledger);
+        assert_eq!(stats.current_streak, -2);
+        assert_eq!((stats.wins, stats.losses, stats.total_trades), (1, 2, 3));
+        assert_eq!(stats.total_pnl_cents, Cents(-20));
+    }
+}
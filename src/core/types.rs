@@ -1,4 +1,104 @@
-use serde::Deserialize;
+use crate::core::implied::ImpliedDistribution;
+use crate::core::seasons::Seasons;
+use serde::{Deserialize, Serialize};
+
+// ── Money ──
+
+/// An amount of money in whole cents: P&L, fees, balances and limits.
+/// Per-contract prices stay plain `u32` — they double as probabilities —
+/// and turn into money only through `Cents::for_contracts`. Arithmetic
+/// panics on overflow instead of wrapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cents(pub i64);
+
+impl Cents {
+    pub const ZERO: Cents = Cents(0);
+
+    /// What `shares` contracts at `price_cents` each cost.
+    pub fn for_contracts(shares: u32, price_cents: u32) -> Self {
+        Cents(shares as i64 * price_cents as i64)
+    }
+
+    /// Balances and limits from the exchange, which reports them unsigned.
+    pub fn from_u64(cents: u64) -> Self {
+        Cents(i64::try_from(cents).unwrap_or(i64::MAX))
+    }
+
+    pub fn checked_add(self, other: Cents) -> Option<Cents> {
+        self.0.checked_add(other.0).map(Cents)
+    }
+
+    pub fn checked_sub(self, other: Cents) -> Option<Cents> {
+        self.0.checked_sub(other.0).map(Cents)
+    }
+
+    /// Subtract, stopping at zero — for capital that can't go negative.
+    pub fn saturating_sub_to_zero(self, other: Cents) -> Cents {
+        Cents(self.0.saturating_sub(other.0).max(0))
+    }
+
+    pub fn abs(self) -> Cents {
+        Cents(self.0.abs())
+    }
+}
+
+impl std::ops::Add for Cents {
+    type Output = Cents;
+    fn add(self, other: Cents) -> Cents {
+        self.checked_add(other).expect("cents overflow")
+    }
+}
+
+impl std::ops::Sub for Cents {
+    type Output = Cents;
+    fn sub(self, other: Cents) -> Cents {
+        self.checked_sub(other).expect("cents overflow")
+    }
+}
+
+impl std::ops::Neg for Cents {
+    type Output = Cents;
+    fn neg(self) -> Cents {
+        Cents(self.0.checked_neg().expect("cents overflow"))
+    }
+}
+
+impl std::ops::AddAssign for Cents {
+    fn add_assign(&mut self, other: Cents) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::SubAssign for Cents {
+    fn sub_assign(&mut self, other: Cents) {
+        *self = *self - other;
+    }
+}
+
+impl std::iter::Sum for Cents {
+    fn sum<I: Iterator<Item = Cents>>(iter: I) -> Cents {
+        iter.fold(Cents::ZERO, |a, b| a + b)
+    }
+}
+
+/// Dollars and cents: `$12.34`, `-$0.05`.
+impl std::fmt::Display for Cents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let s = format!("{}${}.{:02}", sign, abs / 100, abs % 100);
+        f.pad(&s)
+    }
+}
+
+impl std::str::FromStr for Cents {
+    type Err = std::num::ParseIntError;
+    /// Parses a whole number of cents, as env vars and the ledger store them.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Cents)
+    }
+}
 
 // ── AI Decision ──
 
@@ -10,22 +110,201 @@ pub struct TradeDecision {
     pub max_price_cents: Option<u32>,
     pub reasoning: String,
     pub edge_magnitude: f64,
+    #[serde(default)]
+    pub pass_reason: Option<PassReason>,
+    #[serde(default)]
+    pub counterfactual: Option<Counterfactual>,
+    /// The strategy's YES probability, when it got far enough to price one
+    #[serde(default)]
+    pub model_probability: Option<f64>,
+    /// How the net edge was built up, once the strategy got as far as one
+    #[serde(default)]
+    pub edge: Option<EdgeBreakdown>,
+}
+
+impl TradeDecision {
+    pub fn with_probability(mut self, probability: Option<f64>) -> Self {
+        self.model_probability = probability;
+        self
+    }
+
+    pub fn with_edge(mut self, edge: EdgeBreakdown) -> Self {
+        self.edge = Some(edge);
+        self
+    }
+}
+
+/// The net edge on the chosen side split into additive adjustments, in
+/// percentage points:
+/// `raw_pp + bias_pp + confidence_pp + fee_pp + rounding_pp == net_pp`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EdgeBreakdown {
+    pub side: Side,
+    /// Ask on that side the edge was measured against
+    pub price_cents: u32,
+    /// Model probability, before any correction, minus the ask-implied probability
+    pub raw_pp: f64,
+    /// Shift from keeping the forecast off 0% and 100% (`PROB_FLOOR`,
+    /// `PROB_CEILING` and the member count)
+    #[serde(default)]
+    pub bias_pp: f64,
+    /// Shrinkage from the forecast-confidence multiplier (≤ 0 for positive edge)
+    pub confidence_pp: f64,
+    /// Estimated taker fee, negative
+    pub fee_pp: f64,
+    /// The fee rounded up to a whole cent on the order, per contract; ≤ 0
+    #[serde(default)]
+    pub rounding_pp: f64,
+    pub net_pp: f64,
+}
+
+impl EdgeBreakdown {
+    /// The adjustment with the largest magnitude — what moved the edge most.
+    pub fn dominant(&self) -> &'static str {
+        let parts = [
+            ("bias", self.bias_pp),
+            ("confidence", self.confidence_pp),
+            ("fee", self.fee_pp),
+            ("rounding", self.rounding_pp),
+        ];
+        parts
+            .iter()
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .map(|(name, _)| *name)
+            .unwrap_or("none")
+    }
+}
+
+/// The trade a veto rule blocked: what the strategy would have bought had
+/// that one rule been switched off.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Counterfactual {
+    pub rule: PassReason,
+    pub side: Side,
+    pub shares: u32,
+    pub price_cents: u32,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Action {
     Buy,
     Pass,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     Yes,
     No,
 }
 
+/// Why a bracket was passed. Carried on every PASS decision so the journal
+/// can answer "why are we passing so much?".
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PassReason {
+    NoWeather,
+    NoQuote,
+    ExtremePrice,
+    UnknownMarketType,
+    NoProbability,
+    EdgeTooSmall,
+    PriceCap,
+    SpreadTooWide,
+    Illiquid,
+    SourceDisagreement,
+    NoTradeZone,
+    Unparseable,
+    AlreadyExposed,
+    /// `pass = true` in brain/overrides.toml
+    OperatorOverride,
+    /// A tail probability no source outside the ensemble agrees with
+    TailUnconfirmed,
+    /// Locked out after repeated order rejections (brain/risk_state.json)
+    TickerLocked,
+    /// Quorum mode: the second ensemble path disagrees or is missing
+    NoQuorum,
+}
+
+impl PassReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PassReason::NoWeather => "no_weather",
+            PassReason::NoQuote => "no_quote",
+            PassReason::ExtremePrice => "extreme_price",
+            PassReason::UnknownMarketType => "unknown_market_type",
+            PassReason::NoProbability => "no_probability",
+            PassReason::EdgeTooSmall => "edge_too_small",
+            PassReason::PriceCap => "price_cap",
+            PassReason::SpreadTooWide => "spread_too_wide",
+            PassReason::Illiquid => "illiquid",
+            PassReason::SourceDisagreement => "source_disagreement",
+            PassReason::NoTradeZone => "no_trade_zone",
+            PassReason::Unparseable => "unparseable",
+            PassReason::AlreadyExposed => "already_exposed",
+            PassReason::OperatorOverride => "operator_override",
+            PassReason::TailUnconfirmed => "tail_unconfirmed",
+            PassReason::TickerLocked => "ticker_locked",
+            PassReason::NoQuorum => "no_quorum",
+        }
+    }
+}
+
+impl std::fmt::Display for PassReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Why a pending ledger row was cancelled.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    /// Left over from an earlier cycle; the only reason before reasons
+    /// were recorded
+    #[default]
+    StaleCycle,
+    /// brain/KILL present
+    Killswitch,
+    /// Maker mode withdrew or replaced the quote
+    Requote,
+    /// Cancelled by hand with `kalshi-bot cancel`
+    Operator,
+    /// Left the book with nothing filled, per the exchange's fills
+    Unfilled,
+}
+
+impl CancelReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CancelReason::StaleCycle => "stale_cycle",
+            CancelReason::Killswitch => "killswitch",
+            CancelReason::Requote => "requote",
+            CancelReason::Operator => "operator",
+            CancelReason::Unfilled => "unfilled",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            CancelReason::StaleCycle,
+            CancelReason::Killswitch,
+            CancelReason::Requote,
+            CancelReason::Operator,
+            CancelReason::Unfilled,
+        ]
+            .into_iter()
+            .find(|r| r.label() == label)
+    }
+}
+
+impl std::fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
 // ── Market Data ──
 
 #[derive(Debug, Clone)]
@@ -43,9 +322,13 @@ pub struct MarketState {
     pub open_interest: u64,
     pub expiration_time: String,
     pub minutes_to_expiry: f64,
+    /// Minutes since the market opened for trading, if the exchange says
+    pub minutes_since_open: Option<f64>,
     pub floor_strike: Option<f64>,
     pub cap_strike: Option<f64>,
     pub strike_type: String,
+    /// Per-market position limit the exchange reports, if any
+    pub position_limit_cents: Option<Cents>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,15 +337,67 @@ pub struct Orderbook {
     pub no: Vec<(u32, u32)>,
 }
 
+impl Orderbook {
+    /// Bids on the other side, as (price, contracts). Kalshi lists bids
+    /// only, so these are `side`'s offers: a NO bid at B is a YES ask at 100−B.
+    fn offers(&self, side: &Side) -> &[(u32, u32)] {
+        match side {
+            Side::Yes => &self.no,
+            Side::No => &self.yes,
+        }
+    }
+
+    /// `side`'s own bids as (price, contracts), best first — what a holder
+    /// of `side` can sell into.
+    pub fn bids(&self, side: &Side) -> Vec<(u32, u32)> {
+        let mut bids = match side {
+            Side::Yes => self.yes.clone(),
+            Side::No => self.no.clone(),
+        };
+        bids.sort_by_key(|b| std::cmp::Reverse(b.0));
+        bids
+    }
+
+    /// `side`'s offers as (ask, contracts), cheapest first.
+    pub fn asks(&self, side: &Side) -> Vec<(u32, u32)> {
+        let mut asks: Vec<(u32, u32)> = self.offers(side).iter().map(|(p, q)| (100u32.saturating_sub(*p), *q)).collect();
+        asks.sort();
+        asks
+    }
+
+    /// Contracts offered at `ask_cents` on `side`.
+    pub fn ask_depth(&self, side: &Side, ask_cents: u32) -> u32 {
+        self.offers(side)
+            .iter()
+            .filter(|(p, _)| *p + ask_cents == 100)
+            .map(|(_, q)| *q)
+            .sum()
+    }
+
+    /// Contracts offered on `side` at its best ask or up to `window_cents`
+    /// above it; 0 when nobody is offering that side at all.
+    pub fn side_depth(&self, side: &Side, window_cents: u32) -> u32 {
+        let offers = self.offers(side);
+        let Some(best) = offers.iter().map(|(p, _)| *p).max() else {
+            return 0;
+        };
+        offers
+            .iter()
+            .filter(|(p, _)| p + window_cents >= best)
+            .map(|(_, q)| *q)
+            .sum()
+    }
+}
+
 // ── Weather Data ──
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HourlyForecast {
     pub time: String,
     pub temperature_f: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnsembleForecast {
     pub model_count: usize,
     pub mean_high: f64,
@@ -75,7 +410,7 @@ pub struct EnsembleForecast {
     pub p90: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TempBucketProbability {
     pub label: String,
     pub lower: f64,
@@ -83,14 +418,24 @@ pub struct TempBucketProbability {
     pub probability: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum ForecastConfidence {
     High,
     Medium,
     Low,
 }
 
-#[derive(Debug, Clone)]
+impl ForecastConfidence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForecastConfidence::High => "high",
+            ForecastConfidence::Medium => "medium",
+            ForecastConfidence::Low => "low",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeatherSnapshot {
     pub city: String,
     pub current_temp_f: f64,
@@ -98,16 +443,106 @@ pub struct WeatherSnapshot {
     pub nws_forecast_low: Option<f64>,
     pub nws_short_forecast: Option<String>,
     pub open_meteo_forecast_high: f64,
+    /// Highest temperature seen so far today (current reading + past hours).
+    pub observed_high_f: Option<f64>,
+    /// Highest forecast temperature for the hours still left today.
+    pub remaining_forecast_high_f: Option<f64>,
     pub hourly_forecasts: Vec<HourlyForecast>,
     pub ensemble: Option<EnsembleForecast>,
     pub bucket_probabilities: Vec<TempBucketProbability>,
     pub ensemble_member_highs: Vec<f64>,
+    /// Init time (RFC 3339) of the ensemble run behind the members
+    pub ensemble_run: Option<String>,
     pub confidence: ForecastConfidence,
+    /// City-local time of the current reading ("YYYY-MM-DDTHH:MM")
+    #[serde(default)]
+    pub local_time: Option<String>,
+    /// For `Measure::Snowfall` the forecast, ensemble, member and bucket
+    /// fields hold the day's snowfall in inches, and the NWS and
+    /// observed/remaining-high fields are empty
+    #[serde(default)]
+    pub measure: Measure,
+    /// National Blend of Models percentiles for the day's high at the
+    /// settlement station, when NBM_WEIGHT is on
+    #[serde(default)]
+    pub nbm: Option<NbmForecast>,
+    /// Today's high from the HRRR short-range run, when HRRR_WEIGHT is on
+    #[serde(default)]
+    pub hrrr_high_f: Option<f64>,
+    /// Built from cached responses (WEATHER_CACHE=replay), not the network
+    #[serde(default)]
+    pub offline: bool,
+    /// The settlement station's readings so far today (METAR)
+    #[serde(default)]
+    pub observations: Option<Observations>,
+    /// Warnings, watches and advisories active for the city
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<WeatherAlert>,
+    /// The ensemble mean fetched a second way, in quorum mode
+    #[serde(default)]
+    pub quorum: Option<QuorumEnsemble>,
+    /// The day's running high at the settlement station, that brackets are
+    /// ruled in or out against. Set only once its observations are
+    /// attached; the model's `observed_high_f` never stands in
+    #[serde(default)]
+    pub observed_high_so_far: Option<f64>,
+}
+
+/// The day's ensemble through a second, independent path
+/// (QUORUM_ENSEMBLE_URL), to check the first against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumEnsemble {
+    /// Host the members came from
+    pub source: String,
+    /// Mean member high; None when the second path failed
+    pub mean: Option<f64>,
+    pub members: usize,
+}
+
+/// NBM probabilistic guidance for one station and day: the high at a
+/// handful of percentiles, from the text bulletin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NbmForecast {
+    pub station: String,
+    /// Issue time of the bulletin (RFC 3339)
+    pub issued: String,
+    /// Ascending by percentile
+    pub quantiles: Vec<Quantile>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Quantile {
+    /// 0–100
+    pub pct: f64,
+    pub value: f64,
+}
+
+/// Station readings for a city's current local day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Observations {
+    pub city: String,
+    /// Station identifier, when the source reports one
+    pub station: Option<String>,
+    /// Highest reading so far today
+    pub observed_high_f: f64,
+    pub latest_temp_f: f64,
+    /// RFC 3339 time of the latest reading
+    pub observed_at: String,
+}
+
+/// An active weather warning, watch or advisory covering a city.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherAlert {
+    /// e.g. "Heat Advisory"
+    pub event: String,
+    pub severity: String,
+    pub headline: String,
+    pub expires: Option<String>,
 }
 
 // ── Market Type (derived from strike fields) ──
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MarketType {
     Above(f64),
     Below(f64),
@@ -116,19 +551,19 @@ pub enum MarketType {
 
 impl MarketType {
     pub fn from_market(m: &MarketState) -> Option<MarketType> {
-        match m.strike_type.as_str() {
-            "greater" | ">" => m.floor_strike.map(MarketType::Above),
-            "less" | "<" => m.cap_strike.map(MarketType::Below),
-            "between" | "between_inclusive" => {
-                match (m.floor_strike, m.cap_strike) {
-                    (Some(lo), Some(hi)) => Some(MarketType::Between(lo, hi)),
-                    _ => None,
-                }
-            }
+        let floor = m.floor_strike.filter(|v| v.is_finite());
+        let cap = m.cap_strike.filter(|v| v.is_finite());
+        match m.strike_type.trim().to_ascii_lowercase().as_str() {
+            "greater" | "greater_or_equal" | "above" | ">" | ">=" => floor.map(MarketType::Above),
+            "less" | "less_or_equal" | "below" | "<" | "<=" => cap.map(MarketType::Below),
+            "between" | "between_inclusive" | "range" => match (floor, cap) {
+                (Some(lo), Some(hi)) => Self::between(lo, hi),
+                _ => None,
+            },
             _ => {
                 // Fallback: infer from which strikes are present
-                match (m.floor_strike, m.cap_strike) {
-                    (Some(lo), Some(hi)) => Some(MarketType::Between(lo, hi)),
+                match (floor, cap) {
+                    (Some(lo), Some(hi)) => Self::between(lo, hi),
                     (Some(t), None) => Some(MarketType::Above(t)),
                     (None, Some(t)) => Some(MarketType::Below(t)),
                     (None, None) => None,
@@ -136,6 +571,67 @@ impl MarketType {
             }
         }
     }
+
+    /// "above", "below" or "between", without the strikes.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MarketType::Above(_) => "above",
+            MarketType::Below(_) => "below",
+            MarketType::Between(..) => "between",
+        }
+    }
+
+    /// Short bracket label for scans and reports: `>72°`, `2.0-4.0in`.
+    pub fn label(&self, measure: Measure) -> String {
+        match self {
+            MarketType::Above(t) => format!(">{}", measure.strike(*t)),
+            MarketType::Below(t) => format!("<{}", measure.strike(*t)),
+            MarketType::Between(lo, hi) => format!("{}-{}", measure.amount(*lo), measure.strike(*hi)),
+        }
+    }
+
+    /// Whether a day that topped out at `high_f` resolves this bracket YES,
+    /// with the same bounds ensemble members are counted against.
+    pub fn yes_at(&self, high_f: f64) -> bool {
+        match self {
+            MarketType::Above(t) => high_f > *t,
+            MarketType::Below(t) => high_f < *t,
+            MarketType::Between(lo, hi) => high_f >= *lo && high_f < *hi,
+        }
+    }
+
+    /// Some series list the bounds high-first; a zero-width range is unusable.
+    fn between(a: f64, b: f64) -> Option<MarketType> {
+        if a == b {
+            return None;
+        }
+        Some(MarketType::Between(a.min(b), a.max(b)))
+    }
+}
+
+/// Normalize a strike written as text — "75", "75°", "75°F", "74.5 ºF",
+/// "−3°" — to degrees Fahrenheit. Celsius-suffixed strikes are converted.
+/// Snowfall strikes — "2\"", "2.5 in", "3 inches" — come back in inches.
+pub fn parse_strike(raw: &str) -> Option<f64> {
+    let s = raw.trim().replace('\u{2212}', "-");
+    let upper = s.to_ascii_uppercase();
+    if let Some(n) = ["INCHES", "INCH", "IN", "\""].iter().find_map(|suffix| upper.strip_suffix(suffix)) {
+        return n.trim_end().parse().ok().filter(|v: &f64| v.is_finite());
+    }
+    let (number, celsius) = if let Some(n) = upper.strip_suffix('C') {
+        (n, true)
+    } else {
+        (upper.strip_suffix('F').unwrap_or(&upper), false)
+    };
+    let number = number
+        .trim_end()
+        .trim_end_matches(['°', 'º', '˚'])
+        .trim_end();
+    let value: f64 = number.parse().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    Some(if celsius { value * 9.0 / 5.0 + 32.0 } else { value })
 }
 
 // ── Orders & Positions ──
@@ -144,6 +640,8 @@ impl MarketType {
 pub struct OrderResult {
     pub order_id: String,
     pub status: String,
+    /// Fees the exchange charged, once the order has fully executed
+    pub fee_cents: Option<Cents>,
 }
 
 #[derive(Debug)]
@@ -154,140 +652,1981 @@ pub struct OrderRequest {
     pub price_cents: u32,
 }
 
-#[derive(Debug)]
+/// Prefix on every client_order_id the bot submits, so it can tell its own
+/// resting orders apart from ones placed by hand on the same account.
+pub const BOT_ORDER_PREFIX: &str = "wxbot-";
+
+#[derive(Debug, Clone)]
 pub struct RestingOrder {
     pub order_id: String,
+    pub client_order_id: String,
     pub ticker: String,
+    pub side: Side,
+    pub price_cents: u32,
+    pub remaining_count: u32,
 }
 
-#[derive(Debug)]
+impl RestingOrder {
+    pub fn is_ours(&self) -> bool {
+        self.client_order_id.starts_with(BOT_ORDER_PREFIX)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Position {
     pub ticker: String,
     pub side: Side,
     pub count: u32,
+    pub exposure_cents: Cents,
 }
 
-#[derive(Debug)]
+/// What is already riding when a brain decides: held positions and the
+/// bot's resting orders on this event (pending paper rows count as held),
+/// plus the same totalled over the whole account.
+#[derive(Debug, Clone, Default)]
+pub struct Exposure {
+    pub positions: Vec<Position>,
+    pub resting: Vec<RestingOrder>,
+    pub portfolio_held_cents: Cents,
+    pub portfolio_resting_cents: Cents,
+    /// Markets with a held position, across every event
+    pub portfolio_markets: usize,
+}
+
+impl Exposure {
+    /// Exposure on this event that buying `side` of `ticker` would double
+    /// up on: any other bracket, or the other side of this one. The same
+    /// bracket and side is adding to a position, which the position limit
+    /// governs.
+    pub fn conflict(&self, ticker: &str, side: &Side) -> Option<String> {
+        let other = |t: &str, s: &Side| t != ticker || s != side;
+        if let Some(p) = self.positions.iter().find(|p| other(&p.ticker, &p.side)) {
+            return Some(format!("holding {}x {:?} on {}", p.count, p.side, p.ticker));
+        }
+        self.resting
+            .iter()
+            .find(|o| other(&o.ticker, &o.side))
+            .map(|o| format!("{}x {:?} resting @ {}¢ on {}", o.remaining_count, o.side, o.price_cents, o.ticker))
+    }
+}
+
+/// How a market finally resolved.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketResult {
+    Yes,
+    No,
+    /// Voided by the exchange (e.g. bad station data); every position is
+    /// refunded at cost
+    Void,
+}
+
+impl MarketResult {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MarketResult::Yes => "yes",
+            MarketResult::No => "no",
+            MarketResult::Void => "void",
+        }
+    }
+
+    /// Parse the exchange's result string. Empty (not yet determined, or
+    /// blanked during maintenance) and unrecognised values are None.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "yes" => Some(MarketResult::Yes),
+            "no" => Some(MarketResult::No),
+            "void" | "voided" | "canceled" | "cancelled" => Some(MarketResult::Void),
+            _ => None,
+        }
+    }
+
+    /// Whether a position on `side` won; None for a void market, which
+    /// neither wins nor loses.
+    pub fn wins(&self, side: &Side) -> Option<bool> {
+        match self {
+            MarketResult::Yes => Some(*side == Side::Yes),
+            MarketResult::No => Some(*side == Side::No),
+            MarketResult::Void => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MarketResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Accepts a result label, null, or any string older builds queued
+/// ("unknown", "") — the unrecognised ones become None.
+fn lenient_market_result<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<MarketResult>, D::Error> {
+    Ok(Option::<String>::deserialize(d)?.as_deref().and_then(MarketResult::parse))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settlement {
     pub ticker: String,
     pub side: Side,
     pub count: u32,
     pub price_cents: u32,
+    /// Account-level "win"/"loss" from revenue, "void", or "unknown"
     pub result: String,
-    pub pnl_cents: i64,
+    pub pnl_cents: Cents,
     pub settled_time: String,
-    pub market_result: String,
+    /// None when the exchange hasn't reported a usable result
+    #[serde(default, deserialize_with = "lenient_market_result")]
+    pub market_result: Option<MarketResult>,
 }
 
-// ── Stats ──
+/// One execution against an order, as the exchange reports it. The ledger
+/// is reconciled against these rather than the order responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub order_id: String,
+    pub ticker: String,
+    pub side: Side,
+    /// False for a sale out of a held position
+    pub buy: bool,
+    pub count: u32,
+    /// Price per contract on `side`
+    pub price_cents: u32,
+    pub created_time: String,
+}
 
-#[derive(Debug)]
-pub struct Stats {
-    pub total_trades: u32,
-    pub wins: u32,
-    pub losses: u32,
-    pub win_rate: f64,
-    pub total_pnl_cents: i64,
-    pub today_pnl_cents: i64,
-    pub current_streak: i32,
-    pub max_drawdown_cents: i64,
-    pub avg_win_cents: f64,
-    pub avg_loss_cents: f64,
+// ── Order Lifecycle ──
+
+/// Where a bot order is in its life:
+/// Intent → Submitted → PartiallyFilled → Filled/Canceled/Rejected → Settled.
+/// Persisted per order in `brain/orders.json`; see `core::lifecycle`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderState {
+    /// Decided on, not yet accepted by the exchange
+    Intent,
+    /// Resting on the book, nothing filled yet
+    Submitted,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    /// Never reached the book
+    Rejected,
+    Settled,
 }
 
-// ── Prompt Context ──
+impl OrderState {
+    pub const ALL: [OrderState; 7] = [
+        OrderState::Intent,
+        OrderState::Submitted,
+        OrderState::PartiallyFilled,
+        OrderState::Filled,
+        OrderState::Canceled,
+        OrderState::Rejected,
+        OrderState::Settled,
+    ];
 
-#[derive(Debug)]
-pub struct DecisionContext {
-    pub prompt_md: String,
-    pub stats: Stats,
-    pub last_n_trades: Vec<LedgerRow>,
-    pub market: MarketState,
-    pub orderbook: Orderbook,
-    pub weather: Option<WeatherSnapshot>,
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrderState::Intent => "intent",
+            OrderState::Submitted => "submitted",
+            OrderState::PartiallyFilled => "partially_filled",
+            OrderState::Filled => "filled",
+            OrderState::Canceled => "canceled",
+            OrderState::Rejected => "rejected",
+            OrderState::Settled => "settled",
+        }
+    }
+
+    /// Still on the book, so exchange polling can move it along.
+    pub fn is_resting(&self) -> bool {
+        matches!(self, OrderState::Submitted | OrderState::PartiallyFilled)
+    }
+
+    /// Legal next states. A partial fill can repeat as more contracts fill;
+    /// a canceled order may still hold filled contracts, so it can settle.
+    pub fn can_become(&self, next: OrderState) -> bool {
+        use OrderState::*;
+        matches!(
+            (self, next),
+            (Intent, Submitted | PartiallyFilled | Filled | Rejected)
+                | (Submitted, PartiallyFilled | Filled | Canceled)
+                | (PartiallyFilled, PartiallyFilled | Filled | Canceled)
+                | (Filled | Canceled, Settled)
+        )
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct LedgerRow {
-    pub timestamp: String,
+impl std::fmt::Display for OrderState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateChange {
+    pub state: OrderState,
+    pub at: String,
+}
+
+/// One order's current state and how it got there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRecord {
+    pub order_id: String,
     pub ticker: String,
-    pub side: String,
     pub shares: u32,
-    pub price: u32,
-    pub result: String,
-    pub pnl_cents: i64,
-    pub cumulative_cents: i64,
-    pub order_id: String,
+    pub filled: u32,
+    pub state: OrderState,
+    pub history: Vec<StateChange>,
 }
 
-// ── City Config ──
-
-#[derive(Debug, Clone)]
-pub struct CityConfig {
-    pub name: String,
-    pub series_ticker: String,
-    pub lat: f64,
-    pub lon: f64,
-    pub timezone: String,
+/// A buy bigger than the top of book, worked one slice per cycle. Saved
+/// between slices so a restart resumes the plan instead of reading the
+/// first slice's position as a finished trade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapPlan {
+    pub event_ticker: String,
+    pub ticker: String,
+    pub side: Side,
+    pub total_shares: u32,
+    pub filled_shares: u32,
+    pub slices: u32,
+    pub started_at: String,
+    /// Earliest time the next slice may go out
+    pub next_slice_at: String,
 }
 
-impl CityConfig {
-    pub fn all() -> Vec<CityConfig> {
-        vec![
-            CityConfig {
-                name: "New York".into(),
-                series_ticker: "KXHIGHNY".into(),
-                lat: 40.7128, lon: -74.0060,
-                timezone: "America/New_York".into(),
-            },
-            CityConfig {
-                name: "Chicago".into(),
-                series_ticker: "KXHIGHCHI".into(),
-                lat: 41.8781, lon: -87.6298,
-                timezone: "America/Chicago".into(),
-            },
-            CityConfig {
-                name: "Miami".into(),
-                series_ticker: "KXHIGHMI".into(),
-                lat: 25.7617, lon: -80.1918,
-                timezone: "America/New_York".into(),
-            },
-            CityConfig {
-                name: "Austin".into(),
-                series_ticker: "KXHIGHAT".into(),
-                lat: 30.2672, lon: -97.7431,
-                timezone: "America/Chicago".into(),
-            },
-        ]
+impl TwapPlan {
+    pub fn remaining(&self) -> u32 {
+        self.total_shares.saturating_sub(self.filled_shares)
+    }
+
+    pub fn is_due(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.next_slice_at).map_or(true, |t| t <= now)
     }
 }
 
-// ── Config ──
+// ── Paper Exchange ──
+
+/// Everything the simulated exchange (`PAPER_SIMULATE`) holds for the
+/// account, saved to brain/paper_exchange.json between cycles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperBook {
+    pub balance_cents: Cents,
+    /// Source of order ids; never reused
+    pub next_order: u64,
+    pub resting: Vec<PaperOrder>,
+    pub positions: Vec<PaperPosition>,
+    /// Settled positions, newest last
+    pub settlements: Vec<Settlement>,
+    /// Every simulated execution, newest last
+    #[serde(default)]
+    pub fills: Vec<Fill>,
+}
+
+impl PaperBook {
+    pub fn new(balance_cents: Cents) -> Self {
+        Self {
+            balance_cents,
+            next_order: 1,
+            resting: Vec::new(),
+            positions: Vec::new(),
+            settlements: Vec::new(),
+            fills: Vec::new(),
+        }
+    }
+}
+
+/// The unfilled part of a simulated limit buy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperOrder {
+    pub order_id: String,
+    pub ticker: String,
+    pub side: Side,
+    pub price_cents: u32,
+    pub remaining: u32,
+    pub market: MarketType,
+    /// Station city, for settlement
+    pub city: String,
+}
+
+/// Filled simulated contracts awaiting settlement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperPosition {
+    pub ticker: String,
+    pub side: Side,
+    pub count: u32,
+    /// What the fills cost, fees included
+    pub cost_cents: Cents,
+    pub market: MarketType,
+    pub city: String,
+    /// Local date whose high settles the market, YYYY-MM-DD
+    pub settles_on: String,
+}
+
+// ── Risk ──
+
+/// Which risk limit stopped a cycle. Labels are stable — alerting keys on them.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskVeto {
+    /// Available capital below the minimum balance — out of money
+    LowBalance,
+    /// Today's realized loss at the daily limit
+    DailyLoss,
+    /// Too many consecutive losses
+    LossStreak,
+    /// Recent wins and losses more likely under a lost edge than the
+    /// assumed win rate (SPRT)
+    LossSprt,
+}
+
+impl RiskVeto {
+    pub const ALL: [RiskVeto; 4] = [RiskVeto::LowBalance, RiskVeto::DailyLoss, RiskVeto::LossStreak, RiskVeto::LossSprt];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskVeto::LowBalance => "low_balance",
+            RiskVeto::DailyLoss => "daily_loss",
+            RiskVeto::LossStreak => "loss_streak",
+            RiskVeto::LossSprt => "loss_sprt",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskVeto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// A risk veto with the value that tripped it and the configured limit.
+/// Cents for balance and loss, a count for the streak, hundredths of a
+/// log-likelihood ratio for the SPRT.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VetoDetails {
+    pub timestamp: String,
+    pub veto: RiskVeto,
+    pub observed: i64,
+    pub limit: i64,
+}
+
+impl std::fmt::Display for VetoDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.veto {
+            RiskVeto::LowBalance => write!(f, "Balance {}¢ < {}¢ minimum", self.observed, self.limit),
+            RiskVeto::DailyLoss => write!(f, "Daily loss: {}¢ (limit {}¢)", self.observed, self.limit),
+            RiskVeto::LossStreak => write!(f, "{}× consecutive losses (limit {})", self.observed, self.limit),
+            RiskVeto::LossSprt => write!(
+                f,
+                "Losses inconsistent with the assumed edge: log-LR {:.2} (limit {:.2})",
+                self.observed as f64 / 100.0,
+                self.limit as f64 / 100.0
+            ),
+        }
+    }
+}
+
+/// Where the loss SPRT stands after the settled ledger: the log-likelihood
+/// ratio of "edge lost" over "edge as assumed" since the test last
+/// restarted, and the bounds that decide it.
+#[derive(Debug, Clone, Copy)]
+pub struct LossSprt {
+    pub llr: f64,
+    /// At or above: veto
+    pub upper: f64,
+    /// At or below: the edge holds, the test restarts from 0
+    pub lower: f64,
+    /// Wins and losses since the restart
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// Per-ticker order rejections, persisted in brain/risk_state.json. A
+/// ticker the exchange keeps refusing (bad price, paused market) is locked
+/// until an operator runs `kalshi-bot unlock` or the UTC day rolls over.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskState {
+    #[serde(default)]
+    pub tickers: std::collections::BTreeMap<String, TickerRejections>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerRejections {
+    /// UTC day the count applies to, YYYY-MM-DD
+    pub day: String,
+    pub count: u32,
+    pub last_error: String,
+    /// When the count reached REJECTION_LOCKOUT; None while still trading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_at: Option<String>,
+}
+
+// ── Stats ──
+
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub total_trades: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub win_rate: f64,
+    pub total_pnl_cents: Cents,
+    pub today_pnl_cents: Cents,
+    pub current_streak: i32,
+    pub max_drawdown_cents: Cents,
+    pub avg_win_cents: f64,
+    pub avg_loss_cents: f64,
+    pub pass_reasons: Vec<(PassReason, u32)>,
+    pub rule_attribution: Vec<RuleAttribution>,
+    pub fee_drag: Vec<FeeDrag>,
+    /// Cancelled ledger rows by reason; None counts rows with no reason recorded
+    pub cancel_reasons: Vec<(Option<CancelReason>, u32)>,
+    /// Rows refunded by a voided market, left out of every rate above
+    pub voided: u32,
+    pub analytics: Option<Analytics>,
+}
+
+/// Long-run shape of the equity curve, from settled ledger rows grouped by
+/// trade date.
+#[derive(Debug, Clone, Default)]
+pub struct Analytics {
+    pub daily: Vec<DailyPnl>,
+    pub monthly: Vec<MonthlyPnl>,
+    /// Mean over standard deviation of daily P&L for the last
+    /// `SHARPE_WINDOW_DAYS` trading days, ×√365. None with fewer than two
+    /// days or no variation.
+    pub rolling_sharpe: Option<f64>,
+    /// The same over every trading day
+    pub sharpe: Option<f64>,
+    pub longest_losing_streak: u32,
+    pub max_drawdown_cents: Cents,
+    /// Calendar days from a peak until equity got back to it; one that
+    /// hasn't recovered counts up to the last trading day
+    pub longest_drawdown_days: i64,
+    /// Days since the last peak, 0 at a new high
+    pub current_drawdown_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DailyPnl {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub pnl_cents: Cents,
+    /// Cumulative P&L at the end of the day
+    pub equity_cents: Cents,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonthlyPnl {
+    /// "YYYY-MM"
+    pub month: String,
+    pub trades: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub pnl_cents: Cents,
+    /// Worst peak-to-trough fall within the month
+    pub max_drawdown_cents: Cents,
+}
+
+/// Fees against gross P&L for the trades settled in one month. Ledger P&L
+/// is gross of fees.
+#[derive(Debug, Clone)]
+pub struct FeeDrag {
+    /// "YYYY-MM", or "all"
+    pub period: String,
+    pub trades: u32,
+    pub gross_pnl_cents: Cents,
+    /// Fees the exchange reported on fills
+    pub actual_fees_cents: Cents,
+    /// Taker-fee estimates for fills with no reported fee
+    pub estimated_fees_cents: Cents,
+}
+
+impl FeeDrag {
+    pub fn fees_cents(&self) -> Cents {
+        self.actual_fees_cents + self.estimated_fees_cents
+    }
+
+    /// Fees as a percentage of gross P&L; None unless gross P&L is positive.
+    pub fn drag_pct(&self) -> Option<f64> {
+        (self.gross_pnl_cents > Cents::ZERO).then(|| self.fees_cents().0 as f64 / self.gross_pnl_cents.0 as f64 * 100.0)
+    }
+}
+
+// ── Backtest ──
+
+/// Replayed trades at one parameter setting, net of estimated taker fees.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BacktestResult {
+    pub trades: u32,
+    pub wins: u32,
+    pub pnl_cents: Cents,
+}
+
+impl BacktestResult {
+    pub fn from_trades(trades: &[BacktestTrade]) -> Self {
+        Self {
+            trades: trades.len() as u32,
+            wins: trades.iter().filter(|t| t.won).count() as u32,
+            pnl_cents: trades.iter().map(|t| t.pnl_cents).sum(),
+        }
+    }
+
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.trades > 0).then(|| self.wins as f64 / self.trades as f64)
+    }
+}
+
+/// One replayed trade, in journal order.
+#[derive(Debug, Clone)]
+pub struct BacktestTrade {
+    /// "YYYY-MM-DD" of the decision
+    pub day: String,
+    pub city: String,
+    pub ticker: String,
+    pub net_pp: f64,
+    pub shares: u32,
+    pub won: bool,
+    /// Net of the estimated taker fee
+    pub pnl_cents: Cents,
+}
+
+impl BacktestTrade {
+    /// Realized P&L per contract — cents on a $1 payout, so directly
+    /// comparable to `net_pp`.
+    pub fn realized_pp(&self) -> f64 {
+        self.pnl_cents.0 as f64 / self.shares.max(1) as f64
+    }
+}
+
+/// One walk-forward step: the threshold that did best on the training
+/// window, and how it then did on the unseen test window.
+#[derive(Debug, Clone)]
+pub struct WalkForwardFold {
+    /// Inclusive "YYYY-MM-DD" bounds
+    pub train: (String, String),
+    pub test: (String, String),
+    pub min_net_edge_pp: f64,
+    pub in_sample: BacktestResult,
+    pub out_of_sample: BacktestResult,
+    /// The live MIN_NET_EDGE_PP over the same test window
+    pub baseline: BacktestResult,
+    /// Trades behind `out_of_sample` and `baseline`
+    pub out_of_sample_trades: Vec<BacktestTrade>,
+    pub baseline_trades: Vec<BacktestTrade>,
+}
+
+/// Net-edge floor learned for one city / market kind / forecast confidence
+/// bucket from settled journal history.
+#[derive(Debug, Clone)]
+pub struct EdgeFloor {
+    pub city: String,
+    pub market_kind: String,
+    pub confidence: ForecastConfidence,
+    /// Settled decisions in the bucket that reached the edge check
+    pub samples: u32,
+    /// Lowest `EDGE_GRID` threshold whose trades were net profitable;
+    /// None when none were
+    pub learned_pp: Option<f64>,
+    /// `learned_pp` shrunk toward MIN_NET_EDGE_PP by sample count — the
+    /// floor actually applied
+    pub floor_pp: f64,
+}
+
+/// Monthly P&L of the trades one veto rule blocked. Negative P&L is money
+/// the rule saved; positive is money it cost.
+#[derive(Debug, Clone)]
+pub struct RuleAttribution {
+    pub month: String,
+    pub rule: PassReason,
+    pub blocked: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub pnl_cents: Cents,
+}
+
+/// Probabilistic skill of the model's YES probabilities against settled
+/// outcomes, for one city and lead-time bucket.
+#[derive(Debug, Clone)]
+pub struct ForecastSkill {
+    pub city: String,
+    pub lead_time: String,
+    pub forecasts: u32,
+    /// Mean squared error of the probability (0 = perfect, 0.25 = coin flip)
+    pub brier: f64,
+    /// Expected calibration error over 10 equal-width probability bins
+    pub ece: f64,
+    /// The non-empty bins behind `ece`, lowest probability first
+    pub calibration: Vec<CalibrationBin>,
+}
+
+/// Forecasts whose probability fell in one bin, against how often they hit.
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationBin {
+    pub forecasts: u32,
+    pub mean_probability: f64,
+    pub hit_rate: f64,
+}
+
+// ── Prompt Context ──
+
+#[derive(Debug, Clone)]
+pub struct DecisionContext {
+    pub prompt_md: String,
+    pub stats: Stats,
+    pub last_n_trades: Vec<LedgerRow>,
+    pub market: MarketState,
+    pub orderbook: Orderbook,
+    pub weather: Option<WeatherSnapshot>,
+    /// Vig-free distribution implied by every bracket of this event.
+    pub implied: Option<ImpliedDistribution>,
+    pub exposure: Exposure,
+    /// YES probability used in place of the ensemble's: operator-set in
+    /// brain/overrides.toml, or STRATEGY=ml's model
+    pub fair_override: Option<f64>,
+    pub features: FeatureSwitches,
+}
+
+/// Which flagged subsystems (brain/flags.toml) run for this city this
+/// cycle. All on by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureSwitches {
+    pub ensemble: bool,
+    pub bucket_fallback: bool,
+    pub sigmoid_fallback: bool,
+    pub maker_mode: bool,
+    pub exits: bool,
+    pub llm_brain: bool,
+}
+
+impl Default for FeatureSwitches {
+    fn default() -> Self {
+        Self {
+            ensemble: true,
+            bucket_fallback: true,
+            sigmoid_fallback: true,
+            maker_mode: true,
+            exits: true,
+            llm_brain: true,
+        }
+    }
+}
+
+/// Every bracket of one event with its orderbook, for brains that decide
+/// jointly across brackets.
+#[derive(Debug)]
+pub struct EventContext {
+    pub prompt_md: String,
+    pub stats: Stats,
+    pub last_n_trades: Vec<LedgerRow>,
+    pub event_ticker: String,
+    pub brackets: Vec<(MarketState, Orderbook)>,
+    pub weather: Option<WeatherSnapshot>,
+    pub implied: Option<ImpliedDistribution>,
+    pub exposure: Exposure,
+    /// Operator-set YES probabilities by bracket ticker
+    pub fair_overrides: std::collections::BTreeMap<String, f64>,
+    pub features: FeatureSwitches,
+}
+
+impl EventContext {
+    /// Single-bracket view of bracket `i`, as `Brain::decide` takes it.
+    pub fn bracket(&self, i: usize) -> DecisionContext {
+        let (market, orderbook) = &self.brackets[i];
+        DecisionContext {
+            prompt_md: self.prompt_md.clone(),
+            stats: self.stats.clone(),
+            last_n_trades: self.last_n_trades.clone(),
+            market: market.clone(),
+            orderbook: orderbook.clone(),
+            weather: self.weather.clone(),
+            implied: self.implied.clone(),
+            exposure: self.exposure.clone(),
+            fair_override: self.fair_overrides.get(&market.ticker).copied(),
+            features: self.features,
+        }
+    }
+}
+
+/// Event a market ticker belongs to: everything before the bracket suffix,
+/// e.g. KXHIGHNY-25JAN15-B42.5 → KXHIGHNY-25JAN15.
+pub fn event_of(ticker: &str) -> &str {
+    ticker.rsplit_once('-').map_or(ticker, |(event, _)| event)
+}
+
+/// Local date an event settles on, as YYYY-MM-DD: KXHIGHNY-26FEB12 →
+/// 2026-02-12. Also accepts a market ticker.
+pub fn event_date(ticker: &str) -> Option<String> {
+    let date_part = ticker.split('-').nth(1).filter(|d| d.len() >= 6)?;
+    let year_suffix: u32 = date_part[..2].parse().ok()?;
+    let day: u32 = date_part[5..].parse().ok()?;
+    let month = match &date_part[2..5] {
+        "JAN" => 1, "FEB" => 2, "MAR" => 3, "APR" => 4, "MAY" => 5, "JUN" => 6,
+        "JUL" => 7, "AUG" => 8, "SEP" => 9, "OCT" => 10, "NOV" => 11, "DEC" => 12,
+        _ => return None,
+    };
+    Some(format!("20{:02}-{:02}-{:02}", year_suffix, month, day))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerRow {
+    pub timestamp: String,
+    pub ticker: String,
+    pub side: String,
+    pub shares: u32,
+    pub price: u32,
+    pub result: String,
+    pub pnl_cents: Cents,
+    pub cumulative_cents: Cents,
+    pub order_id: String,
+    /// Set on cancelled rows; None on rows cancelled before reasons were kept
+    #[serde(default)]
+    pub cancel_reason: Option<CancelReason>,
+    /// Kalshi event the market belongs to, e.g. "KXHIGHNY-26FEB12"
+    #[serde(default)]
+    pub event_ticker: String,
+    /// `CityConfig::name`; empty when the migration couldn't tell
+    #[serde(default)]
+    pub city: String,
+}
+
+impl LedgerRow {
+    /// Placed and not yet settled or cancelled.
+    pub fn is_pending(&self) -> bool {
+        self.result == "pending"
+    }
+
+    /// The ledger's side column, None if it isn't yes or no.
+    pub fn side(&self) -> Option<Side> {
+        match self.side.to_ascii_lowercase().as_str() {
+            "yes" => Some(Side::Yes),
+            "no" => Some(Side::No),
+            _ => None,
+        }
+    }
+
+    /// Not scored yet: pending, or given up on by the zombie cleanup
+    /// (`unknown`) while the contracts may still be held.
+    pub fn is_open(&self) -> bool {
+        self.is_pending() || self.result == "unknown"
+    }
+
+    /// Settled with a known outcome.
+    pub fn is_settled(&self) -> bool {
+        self.result == "win" || self.result == "loss" || self.is_scratch()
+    }
+
+    /// Sold out at break-even before settlement: settled at zero P&L, and
+    /// neither a win nor a loss, so streaks and the loss SPRT skip it.
+    pub fn is_scratch(&self) -> bool {
+        self.result == "scratch"
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.result == "cancelled"
+    }
+
+    /// The market was voided and the stake refunded: zero P&L, and neither
+    /// a win nor a loss.
+    pub fn is_void(&self) -> bool {
+        self.result == "void"
+    }
+}
+
+// ── Journal ──
+
+/// One line of `brain/journal.jsonl`. Append-only, machine-readable
+/// companion to the markdown ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    Decision(DecisionRecord),
+    ProbabilityCurve(CurveRecord),
+    Downsized(DownsizeRecord),
+    Trade(TradeRecord),
+    MarketOutcome(OutcomeRecord),
+    Execution(ExecutionRecord),
+    RiskVeto(VetoDetails),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub timestamp: String,
+    pub city: String,
+    /// Empty on records from before it was journaled
+    #[serde(default)]
+    pub event_ticker: String,
+    pub ticker: String,
+    pub action: Action,
+    pub side: Option<Side>,
+    pub pass_reason: Option<PassReason>,
+    pub edge_magnitude: f64,
+    #[serde(default)]
+    pub counterfactual: Option<Counterfactual>,
+    #[serde(default)]
+    pub model_probability: Option<f64>,
+    #[serde(default)]
+    pub minutes_to_expiry: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge: Option<EdgeBreakdown>,
+    /// `MarketType::kind()` of the bracket
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ForecastConfidence>,
+    /// `Prompt::version` the decision was made with, for prompt-driven brains
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    /// `Config::strategy_version` the decision was made under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy_version: Option<String>,
+    /// brain/overrides.toml entry that shaped the decision, as applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_override: Option<String>,
+    /// Forecast and market inputs as the brain saw them, for the research
+    /// dataset; None on records from before they were journaled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Box<DecisionInputs>>,
+}
+
+/// What a decision was made on, flattened for offline training.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecisionInputs {
+    pub member_count: usize,
+    pub ensemble_mean: Option<f64>,
+    pub ensemble_std: Option<f64>,
+    pub ensemble_p10: Option<f64>,
+    pub ensemble_p90: Option<f64>,
+    pub nws_forecast_high: Option<f64>,
+    /// High so far today at the settlement station
+    pub observed_high: Option<f64>,
+    pub floor_strike: Option<f64>,
+    pub cap_strike: Option<f64>,
+    pub yes_bid: Option<u32>,
+    pub yes_ask: Option<u32>,
+    pub no_bid: Option<u32>,
+    pub no_ask: Option<u32>,
+    pub volume_24h: u64,
+    pub open_interest: u64,
+}
+
+impl DecisionInputs {
+    pub fn new(market: &MarketState, weather: Option<&WeatherSnapshot>) -> Self {
+        let ensemble = weather.and_then(|w| w.ensemble.as_ref());
+        Self {
+            member_count: weather.map_or(0, |w| w.ensemble_member_highs.len()),
+            ensemble_mean: ensemble.map(|e| e.mean_high),
+            ensemble_std: ensemble.map(|e| e.std_dev),
+            ensemble_p10: ensemble.map(|e| e.p10),
+            ensemble_p90: ensemble.map(|e| e.p90),
+            nws_forecast_high: weather.and_then(|w| w.nws_forecast_high),
+            observed_high: weather.and_then(|w| w.observed_high_so_far),
+            floor_strike: market.floor_strike,
+            cap_strike: market.cap_strike,
+            yes_bid: market.yes_bid,
+            yes_ask: market.yes_ask,
+            no_bid: market.no_bid,
+            no_ask: market.no_ask,
+            volume_24h: market.volume_24h,
+            open_interest: market.open_interest,
+        }
+    }
+}
+
+/// Final result of a market, recorded once it settles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeRecord {
+    pub timestamp: String,
+    pub ticker: String,
+    /// "yes", "no", or "void"
+    pub result: String,
+}
+
+impl OutcomeRecord {
+    /// Voided markets have no outcome to score forecasts or trades against.
+    pub fn is_void(&self) -> bool {
+        self.result == MarketResult::Void.label()
+    }
+}
+
+/// Executed (or paper) order, joinable to the ledger by `order_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub timestamp: String,
+    pub city: String,
+    #[serde(default)]
+    pub event_ticker: String,
+    pub ticker: String,
+    pub order_id: String,
+    pub side: Side,
+    pub shares: u32,
+    pub price_cents: u32,
+    pub paper: bool,
+    /// Ensemble run init time the decision was based on
+    pub model_run: Option<String>,
+    pub member_count: usize,
+    /// Fee reported by the exchange for an immediate fill; None for paper
+    /// trades and orders that rested
+    #[serde(default)]
+    pub fee_cents: Option<Cents>,
+}
+
+/// One order placed while working a buy: a passive price-improvement
+/// attempt or the final cross.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    pub timestamp: String,
+    pub ticker: String,
+    pub order_id: String,
+    pub attempt: u32,
+    /// "passive" or "cross"
+    pub stage: String,
+    pub side: Side,
+    pub price_cents: u32,
+    /// Contracts filled (passive) or submitted (cross)
+    pub shares: u32,
+    /// "filled" / "partial" / "unfilled" for passive; exchange status for cross
+    pub outcome: String,
+}
+
+/// An order shrunk to fit the available balance after an insufficient-funds reject.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownsizeRecord {
+    pub timestamp: String,
+    pub city: String,
+    pub ticker: String,
+    pub requested_shares: u32,
+    pub placed_shares: u32,
+    pub price_cents: u32,
+    pub balance_cents: Cents,
+}
+
+/// Model vs market CDF over integer temperatures for one event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveRecord {
+    pub timestamp: String,
+    pub city: String,
+    pub event_ticker: String,
+    pub points: Vec<CurvePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub temp_f: i32,
+    /// P(high < temp_f) from ensemble members
+    pub model_cdf: f64,
+    /// P(high < temp_f) implied by bracket prices
+    pub market_cdf: Option<f64>,
+}
+
+/// Compact per-snapshot summary kept forever in `brain/snapshots/aggregates.jsonl`
+/// after the raw member arrays have aged out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotAggregate {
+    pub timestamp: String,
+    pub city: String,
+    pub ensemble_run: Option<String>,
+    pub member_count: usize,
+    pub mean_high: Option<f64>,
+    pub std_dev: Option<f64>,
+    pub p10: Option<f64>,
+    pub p90: Option<f64>,
+    pub nws_forecast_high: Option<f64>,
+    pub open_meteo_forecast_high: f64,
+}
+
+// ── City Config ──
+
+/// A span of local hours, `start..end` on a city's clock — when it may open
+/// new positions, or which hours of the day to fetch. Parsed from "7-15".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HourWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl HourWindow {
+    pub fn contains(&self, local_hour: u32) -> bool {
+        (self.start_hour..self.end_hour).contains(&local_hour)
+    }
+}
+
+impl std::fmt::Display for HourWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:00–{:02}:00", self.start_hour, self.end_hour)
+    }
+}
+
+impl std::str::FromStr for HourWindow {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.trim().split_once('-').ok_or_else(|| format!("expected START-END hours, got {:?}", s))?;
+        let hour = |h: &str| h.trim().parse::<u32>().map_err(|_| format!("bad hour {:?} in {:?}", h, s));
+        let (start_hour, end_hour) = (hour(start)?, hour(end)?);
+        if start_hour >= end_hour || end_hour > 24 {
+            return Err(format!("hour window {:?} must satisfy START < END <= 24", s));
+        }
+        Ok(Self { start_hour, end_hour })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CityConfig {
+    pub name: String,
+    pub series_ticker: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub timezone: String,
+    /// Minutes after a model run publishes before this city refreshes, so
+    /// cities don't all hit the weather APIs at once
+    pub refresh_offset_minutes: u32,
+    /// No new entries outside this local-time window; None trades around
+    /// the clock
+    pub trading_hours: Option<HourWindow>,
+    pub measure: Measure,
+    /// ICAO id of the station the market settles on, for station-keyed
+    /// guidance (NBM text bulletins)
+    pub station: Option<String>,
+    /// Strategy and sizing set for this city alone (`…_<SERIES>` settings)
+    pub tuning: CityTuning,
+}
+
+/// Per-city replacements for the process-wide edge floor, price cap and
+/// size cap, so cities with different forecast skill and liquidity can
+/// trade different parameters side by side. Seasonal profiles apply on
+/// top. The per-city trade window is `CityConfig::trading_hours`.
+#[derive(Debug, Clone, Default)]
+pub struct CityTuning {
+    /// MIN_NET_EDGE_PP_<SERIES>
+    pub min_net_edge_pp: Option<f64>,
+    /// MAX_PRICE_CENTS_<SERIES>
+    pub max_price_cents: Option<u32>,
+    /// MAX_SHARES_<SERIES>
+    pub max_shares: Option<u32>,
+}
+
+impl CityTuning {
+    /// MIN_NET_EDGE_PP_<SERIES>, MAX_PRICE_CENTS_<SERIES> and
+    /// MAX_SHARES_<SERIES>, which replace the global values for one city.
+    pub fn from_env(series_ticker: &str) -> anyhow::Result<Self> {
+        fn tuned<T: std::str::FromStr>(key: &str) -> anyhow::Result<Option<T>> {
+            match setting(key) {
+                Ok(v) if !v.trim().is_empty() => v
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| anyhow::anyhow!("{}={} is not valid", key, v)),
+                _ => Ok(None),
+            }
+        }
+        let tuning = Self {
+            min_net_edge_pp: tuned(&format!("MIN_NET_EDGE_PP_{}", series_ticker))?,
+            max_price_cents: tuned(&format!("MAX_PRICE_CENTS_{}", series_ticker))?,
+            max_shares: tuned(&format!("MAX_SHARES_{}", series_ticker))?,
+        };
+        if tuning.max_price_cents.is_some_and(|c| !(1..=99).contains(&c)) {
+            anyhow::bail!("MAX_PRICE_CENTS_{} must be within 1..=99", series_ticker);
+        }
+        Ok(tuning)
+    }
+
+    /// `min_net_edge_pp=8, max_shares=10`, or empty when nothing is set.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(pp) = self.min_net_edge_pp {
+            parts.push(format!("min_net_edge_pp={}", pp));
+        }
+        if let Some(c) = self.max_price_cents {
+            parts.push(format!("max_price_cents={}", c));
+        }
+        if let Some(n) = self.max_shares {
+            parts.push(format!("max_shares={}", n));
+        }
+        parts.join(", ")
+    }
+}
+
+/// What a series settles on. Brackets, ensemble members and buckets are in
+/// the measure's unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Measure {
+    /// The local day's high temperature, °F
+    #[default]
+    HighTemp,
+    /// Snow accumulated over the local day, inches
+    Snowfall,
+}
+
+impl Measure {
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Measure::HighTemp => "°F",
+            Measure::Snowfall => "in",
+        }
+    }
+
+    /// A strike as bracket labels print it: `72°`, `2.5in`.
+    pub fn strike(&self, value: f64) -> String {
+        match self {
+            Measure::HighTemp => format!("{}°", self.amount(value)),
+            Measure::Snowfall => format!("{}in", self.amount(value)),
+        }
+    }
+
+    /// The number alone, to the precision strikes are listed at.
+    pub fn amount(&self, value: f64) -> String {
+        match self {
+            Measure::HighTemp => format!("{:.0}", value),
+            Measure::Snowfall => format!("{:.1}", value),
+        }
+    }
+}
+
+impl CityConfig {
+    /// `base` with this city's tuning applied.
+    pub fn strategy(&self, base: &StrategyParams) -> StrategyParams {
+        let mut params = base.clone();
+        if let Some(pp) = self.tuning.min_net_edge_pp {
+            params.min_net_edge_pp = pp;
+        }
+        if let Some(cents) = self.tuning.max_price_cents {
+            params.max_price_cents = cents;
+        }
+        params
+    }
+
+    /// MAX_SHARES, or this city's own cap.
+    pub fn max_shares(&self, config: &Config) -> u32 {
+        self.tuning.max_shares.unwrap_or(config.max_shares)
+    }
+
+    pub fn all() -> Vec<CityConfig> {
+        vec![
+            CityConfig {
+                name: "New York".into(),
+                series_ticker: "KXHIGHNY".into(),
+                lat: 40.7128, lon: -74.0060,
+                timezone: "America/New_York".into(),
+                refresh_offset_minutes: 0,
+                trading_hours: None,
+                measure: Measure::HighTemp,
+                station: Some("KNYC".into()),
+                tuning: CityTuning::default(),
+            },
+            CityConfig {
+                name: "Chicago".into(),
+                series_ticker: "KXHIGHCHI".into(),
+                lat: 41.8781, lon: -87.6298,
+                timezone: "America/Chicago".into(),
+                refresh_offset_minutes: 5,
+                trading_hours: None,
+                measure: Measure::HighTemp,
+                station: Some("KMDW".into()),
+                tuning: CityTuning::default(),
+            },
+            CityConfig {
+                name: "Miami".into(),
+                series_ticker: "KXHIGHMI".into(),
+                lat: 25.7617, lon: -80.1918,
+                timezone: "America/New_York".into(),
+                refresh_offset_minutes: 10,
+                trading_hours: None,
+                measure: Measure::HighTemp,
+                station: Some("KMIA".into()),
+                tuning: CityTuning::default(),
+            },
+            CityConfig {
+                name: "Austin".into(),
+                series_ticker: "KXHIGHAT".into(),
+                lat: 30.2672, lon: -97.7431,
+                timezone: "America/Chicago".into(),
+                refresh_offset_minutes: 15,
+                trading_hours: None,
+                measure: Measure::HighTemp,
+                station: Some("KAUS".into()),
+                tuning: CityTuning::default(),
+            },
+        ]
+    }
+}
+
+// ── Strategy Params ──
+
+/// Tunables for the deterministic strategy. Liquidity and sizing defaults
+/// reproduce the original hardcoded behaviour.
+#[derive(Debug, Clone)]
+pub struct StrategyParams {
+    /// Bracket is illiquid when BOTH 24h volume and OI are below these
+    pub liquidity_min_volume_24h: u64,
+    pub liquidity_min_open_interest: u64,
+    /// Hours after market open over which thresholds ramp up to full;
+    /// 0 disables the ramp
+    pub liquidity_ramp_hours: f64,
+    /// Fraction of the full thresholds applied right at open
+    pub liquidity_open_scale: f64,
+    /// Bracket is illiquid on a side with fewer contracts than this offered
+    /// near that side's best ask, whatever the market-level volume and OI;
+    /// 0 disables the check
+    pub liquidity_min_side_depth: u32,
+    /// How far above the best ask, in cents, offers still count as near
+    pub liquidity_depth_window_cents: u32,
+    /// Size multiplier when the strike sits inside the ensemble P25–P75
+    pub size_iqr_scale: f64,
+    /// Size multiplier when the strike is beyond P10/P90 and the net edge
+    /// is at least `size_tail_min_edge`; `max_shares` still caps the result
+    pub size_tail_scale: f64,
+    pub size_tail_min_edge: f64,
+    /// Veto a city when NWS and the ensemble mean differ by more than this
+    /// many °F; 0 disables the veto
+    pub max_source_disagreement_f: f64,
+    /// Quorum mode: the two ensemble paths' means may differ by this many
+    /// °F before the city is vetoed
+    pub quorum_tolerance_f: f64,
+    /// Ensemble members to trim from each tail before member-based
+    /// probabilities; 0 keeps every member
+    pub ensemble_trim: usize,
+    pub ensemble_trim_mode: TrimMode,
+    /// Pass when the model probability is within this many points of 50%
+    /// and the ensemble spread is at least `no_trade_zone_min_std_f`;
+    /// 0 disables the zone
+    pub no_trade_zone_pp: f64,
+    pub no_trade_zone_min_std_f: f64,
+    /// Net edge (after confidence and fee) a bracket needs before buying
+    pub min_net_edge_pp: f64,
+    /// Highest price paid per contract; 50¢ keeps reward at least equal
+    /// to risk
+    pub max_price_cents: u32,
+    /// Share of the raw edge kept at medium and low forecast confidence
+    pub confidence_medium_scale: f64,
+    pub confidence_low_scale: f64,
+    /// Weight the observation-trend nowcast reaches by mid-afternoon;
+    /// 0 disables it
+    pub nowcast_weight: f64,
+    /// Hours of readings the nowcast takes its trend from
+    pub nowcast_hours: usize,
+    /// Share of the model probability taken from the NBM percentiles
+    /// instead of the ensemble; 0 disables NBM (and its download)
+    pub nbm_weight: f64,
+    /// Share of the model probability taken from the HRRR high by the time
+    /// a market expires; 0 disables HRRR (and its download)
+    pub hrrr_weight: f64,
+    /// Hours before expiry over which the HRRR weight ramps up from 0
+    pub hrrr_window_hours: f64,
+    /// Error (σ, °F) assumed around the HRRR high
+    pub hrrr_sd_f: f64,
+    /// Bounds on the forecast probability; tightened further for small
+    /// ensembles
+    pub prob_floor: f64,
+    pub prob_ceiling: f64,
+    /// A probability within this of 0 or 1 needs NWS / NBM / HRRR on its
+    /// side before trading; 0 disables the check
+    pub tail_confirm_prob: f64,
+    /// Replace `min_net_edge_pp` with per-bucket floors learned from the
+    /// journal
+    pub adaptive_edge_floor: bool,
+    /// Weight of `min_net_edge_pp` in a learned floor, in settled
+    /// decisions: a bucket with this many samples sits halfway between
+    pub adaptive_edge_prior_samples: f64,
+    /// Loss veto by sequential probability ratio test instead of
+    /// `max_consecutive_losses`: win rate the strategy is assumed to have,
+    /// and the one that means its edge is gone; 0 keeps the streak veto
+    pub loss_sprt_win_prob: f64,
+    pub loss_sprt_fail_win_prob: f64,
+    /// Chance of vetoing while the edge still holds, and of trading on
+    /// after it's gone
+    pub loss_sprt_alpha: f64,
+    pub loss_sprt_beta: f64,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            liquidity_min_volume_24h: 10,
+            liquidity_min_open_interest: 10,
+            liquidity_ramp_hours: 0.0,
+            liquidity_open_scale: 0.2,
+            liquidity_min_side_depth: 0,
+            liquidity_depth_window_cents: 2,
+            size_iqr_scale: 1.0,
+            size_tail_scale: 1.0,
+            size_tail_min_edge: 0.15,
+            max_source_disagreement_f: 4.0,
+            quorum_tolerance_f: 1.5,
+            ensemble_trim: 0,
+            ensemble_trim_mode: TrimMode::Drop,
+            no_trade_zone_pp: 0.0,
+            no_trade_zone_min_std_f: 3.0,
+            min_net_edge_pp: 5.0,
+            max_price_cents: 50,
+            confidence_medium_scale: 0.8,
+            confidence_low_scale: 0.5,
+            nowcast_weight: 0.0,
+            nowcast_hours: 3,
+            nbm_weight: 0.0,
+            hrrr_weight: 0.0,
+            hrrr_window_hours: 12.0,
+            hrrr_sd_f: 2.0,
+            prob_floor: 0.02,
+            prob_ceiling: 0.98,
+            tail_confirm_prob: 0.10,
+            adaptive_edge_floor: false,
+            adaptive_edge_prior_samples: 30.0,
+            loss_sprt_win_prob: 0.6,
+            loss_sprt_fail_win_prob: 0.35,
+            loss_sprt_alpha: 0.02,
+            loss_sprt_beta: 0.2,
+        }
+    }
+}
+
+impl StrategyParams {
+    pub fn from_env() -> Self {
+        let d = Self::default();
+        Self {
+            liquidity_min_volume_24h: env_parse("LIQUIDITY_MIN_VOLUME_24H", d.liquidity_min_volume_24h),
+            liquidity_min_open_interest: env_parse("LIQUIDITY_MIN_OPEN_INTEREST", d.liquidity_min_open_interest),
+            liquidity_ramp_hours: env_parse("LIQUIDITY_RAMP_HOURS", d.liquidity_ramp_hours),
+            liquidity_open_scale: env_parse("LIQUIDITY_OPEN_SCALE", d.liquidity_open_scale),
+            liquidity_min_side_depth: env_parse("LIQUIDITY_MIN_SIDE_DEPTH", d.liquidity_min_side_depth),
+            liquidity_depth_window_cents: env_parse("LIQUIDITY_DEPTH_WINDOW_CENTS", d.liquidity_depth_window_cents),
+            size_iqr_scale: env_parse("SIZE_IQR_SCALE", d.size_iqr_scale),
+            size_tail_scale: env_parse("SIZE_TAIL_SCALE", d.size_tail_scale),
+            size_tail_min_edge: env_parse("SIZE_TAIL_MIN_EDGE", d.size_tail_min_edge),
+            max_source_disagreement_f: env_parse("MAX_SOURCE_DISAGREEMENT_F", d.max_source_disagreement_f),
+            quorum_tolerance_f: env_parse("QUORUM_TOLERANCE_F", d.quorum_tolerance_f),
+            ensemble_trim: env_parse("ENSEMBLE_TRIM", d.ensemble_trim),
+            ensemble_trim_mode: env_parse("ENSEMBLE_TRIM_MODE", d.ensemble_trim_mode),
+            no_trade_zone_pp: env_parse("NO_TRADE_ZONE_PP", d.no_trade_zone_pp),
+            no_trade_zone_min_std_f: env_parse("NO_TRADE_ZONE_MIN_STD_F", d.no_trade_zone_min_std_f),
+            min_net_edge_pp: env_parse("MIN_NET_EDGE_PP", d.min_net_edge_pp),
+            max_price_cents: env_parse("MAX_PRICE_CENTS", d.max_price_cents),
+            confidence_medium_scale: env_parse("CONFIDENCE_MEDIUM_SCALE", d.confidence_medium_scale),
+            confidence_low_scale: env_parse("CONFIDENCE_LOW_SCALE", d.confidence_low_scale),
+            nowcast_weight: env_parse("NOWCAST_WEIGHT", d.nowcast_weight),
+            nowcast_hours: env_parse("NOWCAST_HOURS", d.nowcast_hours),
+            nbm_weight: env_parse("NBM_WEIGHT", d.nbm_weight),
+            hrrr_weight: env_parse("HRRR_WEIGHT", d.hrrr_weight),
+            hrrr_window_hours: env_parse("HRRR_WINDOW_HOURS", d.hrrr_window_hours),
+            hrrr_sd_f: env_parse("HRRR_SD_F", d.hrrr_sd_f),
+            prob_floor: env_parse("PROB_FLOOR", d.prob_floor),
+            prob_ceiling: env_parse("PROB_CEILING", d.prob_ceiling),
+            tail_confirm_prob: env_parse("TAIL_CONFIRM_PROB", d.tail_confirm_prob),
+            adaptive_edge_floor: env_parse("ADAPTIVE_EDGE_FLOOR", d.adaptive_edge_floor),
+            adaptive_edge_prior_samples: env_parse("ADAPTIVE_EDGE_PRIOR_SAMPLES", d.adaptive_edge_prior_samples),
+            loss_sprt_win_prob: env_parse("LOSS_SPRT_WIN_PROB", d.loss_sprt_win_prob),
+            loss_sprt_fail_win_prob: env_parse("LOSS_SPRT_FAIL_WIN_PROB", d.loss_sprt_fail_win_prob),
+            loss_sprt_alpha: env_parse("LOSS_SPRT_ALPHA", d.loss_sprt_alpha),
+            loss_sprt_beta: env_parse("LOSS_SPRT_BETA", d.loss_sprt_beta),
+        }
+    }
+
+    /// Size multiplier from where the contract's strike falls in the
+    /// ensemble spread: shrink inside the interquartile range, where the
+    /// outcome is closest to a coin flip; grow past P10/P90 when the market
+    /// still misprices it by at least `size_tail_min_edge`.
+    pub fn size_scale(&self, ensemble: &EnsembleForecast, market_type: &MarketType, net_edge: f64) -> f64 {
+        let (lo, hi) = match *market_type {
+            MarketType::Above(t) | MarketType::Below(t) => (t, t),
+            MarketType::Between(lo, hi) => (lo, hi),
+        };
+        let in_iqr = lo <= ensemble.p75 && hi >= ensemble.p25;
+        let in_tail = hi < ensemble.p10 || lo > ensemble.p90;
+        if in_iqr {
+            self.size_iqr_scale
+        } else if in_tail && net_edge >= self.size_tail_min_edge {
+            self.size_tail_scale
+        } else {
+            1.0
+        }
+    }
+
+    /// (min volume_24h, min open interest) for a market that opened
+    /// `minutes_since_open` ago. Unknown open time → full thresholds.
+    pub fn liquidity_thresholds(&self, minutes_since_open: Option<f64>) -> (u64, u64) {
+        let scale = match minutes_since_open {
+            Some(m) if self.liquidity_ramp_hours > 0.0 => {
+                let progress = (m / 60.0 / self.liquidity_ramp_hours).clamp(0.0, 1.0);
+                self.liquidity_open_scale + (1.0 - self.liquidity_open_scale) * progress
+            }
+            _ => 1.0,
+        };
+        (
+            (self.liquidity_min_volume_24h as f64 * scale).round() as u64,
+            (self.liquidity_min_open_interest as f64 * scale).round() as u64,
+        )
+    }
+}
+
+/// What `ensemble_trim` does to the outermost members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimMode {
+    /// Remove them, shrinking the member count
+    Drop,
+    /// Clamp them to the next member inward, keeping the count
+    Winsorize,
+}
+
+impl std::str::FromStr for TrimMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "drop" => Ok(TrimMode::Drop),
+            "winsorize" => Ok(TrimMode::Winsorize),
+            other => Err(format!("unknown trim mode '{}'", other)),
+        }
+    }
+}
+
+// ── API Quotas ──
+
+/// Soft request limits, set below the providers' hard caps. Crossing one
+/// degrades polling for the rest of the window instead of failing outright.
+#[derive(Debug, Clone)]
+pub struct QuotaLimits {
+    pub kalshi_hourly: u64,
+    /// Open-Meteo free tier: 5,000/hour, 10,000/day
+    pub open_meteo_hourly: u64,
+    pub open_meteo_daily: u64,
+}
+
+impl QuotaLimits {
+    pub fn from_env() -> Self {
+        Self {
+            kalshi_hourly: env_parse("KALSHI_HOURLY_SOFT_LIMIT", 5000),
+            open_meteo_hourly: env_parse("OPEN_METEO_HOURLY_SOFT_LIMIT", 4000),
+            open_meteo_daily: env_parse("OPEN_METEO_DAILY_SOFT_LIMIT", 8000),
+        }
+    }
+}
+
+// ── Activity ──
+
+/// Trade-frequency monitor: alert when today's share of city evaluations
+/// ending in a buy (or an order) strays far from the trailing baseline.
+#[derive(Debug, Clone)]
+pub struct ActivityLimits {
+    /// Days of history the baseline covers; 0 = monitor off
+    pub baseline_days: u32,
+    /// How many times above or below the baseline counts as an anomaly
+    pub alert_ratio: f64,
+    /// Expected (or, for a spike, actual) count below which nothing is
+    /// flagged — small samples swing too far to mean anything
+    pub min_count: f64,
+}
+
+impl ActivityLimits {
+    pub fn from_env() -> Self {
+        Self {
+            baseline_days: env_parse("ACTIVITY_BASELINE_DAYS", 14),
+            alert_ratio: env_parse("ACTIVITY_ALERT_RATIO", 3.0),
+            min_count: env_parse("ACTIVITY_MIN_COUNT", 4.0),
+        }
+    }
+}
+
+/// Canary sizing for a new `strategy_version`: orders start at `fraction`
+/// of their size and ramp to full size over its first `trades` settled
+/// trades, holding at `fraction` while realized P&L trails the model's
+/// expectation by more than `tolerance_sd` standard deviations.
+#[derive(Debug, Clone)]
+pub struct CanaryLimits {
+    /// Settled trades before full size; 0 = off
+    pub trades: u32,
+    pub fraction: f64,
+    pub tolerance_sd: f64,
+}
+
+impl CanaryLimits {
+    pub fn from_env() -> Self {
+        Self {
+            trades: env_parse("CANARY_TRADES", 20),
+            fraction: env_parse("CANARY_FRACTION", 0.25_f64).clamp(0.0, 1.0),
+            tolerance_sd: env_parse("CANARY_TOLERANCE_SD", 1.0),
+        }
+    }
+}
+
+/// The running strategy version's canary ramp, saved to brain/canary.json.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CanaryState {
+    pub version: String,
+    /// When this version was first seen; its trades are the ones after
+    pub since: String,
+    pub settled: u32,
+    /// Model-expected and settled P&L of those trades, after fees
+    pub expected_cents: f64,
+    pub realized_cents: Cents,
+    /// Size multiplier for the next orders
+    pub fraction: f64,
+    /// Reached full size; stays there until the version changes
+    #[serde(default)]
+    pub graduated: bool,
+}
+
+/// City evaluations per UTC day, saved to brain/activity.json.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityState {
+    /// YYYY-MM-DD → that day's counts
+    #[serde(default)]
+    pub days: std::collections::BTreeMap<String, ActivityDay>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityDay {
+    pub cycles: u32,
+    /// City evaluations that reached the brain
+    pub evaluated: u32,
+    /// Evaluations whose best bracket was a buy
+    pub buys: u32,
+    /// Evaluations that sent an order
+    pub trades: u32,
+    /// Alerts already sent today, so each fires once
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerted: Vec<String>,
+}
+
+// ── Archive ──
+
+/// S3-compatible bucket the research data is copied to, so an ephemeral
+/// instance can go away without taking its journal and snapshots along.
+/// GCS works through its XML API with HMAC keys
+/// (ARCHIVE_ENDPOINT=https://storage.googleapis.com).
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    /// Prepended to every object key, e.g. one prefix per instance
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Minutes between syncs; the cycle uploads once this has passed
+    pub interval_minutes: u64,
+}
+
+impl ArchiveConfig {
+    /// None unless ARCHIVE_BUCKET is set; a bucket without keys is a
+    /// configuration error rather than a silently disabled archive.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let var = |key: &str| setting(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let Some(bucket) = var("ARCHIVE_BUCKET") else { return Ok(None) };
+        let (Some(access_key_id), Some(secret_access_key)) =
+            (var("ARCHIVE_ACCESS_KEY_ID"), var("ARCHIVE_SECRET_ACCESS_KEY"))
+        else {
+            anyhow::bail!("ARCHIVE_BUCKET is set but ARCHIVE_ACCESS_KEY_ID / ARCHIVE_SECRET_ACCESS_KEY are not");
+        };
+        Ok(Some(Self {
+            endpoint: var("ARCHIVE_ENDPOINT")
+                .unwrap_or_else(|| "https://s3.amazonaws.com".into())
+                .trim_end_matches('/')
+                .to_string(),
+            bucket,
+            region: var("ARCHIVE_REGION").unwrap_or_else(|| "us-east-1".into()),
+            prefix: var("ARCHIVE_PREFIX")
+                .unwrap_or_else(|| "weather-bot".into())
+                .trim_matches('/')
+                .to_string(),
+            access_key_id,
+            secret_access_key,
+            interval_minutes: env_parse("ARCHIVE_INTERVAL_MINUTES", 60),
+        }))
+    }
+}
+
+/// What has already been uploaded, saved to brain/archive_state.json: a
+/// size-and-mtime fingerprint per local path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveState {
+    #[serde(default)]
+    pub last_sync: Option<String>,
+    #[serde(default)]
+    pub uploaded: std::collections::BTreeMap<String, String>,
+}
+
+// ── Metrics ──
+
+/// One sample in `brain/metrics.prom` (Prometheus text format).
+#[derive(Debug, Clone)]
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: f64,
+}
+
+/// One city's event as `kalshi-bot scan` evaluated it: the brain's
+/// decision on every bracket whose book loaded. Nothing was placed.
+#[derive(Debug)]
+pub struct EventScan {
+    pub city: String,
+    pub event_ticker: String,
+    pub brackets: Vec<(MarketState, TradeDecision)>,
+}
+
+/// What the last cycle did, saved to brain/cycle_summary.json so health
+/// checks and status views don't have to parse logs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CycleSummary {
+    pub started_at: String,
+    pub finished_at: String,
+    /// "ok", or the risk veto that stopped the cycle
+    pub outcome: String,
+    pub trades: u32,
+    pub cities: Vec<CitySummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CitySummary {
+    pub city: String,
+    #[serde(default)]
+    pub event_ticker: Option<String>,
+    /// "skipped" before the brackets were evaluated, then "pass" or the
+    /// best bracket's action, e.g. "buy yes"; "traded" once an order went out
+    pub action: String,
+    /// Largest net edge across the event's brackets, in pp
+    #[serde(default)]
+    pub best_edge_pp: Option<f64>,
+    #[serde(default)]
+    pub pass_reasons: std::collections::BTreeMap<PassReason, u32>,
+    /// Wall time per step ("markets", "weather", "orderbooks", "brain",
+    /// "total"), in milliseconds
+    #[serde(default)]
+    pub latencies_ms: std::collections::BTreeMap<String, u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+// ── Config file ──
+
+type Settings = std::collections::BTreeMap<String, String>;
+
+/// Settings from the TOML file at CONFIG_FILE (default `config.toml`),
+/// read on first use and replaced by `Config::reload`. Keys are the
+/// environment variable names, in either case; tables only group them, so
+/// `[risk] max_shares = 25` sets MAX_SHARES. Arrays become comma lists
+/// (`cities = ["KXHIGHNY", "KXHIGHCHI"]`).
+static CONFIG_FILE: std::sync::RwLock<Option<std::sync::Arc<Result<Settings, String>>>> = std::sync::RwLock::new(None);
+
+/// CONFIG_FILE, or `config.toml`.
+pub fn config_file_path() -> String {
+    std::env::var("CONFIG_FILE")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "config.toml".into())
+}
+
+fn config_file() -> std::sync::Arc<Result<Settings, String>> {
+    if let Some(loaded) = CONFIG_FILE.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return loaded;
+    }
+    let loaded = std::sync::Arc::new(read_config_file());
+    *CONFIG_FILE.write().unwrap_or_else(|e| e.into_inner()) = Some(loaded.clone());
+    loaded
+}
+
+fn read_config_file() -> Result<Settings, String> {
+    let path = config_file_path();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        // Only a file that was asked for by name has to exist
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && std::env::var("CONFIG_FILE").is_err() => {
+            return Ok(Default::default())
+        }
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    let table: toml::Table = text.parse().map_err(|e| format!("{}: {}", path, e))?;
+    let mut settings = Settings::new();
+    flatten_settings(&table, &mut settings).map_err(|e| format!("{}: {}", path, e))?;
+    tracing::info!("Loaded {} setting(s) from {}", settings.len(), path);
+    Ok(settings)
+}
+
+fn flatten_settings(table: &toml::Table, out: &mut Settings) -> Result<(), String> {
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::Table(inner) => {
+                flatten_settings(inner, out)?;
+                continue;
+            }
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Array(items) => items
+                .iter()
+                .map(|v| match v {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        };
+        if out.insert(key.to_ascii_uppercase(), value).is_some() {
+            return Err(format!("{} is set more than once", key.to_ascii_uppercase()));
+        }
+    }
+    Ok(())
+}
+
+/// A setting: the environment variable when set, else the config file's
+/// value. Same shape as `std::env::var` so callers read either way.
+fn setting(key: &str) -> Result<String, std::env::VarError> {
+    match std::env::var(key) {
+        Err(std::env::VarError::NotPresent) => config_file()
+            .as_ref()
+            .as_ref()
+            .ok()
+            .and_then(|settings| settings.get(key).cloned())
+            .ok_or(std::env::VarError::NotPresent),
+        found => found,
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str, default: T) -> T {
+    match setting(key) {
+        Ok(v) => match v.trim().parse() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                tracing::warn!("{}={} is not valid — using default", key, v);
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+// ── Config ──
+
+/// How live buys are worked (see `core::execution`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionTactic {
+    /// Post at the strategy's limit price straight away
+    Cross,
+    /// Rest one tick below the ask first, cross whatever doesn't fill
+    Improve,
+    /// Rest inside the spread, reprice a few times, cancel whatever still
+    /// doesn't fill — never pays the taker fee
+    Post,
+}
+
+impl std::str::FromStr for ExecutionTactic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cross" => Ok(ExecutionTactic::Cross),
+            "improve" => Ok(ExecutionTactic::Improve),
+            "post" => Ok(ExecutionTactic::Post),
+            other => Err(format!("unknown execution tactic '{}'", other)),
+        }
+    }
+}
+
+/// What the weather adapter does with brain/weather_cache/ (WEATHER_CACHE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherCache {
+    Off,
+    /// Save every weather response as it arrives
+    Record,
+    /// Offline: serve the most recent saved responses and never touch the
+    /// network. Live orders are refused
+    Replay,
+}
+
+impl WeatherCache {
+    pub fn from_env() -> Self {
+        env_parse("WEATHER_CACHE", WeatherCache::Off)
+    }
+}
+
+impl std::str::FromStr for WeatherCache {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "" => Ok(WeatherCache::Off),
+            "record" => Ok(WeatherCache::Record),
+            "replay" | "offline" => Ok(WeatherCache::Replay),
+            other => Err(format!("unknown weather cache mode '{}'", other)),
+        }
+    }
+}
+
+/// Where `storage` keeps the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerBackend {
+    /// brain/ledger.md
+    Markdown,
+    /// brain/ledger.sqlite, imported from ledger.md on first use
+    Sqlite,
+}
+
+impl LedgerBackend {
+    pub fn from_env() -> Self {
+        env_parse("LEDGER_BACKEND", LedgerBackend::Markdown)
+    }
+}
+
+impl std::str::FromStr for LedgerBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Ok(LedgerBackend::Markdown),
+            "sqlite" => Ok(LedgerBackend::Sqlite),
+            other => Err(format!("unknown ledger backend '{}'", other)),
+        }
+    }
+}
+
+/// Whether the process runs one cycle or keeps cycling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    /// One cycle, then exit — for cron
+    Once,
+    /// Cycle every `cycle_interval_minutes` until stopped
+    Loop,
+}
+
+impl std::str::FromStr for RunMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "once" => Ok(RunMode::Once),
+            "loop" => Ok(RunMode::Loop),
+            other => Err(format!("unknown run mode '{}'", other)),
+        }
+    }
+}
 
 pub struct Config {
     pub max_shares: u32,
-    pub max_daily_loss_cents: i64,
+    pub max_daily_loss_cents: Cents,
     pub max_consecutive_losses: u32,
-    pub min_balance_cents: u64,
+    /// Rejections of one ticker in a UTC day before it is locked out; 0 = off
+    pub rejection_lockout: u32,
+    pub min_balance_cents: Cents,
     pub min_minutes_to_expiry: f64,
     pub paper_trade: bool,
     pub confirm_live: bool,
     pub kalshi_base_url: String,
+    /// Retries of a Kalshi call that timed out, hit a 5xx or was rate limited
+    pub kalshi_retries: u32,
+    /// First retry delay; doubles each attempt, with jitter
+    pub kalshi_retry_base_ms: u64,
+    /// Requests per second the client paces itself to; 0 = unpaced
+    pub kalshi_rate_per_sec: f64,
+    /// Requests that may go out back to back before pacing starts
+    pub kalshi_rate_burst: u32,
     pub openrouter_api_key: String,
     pub kalshi_key_id: String,
     pub kalshi_private_key_pem: String,
     pub lockfile_path: String,
+    /// A lock whose heartbeat is older than this may be taken over
+    pub lock_stale_minutes: u64,
     pub cities: Vec<CityConfig>,
+    pub strategy: StrategyParams,
+    /// Per-city, per-season patches to `strategy` (brain/seasons.toml)
+    pub seasons: Seasons,
+    /// Contact (email or URL) sent in the NWS User-Agent
+    pub nws_contact: String,
+    /// Cancel every resting order on the account, not just the bot's own
+    pub cancel_all_resting: bool,
+    /// Days to keep raw weather snapshots; aggregates are kept forever
+    pub snapshot_retention_days: u32,
+    /// Member vs bucket YES gap, in points, that gets a warning; 0 = off
+    pub bucket_check_pp: f64,
+    /// Share of ensemble members outside every bracket that gets a
+    /// warning; 0 = off
+    pub coverage_alert_mass: f64,
+    /// Only refresh a city's forecast after a new model run publishes
+    pub model_schedule: bool,
+    pub quota: QuotaLimits,
+    pub activity: ActivityLimits,
+    /// Keep the bot's quotes resting across cycles and re-quote by diff
+    pub maker_mode: bool,
+    pub execution_tactic: ExecutionTactic,
+    /// How long a price-improvement order rests before crossing
+    pub improve_wait_secs: u64,
+    /// EXECUTION_TACTIC=post: how long each resting order gets to fill
+    pub post_wait_secs: u64,
+    /// EXECUTION_TACTIC=post: times the unfilled rest is repriced before
+    /// it's cancelled
+    pub post_reprices: u32,
+    /// Per-market position limit when the exchange doesn't report one
+    pub market_position_limit_cents: Cents,
+    /// Count open positions' worst-case loss toward the daily loss limit,
+    /// not just what has settled
+    pub daily_loss_includes_open: bool,
+    /// Incoming-webhook URL for reports; unset means log only
+    pub notify_webhook_url: Option<String>,
+    /// Telegram bot for reports and operator commands; needs the chat too
+    pub telegram_bot_token: Option<String>,
+    /// The only chat the bot reports to and takes commands from
+    pub telegram_chat_id: Option<i64>,
+    /// Minutes after trading an event before the bot may trade it again,
+    /// even if the exchange doesn't show the position yet; 0 disables
+    pub trade_cooldown_minutes: u64,
+    /// Minutes between TWAP slices when a buy is bigger than the ask can
+    /// fill; 0 sends the whole size at once
+    pub twap_interval_minutes: u64,
+    /// Most a single city may have riding on positions, resting orders and
+    /// (paper) pending trades; 0 disables the cap
+    pub city_max_exposure_cents: Cents,
+    pub run_mode: RunMode,
+    /// Loop mode: minutes between cycle starts
+    pub cycle_interval_minutes: u64,
+    /// Loop mode: up to this many seconds added at random to each wait, so
+    /// restarts and neighbouring bots don't hit the APIs in step
+    pub cycle_jitter_secs: u64,
+    /// Local hours of the settlement day fetched from the ensemble API; the
+    /// daily high must fall inside them
+    pub ensemble_hours: HourWindow,
+    pub ledger_backend: LedgerBackend,
+    pub weather_cache: WeatherCache,
+    /// Base URL of a second Open-Meteo-compatible ensemble API (a
+    /// self-hosted instance, say). Set, it turns quorum mode on
+    pub quorum_ensemble_url: Option<String>,
+    /// Paper mode: trade against `SimulatedExchange` instead of only
+    /// writing ledger rows
+    pub paper_simulate: bool,
+    /// Starting balance of a new simulated account
+    pub paper_balance_cents: Cents,
+    /// Which registered strategy builds the brain (`STRATEGY`)
+    pub strategy_name: String,
+    /// `STRATEGY_VERSION`, or the strategy name plus a hash of its
+    /// parameters. A new value restarts canary sizing
+    pub strategy_version: String,
+    pub canary: CanaryLimits,
+    /// ONNX model STRATEGY=ml runs (`ML_MODEL_PATH`)
+    pub ml_model_path: String,
+    /// Sell a position at the bid once held this long, whatever its edge;
+    /// 0 = hold to settlement
+    pub max_holding_minutes: u64,
+    /// Oldest the weather and quotes behind a decision may be at order
+    /// time before the book is read again; 0 = never
+    pub quote_sla_secs: u64,
+    /// Sell once the bid is within this many points of the model's value
+    /// of the position, at a profit after fees; 0 = off
+    pub take_profit_pp: f64,
+    /// Sell once the model values the position this many points below its
+    /// entry price; 0 = off
+    pub stop_loss_pp: f64,
+    /// Off-box copy of journals, snapshots and reports (ARCHIVE_BUCKET)
+    pub archive: Option<ArchiveConfig>,
 }
 
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
-        let pem_path = std::env::var("KALSHI_PRIVATE_KEY_PATH")
+        if let Err(e) = config_file().as_ref() {
+            anyhow::bail!("Config file: {}", e);
+        }
+        let pem_path = setting("KALSHI_PRIVATE_KEY_PATH")
             .unwrap_or_else(|_| "./kalshi_private_key.pem".into());
         let pem = std::fs::read_to_string(&pem_path).unwrap_or_default();
 
         let all_cities = CityConfig::all();
-        let cities = match std::env::var("CITIES") {
+        let mut cities = match setting("CITIES") {
             Ok(filter) => {
                 let allowed: Vec<&str> = filter.split(',').map(|s| s.trim()).collect();
                 all_cities.into_iter()
@@ -297,29 +2636,372 @@ impl Config {
             Err(_) => all_cities,
         };
 
+        // SNOW_SERIES=KXSNOWSERIES:KXHIGHNY,… adds a snowfall series priced
+        // at the location of the named temperature series' city
+        if let Ok(pairs) = setting("SNOW_SERIES") {
+            for pair in pairs.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+                let (snow, at) = pair
+                    .split_once(':')
+                    .ok_or_else(|| anyhow::anyhow!("SNOW_SERIES: {:?} should be SNOW_SERIES_TICKER:CITY_SERIES_TICKER", pair))?;
+                let city = CityConfig::all()
+                    .into_iter()
+                    .find(|c| c.series_ticker == at.trim())
+                    .ok_or_else(|| anyhow::anyhow!("SNOW_SERIES: no city with series {:?}", at.trim()))?;
+                cities.push(CityConfig {
+                    name: format!("{} snow", city.name),
+                    series_ticker: snow.trim().to_string(),
+                    measure: Measure::Snowfall,
+                    ..city
+                });
+            }
+        }
+
         if cities.is_empty() {
             anyhow::bail!("No valid cities configured");
         }
 
+        // TRADING_HOURS applies to every city; TRADING_HOURS_<SERIES>
+        // overrides it for one
+        let window = |key: &str| -> anyhow::Result<Option<HourWindow>> {
+            match setting(key) {
+                Ok(v) if !v.trim().is_empty() => v.parse().map(Some).map_err(|e| anyhow::anyhow!("{}: {}", key, e)),
+                _ => Ok(None),
+            }
+        };
+        let default_window = window("TRADING_HOURS")?;
+        for city in &mut cities {
+            city.trading_hours = window(&format!("TRADING_HOURS_{}", city.series_ticker))?.or(default_window);
+        }
+
+        for city in &mut cities {
+            city.tuning = CityTuning::from_env(&city.series_ticker)?;
+        }
+
+        let strategy = StrategyParams::from_env();
+        let strategy_name = setting("STRATEGY")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "rules".into())
+            .to_ascii_lowercase();
+        // Unset, any change to the strategy or its parameters is a new version
+        let strategy_version = setting("STRATEGY_VERSION")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| {
+                use sha2::Digest;
+                let digest = sha2::Sha256::digest(format!("{}|{:?}", strategy_name, strategy));
+                let hex: String = digest.iter().take(4).map(|b| format!("{:02x}", b)).collect();
+                format!("{}-{}", strategy_name, hex)
+            });
+
         Ok(Self {
-            max_shares: 50,
-            max_daily_loss_cents: 1000,
-            max_consecutive_losses: 7,
-            min_balance_cents: 500,
-            min_minutes_to_expiry: 2.0,
-            paper_trade: std::env::var("PAPER_TRADE")
+            max_shares: env_parse("MAX_SHARES", 50),
+            max_daily_loss_cents: env_parse("MAX_DAILY_LOSS_CENTS", Cents(1000)),
+            max_consecutive_losses: env_parse("MAX_CONSECUTIVE_LOSSES", 7),
+            rejection_lockout: env_parse("REJECTION_LOCKOUT", 3),
+            min_balance_cents: env_parse("MIN_BALANCE_CENTS", Cents(500)),
+            min_minutes_to_expiry: env_parse("MIN_MINUTES_TO_EXPIRY", 2.0),
+            paper_trade: setting("PAPER_TRADE")
                 .map(|v| v != "false")
                 .unwrap_or(true),
-            confirm_live: std::env::var("CONFIRM_LIVE")
+            confirm_live: setting("CONFIRM_LIVE")
                 .map(|v| v == "true")
                 .unwrap_or(false),
-            kalshi_base_url: std::env::var("KALSHI_BASE_URL")
+            kalshi_base_url: setting("KALSHI_BASE_URL")
                 .unwrap_or_else(|_| "https://api.elections.kalshi.com".into()),
-            openrouter_api_key: std::env::var("OPENROUTER_API_KEY").unwrap_or_default(),
-            kalshi_key_id: std::env::var("KALSHI_API_KEY_ID").unwrap_or_default(),
+            kalshi_retries: env_parse("KALSHI_RETRIES", 3),
+            kalshi_retry_base_ms: env_parse("KALSHI_RETRY_BASE_MS", 500),
+            kalshi_rate_per_sec: env_parse("KALSHI_RATE_PER_SEC", 10.0),
+            kalshi_rate_burst: env_parse("KALSHI_RATE_BURST", 10),
+            openrouter_api_key: setting("OPENROUTER_API_KEY").unwrap_or_default(),
+            kalshi_key_id: setting("KALSHI_API_KEY_ID").unwrap_or_default(),
             kalshi_private_key_pem: pem,
             lockfile_path: "/tmp/kalshi-bot.lock".into(),
+            lock_stale_minutes: env_parse("LOCK_STALE_MINUTES", 10),
             cities,
+            strategy,
+            seasons: Seasons::load()?,
+            nws_contact: setting("NWS_CONTACT").unwrap_or_default(),
+            cancel_all_resting: setting("CANCEL_ALL_RESTING")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            snapshot_retention_days: env_parse("SNAPSHOT_RETENTION_DAYS", 14),
+            bucket_check_pp: env_parse("BUCKET_CHECK_PP", 5.0),
+            coverage_alert_mass: env_parse("COVERAGE_ALERT_MASS", 0.05),
+            model_schedule: setting("MODEL_SCHEDULE")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            quota: QuotaLimits::from_env(),
+            activity: ActivityLimits::from_env(),
+            maker_mode: setting("MAKER_MODE")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            execution_tactic: env_parse("EXECUTION_TACTIC", ExecutionTactic::Cross),
+            improve_wait_secs: env_parse("IMPROVE_WAIT_SECS", 20),
+            post_wait_secs: env_parse("POST_WAIT_SECS", 30),
+            post_reprices: env_parse("POST_REPRICES", 2),
+            // Kalshi's standard member limit: $25,000 per market
+            market_position_limit_cents: env_parse("MARKET_POSITION_LIMIT_CENTS", Cents(2_500_000)),
+            daily_loss_includes_open: setting("DAILY_LOSS_INCLUDES_OPEN")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            notify_webhook_url: setting("NOTIFY_WEBHOOK_URL").ok().filter(|u| !u.is_empty()),
+            telegram_bot_token: setting("TELEGRAM_BOT_TOKEN").ok().filter(|t| !t.is_empty()),
+            telegram_chat_id: setting("TELEGRAM_CHAT_ID").ok().and_then(|v| v.trim().parse().ok()),
+            trade_cooldown_minutes: env_parse("TRADE_COOLDOWN_MINUTES", 30),
+            twap_interval_minutes: env_parse("TWAP_INTERVAL_MINUTES", 0),
+            city_max_exposure_cents: env_parse("CITY_MAX_EXPOSURE_CENTS", Cents::ZERO),
+            run_mode: env_parse("RUN_MODE", RunMode::Once),
+            cycle_interval_minutes: env_parse("CYCLE_INTERVAL_MINUTES", 15),
+            cycle_jitter_secs: env_parse("CYCLE_JITTER_SECS", 60),
+            ensemble_hours: env_parse("ENSEMBLE_HOURS", HourWindow { start_hour: 0, end_hour: 24 }),
+            ledger_backend: LedgerBackend::from_env(),
+            weather_cache: WeatherCache::from_env(),
+            quorum_ensemble_url: setting("QUORUM_ENSEMBLE_URL")
+                .ok()
+                .map(|u| u.trim().trim_end_matches('/').to_string())
+                .filter(|u| !u.is_empty()),
+            paper_simulate: setting("PAPER_SIMULATE")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            paper_balance_cents: env_parse("PAPER_BALANCE_CENTS", Cents(10_000)),
+            strategy_name,
+            strategy_version,
+            canary: CanaryLimits::from_env(),
+            ml_model_path: setting("ML_MODEL_PATH")
+                .ok()
+                .filter(|p| !p.is_empty())
+                .unwrap_or_else(|| "brain/model.onnx".into()),
+            max_holding_minutes: env_parse("MAX_HOLDING_MINUTES", 0),
+            quote_sla_secs: env_parse("QUOTE_SLA_SECS", 20),
+            take_profit_pp: env_parse("TAKE_PROFIT_PP", 0.0),
+            stop_loss_pp: env_parse("STOP_LOSS_PP", 0.0),
+            archive: ArchiveConfig::from_env()?,
         })
     }
+
+    /// Paper trades exist only as ledger rows: the exchange never sees
+    /// them, so positions, balance and resting orders have to be adjusted
+    /// for pending rows. Not so under simulation.
+    pub fn paper_ledger(&self) -> bool {
+        self.paper_trade && !self.paper_simulate
+    }
+
+    /// This config re-read with config.toml as it is on disk now, for a
+    /// running loop. A file that doesn't parse, or settings that don't
+    /// validate, are an error and the previous file stays in force.
+    /// Settings the process was built on — credentials, the exchange
+    /// client, trading mode, lock, storage, notifier — keep their running
+    /// values; the second value names the ones the file tried to change.
+    pub fn reload(&self) -> anyhow::Result<(Self, Vec<&'static str>)> {
+        let previous = config_file();
+        let fresh = read_config_file().map_err(|e| anyhow::anyhow!(e))?;
+        *CONFIG_FILE.write().unwrap_or_else(|e| e.into_inner()) = Some(std::sync::Arc::new(Ok(fresh)));
+        let mut config = match Self::from_env() {
+            Ok(config) => config,
+            Err(e) => {
+                *CONFIG_FILE.write().unwrap_or_else(|e| e.into_inner()) = Some(previous);
+                return Err(e);
+            }
+        };
+
+        fn keep<T: PartialEq + Clone>(name: &'static str, new: &mut T, running: &T, kept: &mut Vec<&'static str>) {
+            if new != running {
+                *new = running.clone();
+                kept.push(name);
+            }
+        }
+        let mut kept = Vec::new();
+        keep("PAPER_TRADE", &mut config.paper_trade, &self.paper_trade, &mut kept);
+        keep("CONFIRM_LIVE", &mut config.confirm_live, &self.confirm_live, &mut kept);
+        keep("PAPER_SIMULATE", &mut config.paper_simulate, &self.paper_simulate, &mut kept);
+        keep("PAPER_BALANCE_CENTS", &mut config.paper_balance_cents, &self.paper_balance_cents, &mut kept);
+        keep("KALSHI_BASE_URL", &mut config.kalshi_base_url, &self.kalshi_base_url, &mut kept);
+        keep("KALSHI_API_KEY_ID", &mut config.kalshi_key_id, &self.kalshi_key_id, &mut kept);
+        keep("KALSHI_PRIVATE_KEY_PATH", &mut config.kalshi_private_key_pem, &self.kalshi_private_key_pem, &mut kept);
+        keep("KALSHI_RETRIES", &mut config.kalshi_retries, &self.kalshi_retries, &mut kept);
+        keep("KALSHI_RETRY_BASE_MS", &mut config.kalshi_retry_base_ms, &self.kalshi_retry_base_ms, &mut kept);
+        keep("KALSHI_RATE_PER_SEC", &mut config.kalshi_rate_per_sec, &self.kalshi_rate_per_sec, &mut kept);
+        keep("KALSHI_RATE_BURST", &mut config.kalshi_rate_burst, &self.kalshi_rate_burst, &mut kept);
+        keep("LOCK_STALE_MINUTES", &mut config.lock_stale_minutes, &self.lock_stale_minutes, &mut kept);
+        keep("RUN_MODE", &mut config.run_mode, &self.run_mode, &mut kept);
+        keep("LEDGER_BACKEND", &mut config.ledger_backend, &self.ledger_backend, &mut kept);
+        keep("WEATHER_CACHE", &mut config.weather_cache, &self.weather_cache, &mut kept);
+        keep("NWS_CONTACT", &mut config.nws_contact, &self.nws_contact, &mut kept);
+        keep("QUORUM_ENSEMBLE_URL", &mut config.quorum_ensemble_url, &self.quorum_ensemble_url, &mut kept);
+        keep("TELEGRAM_BOT_TOKEN", &mut config.telegram_bot_token, &self.telegram_bot_token, &mut kept);
+        keep("TELEGRAM_CHAT_ID", &mut config.telegram_chat_id, &self.telegram_chat_id, &mut kept);
+        keep("ENSEMBLE_HOURS", &mut config.ensemble_hours, &self.ensemble_hours, &mut kept);
+        // The weather client decides at startup whether to download NBM
+        // and HRRR at all; a weight can move, but not to or from zero
+        if (config.strategy.nbm_weight > 0.0) != (self.strategy.nbm_weight > 0.0) {
+            config.strategy.nbm_weight = self.strategy.nbm_weight;
+            kept.push("NBM_WEIGHT");
+        }
+        if (config.strategy.hrrr_weight > 0.0) != (self.strategy.hrrr_weight > 0.0) {
+            config.strategy.hrrr_weight = self.strategy.hrrr_weight;
+            kept.push("HRRR_WEIGHT");
+        }
+        Ok((config, kept))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(strike_type: &str, floor: Option<f64>, cap: Option<f64>) -> MarketState {
+        MarketState {
+            ticker: "KXHIGHNY-26OCT16-B74.5".into(),
+            event_ticker: "KXHIGHNY-26OCT16".into(),
+            title: String::new(),
+            yes_bid: None,
+            yes_ask: None,
+            no_bid: None,
+            no_ask: None,
+            last_price: None,
+            volume: 0,
+            volume_24h: 0,
+            open_interest: 0,
+            expiration_time: String::new(),
+            minutes_to_expiry: 600.0,
+            minutes_since_open: None,
+            floor_strike: floor,
+            cap_strike: cap,
+            strike_type: strike_type.into(),
+            position_limit_cents: None,
+        }
+    }
+
+    #[test]
+    fn saturating_sub_to_zero_stops_at_zero_without_overflow() {
+        assert_eq!(Cents(500).saturating_sub_to_zero(Cents(120)), Cents(380));
+        assert_eq!(Cents(100).saturating_sub_to_zero(Cents(250)), Cents::ZERO);
+        assert_eq!(Cents(i64::MIN).saturating_sub_to_zero(Cents(1)), Cents::ZERO);
+        assert_eq!(Cents(i64::MAX).saturating_sub_to_zero(Cents(-1)), Cents(i64::MAX));
+    }
+
+    #[test]
+    fn parses_observed_strike_formats() {
+        for (raw, expected) in [
+            ("75", 75.0),
+            (" 75 ", 75.0),
+            ("75°", 75.0),
+            ("75°F", 75.0),
+            ("75 °f", 75.0),
+            ("74.5 ºF", 74.5),
+            ("76.5˚", 76.5),
+            ("−3°", -3.0),
+            ("-3", -3.0),
+            ("0°C", 32.0),
+            ("25C", 77.0),
+            ("2\"", 2.0),
+            ("2.5 in", 2.5),
+            ("3 inches", 3.0),
+            ("1 inch", 1.0),
+        ] {
+            assert_eq!(parse_strike(raw), Some(expected), "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn rejects_unparseable_strikes() {
+        for raw in ["", "°", "seventy", "NaN", "inf", "75°K"] {
+            assert_eq!(parse_strike(raw), None, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn half_degree_ladder_parses_as_between() {
+        let m = market("between", Some(74.5), Some(76.5));
+        assert!(matches!(MarketType::from_market(&m), Some(MarketType::Between(lo, hi)) if lo == 74.5 && hi == 76.5));
+        // High-first bounds are swapped back
+        let m = market("between", Some(76.5), Some(74.5));
+        assert!(matches!(MarketType::from_market(&m), Some(MarketType::Between(lo, hi)) if lo == 74.5 && hi == 76.5));
+        // Zero-width ranges are unusable
+        assert!(MarketType::from_market(&market("between", Some(75.0), Some(75.0))).is_none());
+    }
+
+    #[test]
+    fn strike_type_spellings() {
+        for s in ["greater", "GREATER_OR_EQUAL", " above ", ">", ">="] {
+            assert!(matches!(MarketType::from_market(&market(s, Some(80.0), None)), Some(MarketType::Above(t)) if t == 80.0), "{:?}", s);
+        }
+        for s in ["less", "less_or_equal", "Below", "<", "<="] {
+            assert!(matches!(MarketType::from_market(&market(s, None, Some(60.0))), Some(MarketType::Below(t)) if t == 60.0), "{:?}", s);
+        }
+        for s in ["between", "between_inclusive", "RANGE"] {
+            assert!(matches!(MarketType::from_market(&market(s, Some(70.0), Some(72.0))), Some(MarketType::Between(..))), "{:?}", s);
+        }
+        // A named type without its strike is unusable, not guessed at
+        assert!(MarketType::from_market(&market("greater", None, Some(60.0))).is_none());
+    }
+
+    #[test]
+    fn unknown_strike_type_falls_back_on_strikes_present() {
+        assert!(matches!(MarketType::from_market(&market("custom", Some(70.0), Some(72.0))), Some(MarketType::Between(..))));
+        assert!(matches!(MarketType::from_market(&market("", Some(70.0), None)), Some(MarketType::Above(_))));
+        assert!(matches!(MarketType::from_market(&market("", None, Some(70.0))), Some(MarketType::Below(_))));
+        assert!(MarketType::from_market(&market("", None, None)).is_none());
+        assert!(MarketType::from_market(&market("", Some(f64::NAN), None)).is_none());
+    }
+
+    fn ensemble() -> EnsembleForecast {
+        EnsembleForecast {
+            model_count: 30,
+            mean_high: 75.0,
+            min_high: 68.0,
+            max_high: 82.0,
+            std_dev: 3.0,
+            p10: 71.0,
+            p25: 73.0,
+            p75: 77.0,
+            p90: 79.0,
+        }
+    }
+
+    fn sizing() -> StrategyParams {
+        StrategyParams { size_iqr_scale: 0.5, size_tail_scale: 2.0, size_tail_min_edge: 0.15, ..StrategyParams::default() }
+    }
+
+    #[test]
+    fn size_shrinks_inside_the_interquartile_range() {
+        let p = sizing();
+        let e = ensemble();
+        assert_eq!(p.size_scale(&e, &MarketType::Above(75.0), 0.3), 0.5);
+        assert_eq!(p.size_scale(&e, &MarketType::Below(73.0), 0.3), 0.5);
+        // A bracket overlapping the IQR from outside counts as inside
+        assert_eq!(p.size_scale(&e, &MarketType::Between(70.0, 73.5), 0.3), 0.5);
+    }
+
+    #[test]
+    fn size_grows_past_p10_p90_only_with_enough_edge() {
+        let p = sizing();
+        let e = ensemble();
+        assert_eq!(p.size_scale(&e, &MarketType::Above(80.0), 0.20), 2.0);
+        assert_eq!(p.size_scale(&e, &MarketType::Between(68.0, 70.0), 0.15), 2.0);
+        assert_eq!(p.size_scale(&e, &MarketType::Above(80.0), 0.10), 1.0);
+    }
+
+    #[test]
+    fn size_unchanged_between_the_quartiles_and_tails() {
+        let p = sizing();
+        let e = ensemble();
+        assert_eq!(p.size_scale(&e, &MarketType::Above(78.0), 0.5), 1.0);
+        assert_eq!(p.size_scale(&e, &MarketType::Below(72.0), 0.5), 1.0);
+        // Defaults leave every size alone
+        let d = StrategyParams::default();
+        assert_eq!(d.size_scale(&e, &MarketType::Above(75.0), 0.5), 1.0);
+        assert_eq!(d.size_scale(&e, &MarketType::Above(80.0), 0.5), 1.0);
+    }
+
+    #[test]
+    fn hour_window_parses_start_end() {
+        assert_eq!("9-19".parse(), Ok(HourWindow { start_hour: 9, end_hour: 19 }));
+        assert_eq!(" 0 - 24 ".parse(), Ok(HourWindow { start_hour: 0, end_hour: 24 }));
+        for bad in ["", "9", "19-9", "9-9", "0-25", "a-b"] {
+            assert!(bad.parse::<HourWindow>().is_err(), "{:?}", bad);
+        }
+    }
 }
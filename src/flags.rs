@@ -0,0 +1,210 @@
+use crate::core::types::{CityConfig, FeatureSwitches};
+use crate::storage;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A subsystem that can be rolled out or back from brain/flags.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    /// Ensemble members and buckets in the weather snapshot
+    Ensemble,
+    /// YES probability interpolated from ensemble buckets
+    BucketFallback,
+    /// YES probability from a sigmoid around the point forecast
+    SigmoidFallback,
+    /// MAKER_MODE quoting; off falls back to taking
+    MakerMode,
+    /// Max holding time, take profit and stop loss
+    Exits,
+    /// STRATEGY=llm; off decides with the rules brain
+    LlmBrain,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 6] = [
+        Feature::Ensemble,
+        Feature::BucketFallback,
+        Feature::SigmoidFallback,
+        Feature::MakerMode,
+        Feature::Exits,
+        Feature::LlmBrain,
+    ];
+
+    /// Section name in flags.toml.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feature::Ensemble => "ensemble",
+            Feature::BucketFallback => "bucket_fallback",
+            Feature::SigmoidFallback => "sigmoid_fallback",
+            Feature::MakerMode => "maker_mode",
+            Feature::Exits => "exits",
+            Feature::LlmBrain => "llm_brain",
+        }
+    }
+}
+
+/// brain/flags.toml: which subsystems run, per city and per share of
+/// cycles. A feature without a section is on everywhere.
+///
+/// ```toml
+/// [maker_mode]
+/// cities = ["New York", "KXHIGHCHI"]   # only these; empty = every city
+/// percent = 25                         # of cycles
+///
+/// [llm_brain]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Flags {
+    #[serde(flatten)]
+    pub features: BTreeMap<String, Flag>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Flag {
+    /// Off everywhere when false
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// City names or series tickers it runs for; empty = every city
+    #[serde(default)]
+    pub cities: Vec<String>,
+    /// Share of cycles it runs in, 0..=100
+    #[serde(default = "default_percent")]
+    pub percent: u32,
+    /// Why, for the logs
+    pub note: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_percent() -> u32 {
+    100
+}
+
+impl Flag {
+    fn covers(&self, city: &CityConfig) -> bool {
+        self.cities.is_empty()
+            || self
+                .cities
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(&city.name) || c.eq_ignore_ascii_case(&city.series_ticker))
+    }
+}
+
+impl Flags {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let flags: Self = toml::from_str(text)?;
+        for (name, flag) in &flags.features {
+            if !Feature::ALL.iter().any(|f| f.label() == name) {
+                let names: Vec<&str> = Feature::ALL.iter().map(|f| f.label()).collect();
+                anyhow::bail!("unknown feature {:?} — available: {}", name, names.join(", "));
+            }
+            if flag.percent > 100 {
+                anyhow::bail!("{}: percent {} is over 100", name, flag.percent);
+            }
+        }
+        Ok(flags)
+    }
+
+    pub fn get(&self, feature: Feature) -> Option<&Flag> {
+        self.features.get(feature.label())
+    }
+
+    /// This cycle's view: one roll per feature decides whether a partial
+    /// rollout runs this time.
+    pub fn roll(self) -> CycleFlags {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let rolls = Feature::ALL.iter().map(|f| (*f, rng.gen_range(0..100))).collect();
+        CycleFlags { flags: self, rolls }
+    }
+}
+
+/// Flags resolved for one cycle.
+#[derive(Debug, Clone, Default)]
+pub struct CycleFlags {
+    flags: Flags,
+    rolls: BTreeMap<Feature, u32>,
+}
+
+impl CycleFlags {
+    /// Whether `feature` runs this cycle — for `city`, or with None for
+    /// work that isn't tied to one city (then a city list doesn't matter).
+    pub fn on(&self, feature: Feature, city: Option<&CityConfig>) -> bool {
+        let Some(flag) = self.flags.get(feature) else { return true };
+        flag.enabled
+            && city.is_none_or(|c| flag.covers(c))
+            && self.rolls.get(&feature).copied().unwrap_or(0) < flag.percent
+    }
+
+    /// Every switch for one city, as the brain sees them.
+    pub fn switches(&self, city: &CityConfig) -> FeatureSwitches {
+        FeatureSwitches {
+            ensemble: self.on(Feature::Ensemble, Some(city)),
+            bucket_fallback: self.on(Feature::BucketFallback, Some(city)),
+            sigmoid_fallback: self.on(Feature::SigmoidFallback, Some(city)),
+            maker_mode: self.on(Feature::MakerMode, Some(city)),
+            exits: self.on(Feature::Exits, Some(city)),
+            llm_brain: self.on(Feature::LlmBrain, Some(city)),
+        }
+    }
+
+    /// Features switched off for `city` this cycle, with their notes, for
+    /// the logs.
+    pub fn off_for(&self, city: &CityConfig) -> Vec<String> {
+        Feature::ALL
+            .iter()
+            .filter(|f| !self.on(**f, Some(city)))
+            .map(|f| match self.flags.get(*f).and_then(|flag| flag.note.as_deref()) {
+                Some(note) => format!("{} ({})", f.label(), note),
+                None => f.label().to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Keeps the last good flags and reloads them when the file changes, as
+/// `OverridesWatcher` does. A missing file means every feature is on; an
+/// edit that doesn't parse is logged and ignored.
+pub struct FlagsWatcher {
+    state: Mutex<(Flags, Option<SystemTime>)>,
+}
+
+impl FlagsWatcher {
+    /// Fails if the file exists but doesn't parse.
+    pub fn load() -> anyhow::Result<Self> {
+        let modified = storage::flags_modified();
+        let flags = match storage::read_flags()? {
+            Some(text) => Flags::parse(&text).map_err(|e| anyhow::anyhow!("brain/flags.toml: {:#}", e))?,
+            None => Flags::default(),
+        };
+        if !flags.features.is_empty() {
+            tracing::info!("Loaded flags.toml: {} feature(s) configured", flags.features.len());
+        }
+        Ok(Self { state: Mutex::new((flags, modified)) })
+    }
+
+    pub fn current(&self) -> Flags {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let modified = storage::flags_modified();
+        if modified != state.1 {
+            state.1 = modified;
+            let reloaded = storage::read_flags()
+                .and_then(|text| text.map(|t| Flags::parse(&t)).transpose())
+                .map(Option::unwrap_or_default);
+            match reloaded {
+                Ok(flags) => {
+                    tracing::info!("Reloaded flags.toml: {} feature(s) configured", flags.features.len());
+                    state.0 = flags;
+                }
+                Err(e) => tracing::warn!("flags.toml changed but was rejected, keeping the previous flags: {:#}", e),
+            }
+        }
+        state.0.clone()
+    }
+}
@@ -1,13 +1,29 @@
 mod adapters;
+mod archive;
+mod capture;
+mod cli;
+mod config;
 mod core;
+mod flags;
+mod overrides;
+mod parquet;
 mod ports;
+mod prompt;
+mod quota;
+mod report;
 mod safety;
 mod storage;
+mod strategies;
 
+use adapters::bucket::BucketSink;
 use adapters::weather::WeatherClient;
 use adapters::kalshi::client::KalshiClient;
-use core::rules_brain::RulesBrain;
-use core::types::Config;
+use adapters::simulated::SimulatedExchange;
+use adapters::telegram::TelegramBot;
+use clap::Parser;
+use cli::Command;
+use core::types::{Config, RunMode};
+use ports::exchange::Exchange;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -16,20 +32,232 @@ async fn main() -> anyhow::Result<()> {
     }
     tracing_subscriber::fmt::init();
 
-    let config = Config::from_env()?;
+    let command = cli::Cli::parse().command;
+    if let Some(result) = command.as_ref().and_then(cli::dispatch) {
+        return result;
+    }
+
+    let mut config = Config::from_env()?;
+    match command {
+        Some(Command::Run) => config.run_mode = RunMode::Once,
+        Some(Command::Loop) => config.run_mode = RunMode::Loop,
+        _ => {}
+    }
     let city_names: Vec<&str> = config.cities.iter().map(|c| c.name.as_str()).collect();
     tracing::info!(
-        "paper_trade={} confirm_live={} cities=[{}]",
-        config.paper_trade, config.confirm_live, city_names.join(", ")
+        "paper_trade={} confirm_live={} strategy={} cities=[{}]",
+        config.paper_trade, config.confirm_live, config.strategy_name, city_names.join(", ")
     );
 
+    if let Some(from) = storage::migrate_ledger("brain/ledger.md")? {
+        tracing::info!("Upgraded brain/ledger.md from schema v{} (backup kept)", from);
+    }
+
+    // Runs without startup validation, so a misconfigured or stopped bot
+    // can still be inspected; an unreachable exchange just drops the
+    // account section
+    if let Some(Command::Status { offline }) = command {
+        let exchange = if offline {
+            None
+        } else {
+            match WeatherClient::new(&config).and_then(|w| exchange_for(&config, &std::sync::Arc::new(w))) {
+                Ok(exchange) => Some(exchange),
+                Err(e) => {
+                    tracing::warn!("Exchange unavailable for status: {:#}", e);
+                    None
+                }
+            }
+        };
+        let result = cli::status(&config, exchange.as_deref()).await;
+        if let Err(e) = storage::record_api_usage(&quota::take()) {
+            tracing::warn!("API usage write failed: {}", e);
+        }
+        return result;
+    }
+
     safety::validate_startup(&config)?;
 
-    let _lock = safety::Lockfile::acquire(&config.lockfile_path)?;
+    // Pre-open report: reads markets and forecasts but never trades, so it
+    // runs alongside a live cycle without taking the lock
+    if let Some(Command::Plan) = command {
+        let exchange = KalshiClient::new(&config)?;
+        let weather_feed = WeatherClient::new(&config)?;
+        let notifier = adapters::notifier::notifier_for(&config);
+        let result = core::plan::run_plan(&exchange, &weather_feed, notifier.as_ref(), &config).await;
+        if let Err(e) = storage::record_api_usage(&quota::take()) {
+            tracing::warn!("API usage write failed: {}", e);
+        }
+        return result;
+    }
+
+    // What a cycle would decide right now, from the same inputs; like the
+    // plan it never orders, so it doesn't take the lock either
+    if let Some(Command::Scan) = command {
+        let exchange = KalshiClient::new(&config)?;
+        let weather_feed = WeatherClient::new(&config)?;
+        let brain = strategies::brain_for(&config)?;
+        let (prompt, overrides, flags) = (prompt::PromptWatcher::load()?, overrides::OverridesWatcher::load()?, flags::FlagsWatcher::load()?);
+        let result = core::engine::scan(&exchange, brain.as_ref(), &weather_feed, &prompt, &overrides, &flags, &config).await;
+        if let Err(e) = storage::record_api_usage(&quota::take()) {
+            tracing::warn!("API usage write failed: {}", e);
+        }
+        cli::print_scan(&result?);
+        return Ok(());
+    }
+
+    // Push the research data to the bucket now, e.g. before tearing the
+    // instance down
+    if let Some(Command::Archive) = command {
+        let (Some(sink), Some(archive)) = (BucketSink::from_config(&config), config.archive.as_ref()) else {
+            anyhow::bail!("archive: ARCHIVE_BUCKET is not set");
+        };
+        let (uploaded, failed) = archive::sync(&sink, &archive.prefix).await?;
+        println!("Uploaded {} file(s) to {}", uploaded, archive.bucket);
+        if failed > 0 {
+            anyhow::bail!("{} file(s) failed to upload", failed);
+        }
+        return Ok(());
+    }
 
-    let exchange = KalshiClient::new(&config)?;
-    let brain = RulesBrain::new();
-    let weather_feed = WeatherClient::new()?;
+    let lock = std::sync::Arc::new(safety::Lockfile::acquire(
+        &config.lockfile_path,
+        std::time::Duration::from_secs(config.lock_stale_minutes * 60),
+    )?);
+    let heartbeat_lock = lock.clone();
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(60));
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            if let Err(e) = heartbeat_lock.heartbeat() {
+                tracing::error!("Lock heartbeat failed: {}", e);
+            }
+        }
+    });
+
+    let weather_client = std::sync::Arc::new(WeatherClient::new(&config)?);
+    let exchange = exchange_for(&config, &weather_client)?;
+    let cancelled = match &command {
+        Some(Command::Cancel { order_ids }) => Some(core::engine::cancel_by_operator(exchange.as_ref(), order_ids).await),
+        Some(Command::CancelAll) => Some(core::engine::cancel_all_by_operator(exchange.as_ref()).await),
+        _ => None,
+    };
+    if let Some(result) = cancelled {
+        if let Err(e) = storage::record_api_usage(&quota::take()) {
+            tracing::warn!("API usage write failed: {}", e);
+        }
+        return result;
+    }
 
-    core::engine::run_cycle(&exchange, &brain, &weather_feed, &config).await
+    // Operator commands: loop mode keeps listening; once mode answers what
+    // arrived since the last run
+    if let Some(bot) = TelegramBot::from_config(&config) {
+        match config.run_mode {
+            RunMode::Loop => {
+                tokio::spawn(async move { bot.listen().await });
+            }
+            RunMode::Once => {
+                if let Err(e) = bot.handle_waiting().await {
+                    tracing::warn!("Telegram commands unavailable: {:#}", e);
+                }
+            }
+        }
+    }
+
+    let prompt = prompt::PromptWatcher::load()?;
+    let overrides = overrides::OverridesWatcher::load()?;
+    let flags = flags::FlagsWatcher::load()?;
+    match config.run_mode {
+        RunMode::Once => run_once(exchange.as_ref(), &weather_client, &prompt, &overrides, &flags, &config).await,
+        RunMode::Loop => {
+            let config = config::ConfigWatcher::new(config);
+            run_loop(exchange.as_ref(), &weather_client, &prompt, &overrides, &flags, &config).await
+        }
+    }
+}
+
+/// Kalshi, or with PAPER_SIMULATE the simulator trading against Kalshi's
+/// live books.
+fn exchange_for(config: &Config, weather_client: &std::sync::Arc<WeatherClient>) -> anyhow::Result<Box<dyn Exchange>> {
+    let kalshi = KalshiClient::new(config)?;
+    if config.paper_trade && config.paper_simulate {
+        tracing::info!("Paper trading against the simulated exchange");
+        return Ok(Box::new(SimulatedExchange::new(Box::new(kalshi), weather_client.clone(), config)?));
+    }
+    Ok(Box::new(kalshi))
+}
+
+/// One trading cycle with a fresh prefetch and brain, so a long-running
+/// process picks up new model runs and whatever the strategy has learned.
+async fn run_once(
+    exchange: &dyn Exchange,
+    weather_client: &std::sync::Arc<WeatherClient>,
+    prompt: &prompt::PromptWatcher,
+    overrides: &overrides::OverridesWatcher,
+    flags: &flags::FlagsWatcher,
+    config: &Config,
+) -> anyhow::Result<()> {
+    // Warm start: weather downloads while the cycle cancels, settles and
+    // checks risk
+    let weather_feed = core::prefetch::PrefetchFeed::start(weather_client.clone(), config);
+    let brain = strategies::brain_for(config)?;
+
+    let notifier = adapters::notifier::notifier_for(config);
+    let result = core::engine::run_cycle(exchange, brain.as_ref(), &weather_feed, notifier.as_ref(), prompt, overrides, flags, config).await;
+    // Requests from a cycle that bailed early still count against quotas
+    if let Err(e) = storage::record_api_usage(&quota::take()) {
+        tracing::warn!("API usage write failed: {}", e);
+    }
+    sync_archive(config, false).await;
+    result
+}
+
+/// Upload what changed to ARCHIVE_BUCKET, if configured and due.
+async fn sync_archive(config: &Config, force: bool) {
+    if let (Some(sink), Some(archive)) = (BucketSink::from_config(config), config.archive.as_ref()) {
+        archive::sync_if_due(&sink, archive, force).await;
+    }
+}
+
+/// RUN_MODE=loop: cycle on CYCLE_INTERVAL_MINUTES (sooner when a model run
+/// lands, with MODEL_SCHEDULE) until Ctrl-C. A failed cycle is logged and
+/// the next one runs on schedule. Edits to config.toml and prompt.md apply
+/// from the next cycle.
+async fn run_loop(
+    exchange: &dyn Exchange,
+    weather_client: &std::sync::Arc<WeatherClient>,
+    prompt: &prompt::PromptWatcher,
+    overrides: &overrides::OverridesWatcher,
+    flags: &flags::FlagsWatcher,
+    config_file: &config::ConfigWatcher,
+) -> anyhow::Result<()> {
+    use rand::Rng;
+
+    let config = config_file.current();
+    tracing::info!(
+        "Loop mode: a cycle every {}min (+ up to {}s jitter)",
+        config.cycle_interval_minutes, config.cycle_jitter_secs
+    );
+    loop {
+        let current = config_file.current();
+        let config = current.as_ref();
+        if let Err(e) = run_once(exchange, weather_client, prompt, overrides, flags, config).await {
+            tracing::error!("Cycle failed: {:#} — next cycle on schedule", e);
+        }
+
+        let now = chrono::Utc::now();
+        let next = core::schedule::next_cycle(now, config.cycle_interval_minutes, &config.cities, config.model_schedule);
+        let jitter = rand::thread_rng().gen_range(0..=config.cycle_jitter_secs);
+        let wait = (next - now).to_std().unwrap_or_default() + std::time::Duration::from_secs(jitter);
+        tracing::info!("Next cycle at {} (+{}s jitter)", next.format("%H:%M:%S UTC"), jitter);
+
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("Interrupted — stopping after the last completed cycle");
+                sync_archive(config, true).await;
+                return Ok(());
+            }
+        }
+    }
 }
@@ -0,0 +1,142 @@
+use crate::core::types::CityConfig;
+use crate::storage;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// brain/overrides.toml: the operator's temporary hand on the pipeline,
+/// per city (by name or series ticker) and per bracket ticker.
+///
+/// ```toml
+/// [city."New York"]
+/// pass = true
+/// until = "2026-10-17T04:00:00Z"
+/// note = "hurricane remnants, models all over the place"
+///
+/// [ticker."KXHIGHNY-26OCT16-B71.5"]
+/// max_shares = 5
+/// fair_yes = 0.30
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    #[serde(default)]
+    pub city: BTreeMap<String, Override>,
+    #[serde(default)]
+    pub ticker: BTreeMap<String, Override>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// No new entries
+    #[serde(default)]
+    pub pass: bool,
+    /// Cap on contracts per order
+    pub max_shares: Option<u32>,
+    /// YES probability used in place of the model's (ticker entries only)
+    pub fair_yes: Option<f64>,
+    /// RFC 3339; the entry is ignored from then on
+    pub until: Option<String>,
+    /// Why, for the logs
+    pub note: Option<String>,
+}
+
+impl Override {
+    pub fn active(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.until.as_deref().map(chrono::DateTime::parse_from_rfc3339) {
+            Some(Ok(until)) => now < until,
+            _ => true,
+        }
+    }
+
+    /// ` (note)` for log lines, or nothing.
+    pub fn note_suffix(&self) -> String {
+        self.note.as_deref().map(|n| format!(" ({})", n)).unwrap_or_default()
+    }
+}
+
+impl Overrides {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let overrides: Self = toml::from_str(text)?;
+        for (key, o) in overrides.city.iter().chain(&overrides.ticker) {
+            if let Some(until) = &o.until {
+                chrono::DateTime::parse_from_rfc3339(until)
+                    .map_err(|e| anyhow::anyhow!("{}: until {:?} is not RFC 3339: {}", key, until, e))?;
+            }
+            if let Some(p) = o.fair_yes.filter(|p| !(0.0..=1.0).contains(p)) {
+                anyhow::bail!("{}: fair_yes {} is outside 0..=1", key, p);
+            }
+        }
+        if let Some(key) = overrides.city.iter().find(|(_, o)| o.fair_yes.is_some()).map(|(k, _)| k) {
+            anyhow::bail!("city {}: fair_yes is per ticker, not per city", key);
+        }
+        Ok(overrides)
+    }
+
+    /// The unexpired entry for a city, matched on name or series ticker.
+    pub fn for_city(&self, city: &CityConfig, now: chrono::DateTime<chrono::Utc>) -> Option<&Override> {
+        self.city
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&city.name) || k.eq_ignore_ascii_case(&city.series_ticker))
+            .map(|(_, o)| o)
+            .filter(|o| o.active(now))
+    }
+
+    /// The unexpired entry for a bracket ticker.
+    pub fn for_ticker(&self, ticker: &str, now: chrono::DateTime<chrono::Utc>) -> Option<&Override> {
+        self.ticker.get(ticker).filter(|o| o.active(now))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.city.is_empty() && self.ticker.is_empty()
+    }
+}
+
+/// Keeps the last good overrides and reloads them when the file changes,
+/// as `PromptWatcher` does for the prompt. A missing file means no
+/// overrides; an edit that doesn't parse is logged and ignored.
+pub struct OverridesWatcher {
+    state: Mutex<(Overrides, Option<SystemTime>)>,
+}
+
+impl OverridesWatcher {
+    /// Fails if the file exists but doesn't parse.
+    pub fn load() -> anyhow::Result<Self> {
+        let modified = storage::overrides_modified();
+        let overrides = match storage::read_overrides()? {
+            Some(text) => Overrides::parse(&text).map_err(|e| anyhow::anyhow!("brain/overrides.toml: {:#}", e))?,
+            None => Overrides::default(),
+        };
+        if !overrides.is_empty() {
+            tracing::info!(
+                "Loaded overrides.toml: {} city and {} ticker entries",
+                overrides.city.len(), overrides.ticker.len()
+            );
+        }
+        Ok(Self { state: Mutex::new((overrides, modified)) })
+    }
+
+    pub fn current(&self) -> Overrides {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let modified = storage::overrides_modified();
+        if modified != state.1 {
+            state.1 = modified;
+            let reloaded = storage::read_overrides()
+                .and_then(|text| text.map(|t| Overrides::parse(&t)).transpose())
+                .map(Option::unwrap_or_default);
+            match reloaded {
+                Ok(overrides) => {
+                    tracing::info!(
+                        "Reloaded overrides.toml: {} city and {} ticker entries",
+                        overrides.city.len(), overrides.ticker.len()
+                    );
+                    state.0 = overrides;
+                }
+                Err(e) => tracing::warn!("overrides.toml changed but was rejected, keeping the previous overrides: {:#}", e),
+            }
+        }
+        state.0.clone()
+    }
+}
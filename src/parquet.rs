@@ -0,0 +1,352 @@
+//! Minimal Parquet writer for the research dataset: one row group, one
+//! uncompressed PLAIN data page per column, every column OPTIONAL. Enough
+//! for pandas, polars, DuckDB and Spark to read, without pulling the
+//! Arrow stack into the bot. Metadata is Thrift compact protocol, written
+//! by hand below.
+
+use crate::core::dataset::{Column, Values};
+
+const MAGIC: &[u8] = b"PAR1";
+
+// parquet.thrift enums
+const TYPE_BOOLEAN: i32 = 0;
+const TYPE_INT64: i32 = 2;
+const TYPE_DOUBLE: i32 = 5;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const REPETITION_OPTIONAL: i32 = 1;
+const CONVERTED_UTF8: i32 = 0;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const PAGE_DATA: i32 = 0;
+const CODEC_UNCOMPRESSED: i32 = 0;
+
+/// The whole file for `columns`, which must all be the same length.
+pub fn encode(columns: &[Column]) -> anyhow::Result<Vec<u8>> {
+    let num_rows = columns.first().map_or(0, |c| len(&c.values));
+    if let Some(c) = columns.iter().find(|c| len(&c.values) != num_rows) {
+        anyhow::bail!("column {} has {} values, expected {}", c.name, len(&c.values), num_rows);
+    }
+
+    let mut out = MAGIC.to_vec();
+    let mut chunks = Vec::new();
+    for column in columns {
+        let (defined, data) = plain(&column.values);
+        let mut page = Vec::new();
+        let levels = definition_levels(&defined);
+        page.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+        page.extend_from_slice(&levels);
+        page.extend_from_slice(&data);
+
+        let mut header = Thrift::default();
+        header.i32(1, PAGE_DATA);
+        header.i32(2, page.len() as i32);
+        header.i32(3, page.len() as i32);
+        header.begin_struct(5);
+        header.i32(1, num_rows as i32);
+        header.i32(2, ENCODING_PLAIN);
+        header.i32(3, ENCODING_RLE);
+        header.i32(4, ENCODING_RLE);
+        header.end_struct();
+        header.stop();
+
+        let offset = out.len() as i64;
+        out.extend_from_slice(&header.buf);
+        out.extend_from_slice(&page);
+        chunks.push((column, offset, (header.buf.len() + page.len()) as i64));
+    }
+
+    let mut meta = Thrift::default();
+    meta.i32(1, 1);
+    meta.begin_list(2, columns.len() + 1);
+    meta.begin_element();
+    meta.string(4, "schema");
+    meta.i32(5, columns.len() as i32);
+    meta.end_element();
+    for column in columns {
+        meta.begin_element();
+        meta.i32(1, physical_type(&column.values));
+        meta.i32(3, REPETITION_OPTIONAL);
+        meta.string(4, column.name);
+        if matches!(column.values, Values::Text(_)) {
+            meta.i32(6, CONVERTED_UTF8);
+        }
+        meta.end_element();
+    }
+    meta.i64(3, num_rows as i64);
+    meta.begin_list(4, 1);
+    meta.begin_element();
+    meta.begin_list(1, chunks.len());
+    for (column, offset, size) in &chunks {
+        meta.begin_element();
+        meta.i64(2, *offset);
+        meta.begin_struct(3);
+        meta.i32(1, physical_type(&column.values));
+        meta.list_i32(2, &[ENCODING_PLAIN, ENCODING_RLE]);
+        meta.list_string(3, &[column.name]);
+        meta.i32(4, CODEC_UNCOMPRESSED);
+        meta.i64(5, num_rows as i64);
+        meta.i64(6, *size);
+        meta.i64(7, *size);
+        meta.i64(9, *offset);
+        meta.end_struct();
+        meta.end_element();
+    }
+    let total: i64 = chunks.iter().map(|(_, _, size)| size).sum();
+    meta.i64(2, total);
+    meta.i64(3, num_rows as i64);
+    meta.end_element();
+    meta.string(6, concat!("weather-bot ", env!("CARGO_PKG_VERSION")));
+    meta.stop();
+
+    out.extend_from_slice(&meta.buf);
+    out.extend_from_slice(&(meta.buf.len() as u32).to_le_bytes());
+    out.extend_from_slice(MAGIC);
+    Ok(out)
+}
+
+fn len(values: &Values) -> usize {
+    match values {
+        Values::Int(v) => v.len(),
+        Values::Float(v) => v.len(),
+        Values::Bool(v) => v.len(),
+        Values::Text(v) => v.len(),
+    }
+}
+
+fn physical_type(values: &Values) -> i32 {
+    match values {
+        Values::Int(_) => TYPE_INT64,
+        Values::Float(_) => TYPE_DOUBLE,
+        Values::Bool(_) => TYPE_BOOLEAN,
+        Values::Text(_) => TYPE_BYTE_ARRAY,
+    }
+}
+
+/// Which rows have a value, and the PLAIN encoding of those values.
+fn plain(values: &Values) -> (Vec<bool>, Vec<u8>) {
+    let mut data = Vec::new();
+    let defined = match values {
+        Values::Int(v) => {
+            v.iter().flatten().for_each(|x| data.extend_from_slice(&x.to_le_bytes()));
+            v.iter().map(Option::is_some).collect()
+        }
+        Values::Float(v) => {
+            v.iter().flatten().for_each(|x| data.extend_from_slice(&x.to_le_bytes()));
+            v.iter().map(Option::is_some).collect()
+        }
+        Values::Bool(v) => {
+            // Bit-packed, least significant bit first
+            let bits: Vec<bool> = v.iter().flatten().copied().collect();
+            data = bits
+                .chunks(8)
+                .map(|byte| byte.iter().enumerate().fold(0u8, |acc, (i, b)| acc | ((*b as u8) << i)))
+                .collect();
+            v.iter().map(Option::is_some).collect()
+        }
+        Values::Text(v) => {
+            for s in v.iter().flatten() {
+                data.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                data.extend_from_slice(s.as_bytes());
+            }
+            v.iter().map(Option::is_some).collect()
+        }
+    };
+    (defined, data)
+}
+
+/// Definition levels (0 = null, 1 = present) in the RLE/bit-packing hybrid
+/// at bit width 1, as RLE runs only.
+fn definition_levels(defined: &[bool]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < defined.len() {
+        let run = defined[i..].iter().take_while(|d| **d == defined[i]).count();
+        varint(&mut out, (run as u64) << 1);
+        out.push(defined[i] as u8);
+        i += run;
+    }
+    out
+}
+
+fn varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+// Compact protocol field types
+const CT_I32: u8 = 5;
+const CT_I64: u8 = 6;
+const CT_BINARY: u8 = 8;
+const CT_LIST: u8 = 9;
+const CT_STRUCT: u8 = 12;
+
+/// Thrift compact protocol encoder, just the parts the footer needs. Field
+/// ids are delta-encoded against the last id in the enclosing struct.
+#[derive(Default)]
+struct Thrift {
+    buf: Vec<u8>,
+    last_id: i16,
+    stack: Vec<i16>,
+}
+
+impl Thrift {
+    fn field(&mut self, id: i16, kind: u8) {
+        let delta = id - self.last_id;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | kind);
+        } else {
+            self.buf.push(kind);
+            varint(&mut self.buf, zigzag(id as i64));
+        }
+        self.last_id = id;
+    }
+
+    fn i32(&mut self, id: i16, v: i32) {
+        self.field(id, CT_I32);
+        varint(&mut self.buf, zigzag(v as i64));
+    }
+
+    fn i64(&mut self, id: i16, v: i64) {
+        self.field(id, CT_I64);
+        varint(&mut self.buf, zigzag(v));
+    }
+
+    fn string(&mut self, id: i16, s: &str) {
+        self.field(id, CT_BINARY);
+        self.raw_string(s);
+    }
+
+    fn raw_string(&mut self, s: &str) {
+        varint(&mut self.buf, s.len() as u64);
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn list_header(&mut self, size: usize, kind: u8) {
+        if size < 15 {
+            self.buf.push(((size as u8) << 4) | kind);
+        } else {
+            self.buf.push(0xF0 | kind);
+            varint(&mut self.buf, size as u64);
+        }
+    }
+
+    fn list_i32(&mut self, id: i16, values: &[i32]) {
+        self.field(id, CT_LIST);
+        self.list_header(values.len(), CT_I32);
+        for v in values {
+            varint(&mut self.buf, zigzag(*v as i64));
+        }
+    }
+
+    fn list_string(&mut self, id: i16, values: &[&str]) {
+        self.field(id, CT_LIST);
+        self.list_header(values.len(), CT_BINARY);
+        for s in values {
+            self.raw_string(s);
+        }
+    }
+
+    /// A list of structs; each element is `begin_element` … `end_element`.
+    fn begin_list(&mut self, id: i16, size: usize) {
+        self.field(id, CT_LIST);
+        self.list_header(size, CT_STRUCT);
+    }
+
+    fn begin_element(&mut self) {
+        self.stack.push(self.last_id);
+        self.last_id = 0;
+    }
+
+    fn end_element(&mut self) {
+        self.stop();
+        self.last_id = self.stack.pop().unwrap_or(0);
+    }
+
+    fn begin_struct(&mut self, id: i16) {
+        self.field(id, CT_STRUCT);
+        self.begin_element();
+    }
+
+    fn end_struct(&mut self) {
+        self.end_element();
+    }
+
+    fn stop(&mut self) {
+        self.buf.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use ::parquet::record::Field;
+
+    fn table() -> Vec<Column> {
+        vec![
+            Column { name: "ticker", values: Values::Text(vec![Some("KXHIGHNY-26OCT16-B74.5".into()), None, Some("°F ünïcode".into())]) },
+            Column { name: "minutes", values: Values::Int(vec![Some(612), Some(-3), None]) },
+            Column { name: "yes", values: Values::Float(vec![None, Some(0.415), Some(1.0)]) },
+            Column { name: "won", values: Values::Bool(vec![Some(true), None, Some(false)]) },
+        ]
+    }
+
+    fn read_back(columns: &[Column]) -> (Vec<String>, Vec<Vec<Field>>) {
+        let path = std::env::temp_dir().join(format!("kalshi-bot-{}-{:?}.parquet", std::process::id(), std::thread::current().id()));
+        std::fs::write(&path, encode(columns).unwrap()).unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let names = reader.metadata().file_metadata().schema_descr().columns().iter().map(|c| c.name().to_string()).collect();
+        let rows = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_column_iter().map(|(_, f)| f.clone()).collect())
+            .collect();
+        std::fs::remove_file(path).unwrap();
+        (names, rows)
+    }
+
+    #[test]
+    fn round_trips_through_the_parquet_crate() {
+        let (names, rows) = read_back(&table());
+        assert_eq!(names, ["ticker", "minutes", "yes", "won"]);
+        assert_eq!(
+            rows,
+            [
+                vec![Field::Str("KXHIGHNY-26OCT16-B74.5".into()), Field::Long(612), Field::Null, Field::Bool(true)],
+                vec![Field::Null, Field::Long(-3), Field::Double(0.415), Field::Null],
+                vec![Field::Str("°F ünïcode".into()), Field::Null, Field::Double(1.0), Field::Bool(false)],
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_long_runs_and_many_columns() {
+        // Over 15 columns takes the long list header; 300 rows the
+        // multi-byte varints in the definition-level runs
+        let columns: Vec<Column> = (0..20)
+            .map(|i| Column {
+                name: Box::leak(format!("c{}", i).into_boxed_str()),
+                values: Values::Int((0..300).map(|r| (r % 100 != i).then_some(r * i)).collect()),
+            })
+            .collect();
+        let (names, rows) = read_back(&columns);
+        assert_eq!(names.len(), 20);
+        assert_eq!(rows.len(), 300);
+        assert_eq!(rows[299][19], Field::Long(299 * 19));
+        assert_eq!(rows[105][5], Field::Null);
+    }
+
+    #[test]
+    fn rejects_ragged_columns() {
+        let mut columns = table();
+        columns[1].values = Values::Int(vec![Some(1)]);
+        assert!(encode(&columns).is_err());
+    }
+}
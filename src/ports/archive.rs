@@ -0,0 +1,10 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Off-box object storage for the research dataset (journals, snapshots,
+/// reports).
+#[async_trait]
+pub trait ArchiveSink: Send + Sync {
+    /// Store `body` under `key`, replacing any earlier object.
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()>;
+}
@@ -5,4 +5,23 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait Brain: Send + Sync {
     async fn decide(&self, context: &DecisionContext) -> Result<TradeDecision>;
+
+    /// Whether decisions depend on `prompt_md`. Journaled decisions carry
+    /// the prompt version only for brains that read it.
+    fn uses_prompt(&self) -> bool {
+        false
+    }
+
+    /// One decision per bracket of the event, in `context.brackets` order.
+    /// Brains that weigh brackets against each other (portfolio selection,
+    /// hedging, a single LLM prompt) override this; the default decides
+    /// each bracket on its own.
+    async fn decide_event(&self, context: &EventContext) -> Result<Vec<TradeDecision>> {
+        let mut decisions = Vec::with_capacity(context.brackets.len());
+        for i in 0..context.brackets.len() {
+            let decision = self.decide(&context.bracket(i)).await;
+            decisions.push(decision.map_err(|e| e.context(format!("{}: {}", context.event_ticker, context.brackets[i].0.ticker)))?);
+        }
+        Ok(decisions)
+    }
 }
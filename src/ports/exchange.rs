@@ -8,8 +8,77 @@ pub trait Exchange: Send + Sync {
     async fn orderbook(&self, ticker: &str) -> Result<Orderbook>;
     async fn resting_orders(&self) -> Result<Vec<RestingOrder>>;
     async fn cancel_order(&self, order_id: &str) -> Result<()>;
+    /// Cancel several orders in as few requests as the exchange allows.
+    /// One result per id, in order; the outer error means the whole batch
+    /// failed. The default cancels one at a time.
+    async fn cancel_orders(&self, order_ids: &[String]) -> Result<Vec<Result<()>>> {
+        let mut results = Vec::with_capacity(order_ids.len());
+        for id in order_ids {
+            results.push(self.cancel_order(id).await);
+        }
+        Ok(results)
+    }
     async fn place_order(&self, order: &OrderRequest) -> Result<OrderResult>;
+    /// Change a resting order's price and/or size in place. The returned
+    /// order_id may differ from the original.
+    async fn amend_order(&self, order_id: &str, order: &OrderRequest) -> Result<OrderResult>;
+    /// Sell up to `order.shares` held contracts of `order.side` for at
+    /// least `order.price_cents` each.
+    async fn sell_position(&self, order: &OrderRequest) -> Result<OrderResult>;
     async fn positions(&self) -> Result<Vec<Position>>;
     async fn settlements(&self, ticker: &str) -> Result<Vec<Settlement>>;
-    async fn balance(&self) -> Result<u64>;
+    /// Executions on `ticker` since `since`, buys and sales, from any
+    /// order on the account — the bot's or placed by hand.
+    async fn fills(&self, ticker: &str, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Fill>>;
+    async fn balance(&self) -> Result<Cents>;
+    /// Settled result of any market, None until determined.
+    async fn market_result(&self, ticker: &str) -> Result<Option<MarketResult>>;
 }
+
+/// Typed exchange failures. Adapters return these inside `anyhow::Error` so
+/// the engine can `downcast_ref` and choose retry vs abort vs veto.
+#[derive(Debug, Clone)]
+pub enum ExchangeError {
+    /// Bad key, bad signature, or clock skew (401/403)
+    Auth(String),
+    /// 429 — optional server hint in seconds
+    RateLimited { retry_after_secs: Option<u64> },
+    InsufficientFunds(String),
+    /// Market closed, paused, or not yet open
+    MarketClosed(String),
+    /// Request rejected as malformed (bad price, bad count, ...)
+    Validation(String),
+    NotFound(String),
+    /// 5xx from the exchange
+    Server { status: u16, body: String },
+    /// Timeout, DNS, connection reset
+    Transport(String),
+}
+
+impl ExchangeError {
+    /// Worth trying the same request again later in this cycle.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExchangeError::RateLimited { .. } | ExchangeError::Server { .. } | ExchangeError::Transport(_)
+        )
+    }
+}
+
+impl std::fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExchangeError::Auth(m) => write!(f, "auth failed: {}", m),
+            ExchangeError::RateLimited { retry_after_secs: Some(s) } => write!(f, "rate limited (retry after {}s)", s),
+            ExchangeError::RateLimited { retry_after_secs: None } => write!(f, "rate limited"),
+            ExchangeError::InsufficientFunds(m) => write!(f, "insufficient funds: {}", m),
+            ExchangeError::MarketClosed(m) => write!(f, "market closed: {}", m),
+            ExchangeError::Validation(m) => write!(f, "validation error: {}", m),
+            ExchangeError::NotFound(m) => write!(f, "not found: {}", m),
+            ExchangeError::Server { status, body } => write!(f, "server error {}: {}", status, body),
+            ExchangeError::Transport(m) => write!(f, "transport error: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
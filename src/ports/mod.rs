@@ -1,3 +1,5 @@
+pub mod archive;
 pub mod brain;
 pub mod exchange;
+pub mod notifier;
 pub mod weather_feed;
@@ -0,0 +1,8 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Where human-facing reports go (plan of the day, alerts).
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, subject: &str, body: &str) -> Result<()>;
+}
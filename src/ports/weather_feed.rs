@@ -1,8 +1,28 @@
-use crate::core::types::{CityConfig, WeatherSnapshot};
+use crate::core::types::{CityConfig, Observations, WeatherAlert, WeatherSnapshot};
 use anyhow::Result;
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait WeatherFeed: Send + Sync {
     async fn forecast(&self, city: &CityConfig) -> Result<Option<WeatherSnapshot>>;
+
+    /// Intraday station readings. `None` when the feed has no observation
+    /// source, which is the default.
+    async fn observations(&self, _city: &CityConfig) -> Result<Option<Observations>> {
+        Ok(None)
+    }
+
+    /// Active alerts for the city. `None` when the feed has no alert
+    /// source, which is the default; `Some(vec![])` means none are active.
+    async fn alerts(&self, _city: &CityConfig) -> Result<Option<Vec<WeatherAlert>>> {
+        Ok(None)
+    }
+
+    /// Final high for a past local day (YYYY-MM-DD) — for a snowfall series
+    /// the day's snowfall, whatever the city's markets settle on. `None`
+    /// while the day is still running or when the feed keeps no history,
+    /// the default.
+    async fn daily_high(&self, _city: &CityConfig, _day: &str) -> Result<Option<f64>> {
+        Ok(None)
+    }
 }
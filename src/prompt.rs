@@ -0,0 +1,82 @@
+use crate::storage;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Headings an LLM prompt can't do without: the trading rules and the
+/// output contract `parse_decision` relies on. Market, stats and weather
+/// are appended by the adapter, so the template has no placeholders.
+const REQUIRED_SECTIONS: [&str; 2] = ["## Rules", "## Output"];
+
+/// brain/prompt.md as loaded, with a short content hash so a journaled
+/// decision can be traced to the exact prompt that produced it.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub text: String,
+    pub version: String,
+}
+
+impl Prompt {
+    pub fn parse(text: String) -> anyhow::Result<Self> {
+        validate(&text)?;
+        Ok(Self { version: version_hash(&text), text })
+    }
+}
+
+pub fn validate(text: &str) -> anyhow::Result<()> {
+    if text.trim().is_empty() {
+        anyhow::bail!("prompt.md is empty");
+    }
+    let missing: Vec<&str> = REQUIRED_SECTIONS
+        .iter()
+        .copied()
+        .filter(|s| !text.lines().any(|l| l.trim_start().starts_with(s)))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!("prompt.md is missing required section(s): {}", missing.join(", "));
+    }
+    Ok(())
+}
+
+/// First 12 hex digits of the SHA-256 of the prompt text.
+fn version_hash(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Keeps the last good prompt and reloads it when the file changes on disk.
+/// An edit that fails validation is logged and ignored, so a half-saved
+/// prompt never reaches the brain.
+pub struct PromptWatcher {
+    state: Mutex<(Prompt, Option<SystemTime>)>,
+}
+
+impl PromptWatcher {
+    /// Fails if the prompt on disk is unreadable or invalid.
+    pub fn load() -> anyhow::Result<Self> {
+        let modified = storage::prompt_modified();
+        let prompt = Prompt::parse(storage::read_prompt()?)?;
+        tracing::info!("Loaded prompt.md (version {})", prompt.version);
+        Ok(Self { state: Mutex::new((prompt, modified)) })
+    }
+
+    pub fn current(&self) -> Prompt {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let modified = storage::prompt_modified();
+        if modified != state.1 {
+            state.1 = modified;
+            match storage::read_prompt().and_then(Prompt::parse) {
+                Ok(prompt) if prompt.version != state.0.version => {
+                    tracing::info!("Reloaded prompt.md: version {} → {}", state.0.version, prompt.version);
+                    state.0 = prompt;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("prompt.md changed but was rejected, keeping version {}: {:#}", state.0.version, e),
+            }
+        }
+        state.0.clone()
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// External APIs whose request volume is metered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    Kalshi,
+    OpenMeteo,
+    Nws,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] = [Provider::Kalshi, Provider::OpenMeteo, Provider::Nws];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Provider::Kalshi => "kalshi",
+            Provider::OpenMeteo => "open_meteo",
+            Provider::Nws => "nws",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Provider::Kalshi => 0,
+            Provider::OpenMeteo => 1,
+            Provider::Nws => 2,
+        }
+    }
+}
+
+static REQUESTS: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// Count one outgoing request. Adapters call this right before sending.
+pub fn record(provider: Provider) {
+    REQUESTS[provider.index()].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Requests counted since the last call, per provider. Resets the counters.
+pub fn take() -> Vec<(Provider, u64)> {
+    Provider::ALL
+        .iter()
+        .map(|p| (*p, REQUESTS[p.index()].swap(0, Ordering::Relaxed)))
+        .collect()
+}
+
+/// Requests made in the current UTC hour and day, from `storage`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub hour: u64,
+    pub day: u64,
+}
@@ -0,0 +1,271 @@
+use crate::core::types::{BacktestTrade, Cents, ForecastSkill, WalkForwardFold};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Self-contained HTML report of a walk-forward backtest: inline SVG
+/// charts and tables, no scripts or external assets, so it opens offline
+/// and can be attached anywhere.
+pub fn backtest_html(folds: &[WalkForwardFold], skill: &[ForecastSkill], live_min_net_edge_pp: f64) -> String {
+    let oos: Vec<&BacktestTrade> = folds.iter().flat_map(|f| &f.out_of_sample_trades).collect();
+    let baseline: Vec<&BacktestTrade> = folds.iter().flat_map(|f| &f.baseline_trades).collect();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>weather-bot backtest</title>\n");
+    html.push_str(
+        "<style>body{font-family:sans-serif;margin:2em;color:#222}table{border-collapse:collapse}\
+         td,th{padding:2px 10px;text-align:right}th{border-bottom:1px solid #888}\
+         td:first-child,td:nth-child(2),th:first-child,th:nth-child(2){text-align:left}\
+         svg{display:block;margin:1em 0}.legend span{margin-right:1.5em}</style></head><body>\n",
+    );
+    let _ = writeln!(
+        html,
+        "<h1>Walk-forward backtest</h1><p>Generated {}. {} folds; live MIN_NET_EDGE_PP {:.0}pp.</p>",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"), folds.len(), live_min_net_edge_pp
+    );
+
+    html.push_str("<h2>Folds</h2><table><tr><th>train</th><th>test</th><th>edge</th><th>in-sample</th><th>out-of-sample</th><th>live threshold</th></tr>\n");
+    for f in folds {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}..{}</td><td>{}..{}</td><td>{:.0}pp</td><td>{}x {}</td><td>{}x {}</td><td>{}x {}</td></tr>",
+            f.train.0, f.train.1, f.test.0, f.test.1, f.min_net_edge_pp,
+            f.in_sample.trades, f.in_sample.pnl_cents,
+            f.out_of_sample.trades, f.out_of_sample.pnl_cents,
+            f.baseline.trades, f.baseline.pnl_cents,
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Equity curve (out of sample)</h2>\n");
+    html.push_str(&legend(&[("tuned threshold", COLORS[0]), ("live threshold", COLORS[1])]));
+    html.push_str(&equity_chart(&[&oos, &baseline]));
+
+    html.push_str("<h2>P&amp;L by city (out of sample)</h2>\n");
+    html.push_str(&city_chart(&oos));
+
+    html.push_str("<h2>Predicted net edge vs realized P&amp;L per contract</h2>\n");
+    html.push_str(&edge_scatter(&oos));
+
+    html.push_str("<h2>Calibration</h2>\n");
+    let cities: Vec<&ForecastSkill> = skill.iter().filter(|s| s.lead_time == "all").collect();
+    let names: Vec<(&str, &str)> = cities.iter().zip(COLORS.iter().cycle()).map(|(s, c)| (s.city.as_str(), *c)).collect();
+    html.push_str(&legend(&names));
+    html.push_str(&calibration_chart(&cities));
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 300.0;
+const MARGIN: f64 = 56.0;
+const COLORS: [&str; 6] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b"];
+
+/// Data-to-pixel mapping for one chart, with both ranges padded so points
+/// don't sit on the frame.
+struct Frame {
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Frame {
+    fn new(xs: impl IntoIterator<Item = f64>, ys: impl IntoIterator<Item = f64>) -> Self {
+        let range = |vals: Vec<f64>| {
+            let lo = vals.iter().copied().fold(f64::INFINITY, f64::min);
+            let hi = vals.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            if !lo.is_finite() || !hi.is_finite() {
+                return (0.0, 1.0);
+            }
+            let pad = ((hi - lo) * 0.05).max(0.5);
+            (lo - pad, hi + pad)
+        };
+        Self { x: range(xs.into_iter().collect()), y: range(ys.into_iter().collect()) }
+    }
+
+    fn px(&self, x: f64) -> f64 {
+        MARGIN + (x - self.x.0) / (self.x.1 - self.x.0) * (WIDTH - 2.0 * MARGIN)
+    }
+
+    fn py(&self, y: f64) -> f64 {
+        HEIGHT - MARGIN - (y - self.y.0) / (self.y.1 - self.y.0) * (HEIGHT - 2.0 * MARGIN)
+    }
+
+    /// Plot box, min/max tick labels, axis titles, and a zero line when
+    /// the y range spans zero.
+    fn axes(&self, x_title: &str, y_title: &str, fmt_x: impl Fn(f64) -> String, fmt_y: impl Fn(f64) -> String) -> String {
+        let (left, right, top, bottom) = (MARGIN, WIDTH - MARGIN, MARGIN, HEIGHT - MARGIN);
+        let mut svg = format!(
+            "<rect x=\"{left}\" y=\"{top}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#888\"/>",
+            right - left, bottom - top
+        );
+        if self.y.0 < 0.0 && self.y.1 > 0.0 {
+            let zero = self.py(0.0);
+            let _ = write!(svg, "<line x1=\"{left}\" y1=\"{zero:.1}\" x2=\"{right}\" y2=\"{zero:.1}\" stroke=\"#bbb\" stroke-dasharray=\"4\"/>");
+        }
+        let _ = write!(
+            svg,
+            "<text x=\"{left}\" y=\"{}\" font-size=\"11\">{}</text>\
+             <text x=\"{right}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{}</text>\
+             <text x=\"{}\" y=\"{bottom}\" font-size=\"11\" text-anchor=\"end\">{}</text>\
+             <text x=\"{}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{}</text>\
+             <text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\">{}</text>\
+             <text x=\"14\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\" transform=\"rotate(-90 14 {})\">{}</text>",
+            bottom + 14.0, escape(&fmt_x(self.x.0)),
+            bottom + 14.0, escape(&fmt_x(self.x.1)),
+            left - 4.0, escape(&fmt_y(self.y.0)),
+            left - 4.0, top + 10.0, escape(&fmt_y(self.y.1)),
+            WIDTH / 2.0, HEIGHT - 12.0, escape(x_title),
+            HEIGHT / 2.0, HEIGHT / 2.0, escape(y_title),
+        );
+        svg
+    }
+}
+
+fn svg(body: &str) -> String {
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\">{body}</svg>\n")
+}
+
+fn empty(what: &str) -> String {
+    format!("<p><em>No {} to plot.</em></p>\n", what)
+}
+
+fn legend(series: &[(&str, &str)]) -> String {
+    let items: String = series
+        .iter()
+        .map(|(name, color)| format!("<span style=\"color:{}\">&#9632; {}</span>", color, escape(name)))
+        .collect();
+    format!("<div class=\"legend\">{}</div>\n", items)
+}
+
+fn dollars(cents: f64) -> String {
+    Cents(cents.round() as i64).to_string()
+}
+
+/// Cumulative P&L at the end of each day, one line per series, on a
+/// shared day axis.
+fn equity_chart(series: &[&[&BacktestTrade]]) -> String {
+    let days: Vec<&str> = {
+        let mut d: Vec<&str> = series.iter().flat_map(|s| s.iter().map(|t| t.day.as_str())).collect();
+        d.sort_unstable();
+        d.dedup();
+        d
+    };
+    if days.is_empty() {
+        return empty("out-of-sample trades");
+    }
+    let curves: Vec<Vec<f64>> = series
+        .iter()
+        .map(|trades| {
+            let mut total = Cents::ZERO;
+            days.iter()
+                .map(|day| {
+                    total += trades.iter().filter(|t| t.day == *day).map(|t| t.pnl_cents).sum();
+                    total.0 as f64
+                })
+                .collect()
+        })
+        .collect();
+
+    let frame = Frame {
+        x: (0.0, (days.len() - 1).max(1) as f64),
+        ..Frame::new([0.0], curves.iter().flatten().copied().chain([0.0]))
+    };
+    let mut body = frame.axes("day", "cumulative P&L", |x| days.get(x.round() as usize).unwrap_or(&"").to_string(), dollars);
+    for (curve, color) in curves.iter().zip(COLORS) {
+        let points: Vec<String> = curve.iter().enumerate().map(|(i, y)| format!("{:.1},{:.1}", frame.px(i as f64), frame.py(*y))).collect();
+        let _ = write!(body, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>", points.join(" "), color);
+    }
+    svg(&body)
+}
+
+/// One bar per city, green for profit and red for loss.
+fn city_chart(trades: &[&BacktestTrade]) -> String {
+    let mut by_city: BTreeMap<&str, Cents> = BTreeMap::new();
+    for t in trades {
+        *by_city.entry(t.city.as_str()).or_insert(Cents::ZERO) += t.pnl_cents;
+    }
+    if by_city.is_empty() {
+        return empty("out-of-sample trades");
+    }
+    let frame = Frame {
+        x: (0.0, by_city.len() as f64),
+        ..Frame::new([0.0], by_city.values().map(|c| c.0 as f64).chain([0.0]))
+    };
+    let mut body = frame.axes("city", "P&L", |_| String::new(), dollars);
+    for (i, (city, pnl)) in by_city.iter().enumerate() {
+        let (x0, x1) = (frame.px(i as f64 + 0.15), frame.px(i as f64 + 0.85));
+        let (y0, y1) = (frame.py(0.0), frame.py(pnl.0 as f64));
+        let color = if pnl.0 >= 0 { "#2ca02c" } else { "#d62728" };
+        let _ = write!(
+            body,
+            "<rect x=\"{x0:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{color}\"><title>{} {}</title></rect>\
+             <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"middle\">{}</text>",
+            y0.min(y1), x1 - x0, (y1 - y0).abs(), escape(city), pnl,
+            (x0 + x1) / 2.0, HEIGHT - MARGIN + 28.0, escape(city),
+        );
+    }
+    svg(&body)
+}
+
+/// Predicted net edge against realized P&L per contract, both in points
+/// of the $1 payout; the diagonal is "realized exactly as predicted".
+fn edge_scatter(trades: &[&BacktestTrade]) -> String {
+    if trades.is_empty() {
+        return empty("out-of-sample trades");
+    }
+    let frame = Frame::new(trades.iter().map(|t| t.net_pp).chain([0.0]), trades.iter().map(|t| t.realized_pp()));
+    let mut body = frame.axes("predicted net edge (pp)", "realized per contract (¢)", |x| format!("{:.0}", x), |y| format!("{:.0}", y));
+    let (lo, hi) = (frame.x.0.max(frame.y.0), frame.x.1.min(frame.y.1));
+    if lo < hi {
+        let _ = write!(
+            body,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#bbb\"/>",
+            frame.px(lo), frame.py(lo), frame.px(hi), frame.py(hi)
+        );
+    }
+    for t in trades {
+        let color = if t.won { "#2ca02c" } else { "#d62728" };
+        let _ = write!(
+            body,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{color}\" fill-opacity=\"0.6\"><title>{} {} {:.1}pp → {:.0}¢</title></circle>",
+            frame.px(t.net_pp), frame.py(t.realized_pp()), escape(&t.ticker), t.day, t.net_pp, t.realized_pp()
+        );
+    }
+    svg(&body)
+}
+
+/// Mean forecast probability against hit rate per bin, one line per city.
+fn calibration_chart(cities: &[&ForecastSkill]) -> String {
+    if cities.iter().all(|s| s.calibration.is_empty()) {
+        return empty("settled forecasts");
+    }
+    let frame = Frame { x: (0.0, 1.0), y: (0.0, 1.0) };
+    let pct = |v: f64| format!("{:.0}%", v * 100.0);
+    let mut body = frame.axes("forecast probability", "observed frequency", pct, pct);
+    let _ = write!(
+        body,
+        "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#bbb\"/>",
+        frame.px(0.0), frame.py(0.0), frame.px(1.0), frame.py(1.0)
+    );
+    for (skill, color) in cities.iter().zip(COLORS.iter().cycle()) {
+        let points: Vec<String> = skill
+            .calibration
+            .iter()
+            .map(|b| format!("{:.1},{:.1}", frame.px(b.mean_probability), frame.py(b.hit_rate)))
+            .collect();
+        let _ = write!(body, "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\"/>", points.join(" "));
+        for b in &skill.calibration {
+            let _ = write!(
+                body,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{color}\"><title>{}: {} forecasts, {:.0}% → {:.0}%</title></circle>",
+                frame.px(b.mean_probability), frame.py(b.hit_rate), escape(&skill.city), b.forecasts,
+                b.mean_probability * 100.0, b.hit_rate * 100.0
+            );
+        }
+    }
+    svg(&body)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -1,35 +1,191 @@
-use crate::core::types::Config;
+use crate::core::types::{Config, LedgerBackend, RunMode, WeatherCache};
+use crate::prompt;
 use crate::storage;
+use crate::strategies;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::process;
 
+/// Heartbeat lock. The holder rewrites the timestamp while it runs; another
+/// instance may take over only once the heartbeat is older than
+/// `stale_after`. Unlike a PID check this survives PID reuse and containers
+/// that can't see each other's /proc.
 pub struct Lockfile {
     path: String,
+    token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockState {
+    token: String,
+    pid: u32,
+    heartbeat: String,
 }
 
 impl Lockfile {
-    pub fn acquire(path: &str) -> anyhow::Result<Self> {
-        if let Ok(contents) = fs::read_to_string(path) {
-            let pid: u32 = contents.trim().parse().unwrap_or(0);
-            if pid > 0 && std::path::Path::new(&format!("/proc/{}", pid)).exists() {
-                anyhow::bail!("Another instance running (PID {})", pid);
+    pub fn acquire(path: &str, stale_after: std::time::Duration) -> anyhow::Result<Self> {
+        if let Some(age) = heartbeat_age(path) {
+            if age < stale_after {
+                anyhow::bail!(
+                    "Another instance holds {} (heartbeat {}s ago, stale after {}s)",
+                    path, age.as_secs(), stale_after.as_secs()
+                );
             }
-            tracing::warn!("Removing stale lockfile (PID {} dead)", pid);
+            tracing::warn!("Taking over stale lockfile {} (heartbeat {}s ago)", path, age.as_secs());
         }
 
-        let mut f = fs::File::create(path)?;
-        write!(f, "{}", process::id())?;
-        Ok(Self {
+        let lock = Self {
             path: path.to_string(),
-        })
+            token: uuid::Uuid::new_v4().to_string(),
+        };
+        lock.write_heartbeat()?;
+        // Two instances racing for a stale lock both write; whoever's
+        // rename landed last owns it
+        if !lock.owned() {
+            anyhow::bail!("Lost the race for {} to another instance", path);
+        }
+        Ok(lock)
+    }
+
+    /// Refresh the heartbeat. Fails if another instance has taken the lock
+    /// over, in which case this one must stop trading.
+    pub fn heartbeat(&self) -> anyhow::Result<()> {
+        if !self.owned() {
+            anyhow::bail!("Lockfile {} was taken over by another instance", self.path);
+        }
+        self.write_heartbeat()
+    }
+
+    fn write_heartbeat(&self) -> anyhow::Result<()> {
+        let state = LockState {
+            token: self.token.clone(),
+            pid: process::id(),
+            heartbeat: chrono::Utc::now().to_rfc3339(),
+        };
+        let tmp = format!("{}.{}.tmp", self.path, self.token);
+        let mut f = fs::File::create(&tmp)?;
+        write!(f, "{}", serde_json::to_string(&state)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn owned(&self) -> bool {
+        read_lock(&self.path).is_some_and(|s| s.token == self.token)
     }
 }
 
 impl Drop for Lockfile {
     fn drop(&mut self) {
-        let _ = fs::remove_file(&self.path);
+        if self.owned() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_lock(path: &str) -> Option<LockState> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Time since the lock's last heartbeat, or None if there is no lock.
+/// Old PID-only lockfiles fall back to the file's mtime.
+fn heartbeat_age(path: &str) -> Option<std::time::Duration> {
+    let last = match read_lock(path) {
+        Some(state) => chrono::DateTime::parse_from_rfc3339(&state.heartbeat).ok()?.with_timezone(&chrono::Utc),
+        None => chrono::DateTime::<chrono::Utc>::from(fs::metadata(path).ok()?.modified().ok()?),
+    };
+    Some((chrono::Utc::now() - last).to_std().unwrap_or_default())
+}
+
+/// PID and heartbeat age of the instance holding `path`, if any.
+pub fn lock_holder(path: &str) -> Option<(Option<u32>, std::time::Duration)> {
+    Some((read_lock(path).map(|s| s.pid), heartbeat_age(path)?))
+}
+
+/// Operator-created file that makes every cycle cancel the bot's orders
+/// and stop. Delete it to resume trading.
+pub const KILL_SWITCH: &str = "brain/KILL";
+
+pub fn kill_switch_engaged() -> bool {
+    std::path::Path::new(KILL_SWITCH).exists()
+}
+
+/// While present, cycles settle and exit but take no new entries.
+pub const PAUSE: &str = "brain/PAUSE";
+
+/// Asks the next cycle to cancel the bot's orders, sell every position and
+/// pause. Removed once nothing is left to sell.
+pub const CLOSE_REQUEST: &str = "brain/CLOSE";
+
+pub fn paused() -> bool {
+    std::path::Path::new(PAUSE).exists()
+}
+
+pub fn close_requested() -> bool {
+    std::path::Path::new(CLOSE_REQUEST).exists()
+}
+
+pub fn set_paused(on: bool) -> anyhow::Result<()> {
+    set_flag(PAUSE, on)
+}
+
+pub fn set_close_request(on: bool) -> anyhow::Result<()> {
+    set_flag(CLOSE_REQUEST, on)
+}
+
+/// Mode, last cycle and P&L, from the files the cycle leaves behind — no
+/// exchange request. Telegram's /status and `kalshi-bot status`.
+pub fn status() -> anyhow::Result<String> {
+    let mut lines = Vec::new();
+    let mode = match (kill_switch_engaged(), close_requested(), paused()) {
+        (true, _, _) => "KILLED (brain/KILL)",
+        (_, true, _) => "closing out",
+        (_, _, true) => "paused",
+        _ => "trading",
+    };
+    lines.push(format!("Mode: {}", mode));
+
+    match storage::read_cycle_summary() {
+        Some(c) => {
+            lines.push(format!("Last cycle: {} — {}, {} trade(s)", c.finished_at, c.outcome, c.trades));
+            for city in &c.cities {
+                let error = city.error.as_deref().map(|e| format!(" (error: {})", e)).unwrap_or_default();
+                lines.push(format!("  {}: {}{}", city.city, city.action, error));
+            }
+        }
+        None => lines.push("Last cycle: none recorded".into()),
+    }
+
+    let ledger = storage::read_ledger()?;
+    let s = crate::core::stats::compute(&ledger);
+    lines.push(format!(
+        "P&L: {} total, {} today, streak {:+}, {}W/{}L",
+        s.total_pnl_cents, s.today_pnl_cents, s.current_streak, s.wins, s.losses
+    ));
+    let open: Vec<_> = ledger.iter().filter(|r| r.is_open()).collect();
+    lines.push(format!(
+        "Open rows: {} ({} contracts)",
+        open.len(),
+        open.iter().map(|r| r.shares).sum::<u32>()
+    ));
+    if let Some(c) = storage::read_canary().filter(|c| !c.graduated) {
+        lines.push(format!(
+            "Canary: {} at {:.0}% size, {} settled ({} realized vs {:+.0}¢ expected)",
+            c.version, c.fraction * 100.0, c.settled, c.realized_cents, c.expected_cents
+        ));
     }
+    Ok(lines.join("\n"))
+}
+
+fn set_flag(path: &str, on: bool) -> anyhow::Result<()> {
+    if on {
+        fs::write(path, chrono::Utc::now().to_rfc3339())?;
+    } else if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e.into());
+        }
+    }
+    Ok(())
 }
 
 pub fn validate_startup(config: &Config) -> anyhow::Result<()> {
@@ -44,11 +200,36 @@ pub fn validate_startup(config: &Config) -> anyhow::Result<()> {
         anyhow::bail!("No cities configured — check CITIES env var");
     }
 
+    if config.run_mode == RunMode::Loop && config.cycle_interval_minutes == 0 {
+        anyhow::bail!("RUN_MODE=loop needs CYCLE_INTERVAL_MINUTES of at least 1");
+    }
+
     if config.kalshi_key_id.is_empty() {
         anyhow::bail!("KALSHI_API_KEY_ID not set");
     }
 
-    if !std::path::Path::new("brain/ledger.md").exists() {
+    let s = &config.strategy;
+    if s.loss_sprt_win_prob > 0.0
+        && !(0.0 < s.loss_sprt_fail_win_prob && s.loss_sprt_fail_win_prob < s.loss_sprt_win_prob && s.loss_sprt_win_prob < 1.0)
+    {
+        anyhow::bail!(
+            "LOSS_SPRT_FAIL_WIN_PROB ({}) must be above 0 and below LOSS_SPRT_WIN_PROB ({}), itself below 1",
+            s.loss_sprt_fail_win_prob, s.loss_sprt_win_prob
+        );
+    }
+    if s.loss_sprt_win_prob > 0.0 && ![s.loss_sprt_alpha, s.loss_sprt_beta].iter().all(|e| 0.0 < *e && *e < 0.5) {
+        anyhow::bail!("LOSS_SPRT_ALPHA and LOSS_SPRT_BETA must be between 0 and 0.5");
+    }
+
+    if config.telegram_bot_token.is_some() && config.telegram_chat_id.is_none() {
+        anyhow::bail!("TELEGRAM_BOT_TOKEN is set but TELEGRAM_CHAT_ID isn't — commands are only taken from that chat");
+    }
+
+    // Built once here so an unknown name or missing key fails at startup
+    // rather than every cycle
+    strategies::brain_for(config)?;
+
+    if config.ledger_backend == LedgerBackend::Markdown && !std::path::Path::new("brain/ledger.md").exists() {
         anyhow::bail!("brain/ledger.md not found");
     }
     storage::read_ledger()?;
@@ -56,6 +237,11 @@ pub fn validate_startup(config: &Config) -> anyhow::Result<()> {
     if !std::path::Path::new("brain/prompt.md").exists() {
         anyhow::bail!("brain/prompt.md not found");
     }
+    prompt::validate(&storage::read_prompt()?)?;
+
+    if config.nws_contact.is_empty() {
+        tracing::warn!("NWS_CONTACT not set — NWS may throttle requests without a contact in the User-Agent");
+    }
 
     if !config.paper_trade && !config.confirm_live {
         anyhow::bail!(
@@ -64,6 +250,17 @@ pub fn validate_startup(config: &Config) -> anyhow::Result<()> {
         );
     }
 
+    if config.weather_cache == WeatherCache::Replay {
+        if !config.paper_trade {
+            anyhow::bail!("WEATHER_CACHE=replay serves cached OFFLINE DATA — live orders are refused, set PAPER_TRADE=true");
+        }
+        tracing::warn!("OFFLINE DATA: weather is replayed from brain/weather_cache/, not fetched");
+    }
+
+    if config.paper_simulate && !config.paper_trade {
+        anyhow::bail!("PAPER_SIMULATE=true but PAPER_TRADE=false — the simulator only runs in paper mode");
+    }
+
     if !config.paper_trade {
         tracing::warn!("LIVE TRADING ENABLED — real money at risk");
     }
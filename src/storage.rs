@@ -1,11 +1,120 @@
-use crate::core::types::{LedgerRow, Settlement, Stats};
+use crate::core::lifecycle;
+use crate::core::types::{
+    ActivityState, ArchiveState, CanaryState, CancelReason, Cents, CycleSummary, JournalEntry, LedgerBackend, LedgerRow, MarketResult, Metric, OrderRecord, OrderState, RestingOrder, Settlement,
+    PaperBook, RiskState, SnapshotAggregate, Stats, TwapPlan, WeatherSnapshot,
+};
+use crate::quota::{Provider, Usage};
+use serde::{Deserialize, Serialize};
 use std::io::Write;
 
+mod migrations;
+mod sqlite;
+
+pub use migrations::migrate_ledger;
+
+const PROMPT: &str = "brain/prompt.md";
+
 pub fn read_prompt() -> anyhow::Result<String> {
-    Ok(std::fs::read_to_string("brain/prompt.md")?)
+    Ok(std::fs::read_to_string(PROMPT)?)
+}
+
+/// When prompt.md last changed, or None if it can't be stat'ed.
+pub fn prompt_modified() -> Option<std::time::SystemTime> {
+    std::fs::metadata(PROMPT).and_then(|m| m.modified()).ok()
+}
+
+const OVERRIDES: &str = "brain/overrides.toml";
+
+/// brain/overrides.toml, or None when there isn't one.
+pub fn read_overrides() -> anyhow::Result<Option<String>> {
+    match std::fs::read_to_string(OVERRIDES) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn overrides_modified() -> Option<std::time::SystemTime> {
+    std::fs::metadata(OVERRIDES).and_then(|m| m.modified()).ok()
+}
+
+const FLAGS: &str = "brain/flags.toml";
+
+/// brain/flags.toml, or None when there isn't one.
+pub fn read_flags() -> anyhow::Result<Option<String>> {
+    match std::fs::read_to_string(FLAGS) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn flags_modified() -> Option<std::time::SystemTime> {
+    std::fs::metadata(FLAGS).and_then(|m| m.modified()).ok()
+}
+
+/// When the config file (CONFIG_FILE, else config.toml) last changed, or
+/// None when there isn't one.
+pub fn config_modified() -> Option<std::time::SystemTime> {
+    std::fs::metadata(crate::core::types::config_file_path()).and_then(|m| m.modified()).ok()
+}
+
+/// Where ledger rows are kept. Markdown is the default and stays readable
+/// in a diff; SQLite (`LEDGER_BACKEND=sqlite`) is queryable and updates a
+/// row without rewriting the file.
+pub trait LedgerStore: Send + Sync {
+    fn read(&self) -> anyhow::Result<Vec<LedgerRow>>;
+    fn append(&self, row: &LedgerRow) -> anyhow::Result<()>;
+    /// Apply `f` to the newest row it accepts and save the change. `f`
+    /// returns false to skip a row.
+    fn update_newest(&self, f: &dyn Fn(&mut LedgerRow) -> bool) -> anyhow::Result<()>;
+    /// Keep the exchange's settlement next to the row it settled. The
+    /// markdown ledger has nowhere to put it.
+    fn record_settlement(&self, _order_id: &str, _settlement: &Settlement) -> anyhow::Result<()> {
+        Ok(())
+    }
+    /// Keep a stats snapshot; markdown only has stats.md, rewritten each cycle.
+    fn record_stats(&self, _stats: &Stats) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn ledger_store() -> &'static dyn LedgerStore {
+    static STORE: std::sync::OnceLock<Box<dyn LedgerStore>> = std::sync::OnceLock::new();
+    STORE
+        .get_or_init(|| match LedgerBackend::from_env() {
+            LedgerBackend::Markdown => Box::new(MarkdownLedger),
+            LedgerBackend::Sqlite => Box::new(sqlite::SqliteLedger::new(sqlite::SQLITE_LEDGER)),
+        })
+        .as_ref()
 }
 
 pub fn read_ledger() -> anyhow::Result<Vec<LedgerRow>> {
+    ledger_store().read()
+}
+
+pub fn append_ledger(row: &LedgerRow) -> anyhow::Result<()> {
+    ledger_store().append(row)
+}
+
+/// brain/ledger.md: a markdown table, backed up before every write.
+pub struct MarkdownLedger;
+
+impl LedgerStore for MarkdownLedger {
+    fn read(&self) -> anyhow::Result<Vec<LedgerRow>> {
+        read_ledger_md()
+    }
+
+    fn append(&self, row: &LedgerRow) -> anyhow::Result<()> {
+        append_ledger_md(row)
+    }
+
+    fn update_newest(&self, f: &dyn Fn(&mut LedgerRow) -> bool) -> anyhow::Result<()> {
+        rewrite_ledger_md(f)
+    }
+}
+
+fn read_ledger_md() -> anyhow::Result<Vec<LedgerRow>> {
     let path = "brain/ledger.md";
     let backup = "brain/ledger.md.bak";
 
@@ -40,59 +149,67 @@ fn parse_ledger_content(content: &str) -> Vec<LedgerRow> {
     content
         .lines()
         .filter(|l| l.starts_with('|') && !l.contains("---") && !l.contains("Timestamp"))
-        .filter_map(|line| {
-            let cols: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
-            if cols.len() < 9 {
-                return None;
-            }
-            let order_id = if cols.len() >= 10 {
-                cols[9].to_string()
-            } else {
-                String::new()
-            };
-            Some(LedgerRow {
-                timestamp: cols[1].to_string(),
-                ticker: cols[2].to_string(),
-                side: cols[3].to_string(),
-                shares: cols[4].parse().ok()?,
-                price: cols[5].parse().ok()?,
-                result: cols[6].to_string(),
-                pnl_cents: cols[7].parse().ok()?,
-                cumulative_cents: cols[8].parse().ok()?,
-                order_id,
-            })
-        })
+        .filter_map(parse_ledger_line)
         .collect()
 }
 
-pub fn append_ledger(row: &LedgerRow) -> anyhow::Result<()> {
-    let path = "brain/ledger.md";
-    let backup = "brain/ledger.md.bak";
-
-    if std::path::Path::new(path).exists() {
-        std::fs::copy(path, backup)?;
+/// One table row. Columns added by later schema versions are optional, so
+/// rows from any version parse.
+fn parse_ledger_line(line: &str) -> Option<LedgerRow> {
+    let cols: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
+    if cols.len() < 9 {
+        return None;
     }
+    let col = |i: usize| if cols.len() > i + 1 { cols[i].to_string() } else { String::new() };
+    Some(LedgerRow {
+        timestamp: cols[1].to_string(),
+        ticker: cols[2].to_string(),
+        side: cols[3].to_string(),
+        shares: cols[4].parse().ok()?,
+        price: cols[5].parse().ok()?,
+        result: cols[6].to_string(),
+        pnl_cents: cols[7].parse().ok()?,
+        cumulative_cents: cols[8].parse().ok()?,
+        order_id: col(9),
+        cancel_reason: CancelReason::from_label(&col(10)),
+        event_ticker: col(11),
+        city: col(12),
+    })
+}
 
-    let line = format!(
-        "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+fn format_ledger_line(row: &LedgerRow) -> String {
+    format!(
+        "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
         row.timestamp,
         row.ticker,
         row.side,
         row.shares,
         row.price,
         row.result,
-        row.pnl_cents,
-        row.cumulative_cents,
-        row.order_id
-    );
+        row.pnl_cents.0,
+        row.cumulative_cents.0,
+        row.order_id,
+        row.cancel_reason.map(|r| r.label()).unwrap_or(""),
+        row.event_ticker,
+        row.city
+    )
+}
+
+fn append_ledger_md(row: &LedgerRow) -> anyhow::Result<()> {
+    let path = "brain/ledger.md";
+    let backup = "brain/ledger.md.bak";
+
+    if std::path::Path::new(path).exists() {
+        std::fs::copy(path, backup)?;
+    }
 
     let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
-    writeln!(file, "{}", line)?;
+    writeln!(file, "{}", format_ledger_line(row))?;
 
     Ok(())
 }
 
-pub fn settle_last_trade(settlement: &Settlement) -> anyhow::Result<()> {
+fn rewrite_ledger_md(f: &dyn Fn(&mut LedgerRow) -> bool) -> anyhow::Result<()> {
     let path = "brain/ledger.md";
     let backup = "brain/ledger.md.bak";
 
@@ -103,34 +220,13 @@ pub fn settle_last_trade(settlement: &Settlement) -> anyhow::Result<()> {
     let content = std::fs::read_to_string(path)?;
     let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
 
-    // Find the last pending line matching this ticker and update it
     for line in lines.iter_mut().rev() {
-        if line.contains("| pending |") && line.contains(&settlement.ticker) {
-            let cols: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
-            if cols.len() >= 9 {
-                let shares: i64 = cols[4].parse().unwrap_or(1);
-                let price: i64 = cols[5].parse().unwrap_or(0);
-                let pnl = if settlement.result == "win" {
-                    (100 - price) * shares
-                } else {
-                    -(price * shares)
-                };
-                let prev_cumulative: i64 = cols[8].parse().unwrap_or(0);
-                let new_cumulative = prev_cumulative + pnl;
-                let order_id = if cols.len() >= 10 { cols[9] } else { "" };
-                *line = format!(
-                    "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
-                    cols[1],
-                    cols[2],
-                    cols[3],
-                    cols[4],
-                    cols[5],
-                    settlement.result,
-                    pnl,
-                    new_cumulative,
-                    order_id
-                );
-            }
+        if !line.starts_with('|') || line.contains("---") || line.contains("Timestamp") {
+            continue;
+        }
+        let Some(mut row) = parse_ledger_line(line) else { continue };
+        if f(&mut row) {
+            *line = format_ledger_line(&row);
             break;
         }
     }
@@ -139,49 +235,152 @@ pub fn settle_last_trade(settlement: &Settlement) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn cancel_trade(order_id: &str) -> anyhow::Result<()> {
-    let path = "brain/ledger.md";
-    let backup = "brain/ledger.md.bak";
+/// Settle one pending ledger row. Rows are matched by order_id, so several
+/// pendings on the same ticker (scale-ins) each settle on their own shares
+/// and price. An empty order_id falls back to the newest pending row with
+/// exactly this ticker, for rows written before order ids were recorded.
+pub fn settle_trade(order_id: &str, settlement: &Settlement) -> anyhow::Result<()> {
+    ledger_store().update_newest(&|row| {
+        let matches = if order_id.is_empty() {
+            row.ticker == settlement.ticker
+        } else {
+            row.order_id == order_id
+        };
+        if !row.is_pending() || !matches {
+            return false;
+        }
 
-    if std::path::Path::new(path).exists() {
-        std::fs::copy(path, backup)?;
-    }
+        // Score against the market result when known: the account-level
+        // win/loss covers the whole position, not this row's side. A void
+        // market refunds the stake, so it is never booked as a loss.
+        let result = match (settlement.market_result, row.side()) {
+            (Some(MarketResult::Void), _) => "void",
+            (Some(r), Some(side)) => if r.wins(&side) == Some(true) { "win" } else { "loss" },
+            _ => settlement.result.as_str(),
+        };
+        let price = row.price.min(100);
+        row.pnl_cents = match result {
+            "win" => Cents::for_contracts(row.shares, 100 - price),
+            "loss" => -Cents::for_contracts(row.shares, price),
+            _ => Cents::ZERO,
+        };
+        row.cumulative_cents += row.pnl_cents;
+        row.result = result.to_string();
+        row.cancel_reason = None;
+        true
+    })?;
+    ledger_store().record_settlement(order_id, settlement)
+}
 
-    let content = std::fs::read_to_string(path)?;
-    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+/// Score an open row its contracts were sold out of before settlement:
+/// a win or loss by the sale's P&L, or a scratch at break-even.
+pub fn exit_trade(order_id: &str, pnl: Cents) -> anyhow::Result<()> {
+    ledger_store().update_newest(&|row| {
+        if !row.is_open() || row.order_id.is_empty() || row.order_id != order_id {
+            return false;
+        }
+        row.result = match pnl.cmp(&Cents::ZERO) {
+            std::cmp::Ordering::Greater => "win",
+            std::cmp::Ordering::Less => "loss",
+            std::cmp::Ordering::Equal => "scratch",
+        }
+        .into();
+        row.pnl_cents = pnl;
+        row.cumulative_cents += pnl;
+        row.cancel_reason = None;
+        true
+    })
+}
 
-    for line in lines.iter_mut().rev() {
-        if line.contains("| pending |") && line.contains(order_id) {
-            let cols: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
-            if cols.len() >= 9 {
-                let oid = if cols.len() >= 10 { cols[9] } else { "" };
-                *line = format!(
-                    "| {} | {} | {} | {} | {} | cancelled | 0 | {} | {} |",
-                    cols[1], cols[2], cols[3], cols[4], cols[5], cols[8], oid
-                );
-            }
-            break;
+pub fn cancel_trade(order_id: &str, reason: CancelReason) -> anyhow::Result<()> {
+    ledger_store().update_newest(&|row| {
+        if !row.is_pending() || row.order_id.is_empty() || row.order_id != order_id {
+            return false;
         }
-    }
+        row.result = "cancelled".into();
+        row.pnl_cents = Cents::ZERO;
+        row.cancel_reason = Some(reason);
+        true
+    })
+}
 
-    std::fs::write(path, lines.join("\n") + "\n")?;
+/// Rewrite a pending row after its resting order was amended.
+pub fn amend_trade(order_id: &str, new_order_id: &str, shares: u32, price: u32) -> anyhow::Result<()> {
+    ledger_store().update_newest(&|row| {
+        if !row.is_pending() || row.order_id.is_empty() || row.order_id != order_id {
+            return false;
+        }
+        row.shares = shares;
+        row.price = price;
+        row.pnl_cents = Cents::ZERO;
+        row.order_id = new_order_id.to_string();
+        row.cancel_reason = None;
+        true
+    })
+}
+
+/// Rewrite a bot row to what its order actually filled. A row cancelled
+/// after a partial fill is reopened as pending for the filled contracts.
+pub fn fill_trade(order_id: &str, shares: u32, price: u32) -> anyhow::Result<()> {
+    ledger_store().update_newest(&|row| {
+        if !(row.is_open() || row.result == "cancelled") || row.order_id.is_empty() || row.order_id != order_id {
+            return false;
+        }
+        if row.result == "cancelled" {
+            row.result = "pending".into();
+        }
+        row.shares = shares;
+        row.price = price;
+        row.pnl_cents = Cents::ZERO;
+        row.cancel_reason = None;
+        true
+    })
+}
+
+pub fn append_journal(entry: &JournalEntry) -> anyhow::Result<()> {
+    let line = serde_json::to_string(entry)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("brain/journal.jsonl")?;
+    writeln!(file, "{}", line)?;
     Ok(())
 }
 
+pub fn read_journal() -> anyhow::Result<Vec<JournalEntry>> {
+    let content = match std::fs::read_to_string("brain/journal.jsonl") {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str(l) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable journal line: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
 pub fn write_stats(stats: &Stats) -> anyhow::Result<()> {
-    let content = format!(
+    let mut content = format!(
         "# Stats\n\
          - Total trades: {}\n\
-         - Wins: {} | Losses: {}\n\
+         - Wins: {} | Losses: {} | Voided: {}\n\
          - Win rate: {:.1}%\n\
-         - Total P&L: {}¢\n\
-         - Today P&L: {}¢\n\
+         - Total P&L: {}\n\
+         - Today P&L: {}\n\
          - Streak: {}\n\
-         - Max drawdown: {}¢\n\
+         - Max drawdown: {}\n\
          - Avg win: {:.0}¢ | Avg loss: {:.0}¢\n",
         stats.total_trades,
         stats.wins,
         stats.losses,
+        stats.voided,
         stats.win_rate * 100.0,
         stats.total_pnl_cents,
         stats.today_pnl_cents,
@@ -191,7 +390,796 @@ pub fn write_stats(stats: &Stats) -> anyhow::Result<()> {
         stats.avg_loss_cents,
     );
 
+    if !stats.pass_reasons.is_empty() {
+        content.push_str("\n## Pass reasons\n");
+        for (reason, count) in &stats.pass_reasons {
+            content.push_str(&format!("- {}: {}\n", reason, count));
+        }
+    }
+
+    if !stats.cancel_reasons.is_empty() {
+        content.push_str("\n## Cancellations\n");
+        for (reason, count) in &stats.cancel_reasons {
+            content.push_str(&format!("- {}: {}\n", reason.map(|r| r.label()).unwrap_or("unrecorded"), count));
+        }
+    }
+
+    if !stats.rule_attribution.is_empty() {
+        content.push_str("\n## Pass-rule counterfactuals\n");
+        content.push_str("| Month | Rule | Blocked | W/L | Blocked P&L | Verdict |\n");
+        content.push_str("|-------|------|---------|-----|-------------|---------|\n");
+        for r in &stats.rule_attribution {
+            let verdict = if r.pnl_cents <= Cents::ZERO { "saved" } else { "cost" };
+            content.push_str(&format!(
+                "| {} | {} | {} | {}/{} | {} | {} {} |\n",
+                r.month, r.rule, r.blocked, r.wins, r.losses, r.pnl_cents, verdict, r.pnl_cents.abs()
+            ));
+        }
+    }
+
+    if !stats.fee_drag.is_empty() {
+        content.push_str("\n## Fee drag\n");
+        content.push_str("| Month | Trades | Gross P&L | Fees (actual + est.) | Net P&L | Drag |\n");
+        content.push_str("|-------|--------|-----------|----------------------|---------|------|\n");
+        for f in &stats.fee_drag {
+            let drag = f.drag_pct().map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "n/a".into());
+            content.push_str(&format!(
+                "| {} | {} | {} | {} ({} + ~{}) | {} | {} |\n",
+                f.period, f.trades, f.gross_pnl_cents, f.fees_cents(), f.actual_fees_cents,
+                f.estimated_fees_cents, f.gross_pnl_cents - f.fees_cents(), drag
+            ));
+        }
+    }
+
+    if let Some(a) = stats.analytics.as_ref().filter(|a| !a.daily.is_empty()) {
+        let ratio = |r: Option<f64>| r.map(|r| format!("{:.2}", r)).unwrap_or_else(|| "n/a".into());
+        content.push_str(&format!(
+            "\n## Analytics\n\
+             - Sharpe-like (daily P&L, annualized): {} last {} trading days | {} all time\n\
+             - Longest losing streak: {}\n\
+             - Longest drawdown: {} days | Current: {} days\n",
+            ratio(a.rolling_sharpe),
+            crate::core::stats::SHARPE_WINDOW_DAYS,
+            ratio(a.sharpe),
+            a.longest_losing_streak,
+            a.longest_drawdown_days,
+            a.current_drawdown_days,
+        ));
+        content.push_str("\n## Monthly P&L\n");
+        content.push_str("| Month | Trades | W/L | P&L | Max drawdown |\n");
+        content.push_str("|-------|--------|-----|-----|--------------|\n");
+        for m in &a.monthly {
+            content.push_str(&format!(
+                "| {} | {} | {}/{} | {} | {} |\n",
+                m.month, m.trades, m.wins, m.losses, m.pnl_cents, m.max_drawdown_cents
+            ));
+        }
+    }
+
     std::fs::write("brain/stats.md.tmp", &content)?;
     std::fs::rename("brain/stats.md.tmp", "brain/stats.md")?;
+    ledger_store().record_stats(stats)
+}
+
+// ── Durable write queue ──
+
+const WRITE_QUEUE: &str = "brain/write_queue.jsonl";
+
+/// A ledger mutation that must eventually land. Failed writes are parked in
+/// `brain/write_queue.jsonl` and replayed, in order, at the start of each cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum QueuedWrite {
+    AppendLedger { row: LedgerRow },
+    Settle {
+        /// Ledger row to settle; empty on writes queued by older builds
+        #[serde(default)]
+        order_id: String,
+        settlement: Settlement,
+    },
+    Cancel {
+        order_id: String,
+        /// Absent on writes queued by older builds
+        #[serde(default)]
+        reason: CancelReason,
+    },
+    Amend { order_id: String, new_order_id: String, shares: u32, price: u32 },
+    /// Contracts sold before settlement
+    Exit { order_id: String, pnl_cents: Cents },
+    /// Row brought in line with the exchange's fills for its order
+    Fill { order_id: String, shares: u32, price: u32 },
+}
+
+fn apply(write: &QueuedWrite) -> anyhow::Result<()> {
+    match write {
+        QueuedWrite::AppendLedger { row } => {
+            append_ledger(row)?;
+            update_orders(|orders| {
+                orders
+                    .entry(row.order_id.clone())
+                    .or_insert_with(|| OrderRecord::intent(&row.order_id, &row.ticker, row.shares))
+                    .advance(OrderState::Submitted);
+            });
+            Ok(())
+        }
+        QueuedWrite::Settle { order_id, settlement } => {
+            settle_trade(order_id, settlement)?;
+            advance_order(order_id, OrderState::Settled);
+            Ok(())
+        }
+        QueuedWrite::Cancel { order_id, reason } => {
+            cancel_trade(order_id, *reason)?;
+            advance_order(order_id, OrderState::Canceled);
+            Ok(())
+        }
+        QueuedWrite::Exit { order_id, pnl_cents } => {
+            exit_trade(order_id, *pnl_cents)?;
+            advance_order(order_id, OrderState::Settled);
+            Ok(())
+        }
+        QueuedWrite::Fill { order_id, shares, price } => {
+            fill_trade(order_id, *shares, *price)?;
+            update_orders(|orders| {
+                if let Some(record) = orders.get_mut(order_id) {
+                    if record.state.is_resting() && *shares >= record.shares {
+                        record.advance(OrderState::Filled);
+                    }
+                    record.filled = *shares;
+                }
+            });
+            Ok(())
+        }
+        QueuedWrite::Amend { order_id, new_order_id, shares, price } => {
+            amend_trade(order_id, new_order_id, *shares, *price)?;
+            update_orders(|orders| {
+                if let Some(mut record) = orders.remove(order_id) {
+                    record.order_id = new_order_id.clone();
+                    record.shares = *shares;
+                    orders.insert(new_order_id.clone(), record);
+                }
+            });
+            Ok(())
+        }
+    }
+}
+
+/// Apply a write now; if it fails, queue it for the next cycle instead of
+/// losing it. Only errors if the queue itself can't be written. Anything
+/// already queued goes first so writes never land out of order.
+pub fn write_durable(write: QueuedWrite) -> anyhow::Result<()> {
+    if queued_writes()? == 0 {
+        match apply(&write) {
+            Ok(()) => return Ok(()),
+            Err(e) => tracing::error!("Storage write failed ({}) — queued for retry: {:?}", e, write),
+        }
+    } else {
+        tracing::warn!("Earlier writes still queued — queuing behind them: {:?}", write);
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(WRITE_QUEUE)?;
+    writeln!(file, "{}", serde_json::to_string(&write)?)?;
     Ok(())
 }
+
+/// Lines in the write queue, as written.
+fn queue_lines() -> anyhow::Result<Vec<String>> {
+    let content = match std::fs::read_to_string(WRITE_QUEUE) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Writes still queued, counting any line this build can't read.
+pub fn queued_writes() -> anyhow::Result<usize> {
+    Ok(queue_lines()?.len())
+}
+
+/// Apply queued lines in order until one fails or can't be read. Returns
+/// how many landed; the rest stay queued behind the one that stopped it.
+fn replay_queue(lines: &[String], apply: impl Fn(&QueuedWrite) -> anyhow::Result<()>) -> usize {
+    let mut flushed = 0;
+    for line in lines {
+        let write: QueuedWrite = match serde_json::from_str(line) {
+            Ok(write) => write,
+            Err(e) => {
+                tracing::warn!(
+                    "Unreadable queued write ({}) — kept in {} with {} behind it; fix or remove it by hand: {}",
+                    e, WRITE_QUEUE, lines.len() - flushed - 1, line
+                );
+                break;
+            }
+        };
+        if let Err(e) = apply(&write) {
+            tracing::warn!("Queued write still failing ({}) — {} left", e, lines.len() - flushed);
+            break;
+        }
+        flushed += 1;
+    }
+    flushed
+}
+
+/// Replay queued writes in order, stopping at the first failure so later
+/// writes stay behind it. A line that can't be read stops it too, and is
+/// kept as written. Returns how many remain unflushed.
+pub fn flush_write_queue() -> anyhow::Result<usize> {
+    let queued = queue_lines()?;
+    if queued.is_empty() {
+        return Ok(0);
+    }
+
+    let flushed = replay_queue(&queued, apply);
+
+    let remaining = &queued[flushed..];
+    if remaining.is_empty() {
+        std::fs::remove_file(WRITE_QUEUE)?;
+    } else {
+        std::fs::write(format!("{}.tmp", WRITE_QUEUE), remaining.join("\n") + "\n")?;
+        std::fs::rename(format!("{}.tmp", WRITE_QUEUE), WRITE_QUEUE)?;
+    }
+    if flushed > 0 {
+        tracing::info!("Flushed {} queued storage write(s)", flushed);
+    }
+    Ok(remaining.len())
+}
+
+pub fn write_health(unflushed_writes: usize) -> anyhow::Result<()> {
+    let mut content = format!(
+        "# Health\n\
+         - Last cycle: {}\n\
+         - Unflushed storage writes: {}\n",
+        chrono::Utc::now().to_rfc3339(),
+        unflushed_writes,
+    );
+    if let Some(summary) = read_cycle_summary() {
+        content.push_str(&format!("- Outcome: {} ({} trades)\n", summary.outcome, summary.trades));
+        for c in &summary.cities {
+            let edge = c.best_edge_pp.map(|pp| format!(", best edge {:+.1}pp", pp)).unwrap_or_default();
+            let error = c.error.as_deref().map(|e| format!(" — error: {}", e)).unwrap_or_default();
+            content.push_str(&format!("  - {}: {}{}{}\n", c.city, c.action, edge, error));
+        }
+    }
+    std::fs::write("brain/health.md.tmp", &content)?;
+    std::fs::rename("brain/health.md.tmp", "brain/health.md")?;
+    Ok(())
+}
+
+// ── Cycle summary ──
+
+const CYCLE_SUMMARY: &str = "brain/cycle_summary.json";
+
+/// Replace the last cycle's summary.
+pub fn write_cycle_summary(summary: &CycleSummary) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", CYCLE_SUMMARY);
+    std::fs::write(&tmp, serde_json::to_string_pretty(summary)?)?;
+    std::fs::rename(&tmp, CYCLE_SUMMARY)?;
+    Ok(())
+}
+
+/// The last cycle's summary, if one has been written and still parses.
+pub fn read_cycle_summary() -> Option<CycleSummary> {
+    std::fs::read_to_string(CYCLE_SUMMARY).ok().and_then(|c| serde_json::from_str(&c).ok())
+}
+
+// ── Activity ──
+
+const ACTIVITY: &str = "brain/activity.json";
+
+/// Per-day evaluation counts for the trade-frequency monitor.
+pub fn read_activity() -> ActivityState {
+    std::fs::read_to_string(ACTIVITY)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_activity(state: &ActivityState) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", ACTIVITY);
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(&tmp, ACTIVITY)?;
+    Ok(())
+}
+
+// ── Bucket archive ──
+
+const ARCHIVE_STATE: &str = "brain/archive_state.json";
+
+/// The research dataset copied off-box: journal, ledger, reports, order
+/// history and the whole snapshot archive.
+const ARCHIVED: [&str; 8] = [
+    "brain/journal.jsonl",
+    "brain/ledger.md",
+    "brain/ledger.sqlite",
+    "brain/stats.md",
+    "brain/health.md",
+    "brain/orders.json",
+    "brain/backtest_report.html",
+    SNAPSHOT_DIR,
+];
+
+/// Every file under the archived paths that exists right now.
+pub fn archive_files() -> Vec<std::path::PathBuf> {
+    fn walk(path: &std::path::Path, files: &mut Vec<std::path::PathBuf>) {
+        if path.is_dir() {
+            let Ok(entries) = std::fs::read_dir(path) else { return };
+            let mut children: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+            children.sort();
+            for child in children {
+                walk(&child, files);
+            }
+        } else if path.is_file() && path.extension().is_none_or(|e| e != "tmp") {
+            files.push(path.to_path_buf());
+        }
+    }
+    let mut files = Vec::new();
+    for path in ARCHIVED {
+        walk(std::path::Path::new(path), &mut files);
+    }
+    files
+}
+
+pub fn read_archive_state() -> ArchiveState {
+    std::fs::read_to_string(ARCHIVE_STATE)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_archive_state(state: &ArchiveState) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", ARCHIVE_STATE);
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(&tmp, ARCHIVE_STATE)?;
+    Ok(())
+}
+
+// ── Weather response cache ──
+
+const WEATHER_CACHE_DIR: &str = "brain/weather_cache";
+
+fn weather_cache_path(key: &str) -> std::path::PathBuf {
+    use sha2::{Digest, Sha256};
+    let digest: String = Sha256::digest(key.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    std::path::Path::new(WEATHER_CACHE_DIR).join(format!("{}.json", digest))
+}
+
+/// Save a weather response body under `key`, replacing the previous one.
+pub fn cache_weather_response(key: &str, body: &str) -> anyhow::Result<()> {
+    std::fs::create_dir_all(WEATHER_CACHE_DIR)?;
+    let path = weather_cache_path(key);
+    let envelope = serde_json::json!({
+        "key": key,
+        "recorded_at": chrono::Utc::now().to_rfc3339(),
+        "body": body,
+    });
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(&envelope)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// The last body saved under `key`, with when it was recorded.
+pub fn cached_weather_response(key: &str) -> Option<(String, String)> {
+    let text = std::fs::read_to_string(weather_cache_path(key)).ok()?;
+    let envelope: serde_json::Value = serde_json::from_str(&text).ok()?;
+    Some((
+        envelope.get("body")?.as_str()?.to_string(),
+        envelope.get("recorded_at")?.as_str()?.to_string(),
+    ))
+}
+
+// ── Order lifecycle ──
+
+const ORDERS: &str = "brain/orders.json";
+
+/// Every order's lifecycle state, keyed by order_id.
+pub fn read_orders() -> std::collections::BTreeMap<String, OrderRecord> {
+    std::fs::read_to_string(ORDERS)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Load, change and save the order states. Best-effort: the ledger is the
+/// record of money, so a failed state write only warns. Finished orders
+/// older than 31 days are dropped.
+fn update_orders(f: impl FnOnce(&mut std::collections::BTreeMap<String, OrderRecord>)) {
+    let mut orders = read_orders();
+    f(&mut orders);
+
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(31)).to_rfc3339();
+    orders.retain(|_, o| {
+        let finished = matches!(o.state, OrderState::Settled | OrderState::Rejected);
+        !finished || o.history.last().is_some_and(|c| c.at >= cutoff)
+    });
+
+    let written = serde_json::to_string_pretty(&orders)
+        .map_err(anyhow::Error::from)
+        .and_then(|s| std::fs::write(format!("{}.tmp", ORDERS), s).map_err(Into::into))
+        .and_then(|()| std::fs::rename(format!("{}.tmp", ORDERS), ORDERS).map_err(Into::into));
+    if let Err(e) = written {
+        tracing::warn!("Order state write failed: {}", e);
+    }
+}
+
+fn advance_order(order_id: &str, next: OrderState) {
+    update_orders(|orders| {
+        if let Some(record) = orders.get_mut(order_id) {
+            record.advance(next);
+        }
+    });
+}
+
+/// An order the exchange refused, kept so rejects show up alongside fills.
+pub fn record_rejected(ticker: &str, shares: u32) {
+    let id = format!("rejected-{}", chrono::Utc::now().timestamp_millis());
+    update_orders(|orders| {
+        let mut record = OrderRecord::intent(&id, ticker, shares);
+        record.advance(OrderState::Rejected);
+        orders.insert(id, record);
+    });
+}
+
+/// Move resting orders along from the exchange's current book: partial
+/// fills, and orders that left the book filled.
+pub fn poll_orders(on_book: &[RestingOrder]) {
+    update_orders(|orders| {
+        for record in orders.values_mut() {
+            let resting = on_book.iter().find(|o| o.order_id == record.order_id);
+            if let Some((next, filled)) = lifecycle::poll(record, resting) {
+                if record.advance(next) {
+                    record.filled = filled;
+                    tracing::info!("Order {} {} ({}/{} filled)", record.order_id, next, filled, record.shares);
+                }
+            }
+        }
+    });
+}
+
+// ── API usage & metrics ──
+
+const API_USAGE: &str = "brain/api_usage.json";
+
+/// provider → UTC hour ("YYYY-MM-DDTHH") → requests
+type UsageBuckets = std::collections::BTreeMap<String, std::collections::BTreeMap<String, u64>>;
+
+fn read_usage_buckets() -> UsageBuckets {
+    std::fs::read_to_string(API_USAGE)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Add request counts to the current hour's bucket. Buckets older than
+/// 31 days are dropped.
+pub fn record_api_usage(counts: &[(Provider, u64)]) -> anyhow::Result<()> {
+    if counts.iter().all(|(_, n)| *n == 0) {
+        return Ok(());
+    }
+    let now = chrono::Utc::now();
+    let hour = now.format("%Y-%m-%dT%H").to_string();
+    let cutoff = (now - chrono::Duration::days(31)).format("%Y-%m-%dT%H").to_string();
+
+    let mut buckets = read_usage_buckets();
+    for (provider, n) in counts {
+        let hours = buckets.entry(provider.label().to_string()).or_default();
+        *hours.entry(hour.clone()).or_insert(0) += n;
+        hours.retain(|h, _| *h >= cutoff);
+    }
+    std::fs::write(API_USAGE, serde_json::to_string_pretty(&buckets)?)?;
+    Ok(())
+}
+
+/// Requests recorded for `provider` in the current UTC hour and day.
+pub fn api_usage(provider: Provider) -> Usage {
+    let now = chrono::Utc::now();
+    let hour = now.format("%Y-%m-%dT%H").to_string();
+    let day = now.format("%Y-%m-%d").to_string();
+    let buckets = read_usage_buckets();
+    let Some(hours) = buckets.get(provider.label()) else { return Usage::default() };
+    Usage {
+        hour: hours.get(&hour).copied().unwrap_or(0),
+        day: hours.iter().filter(|(h, _)| h.starts_with(&day)).map(|(_, n)| n).sum(),
+    }
+}
+
+/// Rewrite `brain/metrics.prom` for a node_exporter textfile collector.
+/// Samples are grouped by name, as the text format requires.
+pub fn write_metrics(metrics: &[Metric]) -> anyhow::Result<()> {
+    let mut out = String::new();
+    let mut described: Vec<&str> = Vec::new();
+    for m in metrics {
+        if described.contains(&m.name) {
+            continue;
+        }
+        described.push(m.name);
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", m.name, m.help, m.name));
+        for m in metrics.iter().filter(|s| s.name == m.name) {
+            let labels: Vec<String> = m.labels.iter().map(|(k, v)| format!("{}=\"{}\"", k, v)).collect();
+            out.push_str(&format!("{}{{{}}} {}\n", m.name, labels.join(","), m.value));
+        }
+    }
+    std::fs::write("brain/metrics.prom.tmp", &out)?;
+    std::fs::rename("brain/metrics.prom.tmp", "brain/metrics.prom")?;
+    Ok(())
+}
+
+// ── Forecast refresh schedule ──
+
+const REFRESH_STATE: &str = "brain/refresh_state.json";
+
+/// When each city's forecast was last refreshed, keyed by city name.
+pub fn last_refresh(city: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let content = std::fs::read_to_string(REFRESH_STATE).ok()?;
+    let state: std::collections::BTreeMap<String, String> = serde_json::from_str(&content).ok()?;
+    chrono::DateTime::parse_from_rfc3339(state.get(city)?)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+pub fn record_refresh(city: &str, at: chrono::DateTime<chrono::Utc>) -> anyhow::Result<()> {
+    let mut state: std::collections::BTreeMap<String, String> = std::fs::read_to_string(REFRESH_STATE)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    state.insert(city.to_string(), at.to_rfc3339());
+    std::fs::write(REFRESH_STATE, serde_json::to_string_pretty(&state)?)?;
+    Ok(())
+}
+
+// ── Trade cooldown ──
+
+const LAST_TRADES: &str = "brain/last_trades.json";
+
+/// When the bot last traded each event, keyed by event ticker. Survives
+/// restarts, unlike the exchange's positions, which can lag a fill.
+pub fn last_trade(event_ticker: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let content = std::fs::read_to_string(LAST_TRADES).ok()?;
+    let state: std::collections::BTreeMap<String, String> = serde_json::from_str(&content).ok()?;
+    chrono::DateTime::parse_from_rfc3339(state.get(event_ticker)?)
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
+
+/// Events are daily, so entries older than a week are dropped on write.
+pub fn record_trade(event_ticker: &str, at: chrono::DateTime<chrono::Utc>) -> anyhow::Result<()> {
+    let mut state: std::collections::BTreeMap<String, String> = std::fs::read_to_string(LAST_TRADES)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let cutoff = at - chrono::Duration::days(7);
+    state.retain(|_, t| chrono::DateTime::parse_from_rfc3339(t).map(|t| t > cutoff).unwrap_or(false));
+    state.insert(event_ticker.to_string(), at.to_rfc3339());
+    let tmp = format!("{}.tmp", LAST_TRADES);
+    std::fs::write(&tmp, serde_json::to_string_pretty(&state)?)?;
+    std::fs::rename(&tmp, LAST_TRADES)?;
+    Ok(())
+}
+
+// ── Rejection lockouts ──
+
+const RISK_STATE: &str = "brain/risk_state.json";
+
+pub fn read_risk_state() -> RiskState {
+    std::fs::read_to_string(RISK_STATE)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_risk_state(state: &RiskState) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", RISK_STATE);
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(&tmp, RISK_STATE)?;
+    Ok(())
+}
+
+// ── Canary ──
+
+const CANARY: &str = "brain/canary.json";
+
+/// None before the first cycle with canary sizing on.
+pub fn read_canary() -> Option<CanaryState> {
+    std::fs::read_to_string(CANARY).ok().and_then(|c| serde_json::from_str(&c).ok())
+}
+
+pub fn write_canary(state: &CanaryState) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", CANARY);
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(&tmp, CANARY)?;
+    Ok(())
+}
+
+// ── TWAP plans ──
+
+const TWAP_PLANS: &str = "brain/twap.json";
+
+fn read_twap_plans() -> std::collections::BTreeMap<String, TwapPlan> {
+    std::fs::read_to_string(TWAP_PLANS)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// The TWAP plan in progress on an event, if any.
+pub fn twap_plan(event_ticker: &str) -> Option<TwapPlan> {
+    read_twap_plans().remove(event_ticker)
+}
+
+/// Save or, with `None`, drop an event's plan. Plans are for daily events,
+/// so any started over two days ago are dropped too.
+pub fn write_twap_plan(event_ticker: &str, plan: Option<&TwapPlan>) -> anyhow::Result<()> {
+    let mut plans = read_twap_plans();
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+    plans.retain(|_, p| p.started_at >= cutoff);
+    match plan {
+        Some(p) => plans.insert(event_ticker.to_string(), p.clone()),
+        None => plans.remove(event_ticker),
+    };
+    let tmp = format!("{}.tmp", TWAP_PLANS);
+    std::fs::write(&tmp, serde_json::to_string_pretty(&plans)?)?;
+    std::fs::rename(&tmp, TWAP_PLANS)?;
+    Ok(())
+}
+
+// ── Simulated exchange ──
+
+const PAPER_BOOK: &str = "brain/paper_exchange.json";
+
+/// The simulated account, or None before its first order. A file that
+/// exists but won't parse is an error: starting over would silently reset
+/// the paper balance.
+pub fn paper_book() -> anyhow::Result<Option<PaperBook>> {
+    match std::fs::read_to_string(PAPER_BOOK) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn write_paper_book(book: &PaperBook) -> anyhow::Result<()> {
+    let tmp = format!("{}.tmp", PAPER_BOOK);
+    std::fs::write(&tmp, serde_json::to_string_pretty(book)?)?;
+    std::fs::rename(&tmp, PAPER_BOOK)?;
+    Ok(())
+}
+
+// ── Weather snapshot archive ──
+
+const SNAPSHOT_DIR: &str = "brain/snapshots";
+
+/// Archive a weather snapshot as zstd-compressed JSON under
+/// `brain/snapshots/YYYY-MM-DD/`, plus a one-line aggregate that outlives
+/// the raw file. Snapshots identical to the city's previous one (same model
+/// run, same members, same point forecasts) are skipped. Returns whether
+/// anything was written.
+pub fn archive_snapshot(snapshot: &WeatherSnapshot) -> anyhow::Result<bool> {
+    use sha2::{Digest, Sha256};
+
+    std::fs::create_dir_all(SNAPSHOT_DIR)?;
+
+    let fingerprint = serde_json::to_vec(&(
+        &snapshot.ensemble_run,
+        &snapshot.ensemble_member_highs,
+        snapshot.nws_forecast_high,
+        snapshot.open_meteo_forecast_high,
+    ))?;
+    let digest: String = Sha256::digest(&fingerprint)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let digests_path = format!("{}/digests.json", SNAPSHOT_DIR);
+    let mut digests: std::collections::BTreeMap<String, String> = std::fs::read_to_string(&digests_path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    if digests.get(&snapshot.city) == Some(&digest) {
+        tracing::debug!("[{}] Snapshot unchanged since last cycle — not archived", snapshot.city);
+        return Ok(false);
+    }
+
+    let now = chrono::Utc::now();
+    let day_dir = format!("{}/{}", SNAPSHOT_DIR, now.format("%Y-%m-%d"));
+    std::fs::create_dir_all(&day_dir)?;
+    let slug: String = snapshot
+        .city
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let raw = serde_json::to_vec(snapshot)?;
+    let compressed = zstd::encode_all(&raw[..], 3)?;
+    std::fs::write(format!("{}/{}-{}.json.zst", day_dir, slug, now.format("%H%M%S")), compressed)?;
+
+    let aggregate = SnapshotAggregate {
+        timestamp: now.to_rfc3339(),
+        city: snapshot.city.clone(),
+        ensemble_run: snapshot.ensemble_run.clone(),
+        member_count: snapshot.ensemble_member_highs.len(),
+        mean_high: snapshot.ensemble.as_ref().map(|e| e.mean_high),
+        std_dev: snapshot.ensemble.as_ref().map(|e| e.std_dev),
+        p10: snapshot.ensemble.as_ref().map(|e| e.p10),
+        p90: snapshot.ensemble.as_ref().map(|e| e.p90),
+        nws_forecast_high: snapshot.nws_forecast_high,
+        open_meteo_forecast_high: snapshot.open_meteo_forecast_high,
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(format!("{}/aggregates.jsonl", SNAPSHOT_DIR))?;
+    writeln!(file, "{}", serde_json::to_string(&aggregate)?)?;
+
+    digests.insert(snapshot.city.clone(), digest);
+    std::fs::write(&digests_path, serde_json::to_string_pretty(&digests)?)?;
+    Ok(true)
+}
+
+/// Delete raw snapshot day-directories older than `retention_days`.
+/// Aggregates are never pruned. Returns how many days were removed.
+pub fn prune_snapshots(retention_days: u32) -> anyhow::Result<usize> {
+    let entries = match std::fs::read_dir(SNAPSHOT_DIR) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(retention_days as i64))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_day_dir = entry.path().is_dir() && chrono::NaiveDate::parse_from_str(&name, "%Y-%m-%d").is_ok();
+        if is_day_dir && name < cutoff {
+            std::fs::remove_dir_all(entry.path())?;
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        tracing::info!("Pruned {} day(s) of raw weather snapshots older than {}", removed, cutoff);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn cancel(order_id: &str) -> String {
+        serde_json::to_string(&QueuedWrite::Cancel { order_id: order_id.into(), reason: CancelReason::Unfilled }).unwrap()
+    }
+
+    fn replayed(lines: &[String], failing: &str) -> (usize, Vec<String>) {
+        let applied = RefCell::new(Vec::new());
+        let flushed = replay_queue(lines, |w| {
+            let QueuedWrite::Cancel { order_id, .. } = w else { unreachable!() };
+            if order_id == failing {
+                anyhow::bail!("ledger locked");
+            }
+            applied.borrow_mut().push(order_id.clone());
+            Ok(())
+        });
+        (flushed, applied.into_inner())
+    }
+
+    #[test]
+    fn unreadable_queued_write_stops_the_replay() {
+        let lines = vec![cancel("o1"), r#"{"op":"from_a_newer_build"}"#.to_string(), cancel("o3")];
+        assert_eq!(replayed(&lines, ""), (1, vec!["o1".to_string()]));
+    }
+
+    #[test]
+    fn failing_queued_write_keeps_later_ones_behind_it() {
+        let lines = vec![cancel("o1"), cancel("o2"), cancel("o3")];
+        assert_eq!(replayed(&lines, "o2"), (1, vec!["o1".to_string()]));
+        assert_eq!(replayed(&lines, ""), (3, vec!["o1".to_string(), "o2".to_string(), "o3".to_string()]));
+    }
+}
@@ -0,0 +1,209 @@
+//! Ledger schema versions. Each migration rewrites the whole file from one
+//! version to the next; `migrate_ledger` chains them and keeps a backup of
+//! the original so a bad upgrade can be rolled back by hand.
+
+use crate::core::types::{event_of, CityConfig};
+
+/// Version written by this build.
+pub const LEDGER_VERSION: u32 = 4;
+
+const MARKER_PREFIX: &str = "<!-- ledger-schema: ";
+
+struct Migration {
+    from: u32,
+    description: &'static str,
+    apply: fn(&str) -> String,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "add OrderID column",
+        apply: v1_to_v2,
+    },
+    Migration {
+        from: 2,
+        description: "add CancelReason column",
+        apply: v2_to_v3,
+    },
+    Migration {
+        from: 3,
+        description: "add EventTicker and City columns",
+        apply: v3_to_v4,
+    },
+];
+
+pub fn version_marker(version: u32) -> String {
+    format!("{}{} -->", MARKER_PREFIX, version)
+}
+
+/// Schema version of a ledger file. Files from before versioning have no
+/// marker: v2 if the header already has OrderID, else v1.
+pub fn detect_version(content: &str) -> u32 {
+    let marked = content.lines().find_map(|l| {
+        l.trim()
+            .strip_prefix(MARKER_PREFIX)?
+            .strip_suffix("-->")?
+            .trim()
+            .parse()
+            .ok()
+    });
+    match marked {
+        Some(v) => v,
+        None if content.lines().any(|l| l.contains("| Timestamp") && l.contains("OrderID")) => 2,
+        None => 1,
+    }
+}
+
+/// Upgrade the ledger at `path` to `LEDGER_VERSION` in place, backing the
+/// original up to `<path>.v<N>.bak` first. Returns the version migrated
+/// from, or None if it was already current.
+pub fn migrate_ledger(path: &str) -> anyhow::Result<Option<u32>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let from = detect_version(&content);
+    if from > LEDGER_VERSION {
+        anyhow::bail!(
+            "{} is schema v{} but this build only understands up to v{} — upgrade the bot",
+            path, from, LEDGER_VERSION
+        );
+    }
+    let has_marker = content.contains(MARKER_PREFIX);
+    if from == LEDGER_VERSION && has_marker {
+        return Ok(None);
+    }
+
+    let mut migrated = content.clone();
+    let mut version = from;
+    while version < LEDGER_VERSION {
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| anyhow::anyhow!("no ledger migration from v{}", version))?;
+        tracing::info!("Ledger migration v{} → v{}: {}", version, version + 1, step.description);
+        migrated = (step.apply)(&migrated);
+        version += 1;
+    }
+    let migrated = set_marker(&migrated, LEDGER_VERSION);
+
+    std::fs::copy(path, format!("{}.v{}.bak", path, from))?;
+    let tmp = format!("{}.migrating", path);
+    std::fs::write(&tmp, migrated)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(Some(from))
+}
+
+/// Put the version marker right under the title, replacing any old one.
+fn set_marker(content: &str, version: u32) -> String {
+    let mut lines: Vec<String> = content
+        .lines()
+        .filter(|l| !l.trim().starts_with(MARKER_PREFIX))
+        .map(str::to_string)
+        .collect();
+    let at = lines.iter().position(|l| l.starts_with("# ")).map(|i| i + 1).unwrap_or(0);
+    lines.insert(at, version_marker(version));
+    lines.join("\n") + "\n"
+}
+
+/// v1 → v2: append an empty OrderID column.
+fn v1_to_v2(content: &str) -> String {
+    add_column(content, "OrderID")
+}
+
+/// v2 → v3: append a CancelReason column. Rows cancelled before now get
+/// none — the reason wasn't recorded.
+fn v2_to_v3(content: &str) -> String {
+    add_column(content, "CancelReason")
+}
+
+/// v3 → v4: append EventTicker and City columns, backfilled from each
+/// row's ticker. Rows on series no longer configured get no city.
+fn v3_to_v4(content: &str) -> String {
+    let cities = CityConfig::all();
+    let with_event = fill_column(content, "EventTicker", |ticker| event_of(ticker).to_string());
+    fill_column(&with_event, "City", |ticker| {
+        let series = ticker.split('-').next().unwrap_or(ticker);
+        cities
+            .iter()
+            .find(|c| c.series_ticker == series)
+            .map(|c| c.name.clone())
+            .unwrap_or_default()
+    })
+}
+
+/// Append a column to the table, empty on every data row.
+fn add_column(content: &str, header: &str) -> String {
+    fill_column(content, header, |_| String::new())
+}
+
+/// Append a column to the table, derived from each data row's Ticker.
+fn fill_column(content: &str, header: &str, value: impl Fn(&str) -> String) -> String {
+    content
+        .lines()
+        .map(|l| {
+            if !l.starts_with('|') {
+                l.to_string()
+            } else if l.contains("| Timestamp") {
+                format!("{} {} |", l, header)
+            } else if l.contains("---") {
+                format!("{}{}|", l, "-".repeat(header.len() + 2))
+            } else {
+                let ticker = l.split('|').nth(2).map(str::trim).unwrap_or_default();
+                format!("{} {} |", l, value(ticker))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = "# Trade Ledger\n\n\
+| Timestamp | Ticker | Side | Shares | Price | Result | PnL | Cumulative |\n\
+|-----------|--------|------|--------|-------|--------|-----|------------|\n\
+| 2026-02-12T15:00:00Z | KXHIGHNY-26FEB12-T39 | yes | 5 | 40 | win | 300 | 300 |\n";
+
+    #[test]
+    fn detect_version_prefers_the_marker() {
+        assert_eq!(detect_version(V1), 1);
+        assert_eq!(detect_version("| Timestamp | Ticker | OrderID |\n"), 2);
+        assert_eq!(detect_version(&format!("# Trade Ledger\n{}\n{}", version_marker(3), V1)), 3);
+    }
+
+    #[test]
+    fn migrate_ledger_upgrades_v1_and_keeps_a_backup() {
+        let path = std::env::temp_dir().join(format!("ledger-migrate-{}.md", std::process::id()));
+        let path = path.to_str().unwrap();
+        let backup = format!("{}.v1.bak", path);
+        std::fs::write(path, V1).unwrap();
+
+        assert_eq!(migrate_ledger(path).unwrap(), Some(1));
+        let migrated = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            migrated,
+            "# Trade Ledger\n\
+<!-- ledger-schema: 4 -->\n\n\
+| Timestamp | Ticker | Side | Shares | Price | Result | PnL | Cumulative | OrderID | CancelReason | EventTicker | City |\n\
+|-----------|--------|------|--------|-------|--------|-----|------------|---------|--------------|-------------|------|\n\
+| 2026-02-12T15:00:00Z | KXHIGHNY-26FEB12-T39 | yes | 5 | 40 | win | 300 | 300 |  |  | KXHIGHNY-26FEB12 | New York |\n"
+        );
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), V1);
+        // Already current: left alone
+        assert_eq!(migrate_ledger(path).unwrap(), None);
+
+        // A newer schema than this build knows is refused, not rewritten
+        let newer = format!("# Trade Ledger\n{}\n", version_marker(LEDGER_VERSION + 1));
+        std::fs::write(path, &newer).unwrap();
+        assert!(migrate_ledger(path).is_err());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), newer);
+
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+    }
+}
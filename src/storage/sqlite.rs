@@ -0,0 +1,228 @@
+//! SQLite ledger (`LEDGER_BACKEND=sqlite`). Trades hold the same columns as
+//! the markdown table; settlements and stats snapshots get tables of their
+//! own so they can be joined and queried. On first use an empty database
+//! imports brain/ledger.md, which is left in place untouched.
+
+use super::{read_ledger_md, LedgerStore};
+use crate::core::types::{CancelReason, Cents, LedgerRow, Settlement, Side, Stats};
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub const SQLITE_LEDGER: &str = "brain/ledger.sqlite";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    id               INTEGER PRIMARY KEY,
+    timestamp        TEXT NOT NULL,
+    ticker           TEXT NOT NULL,
+    side             TEXT NOT NULL,
+    shares           INTEGER NOT NULL,
+    price            INTEGER NOT NULL,
+    result           TEXT NOT NULL,
+    pnl_cents        INTEGER NOT NULL,
+    cumulative_cents INTEGER NOT NULL,
+    order_id         TEXT NOT NULL,
+    cancel_reason    TEXT,
+    event_ticker     TEXT NOT NULL,
+    city             TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS trades_order_id ON trades (order_id);
+CREATE INDEX IF NOT EXISTS trades_event_ticker ON trades (event_ticker);
+
+CREATE TABLE IF NOT EXISTS settlements (
+    id            INTEGER PRIMARY KEY,
+    order_id      TEXT NOT NULL,
+    ticker        TEXT NOT NULL,
+    side          TEXT NOT NULL,
+    count         INTEGER NOT NULL,
+    price_cents   INTEGER NOT NULL,
+    result        TEXT NOT NULL,
+    market_result TEXT,
+    pnl_cents     INTEGER NOT NULL,
+    settled_time  TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS settlements_order_id ON settlements (order_id);
+
+CREATE TABLE IF NOT EXISTS stats (
+    timestamp          TEXT PRIMARY KEY,
+    total_trades       INTEGER NOT NULL,
+    wins               INTEGER NOT NULL,
+    losses             INTEGER NOT NULL,
+    voided             INTEGER NOT NULL,
+    win_rate           REAL NOT NULL,
+    total_pnl_cents    INTEGER NOT NULL,
+    today_pnl_cents    INTEGER NOT NULL,
+    current_streak     INTEGER NOT NULL,
+    max_drawdown_cents INTEGER NOT NULL
+);
+";
+
+const TRADE_COLUMNS: &str =
+    "timestamp, ticker, side, shares, price, result, pnl_cents, cumulative_cents, order_id, cancel_reason, event_ticker, city";
+
+pub struct SqliteLedger {
+    path: String,
+}
+
+impl SqliteLedger {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string() }
+    }
+
+    /// Open the database, creating the tables and importing ledger.md the
+    /// first time.
+    fn open(&self) -> anyhow::Result<Connection> {
+        let mut conn = Connection::open(&self.path)?;
+        conn.execute_batch(SCHEMA)?;
+        let empty = conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM trades)", [], |r| r.get::<_, bool>(0))?;
+        if empty && std::path::Path::new("brain/ledger.md").exists() {
+            let rows = read_ledger_md()?;
+            if !rows.is_empty() {
+                let tx = conn.transaction()?;
+                for row in &rows {
+                    insert_trade(&tx, row)?;
+                }
+                tx.commit()?;
+                tracing::info!("Imported {} ledger.md rows into {}", rows.len(), self.path);
+            }
+        }
+        Ok(conn)
+    }
+}
+
+impl LedgerStore for SqliteLedger {
+    fn read(&self) -> anyhow::Result<Vec<LedgerRow>> {
+        let conn = self.open()?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM trades ORDER BY id", TRADE_COLUMNS))?;
+        let rows = stmt.query_map([], trade_from_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    fn append(&self, row: &LedgerRow) -> anyhow::Result<()> {
+        insert_trade(&self.open()?, row)
+    }
+
+    fn update_newest(&self, f: &dyn Fn(&mut LedgerRow) -> bool) -> anyhow::Result<()> {
+        let conn = self.open()?;
+        let found = {
+            let mut stmt = conn.prepare(&format!("SELECT id, {} FROM trades ORDER BY id DESC", TRADE_COLUMNS))?;
+            let mut rows = stmt.query([])?;
+            let mut found = None;
+            while let Some(r) = rows.next()? {
+                let id: i64 = r.get(0)?;
+                let mut row = trade_from_offset(r, 1)?;
+                if f(&mut row) {
+                    found = Some((id, row));
+                    break;
+                }
+            }
+            found
+        };
+        if let Some((id, row)) = found {
+            conn.execute(
+                "UPDATE trades SET shares = ?1, price = ?2, result = ?3, pnl_cents = ?4, cumulative_cents = ?5,
+                 order_id = ?6, cancel_reason = ?7 WHERE id = ?8",
+                params![
+                    row.shares,
+                    row.price,
+                    row.result,
+                    row.pnl_cents.0,
+                    row.cumulative_cents.0,
+                    row.order_id,
+                    row.cancel_reason.map(|r| r.label()),
+                    id,
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn record_settlement(&self, order_id: &str, s: &Settlement) -> anyhow::Result<()> {
+        self.open()?.execute(
+            "INSERT INTO settlements (order_id, ticker, side, count, price_cents, result, market_result, pnl_cents, settled_time)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                order_id,
+                s.ticker,
+                side_label(&s.side),
+                s.count,
+                s.price_cents,
+                s.result,
+                s.market_result.map(|r| r.label()),
+                s.pnl_cents.0,
+                s.settled_time,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn record_stats(&self, stats: &Stats) -> anyhow::Result<()> {
+        self.open()?.execute(
+            "INSERT OR REPLACE INTO stats (timestamp, total_trades, wins, losses, voided, win_rate, total_pnl_cents,
+             today_pnl_cents, current_streak, max_drawdown_cents) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                chrono::Utc::now().to_rfc3339(),
+                stats.total_trades,
+                stats.wins,
+                stats.losses,
+                stats.voided,
+                stats.win_rate,
+                stats.total_pnl_cents.0,
+                stats.today_pnl_cents.0,
+                stats.current_streak,
+                stats.max_drawdown_cents.0,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn side_label(side: &Side) -> &'static str {
+    match side {
+        Side::Yes => "yes",
+        Side::No => "no",
+    }
+}
+
+fn insert_trade(conn: &Connection, row: &LedgerRow) -> anyhow::Result<()> {
+    conn.execute(
+        &format!("INSERT INTO trades ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)", TRADE_COLUMNS),
+        params![
+            row.timestamp,
+            row.ticker,
+            row.side,
+            row.shares,
+            row.price,
+            row.result,
+            row.pnl_cents.0,
+            row.cumulative_cents.0,
+            row.order_id,
+            row.cancel_reason.map(|r| r.label()),
+            row.event_ticker,
+            row.city,
+        ],
+    )?;
+    Ok(())
+}
+
+fn trade_from_row(r: &rusqlite::Row) -> rusqlite::Result<LedgerRow> {
+    trade_from_offset(r, 0)
+}
+
+/// A trade whose TRADE_COLUMNS start at column `at`.
+fn trade_from_offset(r: &rusqlite::Row, at: usize) -> rusqlite::Result<LedgerRow> {
+    let cancel_reason: Option<String> = r.get(at + 9).optional()?.flatten();
+    Ok(LedgerRow {
+        timestamp: r.get(at)?,
+        ticker: r.get(at + 1)?,
+        side: r.get(at + 2)?,
+        shares: r.get(at + 3)?,
+        price: r.get(at + 4)?,
+        result: r.get(at + 5)?,
+        pnl_cents: Cents(r.get(at + 6)?),
+        cumulative_cents: Cents(r.get(at + 7)?),
+        order_id: r.get(at + 8)?,
+        cancel_reason: cancel_reason.as_deref().and_then(CancelReason::from_label),
+        event_ticker: r.get(at + 10)?,
+        city: r.get(at + 11)?,
+    })
+}
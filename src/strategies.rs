@@ -0,0 +1,118 @@
+//! STRATEGY → brain. A new strategy implements `Brain`, adds a constructor
+//! here and an entry to `STRATEGIES`; main.rs builds whichever one is
+//! configured. Strategies that need extra dependencies put their entry
+//! behind `#[cfg(feature = "...")]`.
+
+use crate::adapters::ml_brain::MlBrain;
+use crate::adapters::openrouter::OpenRouterClient;
+use crate::core::backtest;
+use crate::core::rules_brain::RulesBrain;
+use crate::core::types::{Config, DecisionContext, EventContext, TradeDecision};
+use crate::ports::brain::Brain;
+use crate::storage;
+use async_trait::async_trait;
+
+pub struct Strategy {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Called once per cycle, so a brain can pick up state learned since
+    /// the last one
+    pub build: fn(&Config) -> anyhow::Result<Box<dyn Brain>>,
+}
+
+pub const STRATEGIES: &[Strategy] = &[
+    Strategy {
+        name: "rules",
+        description: "Ensemble probabilities vs the book, net of fees (default)",
+        build: rules,
+    },
+    Strategy {
+        name: "llm",
+        description: "OpenRouter model reading brain/prompt.md (needs OPENROUTER_API_KEY)",
+        build: llm,
+    },
+    Strategy {
+        name: "ml",
+        description: "ONNX model at ML_MODEL_PATH pricing brackets, sized by the rules brain",
+        build: ml,
+    },
+];
+
+/// The registered strategy called `name`.
+pub fn find(name: &str) -> anyhow::Result<&'static Strategy> {
+    STRATEGIES.iter().find(|s| s.name == name).ok_or_else(|| {
+        let available: Vec<String> = STRATEGIES.iter().map(|s| format!("\n  {} — {}", s.name, s.description)).collect();
+        anyhow::anyhow!("unknown STRATEGY {:?} — available:{}", name, available.concat())
+    })
+}
+
+/// A fresh brain for the configured strategy.
+pub fn brain_for(config: &Config) -> anyhow::Result<Box<dyn Brain>> {
+    (find(&config.strategy_name)?.build)(config)
+}
+
+/// With ADAPTIVE_EDGE_FLOOR, floors learned from the journal so far.
+fn rules(config: &Config) -> anyhow::Result<Box<dyn Brain>> {
+    let mut brain = RulesBrain::new(config.strategy.clone())
+        .with_cities(config.cities.clone())
+        .with_seasons(config.seasons.clone());
+    if config.strategy.adaptive_edge_floor {
+        match storage::read_journal() {
+            Ok(journal) => {
+                let floors = backtest::edge_floors(
+                    &journal,
+                    config.strategy.min_net_edge_pp,
+                    config.strategy.adaptive_edge_prior_samples,
+                    &backtest::PriceCaps::new(&config.strategy, &config.cities),
+                );
+                tracing::info!("Adaptive edge floor: {} learned bucket(s)", floors.len());
+                brain = brain.with_edge_floors(floors);
+            }
+            Err(e) => tracing::warn!("Journal read for edge floors failed: {} — using MIN_NET_EDGE_PP", e),
+        }
+    }
+    Ok(Box::new(brain))
+}
+
+/// The LLM, with the rules brain standing in wherever brain/flags.toml
+/// has `llm_brain` off.
+fn llm(config: &Config) -> anyhow::Result<Box<dyn Brain>> {
+    if config.openrouter_api_key.is_empty() {
+        anyhow::bail!("STRATEGY=llm needs OPENROUTER_API_KEY");
+    }
+    Ok(Box::new(Flagged { llm: Box::new(OpenRouterClient::new(config)?), rules: rules(config)? }))
+}
+
+/// The model's probability through the rules brain's fees, vetoes and
+/// sizing.
+fn ml(config: &Config) -> anyhow::Result<Box<dyn Brain>> {
+    Ok(Box::new(MlBrain::load(&config.ml_model_path, rules(config)?)?))
+}
+
+struct Flagged {
+    llm: Box<dyn Brain>,
+    rules: Box<dyn Brain>,
+}
+
+#[async_trait]
+impl Brain for Flagged {
+    async fn decide(&self, context: &DecisionContext) -> anyhow::Result<TradeDecision> {
+        if context.features.llm_brain {
+            self.llm.decide(context).await
+        } else {
+            self.rules.decide(context).await
+        }
+    }
+
+    fn uses_prompt(&self) -> bool {
+        self.llm.uses_prompt()
+    }
+
+    async fn decide_event(&self, context: &EventContext) -> anyhow::Result<Vec<TradeDecision>> {
+        if context.features.llm_brain {
+            self.llm.decide_event(context).await
+        } else {
+            self.rules.decide_event(context).await
+        }
+    }
+}